@@ -1,4 +1,3 @@
-
 # Monitors changes in activity status and send notification when it changes
 #
 # Works on: Windows with Powershell
@@ -12,82 +11,40 @@
 # More info at:
 # https://github.com/mikechambers/dcli/
 #
-# Requires dclia v0.1.1
+# Requires dclia v0.99.9
 
 
 ################ Script configuration #################
-$default_check_interval_seconds=15
-
-#only check if Destiny process is running. Set to $false
-#if you want it to check regardless of whether Destiny is running
-#on the same machine
-$only_check_if_destiny_running = $true
+$check_interval_seconds=15
 
 #whether it should print out status and other output to console
 $quiet=$false
 
 #pull setting from environment variables. you can also
 #just enter them here
-
-#you can get member_id and platform by running dclis
-$member_id=$env:MEMBER_ID
-$platform=$env:PLATFORM
+$name=$env:BUNGIE_NAME
 
 #run dclim to sync manifest before running this script
 
 ############# program #############
 
-$old_activity=""
-
-while ($true) {
-
-    $check_interval_seconds = $default_check_interval_seconds
-    #check if Destiny is running and whether we should skip check if its not running
-    $should_check = (Get-Process destiny2 -ErrorAction SilentlyContinue) -or !$only_check_if_destiny_running
-
+[void] [reflection.assembly]::loadwithpartialname("System.Windows.Forms")
+[void] [reflection.assembly]::loadwithpartialname("System.Drawing")
 
-    if ($should_check) {
-        if (!$quiet) {
-            Write-Output "Checking Status..."
-        }
+# assumes dclia is in your path
+# dclia prints a JSON object on its own line each time the status changes
+dclia --name $name --watch --interval $check_interval_seconds --output-format jsonl | ForEach-Object {
+    $activity_event = $_ | ConvertFrom-Json
 
-        # assumes dclia is in your path
-        $activity = (dclia --name mesh#3230) -join "`n"
-        $skip_notification = (($activity -eq "Not currently in an activity") -or ($activity -eq "Currently sitting in Orbit"))
-    
-        #dont send notification the first time we run
-        if($old_activity -eq "") {
-
-            if (!$quiet) {
-                Write-Output $activity
-                Write-Output "Initial status check. Skipping notification."
-            }
-
-            $skip_notification = $true
-            $old_activity = $activity
-        }
-
-        if ( ($old_activity -ne $activity) -and  !$skip_notification)
-        { 
-            [void] [reflection.assembly]::loadwithpartialname("System.Windows.Forms")
-            [void] [reflection.assembly]::loadwithpartialname("System.Drawing")
-            $notify = new-object system.windows.forms.notifyicon
-            $notify.icon = [System.Drawing.SystemIcons]::Information
-            $notify.visible = $true
-            $notify.showballoontip(10,"Destiny 2 Activity Changed",$activity,[system.windows.forms.tooltipicon]::None)
-    
-            if (!$quiet) {
-                Write-Output $activity
-            }
-
-            $old_activity = $activity
-            $check_interval_seconds = 60
-        }
-    } else {
-        if (!$quiet) {
-            Write-Output "Destiny 2 is not running. Skipping status check."
-        }
+    if (!$quiet) {
+        Write-Output "$($activity_event.event) : $($activity_event.human_status)"
     }
 
-    Start-Sleep -Seconds $check_interval_seconds
-}
\ No newline at end of file
+    #only notify when starting an activity, not when going to orbit
+    if ($activity_event.event -eq "started_activity") {
+        $notify = new-object system.windows.forms.notifyicon
+        $notify.icon = [System.Drawing.SystemIcons]::Information
+        $notify.visible = $true
+        $notify.showballoontip(10,"Destiny 2 Activity Changed",$activity_event.human_status,[system.windows.forms.tooltipicon]::None)
+    }
+}
@@ -5,23 +5,34 @@ DROP TABLE IF EXISTS "team_result";
 DROP TABLE IF EXISTS "weapon_result";
 DROP TABLE IF EXISTS "medal_result";
 DROP TABLE IF EXISTS "activity_queue";
+DROP TABLE IF EXISTS "character_snapshot";
+DROP TABLE IF EXISTS "combat_rating";
 DROP TABLE IF EXISTS "character_activity_stats";
 DROP TABLE IF EXISTS "activity";
 DROP TABLE IF EXISTS "character";
 DROP TABLE IF EXISTS "sync";
+DROP TABLE IF EXISTS "clan_member";
+DROP TABLE IF EXISTS "clan";
+DROP TABLE IF EXISTS "member_name";
 DROP TABLE IF EXISTS "member";
+DROP TABLE IF EXISTS "account";
 DROP TABLE IF EXISTS "version";
 
 DROP INDEX IF EXISTS "modes_activity_index";
 DROP INDEX IF EXISTS "character_activity_stats_char_index";
 DROP INDEX IF EXISTS "activity_period_index";
+DROP INDEX IF EXISTS "character_snapshot_character_index";
+DROP INDEX IF EXISTS "combat_rating_character_index";
+DROP INDEX IF EXISTS "member_account_index";
+DROP INDEX IF EXISTS "member_name_name_index";
+DROP INDEX IF EXISTS "clan_member_member_index";
 
 
 CREATE TABLE IF NOT EXISTS "main"."version" (
     "version"   INTEGER NOT NULL UNIQUE
 );
 
-INSERT INTO "main"."version"("version") VALUES (10);
+INSERT INTO "main"."version"("version") VALUES (15);
 
 CREATE TABLE IF NOT EXISTS "main"."activity_queue" (
     "id"	INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
@@ -43,12 +54,58 @@ CREATE TABLE IF NOT EXISTS "sync" (
         REFERENCES "member" ("member_id")
 );
 
+CREATE TABLE IF NOT EXISTS "clan" (
+    "group_id"      INTEGER PRIMARY KEY UNIQUE NOT NULL,
+    "name"          TEXT NOT NULL,
+    "last_synced"   TEXT
+);
+
+CREATE TABLE IF NOT EXISTS "clan_member" (
+    "id"            INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
+    "clan"          INTEGER NOT NULL,
+    "member"	    INTEGER NOT NULL,
+    "joined"        TEXT NOT NULL,
+    "departed"      TEXT,
+    UNIQUE("clan", "member"),
+    FOREIGN KEY ("clan")
+        REFERENCES "clan" ("group_id")
+        ON DELETE CASCADE,
+    FOREIGN KEY ("member")
+        REFERENCES "member" ("member_id")
+        ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS  "account" (
+    "account_id"	    INTEGER PRIMARY KEY UNIQUE NOT NULL,
+    "primary_member"    INTEGER NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS  "member" (
     "member_id"	    INTEGER PRIMARY KEY UNIQUE NOT NULL,
     "platform_id"	INTEGER NOT NULL,
     "display_name"  TEXT,
     "bungie_display_name"  TEXT,
-    "bungie_display_name_code"  TEXT
+    "bungie_display_name_code"  TEXT,
+    "account"       INTEGER,
+
+    FOREIGN KEY ("account")
+        REFERENCES "account" ("account_id")
+        ON DELETE SET NULL
+);
+
+CREATE TABLE IF NOT EXISTS  "member_name" (
+    "id"	INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
+    "member"	    INTEGER NOT NULL,
+    "display_name"  TEXT,
+    "bungie_display_name"  TEXT NOT NULL,
+    "bungie_display_name_code"  TEXT NOT NULL,
+    "first_seen"    TEXT NOT NULL,
+    "last_seen"     TEXT NOT NULL,
+    UNIQUE("member", "bungie_display_name", "bungie_display_name_code"),
+
+    FOREIGN KEY ("member")
+        REFERENCES "member" ("member_id")
+        ON DELETE CASCADE
 );
 
 CREATE TABLE IF NOT EXISTS  "character" (
@@ -61,6 +118,38 @@ CREATE TABLE IF NOT EXISTS  "character" (
         ON DELETE CASCADE
 );
 
+CREATE TABLE IF NOT EXISTS "main"."character_snapshot" (
+    "id"	INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
+    "character"	            INTEGER NOT NULL,
+    "created"               TEXT NOT NULL,
+    "light_level"           INTEGER NOT NULL,
+    "mobility"              INTEGER NOT NULL,
+    "resilience"            INTEGER NOT NULL,
+    "recovery"              INTEGER NOT NULL,
+    "discipline"            INTEGER NOT NULL,
+    "intellect"             INTEGER NOT NULL,
+    "strength"              INTEGER NOT NULL,
+    "emblem_hash"           INTEGER NOT NULL,
+    "minutes_played_total"  INTEGER NOT NULL,
+    "date_last_played"      TEXT NOT NULL,
+
+    FOREIGN KEY ("character")
+        REFERENCES "character" ("character_id")
+        ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS "main"."combat_rating" (
+    "id"	INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
+    "character"	    INTEGER NOT NULL,
+    "mode"          INTEGER NOT NULL,
+    "created"       TEXT NOT NULL,
+    "rating"        REAL NOT NULL,
+
+    FOREIGN KEY ("character")
+        REFERENCES "character" ("character_id")
+        ON DELETE CASCADE
+);
+
 CREATE TABLE IF NOT EXISTS "main"."activity" (
     "activity_id"	INTEGER PRIMARY KEY UNIQUE NOT NULL,
     "period"        TEXT NOT NULL,
@@ -168,6 +257,11 @@ CREATE TABLE IF NOT EXISTS "main"."character_activity_stats" (
 CREATE INDEX modes_activity_index ON modes (activity);
 CREATE INDEX character_activity_stats_char_index ON character_activity_stats (character);
 CREATE INDEX activity_period_index ON activity (period);
+CREATE INDEX character_snapshot_character_index ON character_snapshot (character, created);
+CREATE INDEX combat_rating_character_index ON combat_rating (character, mode, created);
+CREATE INDEX member_account_index ON member (account);
+CREATE INDEX member_name_name_index ON member_name (bungie_display_name, bungie_display_name_code);
+CREATE INDEX clan_member_member_index ON clan_member (member);
 
 CREATE INDEX idx_character_activity_stats_activity ON character_activity_stats(activity);
 
@@ -0,0 +1,132 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//Compares manifest lookup performance with and without the in memory cache.
+//
+//Run from the src directory with:
+//cargo run --release -p dcli --example manifest_cache -- [DATA_DIR] [LOOKUP_COUNT]
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use dcli::error::Error;
+use dcli::manifestinterface::{ManifestInterface, MANIFEST_FILE_NAME};
+use dcli::utils::determine_data_dir;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, Row};
+
+const DEFAULT_LOOKUP_COUNT: usize = 10000;
+
+async fn load_hashes(
+    data_dir: &std::path::Path,
+    table: &str,
+    count: usize,
+) -> Result<Vec<u32>, Error> {
+    let path = format!("{}", data_dir.join(MANIFEST_FILE_NAME).display());
+    let mut db = SqliteConnectOptions::from_str(&path)?
+        .read_only(true)
+        .connect()
+        .await?;
+
+    let rows = sqlx::query(&format!(
+        "SELECT id FROM {} ORDER BY RANDOM() LIMIT {}",
+        table, count
+    ))
+    .fetch_all(&mut db)
+    .await?;
+
+    db.close().await?;
+
+    //ids are stored as signed versions of the hash
+    let mut out: Vec<u32> = Vec::new();
+    for row in rows.iter() {
+        let id: i64 = row.try_get("id")?;
+        out.push(id as u32);
+    }
+
+    Ok(out)
+}
+
+async fn run(
+    data_dir: &std::path::Path,
+    cache: bool,
+    item_hashes: &[u32],
+    activity_hashes: &[u32],
+) -> Result<(Duration, Duration), Error> {
+    let start = Instant::now();
+    let mut manifest = ManifestInterface::new(data_dir, cache).await?;
+    let load_time = start.elapsed();
+
+    let start = Instant::now();
+    for hash in item_hashes.iter() {
+        manifest.get_iventory_item_definition(*hash).await?;
+    }
+
+    for hash in activity_hashes.iter() {
+        manifest.get_activity_definition(*hash).await?;
+    }
+    let lookup_time = start.elapsed();
+
+    manifest.close().await?;
+
+    Ok((load_time, lookup_time))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let mut args = std::env::args().skip(1);
+    let data_dir = determine_data_dir(args.next().map(PathBuf::from))?;
+    let count: usize = args
+        .next()
+        .and_then(|e| e.parse().ok())
+        .unwrap_or(DEFAULT_LOOKUP_COUNT);
+
+    let item_hashes =
+        load_hashes(&data_dir, "DestinyInventoryItemDefinition", count).await?;
+    let activity_hashes =
+        load_hashes(&data_dir, "DestinyActivityDefinition", count).await?;
+
+    let total = item_hashes.len() + activity_hashes.len();
+    println!("Manifest : {}", data_dir.join(MANIFEST_FILE_NAME).display());
+    println!("Lookups  : {}", total);
+    println!();
+    println!(
+        "{:<10}{:>12}{:>12}{:>14}",
+        "MODE", "LOAD", "LOOKUPS", "PER LOOKUP"
+    );
+
+    for cache in [false, true] {
+        let (load_time, lookup_time) =
+            run(&data_dir, cache, &item_hashes, &activity_hashes).await?;
+
+        println!(
+            "{:<10}{:>10}ms{:>10}ms{:>12}us",
+            if cache { "cached" } else { "file" },
+            load_time.as_millis(),
+            lookup_time.as_millis(),
+            lookup_time.as_micros() / total.max(1) as u128,
+        );
+    }
+
+    Ok(())
+}
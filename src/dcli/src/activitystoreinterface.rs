@@ -24,10 +24,15 @@ use std::str::FromStr;
 use std::{collections::HashMap, path::Path};
 use tell::{Tell, TellLevel};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use indicatif::{ProgressBar, ProgressState, ProgressStyle};
 
+use crate::character::CharacterSnapshot;
+use crate::clan::{
+    Clan, ClanMatchup, ClanMember, ClanPlayerStats, ClanRosterUpdate,
+};
 use crate::playeractivitiessummary::PlayerActivitiesSummary;
+use crate::response::character::{CharacterData, CharacterStatsData};
 use crate::utils::{
     format_error, CHECKMATE_CLASH_ACTIVITY_HASH,
     CHECKMATE_CONTROL_ACTIVITY_HASHES, CHECKMATE_COUNTDOWN_ACTIVITY_HASH,
@@ -36,7 +41,10 @@ use crate::utils::{
     IRON_BANNER_FORTRESS_ACTIVITY_HASH, IRON_BANNER_TRIBUTE_ACTIVITY_HASH,
 };
 use crate::{
-    crucible::{CrucibleActivity, Member, PlayerName, Team},
+    crucible::{
+        ActivityRosterEntry, CombatRating, CrucibleActivity, MedalHistory,
+        MedalTotals, Member, PlayerHistory, PlayerName, Team,
+    },
     enums::{
         completionreason::CompletionReason,
         itemtype::{ItemSubType, ItemType},
@@ -55,8 +63,11 @@ use crate::crucible::{
     CruciblePlayerPerformance, CrucibleStats, ExtendedCrucibleStats, Item,
     Medal, MedalStat, Player, WeaponStat,
 };
+use crate::enums::ammotype::AmmoType;
 use crate::enums::character::{CharacterClass, CharacterClassSelection};
+use crate::enums::damagetype::DamageType;
 use crate::enums::medaltier::MedalTier;
+use crate::enums::membership::MembershipSelection;
 use crate::enums::mode::Mode;
 use crate::enums::platform::Platform;
 use crate::{apiinterface::ApiInterface, manifestinterface::ManifestInterface};
@@ -83,7 +94,143 @@ const DCLI_FIX_DATA: &str = "DCLI_FIX_DATA";
 //number of simultaneous requests we make to server when retrieving activity history
 const PGCR_REQUEST_CHUNK_AMOUNT: usize = 50;
 
-const DB_SCHEMA_VERSION: i32 = 10;
+const DB_SCHEMA_VERSION: i32 = 15;
+
+//oldest schema version which can be migrated to DB_SCHEMA_VERSION. Older
+//stores are rebuilt with STORE_DB_SCHEMA.
+const DB_MIGRATION_BASE_VERSION: i32 = 10;
+
+//migrations to bring a store up to date without dropping its data. The
+//migration at index i updates the schema from version
+//DB_MIGRATION_BASE_VERSION + i to the next version. Some tables contain
+//data which cant be restored by syncing (such as character snapshots), so
+//new schema versions should add a migration rather than relying on
+//STORE_DB_SCHEMA dropping and recreating the tables.
+const DB_MIGRATIONS: [&str; 5] = [
+    //11 : character snapshots
+    r#"
+    CREATE TABLE IF NOT EXISTS "main"."character_snapshot" (
+        "id"	INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
+        "character"	            INTEGER NOT NULL,
+        "created"               TEXT NOT NULL,
+        "light_level"           INTEGER NOT NULL,
+        "mobility"              INTEGER NOT NULL,
+        "resilience"            INTEGER NOT NULL,
+        "recovery"              INTEGER NOT NULL,
+        "discipline"            INTEGER NOT NULL,
+        "intellect"             INTEGER NOT NULL,
+        "strength"              INTEGER NOT NULL,
+        "emblem_hash"           INTEGER NOT NULL,
+        "minutes_played_total"  INTEGER NOT NULL,
+        "date_last_played"      TEXT NOT NULL,
+
+        FOREIGN KEY ("character")
+            REFERENCES "character" ("character_id")
+            ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS character_snapshot_character_index ON character_snapshot (character, created);
+    "#,
+    //12 : combat ratings
+    r#"
+    CREATE TABLE IF NOT EXISTS "main"."combat_rating" (
+        "id"	INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
+        "character"	    INTEGER NOT NULL,
+        "mode"          INTEGER NOT NULL,
+        "created"       TEXT NOT NULL,
+        "rating"        REAL NOT NULL,
+
+        FOREIGN KEY ("character")
+            REFERENCES "character" ("character_id")
+            ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS combat_rating_character_index ON combat_rating (character, mode, created);
+    "#,
+    //13 : linked accounts
+    r#"
+    CREATE TABLE IF NOT EXISTS  "account" (
+        "account_id"	    INTEGER PRIMARY KEY UNIQUE NOT NULL,
+        "primary_member"    INTEGER NOT NULL
+    );
+
+    ALTER TABLE "member" ADD COLUMN "account" INTEGER
+        REFERENCES "account" ("account_id") ON DELETE SET NULL;
+
+    CREATE INDEX IF NOT EXISTS member_account_index ON member (account);
+    "#,
+    //14 : name history
+    r#"
+    CREATE TABLE IF NOT EXISTS  "member_name" (
+        "id"	INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
+        "member"	    INTEGER NOT NULL,
+        "display_name"  TEXT,
+        "bungie_display_name"  TEXT NOT NULL,
+        "bungie_display_name_code"  TEXT NOT NULL,
+        "first_seen"    TEXT NOT NULL,
+        "last_seen"     TEXT NOT NULL,
+        UNIQUE("member", "bungie_display_name", "bungie_display_name_code"),
+
+        FOREIGN KEY ("member")
+            REFERENCES "member" ("member_id")
+            ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS member_name_name_index ON member_name (bungie_display_name, bungie_display_name_code);
+    "#,
+    //15 : tracked clans
+    r#"
+    CREATE TABLE IF NOT EXISTS "clan" (
+        "group_id"      INTEGER PRIMARY KEY UNIQUE NOT NULL,
+        "name"          TEXT NOT NULL,
+        "last_synced"   TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS "clan_member" (
+        "id"            INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT UNIQUE,
+        "clan"          INTEGER NOT NULL,
+        "member"	    INTEGER NOT NULL,
+        "joined"        TEXT NOT NULL,
+        "departed"      TEXT,
+        UNIQUE("clan", "member"),
+        FOREIGN KEY ("clan")
+            REFERENCES "clan" ("group_id")
+            ON DELETE CASCADE,
+        FOREIGN KEY ("member")
+            REFERENCES "member" ("member_id")
+            ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS clan_member_member_index ON clan_member (member);
+    "#,
+];
+
+//number of hours after which a stored combat rating for the member's
+//characters is refreshed when syncing
+const COMBAT_RATING_MAX_AGE_HOURS: i64 = 24;
+
+//number of hours after which the roster for a tracked group is refreshed
+//when syncing
+const GROUP_ROSTER_MAX_AGE_HOURS: i64 = 1;
+
+//maps the current members of a clan (clan_member) to the ids for all of
+//their linked memberships (member_id). The clan id must be bound.
+const CLAN_ROSTER_CTE: &str = r#"
+    roster AS (
+        SELECT
+            clan_member.member as clan_member,
+            member.member_id as member_id
+        FROM
+            clan_member
+        INNER JOIN
+            member as m on clan_member.member = m.member_id
+        INNER JOIN
+            member on member.member_id = m.member_id OR member.account = m.account
+        WHERE
+            clan_member.clan = ? AND
+            clan_member.departed IS NULL
+    )
+"#;
 const NO_TEAMS_INDEX: i32 = 253;
 
 pub struct ActivityStoreInterface {
@@ -131,7 +278,7 @@ impl ActivityStoreInterface {
             .await?;
 
         //is this an existing db, or a completely new one / first time?
-        let should_update_schema = match sqlx::query(
+        let version = match sqlx::query(
             r#"
             SELECT max(version) as max_version FROM version
         "#,
@@ -139,14 +286,14 @@ impl ActivityStoreInterface {
         .fetch_one(&mut db)
         .await
         {
-            Ok(e) => {
-                let version: i32 = e.try_get("max_version").unwrap_or(-1);
-                version != DB_SCHEMA_VERSION
-            }
-            Err(_e) => true,
+            Ok(e) => e.try_get("max_version").unwrap_or(-1),
+            Err(_e) => -1,
         };
 
-        if should_update_schema {
+        if (DB_MIGRATION_BASE_VERSION..DB_SCHEMA_VERSION).contains(&version) {
+            tell::update!("Data store needs to be updated.");
+            ActivityStoreInterface::migrate_schema(&mut db, version).await?;
+        } else if version != DB_SCHEMA_VERSION {
             tell::update!("Data store needs to be updated.");
             sqlx::query(STORE_DB_SCHEMA).execute(&mut db).await?;
         }
@@ -164,6 +311,41 @@ impl ActivityStoreInterface {
         })
     }
 
+    //applies the migrations in DB_MIGRATIONS to update the schema from
+    //version to DB_SCHEMA_VERSION, keeping the existing data
+    async fn migrate_schema(
+        db: &mut SqliteConnection,
+        version: i32,
+    ) -> Result<(), Error> {
+        for v in version..DB_SCHEMA_VERSION {
+            let migration =
+                DB_MIGRATIONS[(v - DB_MIGRATION_BASE_VERSION) as usize];
+
+            info!("Migrating data store schema to version {}", v + 1);
+
+            sqlx::query("BEGIN TRANSACTION;").execute(&mut *db).await?;
+
+            let result = match sqlx::query(migration).execute(&mut *db).await {
+                Ok(_) => {
+                    sqlx::query("UPDATE version SET version = ?")
+                        .bind(v + 1)
+                        .execute(&mut *db)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                sqlx::query("ROLLBACK;").execute(&mut *db).await?;
+                return Err(e.into());
+            }
+
+            sqlx::query("COMMIT;").execute(&mut *db).await?;
+        }
+
+        Ok(())
+    }
+
     //todo: this should take a PlayerName
     async fn retrieve_member_by_name(
         &mut self,
@@ -171,9 +353,15 @@ impl ActivityStoreInterface {
     ) -> Result<Option<Member>, Error> {
         //TODO: check name fields are not null / None
 
+        //all of the memberships for an account share the same bungie name, so
+        //prefer the account's primary membership
         let row_option = sqlx::query(
             r#"
-            SELECT "member_id", "platform_id", "display_name", "bungie_display_name", "bungie_display_name_code" from "member" where bungie_display_name= ? and bungie_display_name_code = ?
+            SELECT "member_id", "platform_id", "display_name", "bungie_display_name", "bungie_display_name_code" from "member"
+            LEFT JOIN "account" on member.account = account.account_id
+            where bungie_display_name= ? and bungie_display_name_code = ?
+            ORDER BY account.primary_member = member.member_id DESC
+            LIMIT 1
         "#,
         )
         .bind(player_name.bungie_display_name.as_ref().unwrap())
@@ -184,14 +372,147 @@ impl ActivityStoreInterface {
         //TODO: may want to use this in other areas
         let row = match row_option {
             Some(e) => e,
-            None => return Ok(None),
+            None => match self.retrieve_member_row_by_past_name(player_name).await? {
+                Some(e) => e,
+                None => return Ok(None),
+            },
         };
 
-        let member = self.parse_member_row(&row)?;
+        let mut member = self.parse_member_row(&row)?;
+        member.linked_ids = self.retrieve_linked_member_ids(&member.id).await?;
 
         Ok(Some(member))
     }
 
+    /// Finds the member who most recently had the name, if it is not their
+    /// current name (i.e. they changed their Bungie name).
+    async fn retrieve_member_row_by_past_name(
+        &mut self,
+        player_name: &PlayerName,
+    ) -> Result<Option<sqlx::sqlite::SqliteRow>, Error> {
+        let row_option = sqlx::query(
+            r#"
+            SELECT
+                member.member_id, member.platform_id, member.display_name,
+                member.bungie_display_name, member.bungie_display_name_code
+            FROM
+                member_name
+            INNER JOIN
+                member on member_name.member = member.member_id
+            LEFT JOIN
+                account on member.account = account.account_id
+            WHERE
+                member_name.bungie_display_name = ? AND
+                member_name.bungie_display_name_code = ?
+            ORDER BY
+                member_name.last_seen DESC,
+                account.primary_member = member.member_id DESC
+            LIMIT 1
+        "#,
+        )
+        .bind(player_name.bungie_display_name.as_ref().unwrap())
+        .bind(player_name.bungie_display_name_code.as_ref().unwrap())
+        .fetch_optional(&mut self.db)
+        .await?;
+
+        if let Some(e) = &row_option {
+            let member = self.parse_member_row(e)?;
+            tell::verbose!(
+                "{} found in name history. Current name is {}",
+                player_name.get_bungie_name(),
+                member.name.get_bungie_name()
+            );
+        }
+
+        Ok(row_option)
+    }
+
+    /// Returns the ids for the other memberships linked to the same
+    /// Bungie.net account as the member
+    async fn retrieve_linked_member_ids(
+        &mut self,
+        member_id: &i64,
+    ) -> Result<Vec<i64>, Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                m.member_id
+            FROM
+                member as m
+            INNER JOIN
+                member as p on m.account = p.account
+            WHERE
+                p.member_id = ? AND m.member_id != p.member_id
+            ORDER BY m.member_id
+        "#,
+        )
+        .bind(member_id)
+        .fetch_all(&mut self.db)
+        .await?;
+
+        let mut out: Vec<i64> = Vec::new();
+        for row in rows.iter() {
+            out.push(row.try_get("member_id")?);
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the member to retrieve data for, based on which of the
+    /// memberships linked to the member's account are selected.
+    ///
+    /// Linked memberships are reloaded from the store, since they may have
+    /// been added by syncing after the member was found.
+    ///
+    /// Returns Error::MembershipNotFound if a platform is selected, and the
+    /// member does not have a linked membership for the platform.
+    pub async fn select_membership(
+        &mut self,
+        member: &Member,
+        selection: &MembershipSelection,
+    ) -> Result<Member, Error> {
+        let mut member = member.clone();
+        member.linked_ids = self.retrieve_linked_member_ids(&member.id).await?;
+
+        let platform = match selection {
+            MembershipSelection::All => return Ok(member),
+            MembershipSelection::Primary => {
+                member.linked_ids = Vec::new();
+                return Ok(member);
+            }
+            MembershipSelection::Platform(e) => *e,
+        };
+
+        if member.platform == platform {
+            member.linked_ids = Vec::new();
+            return Ok(member);
+        }
+
+        for id in member.linked_ids.iter() {
+            let row = sqlx::query(
+                r#"
+                SELECT "member_id", "platform_id", "display_name", "bungie_display_name", "bungie_display_name_code" from "member" where member_id = ? and platform_id = ?
+            "#,
+            )
+            .bind(id)
+            .bind(platform.as_id())
+            .fetch_optional(&mut self.db)
+            .await?;
+
+            if let Some(e) = row {
+                return self.parse_member_row(&e);
+            }
+        }
+
+        Err(Error::MembershipNotFound {
+            description: format!(
+                "No {} membership is linked to {}. Linked memberships are stored when syncing with dclisync.",
+                platform,
+                member.name.get_bungie_name()
+            ),
+        })
+    }
+
     fn parse_member_row(
         &mut self,
         member_row: &sqlx::sqlite::SqliteRow,
@@ -215,6 +536,7 @@ impl ActivityStoreInterface {
             name,
             platform: Platform::from_id(platform_id),
             id: member_id,
+            linked_ids: Vec::new(),
         };
 
         Ok(member)
@@ -355,33 +677,18 @@ impl ActivityStoreInterface {
         &mut self,
         player: &PlayerName,
     ) -> Result<(), Error> {
-        let row_option = sqlx::query(
+        //all of the memberships for an account share the same name, so
+        //remove whichever one is being synced
+        sqlx::query(
             r#"
-            SELECT "member_id" from "member" 
-            where bungie_display_name = ? and bungie_display_name_code = ?
+            delete from "sync" where member IN (
+                SELECT "member_id" from "member"
+                where bungie_display_name = ? and bungie_display_name_code = ?
+            )
         "#,
         )
         .bind(player.bungie_display_name.as_ref().unwrap())
         .bind(player.bungie_display_name_code.as_ref().unwrap())
-        .fetch_optional(&mut self.db)
-        .await?;
-
-        let id: i64 = match row_option {
-            Some(e) => {
-                let id: i64 = e.try_get("member_id")?;
-                id
-            }
-            None => {
-                return Ok(());
-            }
-        };
-
-        sqlx::query(
-            r#"
-            delete from "sync" where member = ?
-        "#,
-        )
-        .bind(id)
         .execute(&mut self.db)
         .await?;
 
@@ -430,132 +737,545 @@ impl ActivityStoreInterface {
         Ok(())
     }
 
-    /// TODO currently no way to sync old / delete characters. would be easy to
-    /// add by just moving the character sync into its own api sync_character(id, class_type)
-    /// but not going to worry about it unless someone requests it
-    /// retrieves and stores activity details for ids in activity queue
-    pub async fn sync_member(
+    /// Retrieves the current roster for the group / clan from the API, and
+    /// updates the stored roster, tracking the group if it is not already
+    /// tracked. New members are added to be synced, and members who are no
+    /// longer in the group are marked as departed.
+    pub async fn sync_group(
         &mut self,
-        member: &Member,
-    ) -> Result<SyncResult, Error> {
-        //Note, we need this call in case the user deletes and creates a new character
-        //https://www.bungie.net/Platform/Destiny2/1/Profile/4611686018429783292/?components=100,200
-        let player_info = self
-            .api_interface
-            .get_player_info(&member.id, &member.platform)
-            .await?;
-
-        //TODO: Check is core issue has been fixed
-        //Note, if this profile call fails, then we dont sync user. However, we could
-        //capture and then just sync the characters we already have.
-        //This issue is causing the sync to fail for some users:
-        //https://twitter.com/BungieHelp/status/1595515590160306176?s=20&t=QR7Rs7weMwzTfO2YtTArSg
-        //https://github.com/Bungie-net/api/issues/1738
-
-        let characters = player_info.characters;
+        group_id: i64,
+    ) -> Result<ClanRosterUpdate, Error> {
+        let group = self.api_interface.retrieve_group(group_id).await?;
+        let roster = self.api_interface.retrieve_group_members(group_id).await?;
+        let current = self.retrieve_clan_members(group_id, false).await?;
 
-        self.insert_member(&player_info.user_info.to_member())
-            .await?;
+        self.begin_transaction().await?;
+        match self._sync_group(&group.name, group_id, &roster, &current).await
+        {
+            Ok(e) => {
+                self.commit_transaction().await?;
+                Ok(e)
+            }
+            Err(e) => {
+                self.rollback_transaction().await?;
+                Err(e)
+            }
+        }
+    }
 
-        let mut total_synced = 0;
-        let mut total_in_queue = 0;
+    async fn _sync_group(
+        &mut self,
+        name: &str,
+        group_id: i64,
+        roster: &[ClanMember],
+        current: &[ClanMember],
+    ) -> Result<ClanRosterUpdate, Error> {
+        let now = Utc::now().to_rfc3339();
 
-        tell::update!(
-            "CHECKING FOR NEW ACTIVITIES FOR {} (PUBLIC AND PRIVATE)",
-            member.name.get_bungie_name()
-        );
-        tell::progress!("This may take a few minutes depending on the number of activities.");
+        sqlx::query(
+            r#"
+            INSERT into "clan" ("group_id", "name", "last_synced") VALUES (?, ?, ?)
+            ON CONFLICT(group_id) DO UPDATE
+            set name = excluded.name, last_synced = excluded.last_synced
+        "#,
+        )
+        .bind(group_id)
+        .bind(name)
+        .bind(&now)
+        .execute(&mut self.db)
+        .await?;
 
-        for c in characters.characters {
-            let character_id = &c.id;
-            self.insert_character(&c.id, &c.class_type, &member).await?;
-            tell::progress!("{}", format!("[{}]", c.class_type).to_uppercase());
+        let mut out = ClanRosterUpdate::default();
+        for cm in roster.iter() {
+            //if they dont have a valid Bungie ID / id, then skip
+            if !cm.member.name.is_valid_bungie_name() {
+                tell::verbose!(
+                    "No valid Bungie ID and code. Skipping. : {}",
+                    cm.member.name.get_short_name()
+                );
+                continue;
+            }
 
-            //these calls could be a little more general purpose by taking api ids and not db ids.
-            //however, passing the db ids, lets us optimize a lot of the sql, and avoid
-            //some extra calls to the DB
+            self.insert_member(&cm.member).await?;
 
-            let a = self.sync_activities(character_id).await?;
+            if current.iter().any(|e| e.member.id == cm.member.id) {
+                continue;
+            }
 
-            let _b = self
-                .update_activity_queue(
-                    &member.id,
-                    character_id,
-                    &member.platform,
-                )
-                .await?;
+            //only add new members, so players who were removed from syncing
+            //are not added back
+            self.update_sync_entry(&cm.member.id).await?;
 
-            let c = self.sync_activities(character_id).await?;
+            //clear departed for members who left and have rejoined
+            sqlx::query(
+                r#"
+                INSERT into "clan_member" ("clan", "member", "joined", "departed") VALUES (?, ?, ?, NULL)
+                ON CONFLICT(clan, member) DO UPDATE
+                set joined = excluded.joined, departed = NULL
+            "#,
+            )
+            .bind(group_id)
+            .bind(cm.member.id)
+            .bind(cm.joined.to_rfc3339())
+            .execute(&mut self.db)
+            .await?;
 
-            total_synced += a.total_synced + c.total_synced;
-            total_in_queue += (a.total_available + c.total_available)
-                - (a.total_synced + c.total_synced);
+            out.joined.push(cm.member.clone());
         }
 
-        self.update_sync_entry(&member.id).await?;
-
-        Ok(SyncResult {
-            total_synced,
-            total_available: total_in_queue,
-        })
-    }
-
-    /// download results from ids in queue, and return number of items synced
-    async fn sync_activities(
-        &mut self,
-        character_id: &i64,
-    ) -> Result<SyncResult, Error> {
-        let mut ids: Vec<i64> = Vec::new();
+        for cm in current.iter() {
+            if roster.iter().any(|e| e.member.id == cm.member.id) {
+                continue;
+            }
 
-        //This is to scope rows, so the mutable borrow of self goes out of scope
-        {
-            let mut rows = sqlx::query(
+            sqlx::query(
                 r#"
-                    SELECT
-                        "activity_id"
-                    FROM
-                        "activity_queue"
-                    WHERE
-                        character = ? AND synced = 0
-                "#,
+                UPDATE "clan_member" set departed = ? where clan = ? and member = ?
+            "#,
             )
-            .bind(character_id)
-            .fetch(&mut self.db);
+            .bind(&now)
+            .bind(group_id)
+            .bind(cm.member.id)
+            .execute(&mut self.db)
+            .await?;
 
-            //todo: could probably get this without looping through entire set
-            while let Some(row) = rows.try_next().await? {
-                let activity_id: i64 = row.try_get("activity_id")?;
-                ids.push(activity_id);
+            out.departed.push(cm.member.clone());
+        }
+
+        Ok(out)
+    }
+
+    /// Syncs the rosters for all of the tracked groups which have not been
+    /// synced within the last GROUP_ROSTER_MAX_AGE_HOURS. Errors are logged,
+    /// and do not stop the other groups from syncing.
+    pub async fn sync_groups(&mut self) -> Result<(), Error> {
+        let max_age = Duration::hours(GROUP_ROSTER_MAX_AGE_HOURS);
+        let clans = self.retrieve_clans().await?;
+
+        for clan in clans.iter() {
+            if let Some(e) = clan.last_synced {
+                if Utc::now() - e < max_age {
+                    continue;
+                }
             }
-        };
 
-        if ids.is_empty() {
-            return Ok(SyncResult {
-                total_available: 0,
-                total_synced: 0,
-            });
-        }
+            tell::update!("SYNCING ROSTER FOR {}", clan.name.to_uppercase());
 
-        let mut filtered_ids = Vec::new();
+            match self.sync_group(clan.group_id).await {
+                Ok(e) => {
+                    for m in e.joined.iter() {
+                        tell::update!("Joined : {}", m.name.get_bungie_name());
+                    }
 
-        //remove ids to sync for activities we have already synced
-        for id in ids {
-            if self.has_activity(&id).await {
-                self.remove_from_activity_queue(character_id, &id).await?;
-                continue;
-            } else {
-                filtered_ids.push(id)
+                    for m in e.departed.iter() {
+                        tell::update!(
+                            "Departed : {}",
+                            m.name.get_bungie_name()
+                        );
+                    }
+                }
+                Err(e) => tell::error!(
+                    "{}",
+                    format_error(
+                        &format!(
+                            "Error syncing roster for group : {}",
+                            clan.group_id
+                        ),
+                        e
+                    )
+                ),
             }
         }
-        ids = filtered_ids;
 
-        let total_available = ids.len() as u32;
-        let mut total_synced = 0;
-
-        use std::fmt::Write;
+        Ok(())
+    }
 
-        let pb = if Tell::is_active(TellLevel::Progress) {
-            ProgressBar::new(ids.len() as u64)
+    /// Stops tracking the group. Members will still be synced, and can be
+    /// removed with remove_player_from_sync.
+    pub async fn remove_group(&mut self, group_id: i64) -> Result<(), Error> {
+        let result = sqlx::query(
+            r#"
+            delete from "clan" where group_id = ?
+        "#,
+        )
+        .bind(group_id)
+        .execute(&mut self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::GroupNotFound {
+                description: format!("{} is not tracked.", group_id),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns all of the tracked groups
+    pub async fn retrieve_clans(&mut self) -> Result<Vec<Clan>, Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                group_id, name, last_synced
+            FROM
+                clan
+            ORDER BY
+                name
+        "#,
+        )
+        .fetch_all(&mut self.db)
+        .await?;
+
+        let mut out: Vec<Clan> = Vec::new();
+        for row in rows.iter() {
+            let last_synced: Option<String> = row.try_get("last_synced")?;
+            let last_synced = match last_synced {
+                Some(e) => {
+                    Some(DateTime::parse_from_rfc3339(&e)?.with_timezone(&Utc))
+                }
+                None => None,
+            };
+
+            out.push(Clan {
+                group_id: row.try_get("group_id")?,
+                name: row.try_get("name")?,
+                last_synced,
+            });
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the tracked group. If no group id is specified, returns the
+    /// only tracked group, or Error::GroupNotFound if more than one group is
+    /// tracked.
+    pub async fn retrieve_clan(
+        &mut self,
+        group_id: Option<i64>,
+    ) -> Result<Clan, Error> {
+        let mut clans = self.retrieve_clans().await?;
+
+        let index = match group_id {
+            Some(id) => clans.iter().position(|e| e.group_id == id),
+            None if clans.len() == 1 => Some(0),
+            None if clans.is_empty() => None,
+            None => {
+                return Err(Error::GroupNotFound {
+                    description: String::from(
+                        "Multiple groups are tracked. Please specify a group id.",
+                    ),
+                })
+            }
+        };
+
+        match index {
+            Some(e) => Ok(clans.swap_remove(e)),
+            None => Err(Error::GroupNotFound {
+                description: String::from(
+                    "Group is not tracked. Import it with dclisync --import-group.",
+                ),
+            }),
+        }
+    }
+
+    /// Returns the stored roster for the group, including members who have
+    /// departed if include_departed is true.
+    pub async fn retrieve_clan_members(
+        &mut self,
+        group_id: i64,
+        include_departed: bool,
+    ) -> Result<Vec<ClanMember>, Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                member.member_id, member.platform_id, member.display_name,
+                member.bungie_display_name, member.bungie_display_name_code,
+                clan_member.joined, clan_member.departed
+            FROM
+                clan_member
+            INNER JOIN
+                member on clan_member.member = member.member_id
+            WHERE
+                clan_member.clan = ? AND
+                (clan_member.departed IS NULL OR 1 = ?)
+            ORDER BY
+                member.bungie_display_name COLLATE NOCASE
+        "#,
+        )
+        .bind(group_id)
+        .bind(include_departed as i32)
+        .fetch_all(&mut self.db)
+        .await?;
+
+        let mut out: Vec<ClanMember> = Vec::new();
+        for row in rows.iter() {
+            let joined: String = row.try_get("joined")?;
+            let departed: Option<String> = row.try_get("departed")?;
+            let departed = match departed {
+                Some(e) => {
+                    Some(DateTime::parse_from_rfc3339(&e)?.with_timezone(&Utc))
+                }
+                None => None,
+            };
+
+            out.push(ClanMember {
+                member: self.parse_member_row(row)?,
+                joined: DateTime::parse_from_rfc3339(&joined)?
+                    .with_timezone(&Utc),
+                departed,
+            });
+        }
+
+        Ok(out)
+    }
+
+    /// Syncs the member, along with all of the other Destiny memberships
+    /// linked to the member's Bungie.net account
+    pub async fn sync_member(
+        &mut self,
+        member: &Member,
+    ) -> Result<SyncResult, Error> {
+        let memberships = match self.sync_account(member).await {
+            Ok(e) => e,
+            Err(e) => {
+                //we can still sync the member without the linked memberships
+                tell::verbose!("Could not retrieve linked memberships : {}", e);
+                vec![member.clone()]
+            }
+        };
+
+        tell::update!(
+            "CHECKING FOR NEW ACTIVITIES FOR {} (PUBLIC AND PRIVATE)",
+            member.name.get_bungie_name()
+        );
+        tell::progress!("This may take a few minutes depending on the number of activities.");
+
+        let mut out = SyncResult {
+            total_synced: 0,
+            total_available: 0,
+        };
+
+        let show_platform = memberships.len() > 1;
+        for m in memberships.iter() {
+            if show_platform {
+                tell::progress!(
+                    "{}",
+                    format!("{} membership", m.platform).to_uppercase()
+                );
+            }
+
+            match self.sync_membership(m).await {
+                Ok(e) => out = out + e,
+                Err(e) => {
+                    if m.id == member.id {
+                        return Err(e);
+                    }
+
+                    //an error syncing a linked membership (i.e. private)
+                    //shouldnt stop the primary membership from syncing
+                    tell::error!(
+                        "{}",
+                        format_error(
+                            &format!(
+                                "Error syncing linked {} membership.",
+                                m.platform
+                            ),
+                            e
+                        )
+                    );
+                }
+            }
+        }
+
+        self.update_sync_entry(&member.id).await?;
+
+        Ok(out)
+    }
+
+    /// Retrieves the Destiny memberships linked to the member's Bungie.net
+    /// account, and stores them along with the account and its primary
+    /// membership. Returns the memberships to sync, starting with the member.
+    async fn sync_account(
+        &mut self,
+        member: &Member,
+    ) -> Result<Vec<Member>, Error> {
+        let linked = self
+            .api_interface
+            .retrieve_linked_profiles(&member.id, &member.platform)
+            .await?;
+
+        let account_id = match &linked.bnet_membership {
+            Some(e) => e.membership_id,
+            None => return Ok(vec![member.clone()]),
+        };
+
+        let primary_id = match linked.primary_profile() {
+            Some(e) => e.membership_id,
+            None => member.id,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT into "account" ("account_id", "primary_member") VALUES (?, ?)
+            ON CONFLICT(account_id) DO UPDATE
+            set primary_member = ?
+        "#,
+        )
+        .bind(account_id)
+        .bind(primary_id)
+        .bind(primary_id)
+        .execute(&mut self.db)
+        .await?;
+
+        let mut out: Vec<Member> = vec![member.clone()];
+        for profile in linked.profiles.iter() {
+            let m = profile.to_user_info_card().to_member();
+            self.insert_member(&m).await?;
+
+            sqlx::query(
+                r#"
+                UPDATE "member" set account = ? where member_id = ?
+            "#,
+            )
+            .bind(account_id)
+            .bind(m.id)
+            .execute(&mut self.db)
+            .await?;
+
+            if m.id != member.id {
+                tell::verbose!(
+                    "Found linked {} membership : {}",
+                    m.platform,
+                    m.id
+                );
+                out.push(m);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// TODO currently no way to sync old / delete characters. would be easy to
+    /// add by just moving the character sync into its own api sync_character(id, class_type)
+    /// but not going to worry about it unless someone requests it
+    /// retrieves and stores activity details for ids in activity queue
+    async fn sync_membership(
+        &mut self,
+        member: &Member,
+    ) -> Result<SyncResult, Error> {
+        //Note, we need this call in case the user deletes and creates a new character
+        //https://www.bungie.net/Platform/Destiny2/1/Profile/4611686018429783292/?components=100,200
+        let player_info = self
+            .api_interface
+            .get_player_info(&member.id, &member.platform)
+            .await?;
+
+        //TODO: Check is core issue has been fixed
+        //Note, if this profile call fails, then we dont sync user. However, we could
+        //capture and then just sync the characters we already have.
+        //This issue is causing the sync to fail for some users:
+        //https://twitter.com/BungieHelp/status/1595515590160306176?s=20&t=QR7Rs7weMwzTfO2YtTArSg
+        //https://github.com/Bungie-net/api/issues/1738
+
+        let characters = player_info.characters;
+
+        let m = player_info.user_info.to_member();
+        self.insert_member_name(&m, &Utc::now()).await?;
+        self.insert_member(&m).await?;
+
+        let mut total_synced = 0;
+        let mut total_in_queue = 0;
+
+        for c in characters.characters {
+            let character_id = &c.id;
+            self.insert_character(&c.id, &c.class_type, member).await?;
+            self.insert_character_snapshot(&c).await?;
+            self.sync_character_combat_rating(member, &c.id).await;
+            tell::progress!("{}", format!("[{}]", c.class_type).to_uppercase());
+
+            //these calls could be a little more general purpose by taking api ids and not db ids.
+            //however, passing the db ids, lets us optimize a lot of the sql, and avoid
+            //some extra calls to the DB
+
+            let a = self.sync_activities(character_id).await?;
+
+            let _b = self
+                .update_activity_queue(
+                    &member.id,
+                    character_id,
+                    &member.platform,
+                )
+                .await?;
+
+            let c = self.sync_activities(character_id).await?;
+
+            total_synced += a.total_synced + c.total_synced;
+            total_in_queue += (a.total_available + c.total_available)
+                - (a.total_synced + c.total_synced);
+        }
+
+        Ok(SyncResult {
+            total_synced,
+            total_available: total_in_queue,
+        })
+    }
+
+    /// download results from ids in queue, and return number of items synced
+    async fn sync_activities(
+        &mut self,
+        character_id: &i64,
+    ) -> Result<SyncResult, Error> {
+        let mut ids: Vec<i64> = Vec::new();
+
+        //This is to scope rows, so the mutable borrow of self goes out of scope
+        {
+            let mut rows = sqlx::query(
+                r#"
+                    SELECT
+                        "activity_id"
+                    FROM
+                        "activity_queue"
+                    WHERE
+                        character = ? AND synced = 0
+                "#,
+            )
+            .bind(character_id)
+            .fetch(&mut self.db);
+
+            //todo: could probably get this without looping through entire set
+            while let Some(row) = rows.try_next().await? {
+                let activity_id: i64 = row.try_get("activity_id")?;
+                ids.push(activity_id);
+            }
+        };
+
+        if ids.is_empty() {
+            return Ok(SyncResult {
+                total_available: 0,
+                total_synced: 0,
+            });
+        }
+
+        let mut filtered_ids = Vec::new();
+
+        //remove ids to sync for activities we have already synced
+        for id in ids {
+            if self.has_activity(&id).await {
+                self.remove_from_activity_queue(character_id, &id).await?;
+                continue;
+            } else {
+                filtered_ids.push(id)
+            }
+        }
+        ids = filtered_ids;
+
+        let total_available = ids.len() as u32;
+        let mut total_synced = 0;
+
+        use std::fmt::Write;
+
+        let pb = if Tell::is_active(TellLevel::Progress) {
+            ProgressBar::new(ids.len() as u64)
         } else {
             ProgressBar::hidden()
         };
@@ -1159,12 +1879,20 @@ impl ActivityStoreInterface {
 
         for entry in &data.entries {
             //todo: not sure if we should use membership type of crosssave override
-            let member = &entry.player.user_info.to_member();
+            let member = &mut entry.player.user_info.to_member();
+            self.insert_member_name(member, &data.period).await?;
+
+            //activities are not synced in order, so the member is stored
+            //with the most recently seen name, which may not be this one
+            if let Some(e) = self.retrieve_latest_member_name(&member.id).await?
+            {
+                member.name = e;
+            }
             self.insert_member(member).await?;
 
             let class_type = CharacterClass::from_hash(entry.player.class_hash);
 
-            self.insert_character(&entry.character_id, &class_type, &member)
+            self.insert_character(&entry.character_id, &class_type, member)
                 .await?;
 
             self._insert_character_activity_stats(
@@ -1440,6 +2168,132 @@ impl ActivityStoreInterface {
         Ok(())
     }
 
+    /// Records that the member had their current name at the specified time,
+    /// extending the first and last seen times for the name.
+    async fn insert_member_name(
+        &mut self,
+        member: &Member,
+        seen: &DateTime<Utc>,
+    ) -> Result<(), Error> {
+        //data is often missing from the API, and we cant track a name
+        //without the name and code
+        if member.name.bungie_display_name.is_none()
+            || member.name.bungie_display_name_code.is_none()
+        {
+            return Ok(());
+        }
+
+        let seen = seen.to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT into "member_name" ("member", "display_name", "bungie_display_name", "bungie_display_name_code", "first_seen", "last_seen") VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(member, bungie_display_name, bungie_display_name_code) DO UPDATE
+            set
+                display_name = COALESCE(excluded.display_name, display_name),
+                first_seen = min(first_seen, excluded.first_seen),
+                last_seen = max(last_seen, excluded.last_seen)
+        "#,
+        )
+        .bind(member.id)
+        .bind(&member.name.display_name)
+        .bind(&member.name.bungie_display_name)
+        .bind(&member.name.bungie_display_name_code)
+        .bind(&seen)
+        .bind(&seen)
+        .execute(&mut self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the name the member was most recently seen with
+    async fn retrieve_latest_member_name(
+        &mut self,
+        member_id: &i64,
+    ) -> Result<Option<PlayerName>, Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                display_name, bungie_display_name, bungie_display_name_code
+            FROM
+                member_name
+            WHERE
+                member = ?
+            ORDER BY
+                last_seen DESC
+            LIMIT 1
+        "#,
+        )
+        .bind(member_id)
+        .fetch_optional(&mut self.db)
+        .await?;
+
+        let row = match row {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+
+        Ok(Some(PlayerName {
+            display_name: row.try_get("display_name")?,
+            bungie_display_name: row.try_get("bungie_display_name")?,
+            bungie_display_name_code: row
+                .try_get("bungie_display_name_code")?,
+        }))
+    }
+
+    /// Returns the names that the players in the activity had at the time
+    /// of the activity, keyed by member id.
+    async fn retrieve_activity_member_names(
+        &mut self,
+        activity_id: i64,
+        period: &str,
+    ) -> Result<HashMap<i64, PlayerName>, Error> {
+        //if a player changed their name and then changed it back, the ranges
+        //will overlap, so the shortest range is the most specific
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                member_name.member,
+                member_name.display_name,
+                member_name.bungie_display_name,
+                member_name.bungie_display_name_code
+            FROM
+                member_name
+            INNER JOIN
+                character on character.member = member_name.member
+            INNER JOIN
+                character_activity_stats on character_activity_stats.character = character.character_id
+            WHERE
+                character_activity_stats.activity = ? AND
+                member_name.first_seen <= ? AND
+                member_name.last_seen >= ?
+            ORDER BY
+                julianday(member_name.last_seen) - julianday(member_name.first_seen) DESC
+        "#,
+        )
+        .bind(activity_id)
+        .bind(period)
+        .bind(period)
+        .fetch_all(&mut self.db)
+        .await?;
+
+        //rows are sorted longest range first, so shorter ranges replace them
+        let mut out: HashMap<i64, PlayerName> = HashMap::new();
+        for row in rows.iter() {
+            out.insert(
+                row.try_get("member")?,
+                PlayerName {
+                    display_name: row.try_get("display_name")?,
+                    bungie_display_name: row.try_get("bungie_display_name")?,
+                    bungie_display_name_code: row
+                        .try_get("bungie_display_name_code")?,
+                },
+            );
+        }
+
+        Ok(out)
+    }
+
     async fn has_member(&mut self, member: &Member) -> bool {
         let out = sqlx::query(
             r#"
@@ -1504,179 +2358,628 @@ impl ActivityStoreInterface {
         Ok(())
     }
 
-    async fn get_max_activity_id(
+    /// stores the current power, stats and time played for the character, if
+    /// they have changed since the last snapshot was stored
+    async fn insert_character_snapshot(
+        &mut self,
+        character: &CharacterData,
+    ) -> Result<(), Error> {
+        if let Some(e) =
+            self.retrieve_last_character_snapshot(&character.id).await?
+        {
+            if !e.has_changed(character) {
+                return Ok(());
+            }
+        }
+
+        let stats = &character.stats;
+        sqlx::query(
+            r#"
+            INSERT into "character_snapshot" ("character", "created", "light_level", "mobility", "resilience", "recovery", "discipline", "intellect", "strength", "emblem_hash", "minutes_played_total", "date_last_played")
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(character.id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(stats.power)
+        .bind(stats.mobility)
+        .bind(stats.resilience)
+        .bind(stats.recovery)
+        .bind(stats.discipline)
+        .bind(stats.intellect)
+        .bind(stats.strength)
+        .bind(character.emblem_hash)
+        .bind(character.minutes_played_total)
+        .bind(character.date_last_played.to_rfc3339())
+        .execute(&mut self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn retrieve_last_character_snapshot(
         &mut self,
         character_id: &i64,
-        mode: &Mode,
-    ) -> Result<i64, Error> {
-        let rows = sqlx::query(
+    ) -> Result<Option<CharacterSnapshot>, Error> {
+        let row = sqlx::query(
             r#"
             SELECT
-                activity_queue.activity_id as max_activity_id
+                character_snapshot.*,
+                character.class as character_class
             FROM
-                "activity_queue"
+                character_snapshot
             INNER JOIN
-                activity ON activity_queue.activity_id = activity.activity_id,
-                character_activity_stats ON character_activity_stats.activity = activity.activity_id,
-                character on character_activity_stats.character = character.character_id,
-                modes ON modes.activity = activity.activity_id and modes.mode = ?
+                character on character_snapshot.character = character.character_id
             WHERE
-                character_activity_stats.character = ? AND
-                activity_queue.character = ?
-            ORDER BY activity.period DESC LIMIT 1
+                character_snapshot.character = ?
+            ORDER BY
+                created DESC
+            LIMIT 1
         "#,
         )
-        .bind(mode.as_id())
-        .bind(character_id)
         .bind(character_id)
-        .fetch_all(&mut self.db)
+        .fetch_optional(&mut self.db)
         .await?;
 
-        if rows.is_empty() {
-            return Ok(0);
+        match row {
+            Some(e) => Ok(Some(self.parse_character_snapshot_row(&e)?)),
+            None => Ok(None),
         }
-
-        let row = &rows[0];
-        let activity_id: i64 = row.try_get("max_activity_id")?;
-
-        Ok(activity_id)
     }
 
-    pub async fn retrieve_activity(
+    /// Returns the stored character snapshot closest to the specified date /
+    /// time, or None if no snapshots have been stored for the character.
+    pub async fn retrieve_closest_character_snapshot(
         &mut self,
-        activity_id: i64,
-        manifest: &mut ManifestInterface,
-    ) -> Result<CrucibleActivity, Error> {
-        let activity_row = match sqlx::query(
+        character_id: &i64,
+        date_time: &DateTime<Utc>,
+    ) -> Result<Option<CharacterSnapshot>, Error> {
+        let row = sqlx::query(
             r#"
             SELECT
-                activity.activity_id,
-                activity.period,
-                activity.mode as activity_mode,
-                activity.director_activity_hash,
-                activity.reference_id,
-                activity.platform
+                character_snapshot.*,
+                character.class as character_class
             FROM
-                activity
+                character_snapshot
             INNER JOIN
-                character_activity_stats on character_activity_stats.activity = activity.activity_id,
-                character on character_activity_stats.character = character.character_id,
-                member on character.member = member.member_id
+                character on character_snapshot.character = character.character_id
             WHERE
-                activity_id = ?
+                character_snapshot.character = ?
             ORDER BY
-                period DESC LIMIT 1
-            "#,
+                abs(julianday(created) - julianday(?))
+            LIMIT 1
+        "#,
         )
-        .bind(activity_id)
-        .fetch_one(&mut self.db)
-        .await
-        {
-            Ok(e) => e,
-            Err(e) => match e {
-                sqlx::Error::RowNotFound => {
-                    return Err(Error::ActivityNotFound);
-                }
-                _ => {
-                    return Err(Error::from(e));
-                }
-            },
-        };
+        .bind(character_id)
+        .bind(date_time.to_rfc3339())
+        .fetch_optional(&mut self.db)
+        .await?;
 
-        let crucible_activity =
-            self.populate_activity_data(&activity_row, manifest).await?;
-        Ok(crucible_activity)
+        match row {
+            Some(e) => Ok(Some(self.parse_character_snapshot_row(&e)?)),
+            None => Ok(None),
+        }
     }
 
-    pub async fn retrieve_last_activity(
+    //stores the all pvp combat rating for one of the member's characters.
+    //failures are logged but don't stop the sync, since the rating is not
+    //required for the activity data
+    async fn sync_character_combat_rating(
         &mut self,
         member: &Member,
-        character_selection: &CharacterClassSelection,
-        mode: &Mode,
-        manifest: &mut ManifestInterface,
-    ) -> Result<CrucibleActivity, Error> {
-        let member_id = &member.id;
+        character_id: &i64,
+    ) {
+        let mode = Mode::AllPvP;
+        let max_age = Duration::hours(COMBAT_RATING_MAX_AGE_HOURS);
 
-        let class_id = self
-            .get_sql_character_class_id(member, character_selection)
-            .await?;
+        match self.retrieve_last_combat_rating(character_id, &mode).await {
+            Ok(Some(e)) if Utc::now() - e.created < max_age => return,
+            Ok(_) => (),
+            Err(e) => {
+                tell::verbose!("Could not retrieve combat rating : {}", e);
+                return;
+            }
+        };
 
-        //TODO: Can remove some of the member clauses below
-        let activity_row = match sqlx::query(
-                r#"
-                SELECT
-                    activity.activity_id,
-                    activity.period,
-                    activity.mode as activity_mode,
-                    activity.director_activity_hash,
-                    activity.reference_id,
-                    activity.platform
-                FROM
-                    activity
-                INNER JOIN
-                    character_activity_stats on character_activity_stats.activity = activity.activity_id,
-                    character on character_activity_stats.character = character.character_id,
-                    member on character.member = member.member_id AND member.member_id = ?
-                WHERE
-                    exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
-                    (character.class = ? OR 4 = ?) 
-                ORDER BY
-                    period DESC LIMIT 1
-                "#,
+        let result = self
+            .api_interface
+            .retrieve_alltime_crucible_stats(
+                &member.id,
+                character_id,
+                &member.platform,
+                &mode,
             )
-            .bind(member_id.to_string())
-            .bind(mode.as_id().to_string())
-            .bind(class_id)
-            .bind(class_id)
-            .fetch_one(&mut self.db)
-            .await
-            {
-                Ok(e) => e,
-                Err(e) => match e {
-                    sqlx::Error::RowNotFound => {
-                        return Err(Error::ActivityNotFound);
-                    }
-                    _ => {
-                        return Err(Error::from(e));
-                    }
-                },
-            };
-
-        let crucible_activity =
-            self.populate_activity_data(&activity_row, manifest).await?;
-        Ok(crucible_activity)
+            .await;
+
+        match result {
+            Ok(Some(e)) => {
+                if let Err(e) = self
+                    .insert_combat_rating(character_id, &mode, e.combat_rating)
+                    .await
+                {
+                    tell::verbose!("Could not store combat rating : {}", e);
+                }
+            }
+            Ok(None) => (),
+            Err(e) => {
+                tell::verbose!("Could not retrieve combat rating : {}", e);
+            }
+        };
     }
 
-    async fn populate_activity_data(
+    async fn insert_combat_rating(
         &mut self,
-        activity_row: &sqlx::sqlite::SqliteRow,
-        manifest: &mut ManifestInterface,
-    ) -> Result<CrucibleActivity, Error> {
-        let activity_id: i64 = activity_row.try_get("activity_id")?;
+        character_id: &i64,
+        mode: &Mode,
+        rating: f32,
+    ) -> Result<(), Error> {
+        sqlx::query(
+            r#"
+            INSERT into "combat_rating" ("character", "mode", "created", "rating")
+            VALUES (?, ?, ?, ?)
+        "#,
+        )
+        .bind(character_id)
+        .bind(mode.as_id())
+        .bind(Utc::now().to_rfc3339())
+        .bind(rating)
+        .execute(&mut self.db)
+        .await?;
 
-        let team_rows = sqlx::query(
+        Ok(())
+    }
+
+    async fn retrieve_last_combat_rating(
+        &mut self,
+        character_id: &i64,
+        mode: &Mode,
+    ) -> Result<Option<CombatRating>, Error> {
+        let row = sqlx::query(
             r#"
             SELECT
                 *
             FROM
-                team_result
+                combat_rating
             WHERE
-                activity = ?
-            "#,
+                character = ? AND
+                mode = ?
+            ORDER BY
+                created DESC
+            LIMIT 1
+        "#,
         )
-        .bind(activity_id)
-        .fetch_all(&mut self.db)
+        .bind(character_id)
+        .bind(mode.as_id())
+        .fetch_optional(&mut self.db)
         .await?;
 
-        let mut teams: HashMap<i32, Team> = HashMap::new();
+        match row {
+            Some(e) => Ok(Some(self.parse_combat_rating_row(&e)?)),
+            None => Ok(None),
+        }
+    }
 
-        let mut team_names = vec![
-            "Alpha".to_string(),
-            "Bravo".to_string(),
-            "Charlie".to_string(),
-            "Delta".to_string(),
-            "Echo".to_string(),
-            "Foxtrot".to_string(),
+    //returns the last rating stored at or before date_time, or if there
+    //isnt one, the first rating stored after it. Ratings for the mode are
+    //preferred, falling back to the all pvp ratings stored when syncing.
+    async fn retrieve_closest_combat_rating(
+        &mut self,
+        character_id: &i64,
+        mode: &Mode,
+        date_time: &DateTime<Utc>,
+    ) -> Result<Option<CombatRating>, Error> {
+        let date_time = date_time.to_rfc3339();
+        let row = sqlx::query(
+            r#"
+            SELECT
+                *
+            FROM
+                combat_rating
+            WHERE
+                character = ? AND
+                mode IN (?, ?)
+            ORDER BY
+                mode = ? DESC,
+                julianday(created) <= julianday(?) DESC,
+                CASE
+                    WHEN julianday(created) <= julianday(?)
+                    THEN julianday(created)
+                    ELSE -julianday(created)
+                END DESC
+            LIMIT 1
+        "#,
+        )
+        .bind(character_id)
+        .bind(mode.as_id())
+        .bind(Mode::AllPvP.as_id())
+        .bind(mode.as_id())
+        .bind(&date_time)
+        .bind(&date_time)
+        .fetch_optional(&mut self.db)
+        .await?;
+
+        match row {
+            Some(e) => Ok(Some(self.parse_combat_rating_row(&e)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn parse_combat_rating_row(
+        &self,
+        row: &sqlx::sqlite::SqliteRow,
+    ) -> Result<CombatRating, Error> {
+        let mode: u32 = row.try_get_unchecked("mode")?;
+        let created: String = row.try_get_unchecked("created")?;
+        let rating: f64 = row.try_get_unchecked("rating")?;
+
+        Ok(CombatRating {
+            character_id: row.try_get_unchecked("character")?,
+            mode: Mode::from_id(mode)?,
+            created: DateTime::parse_from_rfc3339(&created)?
+                .with_timezone(&Utc),
+            rating: rating as f32,
+        })
+    }
+
+    /// Returns the current combat ratings for the players in the mode, keyed
+    /// by Player::calculate_hash.
+    ///
+    /// Stored ratings older than max_age are refreshed from the API and
+    /// stored. If a rating cannot be retrieved from the API, the last stored
+    /// rating is used. Players without a rating are not included.
+    pub async fn retrieve_combat_ratings(
+        &mut self,
+        players: &[&Player],
+        mode: &Mode,
+        max_age: &Duration,
+    ) -> Result<HashMap<u64, f32>, Error> {
+        let mut out: HashMap<u64, f32> = HashMap::new();
+        let mut stale: Vec<&Player> = Vec::new();
+
+        let now = Utc::now();
+        for p in players {
+            match self
+                .retrieve_last_combat_rating(&p.character_id, mode)
+                .await?
+            {
+                Some(e) => {
+                    out.insert(p.calculate_hash(), e.rating);
+                    if now - e.created >= *max_age {
+                        stale.push(p);
+                    }
+                }
+                None => stale.push(p),
+            }
+        }
+
+        if stale.is_empty() {
+            return Ok(out);
+        }
+
+        let mut futures = Vec::new();
+        for p in stale.iter() {
+            futures.push(self.api_interface.retrieve_alltime_crucible_stats(
+                &p.member_id,
+                &p.character_id,
+                &p.platform,
+                mode,
+            ));
+        }
+
+        let results = futures::future::join_all(futures).await;
+
+        for (p, r) in stale.iter().zip(results) {
+            match r {
+                Ok(Some(e)) => {
+                    self.insert_combat_rating(
+                        &p.character_id,
+                        mode,
+                        e.combat_rating,
+                    )
+                    .await?;
+                    out.insert(p.calculate_hash(), e.combat_rating);
+                }
+                Ok(None) => (),
+                Err(e) => {
+                    tell::verbose!(
+                        "Could not retrieve combat rating for {} : {}",
+                        p.name.get_bungie_name(),
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the stored combat ratings for the players in the mode at the
+    /// specified date / time, keyed by Player::calculate_hash.
+    ///
+    /// Uses the last rating stored at or before the date / time, or the
+    /// first one stored after it if there isn't one. If there is no rating
+    /// stored for the mode, the all pvp rating (which is stored for the
+    /// member's characters when syncing) is used.
+    ///
+    /// Ratings are not retrieved from the API, so players without a stored
+    /// rating are not included.
+    pub async fn retrieve_combat_ratings_at(
+        &mut self,
+        players: &[&Player],
+        mode: &Mode,
+        date_time: &DateTime<Utc>,
+    ) -> Result<HashMap<u64, f32>, Error> {
+        let mut out: HashMap<u64, f32> = HashMap::new();
+        for p in players {
+            if let Some(e) = self
+                .retrieve_closest_combat_rating(
+                    &p.character_id,
+                    mode,
+                    date_time,
+                )
+                .await?
+            {
+                out.insert(p.calculate_hash(), e.rating);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the character snapshots stored for the member within the time
+    /// period, sorted by character and then date.
+    pub async fn retrieve_character_snapshots(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+        time_period: &DateTimePeriod,
+    ) -> Result<Vec<CharacterSnapshot>, Error> {
+        let class_id = self
+            .get_sql_character_class_id(member, character_selection)
+            .await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                character_snapshot.*,
+                character.class as character_class
+            FROM
+                character_snapshot
+            INNER JOIN
+                character on character_snapshot.character = character.character_id
+            WHERE
+                character.member IN (SELECT value FROM json_each(?)) AND
+                (character.class = ? OR 4 = ?) AND
+                created > ? AND
+                created < ?
+            ORDER BY
+                character_snapshot.character, created ASC
+        "#,
+        )
+        .bind(membership_ids_json(member))
+        .bind(class_id)
+        .bind(class_id)
+        .bind(time_period.get_start().to_rfc3339())
+        .bind(time_period.get_end().to_rfc3339())
+        .fetch_all(&mut self.db)
+        .await?;
+
+        let mut out: Vec<CharacterSnapshot> = Vec::new();
+        for r in rows.iter() {
+            out.push(self.parse_character_snapshot_row(r)?);
+        }
+
+        Ok(out)
+    }
+
+    fn parse_character_snapshot_row(
+        &self,
+        row: &sqlx::sqlite::SqliteRow,
+    ) -> Result<CharacterSnapshot, Error> {
+        let class_id: u32 = row.try_get("character_class")?;
+
+        let created: String = row.try_get("created")?;
+        let created =
+            DateTime::parse_from_rfc3339(&created)?.with_timezone(&Utc);
+
+        let date_last_played: String = row.try_get("date_last_played")?;
+        let date_last_played = DateTime::parse_from_rfc3339(&date_last_played)?
+            .with_timezone(&Utc);
+
+        let stats = CharacterStatsData {
+            power: row.try_get("light_level")?,
+            mobility: row.try_get("mobility")?,
+            resilience: row.try_get("resilience")?,
+            recovery: row.try_get("recovery")?,
+            discipline: row.try_get("discipline")?,
+            intellect: row.try_get("intellect")?,
+            strength: row.try_get("strength")?,
+        };
+
+        Ok(CharacterSnapshot {
+            character_id: row.try_get("character")?,
+            class_type: CharacterClass::from_id(class_id),
+            created,
+            stats,
+            emblem_hash: row.try_get("emblem_hash")?,
+            minutes_played_total: row.try_get("minutes_played_total")?,
+            date_last_played,
+        })
+    }
+
+    async fn get_max_activity_id(
+        &mut self,
+        character_id: &i64,
+        mode: &Mode,
+    ) -> Result<i64, Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                activity_queue.activity_id as max_activity_id
+            FROM
+                "activity_queue"
+            INNER JOIN
+                activity ON activity_queue.activity_id = activity.activity_id,
+                character_activity_stats ON character_activity_stats.activity = activity.activity_id,
+                character on character_activity_stats.character = character.character_id,
+                modes ON modes.activity = activity.activity_id and modes.mode = ?
+            WHERE
+                character_activity_stats.character = ? AND
+                activity_queue.character = ?
+            ORDER BY activity.period DESC LIMIT 1
+        "#,
+        )
+        .bind(mode.as_id())
+        .bind(character_id)
+        .bind(character_id)
+        .fetch_all(&mut self.db)
+        .await?;
+
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let row = &rows[0];
+        let activity_id: i64 = row.try_get("max_activity_id")?;
+
+        Ok(activity_id)
+    }
+
+    pub async fn retrieve_activity(
+        &mut self,
+        activity_id: i64,
+        manifest: &mut ManifestInterface,
+    ) -> Result<CrucibleActivity, Error> {
+        let activity_row = match sqlx::query(
+            r#"
+            SELECT
+                activity.activity_id,
+                activity.period,
+                activity.mode as activity_mode,
+                activity.director_activity_hash,
+                activity.reference_id,
+                activity.platform,
+                exists (select 1 from team_result where activity = activity.activity_id) as has_teams
+            FROM
+                activity
+            INNER JOIN
+                character_activity_stats on character_activity_stats.activity = activity.activity_id,
+                character on character_activity_stats.character = character.character_id,
+                member on character.member = member.member_id
+            WHERE
+                activity_id = ?
+            ORDER BY
+                period DESC LIMIT 1
+            "#,
+        )
+        .bind(activity_id)
+        .fetch_one(&mut self.db)
+        .await
+        {
+            Ok(e) => e,
+            Err(e) => match e {
+                sqlx::Error::RowNotFound => {
+                    return Err(Error::ActivityNotFound);
+                }
+                _ => {
+                    return Err(Error::from(e));
+                }
+            },
+        };
+
+        let crucible_activity =
+            self.populate_activity_data(&activity_row, manifest).await?;
+        Ok(crucible_activity)
+    }
+
+    pub async fn retrieve_last_activity(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+        mode: &Mode,
+        manifest: &mut ManifestInterface,
+    ) -> Result<CrucibleActivity, Error> {
+        let class_id = self
+            .get_sql_character_class_id(member, character_selection)
+            .await?;
+
+        //TODO: Can remove some of the member clauses below
+        let activity_row = match sqlx::query(
+                r#"
+                SELECT
+                    activity.activity_id,
+                    activity.period,
+                    activity.mode as activity_mode,
+                    activity.director_activity_hash,
+                    activity.reference_id,
+                    activity.platform,
+                    exists (select 1 from team_result where activity = activity.activity_id) as has_teams
+                FROM
+                    activity
+                INNER JOIN
+                    character_activity_stats on character_activity_stats.activity = activity.activity_id,
+                    character on character_activity_stats.character = character.character_id,
+                    member on character.member = member.member_id AND member.member_id IN (SELECT value FROM json_each(?))
+                WHERE
+                    exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                    (character.class = ? OR 4 = ?) 
+                ORDER BY
+                    period DESC LIMIT 1
+                "#,
+            )
+            .bind(membership_ids_json(member))
+            .bind(mode.as_id().to_string())
+            .bind(class_id)
+            .bind(class_id)
+            .fetch_one(&mut self.db)
+            .await
+            {
+                Ok(e) => e,
+                Err(e) => match e {
+                    sqlx::Error::RowNotFound => {
+                        return Err(Error::ActivityNotFound);
+                    }
+                    _ => {
+                        return Err(Error::from(e));
+                    }
+                },
+            };
+
+        let crucible_activity =
+            self.populate_activity_data(&activity_row, manifest).await?;
+        Ok(crucible_activity)
+    }
+
+    async fn populate_activity_data(
+        &mut self,
+        activity_row: &sqlx::sqlite::SqliteRow,
+        manifest: &mut ManifestInterface,
+    ) -> Result<CrucibleActivity, Error> {
+        let activity_id: i64 = activity_row.try_get("activity_id")?;
+
+        let team_rows = sqlx::query(
+            r#"
+            SELECT
+                *
+            FROM
+                team_result
+            WHERE
+                activity = ?
+            "#,
+        )
+        .bind(activity_id)
+        .fetch_all(&mut self.db)
+        .await?;
+
+        let mut teams: HashMap<i32, Team> = HashMap::new();
+
+        let mut team_names = vec![
+            "Alpha".to_string(),
+            "Bravo".to_string(),
+            "Charlie".to_string(),
+            "Delta".to_string(),
+            "Echo".to_string(),
+            "Foxtrot".to_string(),
         ];
         team_names.reverse();
 
@@ -1684,150 +2987,746 @@ impl ActivityStoreInterface {
             let standing: i32 = t.try_get("standing")?;
             let standing = Standing::from_value(standing as u32);
 
-            let id: i32 = t.try_get("team_id")?;
-            let score: u32 = t.try_get("score")?;
+            let id: i32 = t.try_get("team_id")?;
+            let score: u32 = t.try_get("score")?;
+
+            let player_performances: Vec<CruciblePlayerPerformance> =
+                Vec::new();
+
+            let display_name = team_names.pop().unwrap_or_default();
+
+            let team = Team {
+                id,
+                standing,
+                score,
+                player_performances,
+                display_name,
+            };
+
+            teams.insert(id, team);
+        }
+
+        //Rumble wont have any teams, so we put all items in one team
+        //this also covered any bugs where no teams are specified
+        let mut no_teams = false;
+        if teams.is_empty() {
+            let display_name = team_names.pop().unwrap_or_default();
+
+            let team = Team {
+                standing: Standing::Unknown,
+                id: NO_TEAMS_INDEX,
+                score: 0,
+                player_performances: Vec::new(),
+                display_name,
+            };
+
+            teams.insert(NO_TEAMS_INDEX, team);
+            no_teams = true;
+        }
+
+        //TODO: need to account for character and member, need to join both
+        let character_rows = sqlx::query(
+            r#"
+            SELECT
+                *,
+                character_activity_stats.id as character_activity_stats_index
+            FROM
+                character_activity_stats
+            INNER JOIN
+                character on character_activity_stats.character = character.character_id,
+                member on character.member = member.member_id
+            WHERE
+                activity = ?
+            "#,
+        )
+        .bind(activity_id)
+        .fetch_all(&mut self.db)
+        .await?;
+
+        //show the names players had at the time of the activity
+        let period: String = activity_row.try_get_unchecked("period")?;
+        let names = self
+            .retrieve_activity_member_names(activity_id, &period)
+            .await?;
+
+        for c_row in character_rows {
+            let stats = self.parse_crucible_stats(manifest, &c_row).await?;
+
+            let mut player = self.parse_player(&c_row).await?;
+            if let Some(e) = names.get(&player.member_id) {
+                player.name = e.clone();
+            }
+
+            let cpp = CruciblePlayerPerformance { player, stats };
+
+            let index = if no_teams {
+                NO_TEAMS_INDEX
+            } else {
+                cpp.stats.team
+            };
+
+            match teams.get_mut(&index) {
+                Some(e) => e.player_performances.push(cpp),
+                None => {
+                    tell::update!("Invalid Team ID ({}) : Skipping", &index)
+                }
+            }
+        }
+
+        let details = self.parse_activity(manifest, activity_row).await?;
+
+        Ok(CrucibleActivity { details, teams })
+    }
+
+    //returns the last played class for the specified member for activities
+    //that have been synced
+    pub async fn retrieve_last_active_class(
+        &mut self,
+        member: &Member,
+    ) -> Result<CharacterClass, Error> {
+        let result = sqlx::query(
+            r#"
+        SELECT
+            character.class as character_class
+        FROM
+            character_activity_stats
+        INNER JOIN
+            activity ON character_activity_stats.activity = activity.activity_id,
+            character on character_activity_stats.character = character.character_id,
+            member on member.member_id = character.member
+        WHERE 
+            member.member_id IN (SELECT value FROM json_each(?))
+        ORDER BY
+            activity.period DESC
+            limit 1
+        "#,
+        )
+        .bind(membership_ids_json(member))
+        .fetch_one(&mut self.db)
+        .await?;
+
+        let class_id: u32 = result.try_get("character_class")?;
+
+        Ok(CharacterClass::from_id(class_id))
+    }
+
+    //returns the id used in the db queries for the specified class
+    //for ALL returns 4, and last_active finds last active
+    pub async fn get_sql_character_class_id(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+    ) -> Result<u32, Error> {
+        let class_id =
+            if character_selection == &CharacterClassSelection::LastActive {
+                let character_class =
+                    self.retrieve_last_active_class(member).await?;
+                character_class.as_id()
+            } else {
+                character_selection.as_id()
+            };
+
+        Ok(class_id)
+    }
+
+    pub async fn retrieve_activities_summary(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+        mode: &Mode,
+        time_period: &DateTimePeriod,
+    ) -> Result<Option<PlayerActivitiesSummary>, Error> {
+        let restrict_mode_id = if mode.is_private() {
+            -1
+        } else {
+            //if not private, then we dont include any results that are private
+            Mode::PrivateMatchesAll.as_id() as i32
+        };
+
+        let class_id = self
+            .get_sql_character_class_id(member, character_selection)
+            .await?;
+
+        let summary = sqlx::query_as::<_, PlayerActivitiesSummary>(r#"
+        SELECT
+            count(*) as total_activities,
+            COALESCE(sum(time_played_seconds),0) as time_played_seconds,
+            COALESCE(sum(character_activity_stats.standing = 0),0) as wins,
+            COALESCE(sum( character_activity_stats.completion_reason = 4),0) as completion_reason_mercy,
+            COALESCE(sum(completed),0) as completed,
+            COALESCE(sum(assists),0) as assists,
+            COALESCE(sum(character_activity_stats.kills),0) as kills,
+            COALESCE(sum(deaths),0) as deaths,
+            COALESCE(sum(opponents_defeated),0) as opponents_defeated,
+            COALESCE(sum(weapon_kills_grenade),0) as grenade_kills,
+            COALESCE(sum(weapon_kills_melee),0) as melee_kills,
+            COALESCE(sum(weapon_kills_super),0) as super_kills,
+            COALESCE(sum(weapon_kills_ability),0) as ability_kills,
+            COALESCE(sum(character_activity_stats.precision_kills),0) as precision,
+            COALESCE(max(assists),0) as highest_assists,
+            COALESCE(max(character_activity_stats.kills),0) as highest_kills,
+            COALESCE(max(deaths),0) as highest_deaths,
+            COALESCE(max(opponents_defeated),0) as highest_opponents_defeated,
+            COALESCE(max(weapon_kills_grenade),0) as highest_grenade_kills,
+            COALESCE(max(weapon_kills_melee),0) as highest_melee_kills,
+            COALESCE(max(weapon_kills_super),0) as highest_super_kills,
+            COALESCE(max(weapon_kills_ability),0) as highest_ability_kills,
+            COALESCE(max(
+                cast(character_activity_stats.kills as real) 
+                / 
+                cast(
+                    IFNULL(
+                        NULLIF(character_activity_stats.deaths, 0), 
+                    1) as real
+                )),0.0)
+            as highest_kills_deaths_ratio,
+            COALESCE(max(
+                cast(character_activity_stats.kills + character_activity_stats.assists * 0.5 as real) 
+                / 
+                cast(
+                    IFNULL(
+                        NULLIF(character_activity_stats.deaths, 0), 
+                    1) as real
+                )),0.0)
+            as highest_kills_deaths_assists_ratio,
+            COALESCE(max(
+                cast((character_activity_stats.kills + character_activity_stats.assists) as real) 
+                / 
+                cast(
+                    IFNULL(
+                        NULLIF(character_activity_stats.deaths, 0), 
+                    1) as real
+                )),0.0)
+            as highest_efficiency
+        FROM
+            character_activity_stats
+        INNER JOIN
+            activity ON character_activity_stats.activity = activity.activity_id,
+            character on character_activity_stats.character = character.character_id,
+            member on member.member_id = character.member
+        WHERE
+            member.member_id IN (SELECT value FROM json_each(?)) AND
+            (character.class = ? OR 4 = ?) AND
+            period > ? AND
+            period < ? AND
+            exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+            not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+        "#,
+        )
+        .bind(membership_ids_json(member))
+        .bind(class_id)
+        .bind(class_id)
+        .bind(time_period.get_start().to_rfc3339())
+        .bind(time_period.get_end().to_rfc3339())
+        .bind(mode.as_id())
+        .bind(restrict_mode_id)
+        .fetch_one(&mut self.db)
+        .await?;
+
+        Ok(Some(summary))
+    }
 
-            let player_performances: Vec<CruciblePlayerPerformance> =
-                Vec::new();
+    pub async fn retrieve_activities_since(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+        mode: &Mode,
+        time_period: &DateTimePeriod,
+        manifest: &mut ManifestInterface,
+    ) -> Result<Option<Vec<CruciblePlayerActivityPerformance>>, Error> {
+        //if mode if private, we dont restrict results
+        let restrict_mode_id = if mode.is_private() {
+            -1
+        } else {
+            //if not private, then we dont include any results that are private
+            Mode::PrivateMatchesAll.as_id() as i32
+        };
 
-            let display_name = team_names.pop().unwrap_or_default();
+        let class_id = self
+            .get_sql_character_class_id(member, character_selection)
+            .await?;
 
-            let team = Team {
-                id,
-                standing,
-                score,
-                player_performances,
-                display_name,
-            };
+        //todo: note, this might break if user has multiple characters of the same
+        //class. need to test
+        let activity_rows = sqlx::query(
+            r#"
+            SELECT
+                *,
+                activity.mode as activity_mode,
+                exists (select 1 from team_result where activity = activity.activity_id) as has_teams,
+                character_activity_stats.id as character_activity_stats_index  
+            FROM
+                character_activity_stats
+            INNER JOIN
+                activity ON character_activity_stats.activity = activity.activity_id,
+                character on character_activity_stats.character = character.character_id,
+                member on member.member_id = character.member
+            WHERE
+                member.member_id IN (SELECT value FROM json_each(?)) AND
+                (character.class = ? OR 4 = ?) AND
+                period > ? AND
+                period < ? AND
+                exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+            ORDER BY
+                activity.period DESC
+            "#,
+        )
+        .bind(membership_ids_json(member))
+        .bind(class_id)
+        .bind(class_id)
+        .bind(time_period.get_start().to_rfc3339())
+        .bind(time_period.get_end().to_rfc3339())
+        .bind(mode.as_id().to_string())
+        .bind(restrict_mode_id.to_string())
+        .fetch_all(&mut self.db)
+        .await?;
 
-            teams.insert(id, team);
+        if activity_rows.is_empty() {
+            return Ok(None);
         }
 
-        //Rumble wont have any teams, so we put all items in one team
-        //this also covered any bugs where no teams are specified
-        let mut no_teams = false;
-        if teams.is_empty() {
-            let display_name = team_names.pop().unwrap_or_default();
+        let p = self
+            .parse_individual_performance_rows(manifest, &activity_rows)
+            .await?;
 
-            let team = Team {
-                standing: Standing::Unknown,
-                id: NO_TEAMS_INDEX,
-                score: 0,
-                player_performances: Vec::new(),
-                display_name,
-            };
+        Ok(Some(p))
+    }
 
-            teams.insert(NO_TEAMS_INDEX, team);
-            no_teams = true;
-        }
+    /// Returns all of the players in each of the member's activities within
+    /// the time period, keyed by activity id. Players are sorted by team.
+    pub async fn retrieve_activity_rosters(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+        mode: &Mode,
+        time_period: &DateTimePeriod,
+    ) -> Result<HashMap<i64, Vec<ActivityRosterEntry>>, Error> {
+        let restrict_mode_id = if mode.is_private() {
+            -1
+        } else {
+            Mode::PrivateMatchesAll.as_id() as i32
+        };
 
-        //TODO: need to account for character and member, need to join both
-        let character_rows = sqlx::query(
+        let class_id = self
+            .get_sql_character_class_id(member, character_selection)
+            .await?;
+
+        let rows = sqlx::query(
             r#"
             SELECT
-                *,
-                character_activity_stats.id as character_activity_stats_index
+                character_activity_stats.activity,
+                character_activity_stats.team,
+                character_activity_stats.fireteam_id,
+                character_activity_stats.standing,
+                character_activity_stats.completion_reason,
+                character_activity_stats.completed,
+                character_activity_stats.start_seconds,
+                character_activity_stats.team_score,
+                character_activity_stats.kills,
+                character_activity_stats.deaths,
+                character_activity_stats.assists,
+                character_activity_stats.light_level,
+                character.character_id,
+                character.class,
+                member.member_id,
+                member.platform_id,
+                member.display_name,
+                member.bungie_display_name,
+                member.bungie_display_name_code,
+                --the last rating stored before the activity, or the first
+                --after it, preferring the activity's mode over all pvp
+                COALESCE(
+                    (
+                        SELECT rating FROM combat_rating
+                        WHERE
+                            combat_rating.character = character.character_id AND
+                            combat_rating.mode = activity.mode AND
+                            julianday(created) <= julianday(activity.period)
+                        ORDER BY julianday(created) DESC
+                        LIMIT 1
+                    ),
+                    (
+                        SELECT rating FROM combat_rating
+                        WHERE
+                            combat_rating.character = character.character_id AND
+                            combat_rating.mode = activity.mode AND
+                            julianday(created) > julianday(activity.period)
+                        ORDER BY julianday(created) ASC
+                        LIMIT 1
+                    ),
+                    (
+                        SELECT rating FROM combat_rating
+                        WHERE
+                            combat_rating.character = character.character_id AND
+                            combat_rating.mode = ? AND
+                            julianday(created) <= julianday(activity.period)
+                        ORDER BY julianday(created) DESC
+                        LIMIT 1
+                    ),
+                    (
+                        SELECT rating FROM combat_rating
+                        WHERE
+                            combat_rating.character = character.character_id AND
+                            combat_rating.mode = ? AND
+                            julianday(created) > julianday(activity.period)
+                        ORDER BY julianday(created) ASC
+                        LIMIT 1
+                    )
+                ) as combat_rating
             FROM
                 character_activity_stats
             INNER JOIN
                 character on character_activity_stats.character = character.character_id,
-                member on character.member = member.member_id
+                member on member.member_id = character.member,
+                activity on character_activity_stats.activity = activity.activity_id
             WHERE
-                activity = ?
+                character_activity_stats.activity IN (
+                    SELECT
+                        activity.activity_id
+                    FROM
+                        character_activity_stats
+                    INNER JOIN
+                        activity ON character_activity_stats.activity = activity.activity_id,
+                        character on character_activity_stats.character = character.character_id
+                    WHERE
+                        character.member IN (SELECT value FROM json_each(?)) AND
+                        (character.class = ? OR 4 = ?) AND
+                        period > ? AND
+                        period < ? AND
+                        exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                        not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+                )
+            ORDER BY
+                character_activity_stats.activity, character_activity_stats.team
             "#,
         )
-        .bind(activity_id)
+        .bind(Mode::AllPvP.as_id())
+        .bind(Mode::AllPvP.as_id())
+        .bind(membership_ids_json(member))
+        .bind(class_id)
+        .bind(class_id)
+        .bind(time_period.get_start().to_rfc3339())
+        .bind(time_period.get_end().to_rfc3339())
+        .bind(mode.as_id().to_string())
+        .bind(restrict_mode_id.to_string())
         .fetch_all(&mut self.db)
         .await?;
 
-        for c_row in character_rows {
-            let stats = self.parse_crucible_stats(manifest, &c_row).await?;
+        let mut out: HashMap<i64, Vec<ActivityRosterEntry>> = HashMap::new();
+        for row in rows.iter() {
+            let entry = self.parse_activity_roster_row(row).await?;
+            out.entry(entry.activity_id).or_default().push(entry);
+        }
 
-            let player = self.parse_player(&c_row).await?;
+        Ok(out)
+    }
 
-            let cpp = CruciblePlayerPerformance { player, stats };
+    /// Retrieves the stored totals for the mode for every player who played
+    /// in the member's activities within the time period, keyed by activity
+    /// id and then member id.
+    ///
+    /// Totals for each activity only include the player's stored activities
+    /// for the mode played before that activity, so they dont include the
+    /// results of the activity itself, or of any later activities. Players
+    /// without any earlier activities are not included.
+    pub async fn retrieve_roster_histories(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+        mode: &Mode,
+        time_period: &DateTimePeriod,
+    ) -> Result<HashMap<i64, HashMap<i64, PlayerHistory>>, Error> {
+        let restrict_mode_id = if mode.is_private() {
+            -1
+        } else {
+            Mode::PrivateMatchesAll.as_id() as i32
+        };
 
-            let index = if no_teams {
-                NO_TEAMS_INDEX
-            } else {
-                cpp.stats.team
+        let class_id = self
+            .get_sql_character_class_id(member, character_selection)
+            .await?;
+
+        let rows = sqlx::query(
+            r#"
+            WITH roster AS (
+                SELECT DISTINCT
+                    activity.activity_id as activity,
+                    activity.period as period,
+                    c.member as member
+                FROM
+                    character_activity_stats as s
+                INNER JOIN
+                    character as c on s.character = c.character_id,
+                    activity on s.activity = activity.activity_id
+                WHERE
+                    s.activity IN (
+                        SELECT
+                            activity.activity_id
+                        FROM
+                            character_activity_stats
+                        INNER JOIN
+                            activity ON character_activity_stats.activity = activity.activity_id,
+                            character on character_activity_stats.character = character.character_id
+                        WHERE
+                            character.member IN (SELECT value FROM json_each(?)) AND
+                            (character.class = ? OR 4 = ?) AND
+                            period > ? AND
+                            period < ? AND
+                            exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                            not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+                    )
+            )
+            SELECT
+                roster.activity as activity,
+                roster.member as member_id,
+                count(character_activity_stats.id) as activity_count,
+                sum(character_activity_stats.kills) as kills,
+                sum(character_activity_stats.deaths) as deaths,
+                sum(character_activity_stats.assists) as assists
+            FROM
+                roster
+            INNER JOIN
+                character on character.member = roster.member,
+                character_activity_stats on character_activity_stats.character = character.character_id,
+                activity on character_activity_stats.activity = activity.activity_id
+            WHERE
+                activity.period < roster.period AND
+                exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+            GROUP BY
+                roster.activity, roster.member
+            "#,
+        )
+        .bind(membership_ids_json(member))
+        .bind(class_id)
+        .bind(class_id)
+        .bind(time_period.get_start().to_rfc3339())
+        .bind(time_period.get_end().to_rfc3339())
+        .bind(mode.as_id().to_string())
+        .bind(restrict_mode_id.to_string())
+        .bind(mode.as_id().to_string())
+        .bind(restrict_mode_id.to_string())
+        .fetch_all(&mut self.db)
+        .await?;
+
+        let mut out: HashMap<i64, HashMap<i64, PlayerHistory>> =
+            HashMap::new();
+        for row in rows.iter() {
+            let activity_id: i64 = row.try_get_unchecked("activity")?;
+            let history = PlayerHistory {
+                member_id: row.try_get_unchecked("member_id")?,
+                activity_count: row.try_get_unchecked("activity_count")?,
+                kills: row.try_get_unchecked("kills")?,
+                deaths: row.try_get_unchecked("deaths")?,
+                assists: row.try_get_unchecked("assists")?,
             };
 
-            match teams.get_mut(&index) {
-                Some(e) => e.player_performances.push(cpp),
-                None => {
-                    tell::update!("Invalid Team ID ({}) : Skipping", &index)
-                }
-            }
+            out.entry(activity_id)
+                .or_default()
+                .insert(history.member_id, history);
         }
 
-        let details = self.parse_activity(manifest, activity_row).await?;
+        Ok(out)
+    }
 
-        Ok(CrucibleActivity { details, teams })
+    /// Retrieves the totals for each current member of the clan, across
+    /// all of their linked memberships, for activities within the time
+    /// period. Members without any activities are not included.
+    pub async fn retrieve_clan_leaderboard(
+        &mut self,
+        clan: &Clan,
+        mode: &Mode,
+        time_period: &DateTimePeriod,
+    ) -> Result<Vec<ClanPlayerStats>, Error> {
+        let restrict_mode_id = if mode.is_private() {
+            -1
+        } else {
+            Mode::PrivateMatchesAll.as_id() as i32
+        };
+
+        let query = format!(
+            r#"
+            WITH {}
+            SELECT
+                roster.clan_member as member_id,
+                count(character_activity_stats.id) as games,
+                sum(character_activity_stats.standing = 0) as wins,
+                sum(character_activity_stats.kills) as kills,
+                sum(character_activity_stats.deaths) as deaths,
+                sum(character_activity_stats.assists) as assists,
+                sum(character_activity_stats.all_medals_earned) as medals,
+                sum(character_activity_stats.time_played_seconds) as time_played_seconds
+            FROM
+                character_activity_stats
+            INNER JOIN
+                character on character_activity_stats.character = character.character_id
+            INNER JOIN
+                roster on character.member = roster.member_id
+            INNER JOIN
+                activity on character_activity_stats.activity = activity.activity_id
+            WHERE
+                period > ? AND
+                period < ? AND
+                exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+            GROUP BY
+                roster.clan_member
+            "#,
+            CLAN_ROSTER_CTE
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(clan.group_id)
+            .bind(time_period.get_start().to_rfc3339())
+            .bind(time_period.get_end().to_rfc3339())
+            .bind(mode.as_id().to_string())
+            .bind(restrict_mode_id.to_string())
+            .fetch_all(&mut self.db)
+            .await?;
+
+        let members = self.retrieve_clan_members(clan.group_id, false).await?;
+
+        let mut out: Vec<ClanPlayerStats> = Vec::new();
+        for row in rows.iter() {
+            let member_id: i64 = row.try_get_unchecked("member_id")?;
+            let member = match members.iter().find(|e| e.member.id == member_id)
+            {
+                Some(e) => e.member.clone(),
+                None => continue,
+            };
+
+            out.push(ClanPlayerStats {
+                member,
+                games: row.try_get_unchecked("games")?,
+                wins: row.try_get_unchecked("wins")?,
+                kills: row.try_get_unchecked("kills")?,
+                deaths: row.try_get_unchecked("deaths")?,
+                assists: row.try_get_unchecked("assists")?,
+                medals: row.try_get_unchecked("medals")?,
+                time_played_seconds: row
+                    .try_get_unchecked("time_played_seconds")?,
+            });
+        }
+
+        Ok(out)
     }
 
-    //returns the last played class for the specified member for activities
-    //that have been synced
-    pub async fn retrieve_last_active_class(
+    /// Retrieves the results for each pair of current clan members who
+    /// played in the same activities within the time period, either on the
+    /// same team or against each other.
+    pub async fn retrieve_clan_matchups(
         &mut self,
-        member: &Member,
-    ) -> Result<CharacterClass, Error> {
-        let result = sqlx::query(
+        clan: &Clan,
+        mode: &Mode,
+        time_period: &DateTimePeriod,
+    ) -> Result<Vec<ClanMatchup>, Error> {
+        let restrict_mode_id = if mode.is_private() {
+            -1
+        } else {
+            Mode::PrivateMatchesAll.as_id() as i32
+        };
+
+        //activities without team results (i.e. rumble) are free for alls,
+        //so the players are always opponents
+        let query = format!(
             r#"
-        SELECT
-            character.class as character_class
-        FROM
-            character_activity_stats
-        INNER JOIN
-            activity ON character_activity_stats.activity = activity.activity_id,
-            character on character_activity_stats.character = character.character_id,
-            member on member.member_id = character.member
-        WHERE 
-            member.member_id = ?
-        ORDER BY
-            activity.period DESC
-            limit 1
-        "#,
-        )
-        .bind(member.id)
-        .fetch_one(&mut self.db)
-        .await?;
+            WITH {},
+            stats AS (
+                SELECT
+                    roster.clan_member,
+                    character_activity_stats.activity,
+                    character_activity_stats.standing,
+                    CASE
+                        WHEN exists (select 1 from team_result where activity = activity.activity_id)
+                        THEN character_activity_stats.team
+                        ELSE character_activity_stats.id
+                    END as team
+                FROM
+                    character_activity_stats
+                INNER JOIN
+                    character on character_activity_stats.character = character.character_id
+                INNER JOIN
+                    roster on character.member = roster.member_id
+                INNER JOIN
+                    activity on character_activity_stats.activity = activity.activity_id
+                WHERE
+                    period > ? AND
+                    period < ? AND
+                    exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                    not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+            )
+            SELECT
+                a.clan_member as member_id,
+                b.clan_member as other_id,
+                sum(a.team = b.team) as games_together,
+                sum(a.team = b.team AND a.standing = 0) as wins_together,
+                sum(a.team != b.team) as games_against,
+                sum(a.team != b.team AND a.standing = 0) as member_wins,
+                sum(a.team != b.team AND b.standing = 0) as other_wins
+            FROM
+                stats as a
+            INNER JOIN
+                stats as b on a.activity = b.activity AND a.clan_member < b.clan_member
+            GROUP BY
+                a.clan_member, b.clan_member
+            "#,
+            CLAN_ROSTER_CTE
+        );
 
-        let class_id: u32 = result.try_get("character_class")?;
+        let rows = sqlx::query(&query)
+            .bind(clan.group_id)
+            .bind(time_period.get_start().to_rfc3339())
+            .bind(time_period.get_end().to_rfc3339())
+            .bind(mode.as_id().to_string())
+            .bind(restrict_mode_id.to_string())
+            .fetch_all(&mut self.db)
+            .await?;
 
-        Ok(CharacterClass::from_id(class_id))
-    }
+        let members = self.retrieve_clan_members(clan.group_id, false).await?;
+        let find = |id: i64| {
+            members
+                .iter()
+                .find(|e| e.member.id == id)
+                .map(|e| e.member.clone())
+        };
 
-    //returns the id used in the db queries for the specified class
-    //for ALL returns 4, and last_active finds last active
-    pub async fn get_sql_character_class_id(
-        &mut self,
-        member: &Member,
-        character_selection: &CharacterClassSelection,
-    ) -> Result<u32, Error> {
-        let class_id =
-            if character_selection == &CharacterClassSelection::LastActive {
-                let character_class =
-                    self.retrieve_last_active_class(member).await?;
-                character_class.as_id()
-            } else {
-                character_selection.as_id()
+        let mut out: Vec<ClanMatchup> = Vec::new();
+        for row in rows.iter() {
+            let (member, other) = match (
+                find(row.try_get_unchecked("member_id")?),
+                find(row.try_get_unchecked("other_id")?),
+            ) {
+                (Some(m), Some(o)) => (m, o),
+                _ => continue,
             };
 
-        Ok(class_id)
+            out.push(ClanMatchup {
+                member,
+                other,
+                games_together: row.try_get_unchecked("games_together")?,
+                wins_together: row.try_get_unchecked("wins_together")?,
+                games_against: row.try_get_unchecked("games_against")?,
+                member_wins: row.try_get_unchecked("member_wins")?,
+                other_wins: row.try_get_unchecked("other_wins")?,
+            });
+        }
+
+        Ok(out)
     }
 
-    pub async fn retrieve_activities_summary(
+    /// Retrieves the member's history for each medal they have earned,
+    /// across all of their stored activities for the mode.
+    pub async fn retrieve_medal_history(
         &mut self,
         member: &Member,
         character_selection: &CharacterClassSelection,
         mode: &Mode,
-        time_period: &DateTimePeriod,
-    ) -> Result<Option<PlayerActivitiesSummary>, Error> {
+        manifest: &mut ManifestInterface,
+    ) -> Result<Vec<MedalHistory>, Error> {
         let restrict_mode_id = if mode.is_private() {
             -1
         } else {
-            //if not private, then we dont include any results that are private
             Mode::PrivateMatchesAll.as_id() as i32
         };
 
@@ -1835,98 +3734,136 @@ impl ActivityStoreInterface {
             .get_sql_character_class_id(member, character_selection)
             .await?;
 
-        let summary = sqlx::query_as::<_, PlayerActivitiesSummary>(r#"
-        SELECT
-            count(*) as total_activities,
-            COALESCE(sum(time_played_seconds),0) as time_played_seconds,
-            COALESCE(sum(character_activity_stats.standing = 0),0) as wins,
-            COALESCE(sum( character_activity_stats.completion_reason = 4),0) as completion_reason_mercy,
-            COALESCE(sum(completed),0) as completed,
-            COALESCE(sum(assists),0) as assists,
-            COALESCE(sum(character_activity_stats.kills),0) as kills,
-            COALESCE(sum(deaths),0) as deaths,
-            COALESCE(sum(opponents_defeated),0) as opponents_defeated,
-            COALESCE(sum(weapon_kills_grenade),0) as grenade_kills,
-            COALESCE(sum(weapon_kills_melee),0) as melee_kills,
-            COALESCE(sum(weapon_kills_super),0) as super_kills,
-            COALESCE(sum(weapon_kills_ability),0) as ability_kills,
-            COALESCE(sum(character_activity_stats.precision_kills),0) as precision,
-            COALESCE(max(assists),0) as highest_assists,
-            COALESCE(max(character_activity_stats.kills),0) as highest_kills,
-            COALESCE(max(deaths),0) as highest_deaths,
-            COALESCE(max(opponents_defeated),0) as highest_opponents_defeated,
-            COALESCE(max(weapon_kills_grenade),0) as highest_grenade_kills,
-            COALESCE(max(weapon_kills_melee),0) as highest_melee_kills,
-            COALESCE(max(weapon_kills_super),0) as highest_super_kills,
-            COALESCE(max(weapon_kills_ability),0) as highest_ability_kills,
-            COALESCE(max(
-                cast(character_activity_stats.kills as real) 
-                / 
-                cast(
-                    IFNULL(
-                        NULLIF(character_activity_stats.deaths, 0), 
-                    1) as real
-                )),0.0)
-            as highest_kills_deaths_ratio,
-            COALESCE(max(
-                cast(character_activity_stats.kills + character_activity_stats.assists * 0.5 as real) 
-                / 
-                cast(
-                    IFNULL(
-                        NULLIF(character_activity_stats.deaths, 0), 
-                    1) as real
-                )),0.0)
-            as highest_kills_deaths_assists_ratio,
-            COALESCE(max(
-                cast((character_activity_stats.kills + character_activity_stats.assists) as real) 
-                / 
-                cast(
-                    IFNULL(
-                        NULLIF(character_activity_stats.deaths, 0), 
-                    1) as real
-                )),0.0)
-            as highest_efficiency
-        FROM
-            character_activity_stats
-        INNER JOIN
-            activity ON character_activity_stats.activity = activity.activity_id,
-            character on character_activity_stats.character = character.character_id,
-            member on member.member_id = character.member
-        WHERE
-            member.member_id = ? AND
-            (character.class = ? OR 4 = ?) AND
-            period > ? AND
-            period < ? AND
-            exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
-            not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
-        "#,
+        let rows = sqlx::query(
+            r#"
+            WITH member_activities AS (
+                SELECT
+                    character_activity_stats.id as stats_id,
+                    activity.period as period
+                FROM
+                    character_activity_stats
+                INNER JOIN
+                    activity ON character_activity_stats.activity = activity.activity_id,
+                    character on character_activity_stats.character = character.character_id
+                WHERE
+                    character.member IN (SELECT value FROM json_each(?)) AND
+                    (character.class = ? OR 4 = ?) AND
+                    exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                    not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+            ),
+            medals AS (
+                SELECT
+                    medal_result.reference_id as reference_id,
+                    min(member_activities.period) as first_earned,
+                    max(member_activities.period) as last_earned,
+                    count(medal_result.id) as activity_count,
+                    sum(medal_result.count) as count
+                FROM
+                    medal_result
+                INNER JOIN
+                    member_activities ON medal_result.character_activity_stats = member_activities.stats_id
+                WHERE
+                    medal_result.count > 0
+                GROUP BY
+                    medal_result.reference_id
+            )
+            SELECT
+                medals.*,
+                (
+                    SELECT
+                        count(*)
+                    FROM
+                        member_activities
+                    WHERE
+                        member_activities.period > medals.last_earned
+                ) as activities_since
+            FROM
+                medals
+            "#,
         )
-        .bind(member.id)
+        .bind(membership_ids_json(member))
         .bind(class_id)
         .bind(class_id)
-        .bind(time_period.get_start().to_rfc3339())
-        .bind(time_period.get_end().to_rfc3339())
-        .bind(mode.as_id())
-        .bind(restrict_mode_id)
-        .fetch_one(&mut self.db)
+        .bind(mode.as_id().to_string())
+        .bind(restrict_mode_id.to_string())
+        .fetch_all(&mut self.db)
         .await?;
 
-        Ok(Some(summary))
+        let mut out: Vec<MedalHistory> = Vec::with_capacity(rows.len());
+        for row in rows.iter() {
+            let reference_id: String = row.try_get_unchecked("reference_id")?;
+            let first_earned: String = row.try_get_unchecked("first_earned")?;
+            let last_earned: String = row.try_get_unchecked("last_earned")?;
+
+            out.push(MedalHistory {
+                medal: self.retrieve_medal(manifest, reference_id).await?,
+                first_earned: DateTime::parse_from_rfc3339(&first_earned)?
+                    .with_timezone(&Utc),
+                last_earned: DateTime::parse_from_rfc3339(&last_earned)?
+                    .with_timezone(&Utc),
+                activity_count: row.try_get_unchecked("activity_count")?,
+                count: row.try_get_unchecked("count")?,
+                activities_since: row.try_get_unchecked("activities_since")?,
+            });
+        }
+
+        Ok(out)
     }
 
-    pub async fn retrieve_activities_since(
+    /// Retrieves the member's medal totals for their activities within the
+    /// time period.
+    pub async fn retrieve_medal_totals(
         &mut self,
         member: &Member,
         character_selection: &CharacterClassSelection,
         mode: &Mode,
         time_period: &DateTimePeriod,
         manifest: &mut ManifestInterface,
-    ) -> Result<Option<Vec<CruciblePlayerActivityPerformance>>, Error> {
-        //if mode if private, we dont restrict results
+    ) -> Result<MedalTotals, Error> {
+        self.retrieve_activity_medal_totals(
+            member,
+            character_selection,
+            mode,
+            time_period,
+            manifest,
+            false,
+        )
+        .await
+    }
+
+    /// Retrieves the medal totals for all other players in the member's
+    /// activities within the time period.
+    pub async fn retrieve_lobby_medal_totals(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+        mode: &Mode,
+        time_period: &DateTimePeriod,
+        manifest: &mut ManifestInterface,
+    ) -> Result<MedalTotals, Error> {
+        self.retrieve_activity_medal_totals(
+            member,
+            character_selection,
+            mode,
+            time_period,
+            manifest,
+            true,
+        )
+        .await
+    }
+
+    async fn retrieve_activity_medal_totals(
+        &mut self,
+        member: &Member,
+        character_selection: &CharacterClassSelection,
+        mode: &Mode,
+        time_period: &DateTimePeriod,
+        manifest: &mut ManifestInterface,
+        lobby: bool,
+    ) -> Result<MedalTotals, Error> {
         let restrict_mode_id = if mode.is_private() {
             -1
         } else {
-            //if not private, then we dont include any results that are private
             Mode::PrivateMatchesAll.as_id() as i32
         };
 
@@ -1934,50 +3871,154 @@ impl ActivityStoreInterface {
             .get_sql_character_class_id(member, character_selection)
             .await?;
 
-        //todo: note, this might break if user has multiple characters of the same
-        //class. need to test
-        let activity_rows = sqlx::query(
+        //either the member's stats, or the stats for everyone else, in the
+        //member's activities
+        let member_op = if lobby { "NOT IN" } else { "IN" };
+        let stats_ids = format!(
             r#"
             SELECT
-                *,
-                activity.mode as activity_mode,
-                character_activity_stats.id as character_activity_stats_index  
+                s.id
             FROM
-                character_activity_stats
+                character_activity_stats as s
             INNER JOIN
-                activity ON character_activity_stats.activity = activity.activity_id,
-                character on character_activity_stats.character = character.character_id,
-                member on member.member_id = character.member
+                character as c on s.character = c.character_id
             WHERE
-                member.member_id = ? AND
-                (character.class = ? OR 4 = ?) AND
-                period > ? AND
-                period < ? AND
-                exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
-                not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
-            ORDER BY
-                activity.period DESC
+                c.member {member_op} (SELECT value FROM json_each(?)) AND
+                s.activity IN (
+                    SELECT
+                        activity.activity_id
+                    FROM
+                        character_activity_stats
+                    INNER JOIN
+                        activity ON character_activity_stats.activity = activity.activity_id,
+                        character on character_activity_stats.character = character.character_id
+                    WHERE
+                        character.member IN (SELECT value FROM json_each(?)) AND
+                        (character.class = ? OR 4 = ?) AND
+                        period > ? AND
+                        period < ? AND
+                        exists (select 1 from modes where activity = activity.activity_id and mode = ?) AND
+                        not exists (select 1 from modes where activity = activity.activity_id and mode = ?)
+                )
             "#,
-        )
-        .bind(member.id)
-        .bind(class_id)
-        .bind(class_id)
-        .bind(time_period.get_start().to_rfc3339())
-        .bind(time_period.get_end().to_rfc3339())
-        .bind(mode.as_id().to_string())
-        .bind(restrict_mode_id.to_string())
-        .fetch_all(&mut self.db)
-        .await?;
+            member_op = member_op
+        );
 
-        if activity_rows.is_empty() {
-            return Ok(None);
+        let count_query =
+            format!("SELECT count(*) as activity_count FROM ({})", stats_ids);
+        let row = sqlx::query(&count_query)
+            .bind(membership_ids_json(member))
+            .bind(membership_ids_json(member))
+            .bind(class_id)
+            .bind(class_id)
+            .bind(time_period.get_start().to_rfc3339())
+            .bind(time_period.get_end().to_rfc3339())
+            .bind(mode.as_id().to_string())
+            .bind(restrict_mode_id.to_string())
+            .fetch_one(&mut self.db)
+            .await?;
+
+        let activity_count: u32 = row.try_get_unchecked("activity_count")?;
+
+        let medal_query = format!(
+            r#"
+            SELECT
+                reference_id,
+                sum(count) as count
+            FROM
+                medal_result
+            WHERE
+                character_activity_stats IN ({})
+            GROUP BY
+                reference_id
+            "#,
+            stats_ids
+        );
+
+        let rows = sqlx::query(&medal_query)
+            .bind(membership_ids_json(member))
+            .bind(membership_ids_json(member))
+            .bind(class_id)
+            .bind(class_id)
+            .bind(time_period.get_start().to_rfc3339())
+            .bind(time_period.get_end().to_rfc3339())
+            .bind(mode.as_id().to_string())
+            .bind(restrict_mode_id.to_string())
+            .fetch_all(&mut self.db)
+            .await?;
+
+        let mut medals: Vec<MedalStat> = Vec::with_capacity(rows.len());
+        for row in rows.iter() {
+            let reference_id: String = row.try_get_unchecked("reference_id")?;
+            medals.push(MedalStat {
+                medal: self.retrieve_medal(manifest, reference_id).await?,
+                count: row.try_get_unchecked("count")?,
+            });
         }
 
-        let p = self
-            .parse_individual_performance_rows(manifest, &activity_rows)
+        Ok(MedalTotals {
+            activity_count,
+            medals,
+        })
+    }
+
+    async fn parse_activity_roster_row(
+        &mut self,
+        row: &sqlx::sqlite::SqliteRow,
+    ) -> Result<ActivityRosterEntry, Error> {
+        let player = self.parse_player(row).await?;
+
+        let standing: u32 = row.try_get_unchecked("standing")?;
+        let completion_reason: u32 =
+            row.try_get_unchecked("completion_reason")?;
+        let completed: i32 = row.try_get_unchecked("completed")?;
+        let combat_rating: Option<f64> =
+            row.try_get_unchecked("combat_rating")?;
+
+        Ok(ActivityRosterEntry {
+            activity_id: row.try_get_unchecked("activity")?,
+            player,
+            team: row.try_get_unchecked("team")?,
+            fireteam_id: row.try_get_unchecked("fireteam_id")?,
+            standing: Standing::from_value(standing),
+            completion_reason: CompletionReason::from_id(completion_reason),
+            completed: completed == 1,
+            start_seconds: row.try_get_unchecked("start_seconds")?,
+            team_score: row.try_get_unchecked("team_score")?,
+            kills: row.try_get_unchecked("kills")?,
+            deaths: row.try_get_unchecked("deaths")?,
+            assists: row.try_get_unchecked("assists")?,
+            combat_rating: combat_rating.map(|e| e as f32),
+        })
+    }
+
+    async fn retrieve_medal(
+        &self,
+        manifest: &mut ManifestInterface,
+        reference_id: String,
+    ) -> Result<Medal, Error> {
+        let medal_definition = manifest
+            .get_historical_stats_definition(&reference_id)
             .await?;
 
-        Ok(Some(p))
+        let medal = match medal_definition {
+            Some(e) => Medal {
+                id: e.id,
+                icon_image_path: e.icon_image_path,
+                tier: e.medal_tier.unwrap_or(MedalTier::Unknown),
+                name: e.name,
+                description: e.description,
+            },
+            None => Medal {
+                id: reference_id,
+                icon_image_path: None,
+                tier: MedalTier::Unknown,
+                name: "Unknown".to_string(),
+                description: "".to_string(),
+            },
+        };
+
+        Ok(medal)
     }
 
     async fn parse_individual_performance_rows(
@@ -2022,6 +4063,8 @@ impl ActivityStoreInterface {
         let reference_id: u32 =
             activity_row.try_get_unchecked("reference_id")?;
 
+        let has_teams: i32 = activity_row.try_get_unchecked("has_teams")?;
+
         let activity_definition =
             manifest.get_activity_definition(reference_id).await?;
 
@@ -2038,6 +4081,7 @@ impl ActivityStoreInterface {
             platform: Platform::from_id(platform_id),
             director_activity_hash,
             reference_id,
+            has_teams: has_teams == 1,
         };
 
         Ok(activity_detail)
@@ -2085,6 +4129,7 @@ impl ActivityStoreInterface {
             activity_row.try_get_unchecked("player_count")?;
 
         let team_score: u32 = activity_row.try_get_unchecked("team_score")?;
+        let emblem_hash: u32 = activity_row.try_get_unchecked("emblem_hash")?;
 
         let precision_kills: u32 =
             activity_row.try_get_unchecked("precision_kills")?;
@@ -2142,6 +4187,9 @@ impl ActivityStoreInterface {
             let name: String;
             let item_type: ItemType;
             let item_sub_type: ItemSubType;
+            let ammo_type: AmmoType;
+            let damage_type: DamageType;
+            let icon_path: Option<String>;
 
             match item_definition {
                 Some(e) => {
@@ -2150,12 +4198,21 @@ impl ActivityStoreInterface {
                     name = e.display_properties.name;
                     item_type = e.item_type;
                     item_sub_type = e.item_sub_type;
+                    ammo_type = e
+                        .equipping_block
+                        .map(|b| b.ammo_type)
+                        .unwrap_or(AmmoType::Unknown);
+                    damage_type = e.default_damage_type;
+                    icon_path = e.display_properties.icon_path;
                 }
                 None => {
                     name = "Unknown".to_string();
                     description = "".to_string();
                     item_type = ItemType::Unknown;
                     item_sub_type = ItemSubType::Unknown;
+                    ammo_type = AmmoType::Unknown;
+                    damage_type = DamageType::None;
+                    icon_path = None;
                 }
             };
 
@@ -2165,6 +4222,9 @@ impl ActivityStoreInterface {
                 description,
                 item_type,
                 item_sub_type,
+                ammo_type,
+                damage_type,
+                icon_path,
             };
 
             let ws = WeaponStat {
@@ -2196,40 +4256,7 @@ impl ActivityStoreInterface {
 
             let count: u32 = medal_row.try_get_unchecked("count")?;
 
-            let medal_definition = manifest
-                .get_historical_stats_definition(&reference_id)
-                .await?;
-
-            let id: String;
-            let icon_image_path: Option<String>;
-            let tier: MedalTier;
-            let name: String;
-            let description: String;
-
-            match medal_definition {
-                Some(e) => {
-                    id = e.id;
-                    icon_image_path = e.icon_image_path;
-                    tier = e.medal_tier.unwrap_or(MedalTier::Unknown);
-                    name = e.name;
-                    description = e.description;
-                }
-                None => {
-                    id = reference_id;
-                    icon_image_path = None;
-                    tier = MedalTier::Unknown;
-                    name = "Unknown".to_string();
-                    description = "".to_string();
-                }
-            };
-
-            let medal = Medal {
-                id,
-                icon_image_path,
-                tier,
-                name,
-                description,
-            };
+            let medal = self.retrieve_medal(manifest, reference_id).await?;
 
             let medal_stat = MedalStat { medal, count };
             medal_stats.push(medal_stat);
@@ -2269,6 +4296,7 @@ impl ActivityStoreInterface {
             time_played_seconds,
             player_count,
             team_score,
+            emblem_hash,
             extended: Some(extended),
         };
 
@@ -2352,3 +4380,15 @@ impl std::ops::Add<SyncResult> for SyncResult {
         }
     }
 }
+
+/// Returns a JSON array of the ids for the member and its linked memberships,
+/// which is bound to queries and expanded with json_each, so stored data for
+/// all of the memberships is included.
+fn membership_ids_json(member: &Member) -> String {
+    let ids: Vec<String> = std::iter::once(member.id)
+        .chain(member.linked_ids.iter().copied())
+        .map(|e| e.to_string())
+        .collect();
+
+    format!("[{}]", ids.join(","))
+}
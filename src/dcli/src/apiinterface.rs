@@ -47,9 +47,12 @@ use crate::response::{
 };
 use crate::response::{character::CharacterData, gmd::GetMembershipData};
 use crate::response::{
-    ggms::{GetGroupMemberResponse, GroupMemberResponse},
+    ggms::{
+        GetGroupMemberResponse, GetGroupResponse, GroupMemberResponse, GroupV2,
+    },
     gmd::UserMembershipData,
 };
+use crate::clan::ClanMember;
 use crate::utils::Period;
 use crate::{apiclient::ApiClient, crucible::Player};
 use crate::{
@@ -59,9 +62,7 @@ use crate::{
 use crate::{crucible::Member, response::drs::API_RESPONSE_STATUS_SUCCESS};
 use crate::{crucible::PlayerName, error::Error};
 use crate::{enums::mode::Mode, response::pgcr::UserInfoCard};
-use crate::{
-    enums::platform::Platform, response::pgcr::DestinyProfileUserInfoCard,
-};
+use crate::enums::platform::Platform;
 
 use crate::character::Characters;
 
@@ -83,10 +84,34 @@ impl ApiInterface {
         //some methods may require it and will throw errors if its not set
     }
 
+    /// Retrieves the details (id and name) for the specified group / clan
+    pub async fn retrieve_group(
+        &self,
+        group_id: i64,
+    ) -> Result<GroupV2, Error> {
+        let url = format!(
+            "{base}/Platform/GroupV2/{group_id}/",
+            base = API_BASE_URL,
+            group_id = group_id
+        );
+
+        let r: GetGroupResponse = self
+            .client
+            .call_and_parse::<GetGroupResponse>(&url)
+            .await?;
+
+        match r.response {
+            Some(e) => Ok(e.detail),
+            None => Err(Error::ApiRequest {
+                description: String::from("No response data from API Call."),
+            }),
+        }
+    }
+
     pub async fn retrieve_group_members(
         &self,
-        group_id: u32,
-    ) -> Result<Vec<Member>, Error> {
+        group_id: i64,
+    ) -> Result<Vec<ClanMember>, Error> {
         let url = format!(
             "{base}/Platform/GroupV2/{group_id}/Members/",
             base = API_BASE_URL,
@@ -109,9 +134,13 @@ impl ApiInterface {
             }
         };
 
-        let mut out: Vec<Member> = Vec::new();
+        let mut out: Vec<ClanMember> = Vec::new();
         for info in response.results.iter() {
-            out.push(info.destiny_user_info.to_member());
+            out.push(ClanMember {
+                member: info.destiny_user_info.to_member(),
+                joined: info.join_date,
+                departed: None,
+            });
         }
 
         Ok(out)
@@ -265,20 +294,10 @@ impl ApiInterface {
             //TODO: should we have an error here if no profiles are returned?
             //that should not happen
 
-            if linked_profiles.profiles.is_empty() {
-                return Err(Error::NoProfilesFound);
+            match linked_profiles.primary_profile() {
+                Some(e) => e.to_user_info_card(),
+                None => return Err(Error::NoProfilesFound),
             }
-
-            let mut most_recent: &DestinyProfileUserInfoCard =
-                &linked_profiles.profiles[0];
-
-            for profile in linked_profiles.profiles.iter().skip(1) {
-                if profile.date_last_played.gt(&most_recent.date_last_played) {
-                    most_recent = profile;
-                }
-            }
-
-            most_recent.to_user_info_card()
         };
 
         Ok(out)
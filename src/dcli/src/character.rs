@@ -20,7 +20,9 @@
 * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
-use crate::response::character::CharacterData;
+use chrono::{DateTime, Utc};
+
+use crate::response::character::{CharacterData, CharacterStatsData};
 use crate::{enums::character::CharacterClass, response::pgcr::UserInfoCard};
 
 pub struct PlayerInfo {
@@ -85,3 +87,26 @@ impl Characters {
         Some(&self.characters[0])
     }
 }
+
+/// Power, stats and time played for a character at a point in time, as stored
+/// in the activity store when syncing.
+#[derive(Debug, Clone)]
+pub struct CharacterSnapshot {
+    pub character_id: i64,
+    pub class_type: CharacterClass,
+    pub created: DateTime<Utc>,
+    pub stats: CharacterStatsData,
+    pub emblem_hash: u32,
+    pub minutes_played_total: u32,
+    pub date_last_played: DateTime<Utc>,
+}
+
+impl CharacterSnapshot {
+    /// Whether the power, stats, emblem or time played differ from the
+    /// specified character data.
+    pub fn has_changed(&self, character: &CharacterData) -> bool {
+        self.stats != character.stats
+            || self.emblem_hash != character.emblem_hash
+            || self.minutes_played_total != character.minutes_played_total
+    }
+}
@@ -0,0 +1,138 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+
+use crate::crucible::Member;
+use crate::enums::clansort::ClanSort;
+use crate::utils::{
+    calculate_efficiency, calculate_kills_deaths_ratio, calculate_percent,
+};
+
+/// A Destiny 2 group / clan whose roster is tracked in the activity store.
+#[derive(Debug, Clone)]
+pub struct Clan {
+    pub group_id: i64,
+    pub name: String,
+
+    /// When the roster was last synced with the API
+    pub last_synced: Option<DateTime<Utc>>,
+}
+
+/// A member of a clan's roster.
+#[derive(Debug, Clone)]
+pub struct ClanMember {
+    pub member: Member,
+    pub joined: DateTime<Utc>,
+
+    /// When the member was found to no longer be in the clan. None if they
+    /// are a current member.
+    pub departed: Option<DateTime<Utc>>,
+}
+
+/// Changes to a clan's roster found when syncing it.
+#[derive(Debug, Default, Clone)]
+pub struct ClanRosterUpdate {
+    pub joined: Vec<Member>,
+    pub departed: Vec<Member>,
+}
+
+/// Totals for a clan member across the activities in the activity store
+/// for a mode and period.
+#[derive(Debug, Clone)]
+pub struct ClanPlayerStats {
+    pub member: Member,
+    pub games: u32,
+    pub wins: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+    pub medals: u32,
+    pub time_played_seconds: u32,
+}
+
+impl ClanPlayerStats {
+    pub fn losses(&self) -> u32 {
+        self.games - self.wins
+    }
+
+    pub fn win_percent(&self) -> f32 {
+        calculate_percent(self.wins, self.games)
+    }
+
+    pub fn kills_deaths_ratio(&self) -> f32 {
+        calculate_kills_deaths_ratio(self.kills, self.deaths)
+    }
+
+    pub fn efficiency(&self) -> f32 {
+        calculate_efficiency(self.kills, self.deaths, self.assists)
+    }
+
+    /// Compares for sorting by the specified stat, highest first. Ties are
+    /// sorted by the number of games played.
+    pub fn cmp_by(&self, other: &ClanPlayerStats, sort: &ClanSort) -> Ordering {
+        let out = match sort {
+            ClanSort::KillsDeaths => other
+                .kills_deaths_ratio()
+                .partial_cmp(&self.kills_deaths_ratio()),
+            ClanSort::Efficiency => {
+                other.efficiency().partial_cmp(&self.efficiency())
+            }
+            ClanSort::WinPercent => {
+                other.win_percent().partial_cmp(&self.win_percent())
+            }
+            ClanSort::Games => Some(other.games.cmp(&self.games)),
+            ClanSort::Kills => Some(other.kills.cmp(&self.kills)),
+            ClanSort::Medals => Some(other.medals.cmp(&self.medals)),
+        };
+
+        out.unwrap_or(Ordering::Equal)
+            .then_with(|| other.games.cmp(&self.games))
+    }
+}
+
+/// Results for activities where two clan members played with, or against,
+/// each other.
+#[derive(Debug, Clone)]
+pub struct ClanMatchup {
+    pub member: Member,
+    pub other: Member,
+
+    /// Activities where both were on the same team, and how many of them
+    /// they won.
+    pub games_together: u32,
+    pub wins_together: u32,
+
+    /// Activities where they were on opposing teams, and how many of them
+    /// each won.
+    pub games_against: u32,
+    pub member_wins: u32,
+    pub other_wins: u32,
+}
+
+impl ClanMatchup {
+    pub fn win_percent_together(&self) -> f32 {
+        calculate_percent(self.wins_together, self.games_together)
+    }
+}
@@ -0,0 +1,140 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use crate::crucible::{
+    AggregateCruciblePerformances, CruciblePlayerActivityPerformance,
+    WeaponStat,
+};
+use crate::enums::standing::Standing;
+use crate::leavers::MatchResults;
+
+/// Results for the matches two players both played in, as teammates and as
+/// opponents.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SharedMatches {
+    /// Matches where the players were on the same team
+    pub together: MatchResults,
+
+    /// Matches where the players were on opposing teams, with wins for the
+    /// first player
+    pub against: MatchResults,
+
+    /// Wins for the second player in matches where they were on opposing
+    /// teams. Both players can lose in free for all (rumble) matches.
+    pub other_wins: u32,
+}
+
+impl SharedMatches {
+    pub fn with_performances(
+        performances: &[CruciblePlayerActivityPerformance],
+        other_performances: &[CruciblePlayerActivityPerformance],
+    ) -> SharedMatches {
+        let others: HashMap<i64, &CruciblePlayerActivityPerformance> =
+            other_performances
+                .iter()
+                .map(|e| (e.activity_detail.id, e))
+                .collect();
+
+        let mut out = SharedMatches::default();
+        for p in performances.iter() {
+            let o = match others.get(&p.activity_detail.id) {
+                Some(e) => e,
+                None => continue,
+            };
+
+            let stats = &p.performance.stats;
+            let other_stats = &o.performance.stats;
+            let is_win = stats.standing == Standing::Victory;
+
+            //activities without teams (i.e. rumble) are free for alls, so
+            //the players are always opponents
+            if p.activity_detail.has_teams && stats.team == other_stats.team {
+                out.together.add(is_win);
+            } else {
+                out.against.add(is_win);
+                if other_stats.standing == Standing::Victory {
+                    out.other_wins += 1;
+                }
+            }
+        }
+
+        out
+    }
+
+    pub fn matches(&self) -> u32 {
+        self.together.matches + self.against.matches
+    }
+}
+
+/// Returns the ids of the weapons which are in the top weapons (by kills)
+/// for every player, ordered by their combined kills.
+pub fn find_common_weapons(
+    aggregates: &[&AggregateCruciblePerformances],
+    count: usize,
+) -> Vec<u32> {
+    let mut kills: HashMap<u32, (usize, u32)> = HashMap::new();
+    for a in aggregates.iter() {
+        for w in top_weapons(a, count).iter() {
+            let e = kills.entry(w.weapon.id).or_insert((0, 0));
+            e.0 += 1;
+            e.1 += w.kills;
+        }
+    }
+
+    let mut out: Vec<(u32, u32)> = kills
+        .into_iter()
+        .filter(|(_id, (players, _kills))| *players == aggregates.len())
+        .map(|(id, (_players, kills))| (id, kills))
+        .collect();
+
+    out.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    out.into_iter().map(|(id, _kills)| id).collect()
+}
+
+/// Returns the weapon stats for the weapon id, if the player used it.
+pub fn find_weapon_stat(
+    aggregate: &AggregateCruciblePerformances,
+    weapon_id: u32,
+) -> Option<&WeaponStat> {
+    aggregate
+        .extended
+        .as_ref()?
+        .weapons
+        .iter()
+        .find(|e| e.weapon.id == weapon_id)
+}
+
+fn top_weapons(
+    aggregate: &AggregateCruciblePerformances,
+    count: usize,
+) -> Vec<&WeaponStat> {
+    let mut weapons: Vec<&WeaponStat> = match aggregate.extended.as_ref() {
+        Some(e) => e.weapons.iter().collect(),
+        None => return Vec::new(),
+    };
+
+    weapons.sort_by(|a, b| b.kills.cmp(&a.kills));
+    weapons.truncate(count);
+    weapons
+}
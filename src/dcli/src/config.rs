@@ -0,0 +1,330 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono_tz::Tz;
+use serde_derive::Deserialize;
+
+use crate::crucible::PlayerName;
+use crate::enums::character::CharacterClassSelection;
+use crate::enums::membership::MembershipSelection;
+use crate::enums::mode::Mode;
+use crate::enums::moment::Moment;
+use crate::error::Error;
+use crate::manifesthistory::DEFAULT_MANIFEST_LOCALE;
+use crate::output::Output;
+use crate::timezone::{get_system_timezone, parse_timezone};
+
+pub const CONFIG_FILE_NAME: &str = "dcli.toml";
+
+//if set, points to the config file to use, and overrides the default
+//search locations
+pub const CONFIG_PATH_ENV_VAR: &str = "DCLI_CONFIG";
+
+/// Settings shared across all of the dcli tools, loaded from dcli.toml.
+///
+/// Values passed on the command line always take precedence over values in
+/// the config file, which in turn take precedence over the tool defaults.
+///
+/// Example:
+///
+/// ```toml
+/// player = "mesh#3230"
+/// mode = "all_pvp"
+/// moment = "weekly"
+/// class = "all"
+/// membership = "all"
+/// output_format = "default"
+/// locale = "en"
+///
+/// [aliases]
+/// me = "mesh#3230"
+///
+/// [api]
+/// key = "YOUR_API_KEY"
+/// ```
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub data_dir: Option<PathBuf>,
+    pub player: Option<String>,
+    pub aliases: HashMap<String, String>,
+    pub mode: Option<String>,
+    pub moment: Option<String>,
+    pub class: Option<String>,
+    pub membership: Option<String>,
+    pub output_format: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub api: ApiConfig,
+
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    pub key: Option<String>,
+}
+
+impl Config {
+    /// Finds and loads the config file, returning an empty config if no
+    /// config file exists.
+    ///
+    /// Locations are checked in the following order: the path in the
+    /// DCLI_CONFIG environment variable, dcli.toml in the data directory
+    /// (either the one passed in or the system default), and dcli/dcli.toml
+    /// in the system config directory (XDG_CONFIG_HOME on Linux).
+    pub fn load(data_dir: &Option<PathBuf>) -> Result<Config, Error> {
+        if let Ok(e) = std::env::var(CONFIG_PATH_ENV_VAR) {
+            let path = PathBuf::from(e);
+            if !path.exists() {
+                return Err(Error::IoFileDoesNotExist {
+                    description: format!("{}", path.display()),
+                });
+            }
+
+            return Config::from_path(&path);
+        }
+
+        match Config::find_config_path(data_dir) {
+            Some(e) => Config::from_path(&e),
+            None => Ok(Config::default()),
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Config, Error> {
+        let s = std::fs::read_to_string(path)?;
+
+        let mut config: Config =
+            toml::from_str(&s).map_err(|e| Error::Config {
+                description: format!("{} : {}", path.display(), e),
+            })?;
+
+        config.path = Some(path.to_path_buf());
+
+        Ok(config)
+    }
+
+    fn find_config_path(data_dir: &Option<PathBuf>) -> Option<PathBuf> {
+        let data_dir = match data_dir {
+            Some(e) => Some(e.clone()),
+            None => dirs_next::data_local_dir().map(|e| e.join("dcli")),
+        };
+
+        let config_dir = dirs_next::config_dir().map(|e| e.join("dcli"));
+
+        [data_dir, config_dir]
+            .iter()
+            .flatten()
+            .map(|e| e.join(CONFIG_FILE_NAME))
+            .find(|e| e.is_file())
+    }
+
+    pub fn data_dir(&self, data_dir: Option<PathBuf>) -> Option<PathBuf> {
+        data_dir.or_else(|| self.data_dir.clone())
+    }
+
+    pub fn api_key(&self, api_key: Option<String>) -> Option<String> {
+        api_key.or_else(|| self.api.key.clone())
+    }
+
+    /// Returns the manifest locale (en, de, fr, ja, etc...) to use
+    pub fn locale(&self, locale: Option<String>) -> String {
+        locale
+            .or_else(|| self.locale.clone())
+            .unwrap_or_else(|| DEFAULT_MANIFEST_LOCALE.to_string())
+    }
+
+    /// Returns the timezone to use, checking the command line first, then
+    /// the config, and then the TZ environment variable and system timezone.
+    pub fn timezone(&self, timezone: Option<String>) -> Result<Tz, Error> {
+        if let Some(e) = timezone {
+            return parse_timezone(&e);
+        }
+
+        match &self.timezone {
+            Some(e) => parse_timezone(e).map_err(|err| Error::Config {
+                description: format!("Invalid value for timezone : {}", err),
+            }),
+            None => Ok(get_system_timezone()),
+        }
+    }
+
+    /// Returns the player name to use, checking the command line name first
+    /// and then the default player in the config. Either may be an alias
+    /// defined in the config.
+    pub fn player_name(
+        &self,
+        name: &Option<String>,
+    ) -> Result<PlayerName, Error> {
+        let name = match name.as_ref().or(self.player.as_ref()) {
+            Some(e) => e,
+            None => {
+                return Err(Error::InvalidArgument {
+                    description: "No player specified. Pass --name or set \
+                        player in the config file."
+                        .to_string(),
+                })
+            }
+        };
+
+        let name = match self.aliases.get(name) {
+            Some(e) => e,
+            None => name,
+        };
+
+        PlayerName::from_str(name).map_err(|e| Error::InvalidArgument {
+            description: format!("{} : {}", name, e),
+        })
+    }
+
+    pub fn mode<E: Display>(
+        &self,
+        mode: Option<Mode>,
+        default: &str,
+        parse: fn(&str) -> Result<Mode, E>,
+    ) -> Result<Mode, Error> {
+        resolve(mode, &self.mode, default, "mode", parse)
+    }
+
+    pub fn moment(
+        &self,
+        moment: Option<Moment>,
+        default: &str,
+    ) -> Result<Moment, Error> {
+        let from_command_line = moment.is_some();
+        let out =
+            resolve(moment, &self.moment, default, "moment", Moment::from_str)?;
+
+        //custom moments need a time passed from the command line, which
+        //structopt only enforces when the moment is also passed there
+        if !from_command_line && out == Moment::Custom {
+            return Err(Error::Config {
+                description:
+                    "moment cannot be set to custom in the config file."
+                        .to_string(),
+            });
+        }
+
+        Ok(out)
+    }
+
+    pub fn class(
+        &self,
+        class: Option<CharacterClassSelection>,
+        default: &str,
+    ) -> Result<CharacterClassSelection, Error> {
+        resolve(
+            class,
+            &self.class,
+            default,
+            "class",
+            CharacterClassSelection::from_str,
+        )
+    }
+
+    pub fn membership(
+        &self,
+        membership: Option<MembershipSelection>,
+        default: &str,
+    ) -> Result<MembershipSelection, Error> {
+        resolve(
+            membership,
+            &self.membership,
+            default,
+            "membership",
+            MembershipSelection::from_str,
+        )
+    }
+
+    pub fn output(
+        &self,
+        output: Option<Output>,
+        default: &str,
+    ) -> Result<Output, Error> {
+        resolve(
+            output,
+            &self.output_format,
+            default,
+            "output_format",
+            Output::from_str,
+        )
+    }
+
+    /// Resolves the output format for tools which only support some of the
+    /// output formats. An unsupported format, either passed on the command
+    /// line or set via output_format in the config file, is an error.
+    pub fn output_supported(
+        &self,
+        output: Option<Output>,
+        default: &str,
+        supported: &[Output],
+    ) -> Result<Output, Error> {
+        if let Some(e) = output {
+            if !supported.contains(&e) {
+                return Err(Error::InvalidArgument {
+                    description: format!("{} output is not supported", e),
+                });
+            }
+        }
+
+        let out = self.output(output, default)?;
+        if !supported.contains(&out) {
+            return Err(Error::Config {
+                description: format!(
+                    "Invalid value for output_format : {} output is not supported",
+                    out
+                ),
+            });
+        }
+
+        Ok(out)
+    }
+}
+
+fn resolve<T, E: Display>(
+    value: Option<T>,
+    config_value: &Option<String>,
+    default: &str,
+    key: &str,
+    parse: fn(&str) -> Result<T, E>,
+) -> Result<T, Error> {
+    if let Some(e) = value {
+        return Ok(e);
+    }
+
+    match config_value {
+        Some(e) => parse(e).map_err(|err| Error::Config {
+            description: format!("Invalid value for {} : {} : {}", key, e, err),
+        }),
+        //defaults are hardcoded by the apps, so should always parse
+        None => parse(default).map_err(|err| Error::InvalidArgument {
+            description: format!("{} : {}", default, err),
+        }),
+    }
+}
@@ -20,6 +20,8 @@
 * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
+use crate::enums::ammotype::AmmoType;
+use crate::enums::damagetype::DamageType;
 use crate::enums::mode::Mode;
 use crate::enums::platform::Platform;
 use crate::enums::standing::Standing;
@@ -59,11 +61,11 @@ pub struct CrucibleActivity {
 impl CrucibleActivity {
     pub fn get_member_performance(
         &self,
-        member_id: &i64,
+        member: &Member,
     ) -> Option<&CruciblePlayerPerformance> {
         for t in self.teams.values() {
             for p in &t.player_performances {
-                if &p.player.member_id == member_id {
+                if member.has_membership_id(p.player.member_id) {
                     return Some(p);
                 }
             }
@@ -85,6 +87,69 @@ pub struct CruciblePlayerActivityPerformance {
     pub activity_detail: ActivityDetail,
 }
 
+/// A player's team, fireteam and results for an activity, without weapon or
+/// medal details. Used when looking at the players in a large number of
+/// activities.
+#[derive(Debug, Clone)]
+pub struct ActivityRosterEntry {
+    pub activity_id: i64,
+    pub player: Player,
+    pub team: i32,
+    pub fireteam_id: String,
+    pub standing: Standing,
+    pub completion_reason: CompletionReason,
+    pub completed: bool,
+    pub start_seconds: u32,
+    pub team_score: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+
+    /// The player's stored combat rating from when the activity was played,
+    /// if one has been stored
+    pub combat_rating: Option<f32>,
+}
+
+impl ActivityRosterEntry {
+    pub fn joined_late(&self) -> bool {
+        self.start_seconds > PLAYER_START_BUFFER
+    }
+
+    pub fn left_early(&self) -> bool {
+        !self.completed
+    }
+}
+
+/// A player's totals across their activities stored in the activity store
+/// for a mode.
+///
+/// Only activities which include a synced member are stored, so for other
+/// players this is a sample of their history, not their complete history.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PlayerHistory {
+    pub member_id: i64,
+    pub activity_count: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+}
+
+impl PlayerHistory {
+    pub fn kills_deaths_ratio(&self) -> f32 {
+        calculate_kills_deaths_ratio(self.kills, self.deaths)
+    }
+}
+
+/// A character's combat rating for a mode, as returned by the API at the
+/// time it was stored.
+#[derive(Debug, Clone)]
+pub struct CombatRating {
+    pub character_id: i64,
+    pub mode: Mode,
+    pub created: DateTime<Utc>,
+    pub rating: f32,
+}
+
 #[derive(Debug, Clone)]
 pub struct CrucibleStats {
     pub assists: u32,
@@ -106,6 +171,7 @@ pub struct CrucibleStats {
     pub time_played_seconds: u32,
     pub player_count: u32,
     pub team_score: u32,
+    pub emblem_hash: u32,
 
     pub extended: Option<ExtendedCrucibleStats>,
 }
@@ -162,6 +228,19 @@ pub struct Member {
     pub name: PlayerName,
     pub platform: Platform,
     pub id: i64,
+
+    /// Ids for the other Destiny memberships linked to the same Bungie.net
+    /// account (i.e. from before cross save was enabled). Stored data for
+    /// these memberships is included when retrieving data for the member.
+    pub linked_ids: Vec<i64>,
+}
+
+impl Member {
+    /// Returns true if the id is for this membership, or one of its linked
+    /// memberships
+    pub fn has_membership_id(&self, member_id: i64) -> bool {
+        self.id == member_id || self.linked_ids.contains(&member_id)
+    }
 }
 
 //TODO: might need to make the properties Options, or drop display_name
@@ -280,6 +359,9 @@ pub struct Item {
     pub description: String,
     pub item_type: ItemType,
     pub item_sub_type: ItemSubType,
+    pub ammo_type: AmmoType,
+    pub damage_type: DamageType,
+    pub icon_path: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -288,6 +370,30 @@ pub struct MedalStat {
     pub count: u32,
 }
 
+/// A member's history for a medal, across all of their stored activities
+/// for a mode.
+#[derive(Debug, Clone)]
+pub struct MedalHistory {
+    pub medal: Medal,
+    pub first_earned: DateTime<Utc>,
+    pub last_earned: DateTime<Utc>,
+
+    /// Number of activities the medal was earned in
+    pub activity_count: u32,
+    pub count: u32,
+
+    /// Number of activities played since the medal was last earned
+    pub activities_since: u32,
+}
+
+/// Medal totals across a number of player activities.
+#[derive(Debug, Clone, Default)]
+pub struct MedalTotals {
+    /// Number of player activities the totals are for
+    pub activity_count: u32,
+    pub medals: Vec<MedalStat>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Medal {
     pub id: String,
@@ -588,4 +694,8 @@ pub struct ActivityDetail {
     pub platform: Platform,
     pub director_activity_hash: u32,
     pub reference_id: u32,
+
+    /// Whether team results were stored for the activity. Activities without
+    /// teams (i.e. rumble) are free for alls.
+    pub has_teams: bool,
 }
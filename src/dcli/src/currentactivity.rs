@@ -0,0 +1,195 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use chrono::{DateTime, Utc};
+
+use crate::apiinterface::ApiInterface;
+use crate::crucible::Member;
+use crate::enums::mode::Mode;
+use crate::error::Error;
+use crate::manifestinterface::ManifestInterface;
+
+const ORBIT_PLACE_HASH: u32 = 2961497387;
+
+#[derive(Debug, Clone)]
+pub struct CurrentActivity {
+    pub mode: Mode,
+    pub activity_type_name: String,
+    pub activity_name: String,
+    pub place_name: String,
+    pub destination_name: String,
+    pub description: String,
+    pub started: DateTime<Utc>,
+}
+
+impl CurrentActivity {
+    pub fn human_status(&self) -> String {
+        let mode = self.mode;
+        if mode == Mode::Patrol {
+            format!("Exploring on {}", self.place_name)
+        } else if mode.is_gambit() || mode.is_crucible() {
+            format!(
+                "Playing {} on {} ({})",
+                self.activity_type_name, self.activity_name, self.description
+            )
+        } else if mode == Mode::Strike {
+            format!(
+                "Running {} {} on {}",
+                self.activity_name, self.activity_type_name, self.place_name
+            )
+        } else if mode == Mode::Social {
+            format!(
+                "Hanging out in the {} on {}",
+                self.activity_name, self.place_name
+            )
+        } else if mode == Mode::Story {
+            format!(
+                "Playing {} story on {}",
+                self.activity_name, self.place_name
+            )
+        } else if mode.is_nightfall() {
+            format!(
+                "Playing {} {} on {}",
+                self.description, self.activity_name, self.place_name
+            )
+        } else {
+            format!(
+                "Playing {} {} on {}",
+                self.activity_name, self.activity_type_name, self.place_name
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ActivityStatus {
+    NotInActivity,
+    Orbit,
+    InActivity(CurrentActivity),
+}
+
+impl ActivityStatus {
+    pub fn human_status(&self) -> String {
+        match self {
+            ActivityStatus::NotInActivity => {
+                "Not currently in an activity".to_string()
+            }
+            ActivityStatus::Orbit => "Currently sitting in Orbit".to_string(),
+            ActivityStatus::InActivity(e) => e.human_status(),
+        }
+    }
+}
+
+/// Retrieves the current activity for the member from the API, and looks
+/// up the activity, place and destination names in the manifest.
+///
+/// Returns Error::ManifestItemNotFound if the activity is not in the
+/// manifest, which usually means the manifest needs to be synced.
+pub async fn retrieve_activity_status(
+    client: &ApiInterface,
+    manifest: &mut ManifestInterface,
+    member: &Member,
+) -> Result<ActivityStatus, Error> {
+    let activity_data_a = match client
+        .retrieve_current_activity(member.id, member.platform)
+        .await?
+    {
+        Some(e) => e,
+        None => return Ok(ActivityStatus::NotInActivity),
+    };
+
+    tell::verbose!(
+        "Getting activity definition data from manifest : {}",
+        activity_data_a.current_activity_hash
+    );
+    let activity_data_m = manifest
+        .get_activity_definition(activity_data_a.current_activity_hash)
+        .await?
+        .ok_or(Error::ManifestItemNotFound {
+            description: "Unknown activity. Make sure you have synced the latest version of the manifest using dclim.".to_string(),
+        })?;
+
+    if activity_data_m.place_hash == ORBIT_PLACE_HASH {
+        return Ok(ActivityStatus::Orbit);
+    }
+
+    let place_data_m = manifest
+        .get_place_definition(activity_data_m.place_hash)
+        .await?
+        .ok_or(Error::ManifestItemNotFound {
+            description: "Unknown location. Make sure you have synced the latest version of the manifest using dclim.".to_string(),
+        })?;
+
+    let destination_data_m = manifest
+        .get_destination_definition(activity_data_m.destination_hash)
+        .await?
+        .ok_or(Error::ManifestItemNotFound {
+            description: "Unknown destination. Make sure you have synced the latest version of the manifest using dclim.".to_string(),
+        })?;
+
+    //lets find out the mode / activity type name
+    let (mode, activity_type_name) = match activity_data_a
+        .current_activity_mode_type
+    {
+        // if its set in the API data, we use that
+        // this is due to this bug:
+        // https://github.com/Bungie-net/api/issues/1341
+        Some(e) => (e, format!("{}", e)),
+        None => {
+            tell::verbose!(
+                "Activity mode not returned from API. Checking Manifest : {}",
+                activity_data_m.activity_type_hash
+            );
+            //otherwise, we go into the manifest to find it
+            let name = match manifest
+                .get_activity_type_definition(
+                    activity_data_m.activity_type_hash,
+                )
+                .await
+            {
+                Ok(Some(e)) => e.display_properties.name,
+                Ok(None) => "Unknown".to_string(),
+                Err(e) => {
+                    tell::verbose!(
+                        "Activity Mode not found in Manifest : {:?}",
+                        e
+                    );
+                    "Unknown".to_string()
+                }
+            };
+            (Mode::None, name)
+        }
+    };
+
+    Ok(ActivityStatus::InActivity(CurrentActivity {
+        mode,
+        activity_type_name,
+        activity_name: activity_data_m.display_properties.name,
+        place_name: place_data_m.display_properties.name,
+        destination_name: destination_data_m.display_properties.name,
+        description: activity_data_m
+            .display_properties
+            .description
+            .unwrap_or_default(),
+        started: activity_data_a.date_activity_started,
+    }))
+}
@@ -0,0 +1,325 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::output::Output;
+
+//Simple document model used to render reports as Markdown or as an HTML
+//fragment, so they can be pasted into Discord, GitHub or email.
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Alignment {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug)]
+pub struct Table {
+    headers: Vec<(String, Alignment)>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[(&str, Alignment)]) -> Table {
+        Table {
+            headers: headers
+                .iter()
+                .map(|(name, align)| (name.to_string(), *align))
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row to the table. Rows with fewer cells than there are
+    /// columns are padded with empty cells.
+    pub fn add_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn cell(&self, row: &[String], index: usize) -> String {
+        row.get(index).cloned().unwrap_or_default()
+    }
+
+    fn to_markdown(&self) -> String {
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|(name, _)| escape_markdown(name))
+            .collect();
+
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                (0..headers.len())
+                    .map(|i| escape_markdown(&self.cell(row, i)))
+                    .collect()
+            })
+            .collect();
+
+        //pad the columns so the table is also readable as plain text
+        let mut widths: Vec<usize> = headers
+            .iter()
+            .map(|h| std::cmp::max(h.chars().count(), 3))
+            .collect();
+        for row in &rows {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = std::cmp::max(widths[i], cell.chars().count());
+            }
+        }
+
+        let format_row = |cells: &[String]| -> String {
+            let cols: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| match self.headers[i].1 {
+                    Alignment::Left => format!("{:<w$}", cell, w = widths[i]),
+                    Alignment::Right => format!("{:>w$}", cell, w = widths[i]),
+                })
+                .collect();
+            format!("| {} |", cols.join(" | "))
+        };
+
+        let divider: Vec<String> = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(i, (_, align))| {
+                let dashes = "-".repeat(widths[i] - 1);
+                match align {
+                    Alignment::Left => format!(":{}", dashes),
+                    Alignment::Right => format!("{}:", dashes),
+                }
+            })
+            .collect();
+
+        let mut out =
+            vec![format_row(&headers), format!("| {} |", divider.join(" | "))];
+        for row in &rows {
+            out.push(format_row(row));
+        }
+
+        out.join("\n")
+    }
+
+    fn to_html(&self) -> String {
+        //styles are inlined, as most mail clients strip style blocks
+        let mut out = vec![
+            "<table style=\"border-collapse:collapse;\">".to_string(),
+            "<tr>".to_string(),
+        ];
+
+        for (name, align) in &self.headers {
+            out.push(format!(
+                "<th style=\"{}border-bottom:1px solid #999;\">{}</th>",
+                cell_style(*align),
+                escape_html(name)
+            ));
+        }
+        out.push("</tr>".to_string());
+
+        for row in &self.rows {
+            out.push("<tr>".to_string());
+            for (i, (_, align)) in self.headers.iter().enumerate() {
+                out.push(format!(
+                    "<td style=\"{}\">{}</td>",
+                    cell_style(*align),
+                    escape_html(&self.cell(row, i))
+                ));
+            }
+            out.push("</tr>".to_string());
+        }
+        out.push("</table>".to_string());
+
+        out.join("\n")
+    }
+}
+
+fn cell_style(align: Alignment) -> &'static str {
+    match align {
+        Alignment::Left => "padding:2px 8px;text-align:left;",
+        Alignment::Right => "padding:2px 8px;text-align:right;",
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    Table(Table),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Document {
+    blocks: Vec<Block>,
+}
+
+impl Document {
+    pub fn new() -> Document {
+        Document { blocks: Vec::new() }
+    }
+
+    /// Adds a heading. level is clamped to between 1 and 6.
+    pub fn add_heading(&mut self, level: usize, text: &str) {
+        self.blocks
+            .push(Block::Heading(level.clamp(1, 6), text.to_string()));
+    }
+
+    pub fn add_paragraph(&mut self, text: &str) {
+        self.blocks.push(Block::Paragraph(text.to_string()));
+    }
+
+    pub fn add_table(&mut self, table: Table) {
+        self.blocks.push(Block::Table(table));
+    }
+
+    /// Renders the document for the specified output. Output::Html returns
+    /// an HTML fragment, all other outputs return Markdown.
+    pub fn render(&self, output: Output) -> String {
+        match output {
+            Output::Html => self.to_html(),
+            _ => self.to_markdown(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let blocks: Vec<String> = self
+            .blocks
+            .iter()
+            .map(|b| match b {
+                Block::Heading(level, text) => {
+                    format!("{} {}", "#".repeat(*level), escape_markdown(text))
+                }
+                Block::Paragraph(text) => escape_markdown(text),
+                Block::Table(table) => table.to_markdown(),
+            })
+            .collect();
+
+        blocks.join("\n\n")
+    }
+
+    pub fn to_html(&self) -> String {
+        let blocks: Vec<String> = self
+            .blocks
+            .iter()
+            .map(|b| match b {
+                Block::Heading(level, text) => {
+                    format!("<h{l}>{}</h{l}>", escape_html(text), l = level)
+                }
+                Block::Paragraph(text) => {
+                    format!("<p>{}</p>", escape_html(text))
+                }
+                Block::Table(table) => table.to_html(),
+            })
+            .collect();
+
+        blocks.join("\n")
+    }
+}
+
+/// Escapes characters which have special meaning in HTML.
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes characters which have special meaning in Markdown, so that
+/// player and weapon names are displayed as is. Newlines are replaced with
+/// spaces, as they would break table rows.
+pub fn escape_markdown(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '|' | '[' | ']' | '<' | '>' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_html_special_characters() {
+        assert_eq!(
+            escape_html("<b>\"Tom\" & 'Jerry'</b>"),
+            "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;"
+        );
+        assert_eq!(escape_html("mesh#3230"), "mesh#3230");
+    }
+
+    #[test]
+    fn escape_markdown_special_characters() {
+        assert_eq!(escape_markdown("x_x | *y*"), "x\\_x \\| \\*y\\*");
+        assert_eq!(escape_markdown("[a](b) `c` \\"), "\\[a\\](b) \\`c\\` \\\\");
+        assert_eq!(escape_markdown("a\nb\rc"), "a b c");
+        assert_eq!(escape_markdown("mesh#3230"), "mesh#3230");
+    }
+
+    #[test]
+    fn markdown_table_pads_and_escapes_cells() {
+        let mut table =
+            Table::new(&[("NAME", Alignment::Left), ("K/D", Alignment::Right)]);
+        table.add_row(vec!["a_b".to_string(), "1.50".to_string()]);
+        table.add_row(vec!["c".to_string()]);
+
+        assert_eq!(
+            table.to_markdown(),
+            "| NAME |  K/D |\n\
+             | :--- | ---: |\n\
+             | a\\_b | 1.50 |\n\
+             | c    |      |"
+        );
+    }
+
+    #[test]
+    fn render_document() {
+        let mut doc = Document::new();
+        doc.add_heading(9, "<Title>");
+        doc.add_paragraph("a & b");
+
+        assert_eq!(doc.render(Output::Markdown), "###### \\<Title\\>\n\na & b");
+        assert_eq!(
+            doc.render(Output::Html),
+            "<h6>&lt;Title&gt;</h6>\n<p>a &amp; b</p>"
+        );
+    }
+}
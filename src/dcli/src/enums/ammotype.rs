@@ -0,0 +1,77 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+#[derive(
+    Eq, PartialEq, Hash, Debug, Clone, Copy, Deserialize_repr, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum AmmoType {
+    None = 0,
+    Primary = 1,
+    Special = 2,
+    Heavy = 3,
+    Unknown = 4,
+}
+
+impl Default for AmmoType {
+    fn default() -> Self {
+        AmmoType::None
+    }
+}
+
+impl AmmoType {
+    /// Returns Unknown for ammo types which are not known
+    pub fn from_id(id: i32) -> AmmoType {
+        match id {
+            0 => AmmoType::None,
+            1 => AmmoType::Primary,
+            2 => AmmoType::Special,
+            3 => AmmoType::Heavy,
+            _ => AmmoType::Unknown,
+        }
+    }
+
+    pub fn get_order(&self) -> u32 {
+        match self {
+            AmmoType::Primary => 300,
+            AmmoType::Special => 200,
+            AmmoType::Heavy => 100,
+            AmmoType::None | AmmoType::Unknown => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for AmmoType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let out = match self {
+            AmmoType::None => "None",
+            AmmoType::Primary => "Primary",
+            AmmoType::Special => "Special",
+            AmmoType::Heavy => "Heavy",
+            AmmoType::Unknown => "Unknown",
+        };
+
+        write!(f, "{}", out)
+    }
+}
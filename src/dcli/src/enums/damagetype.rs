@@ -0,0 +1,77 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+#[derive(
+    Eq, PartialEq, Hash, Debug, Clone, Copy, Deserialize_repr, Serialize_repr,
+)]
+#[repr(i32)]
+pub enum DamageType {
+    None = 0,
+    Kinetic = 1,
+    Arc = 2,
+    Thermal = 3,
+    Void = 4,
+    Raid = 5,
+    Stasis = 6,
+    Strand = 7,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        DamageType::None
+    }
+}
+
+impl DamageType {
+    /// Returns None for damage types which are not known
+    pub fn from_id(id: i32) -> DamageType {
+        match id {
+            1 => DamageType::Kinetic,
+            2 => DamageType::Arc,
+            3 => DamageType::Thermal,
+            4 => DamageType::Void,
+            5 => DamageType::Raid,
+            6 => DamageType::Stasis,
+            7 => DamageType::Strand,
+            _ => DamageType::None,
+        }
+    }
+}
+
+impl std::fmt::Display for DamageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let out = match self {
+            DamageType::None => "None",
+            DamageType::Kinetic => "Kinetic",
+            DamageType::Arc => "Arc",
+            DamageType::Thermal => "Solar",
+            DamageType::Void => "Void",
+            DamageType::Raid => "Raid",
+            DamageType::Stasis => "Stasis",
+            DamageType::Strand => "Strand",
+        };
+
+        write!(f, "{}", out)
+    }
+}
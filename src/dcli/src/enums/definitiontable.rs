@@ -0,0 +1,98 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Manifest definition tables which can be searched by name.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum DefinitionTable {
+    InventoryItem,
+    Activity,
+    ActivityType,
+    Destination,
+    Place,
+    HistoricalStats,
+}
+
+impl DefinitionTable {
+    pub fn all() -> Vec<DefinitionTable> {
+        vec![
+            DefinitionTable::InventoryItem,
+            DefinitionTable::Activity,
+            DefinitionTable::ActivityType,
+            DefinitionTable::Destination,
+            DefinitionTable::Place,
+            DefinitionTable::HistoricalStats,
+        ]
+    }
+
+    /// Name of the table within the manifest database
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            DefinitionTable::InventoryItem => "DestinyInventoryItemDefinition",
+            DefinitionTable::Activity => "DestinyActivityDefinition",
+            DefinitionTable::ActivityType => "DestinyActivityTypeDefinition",
+            DefinitionTable::Destination => "DestinyDestinationDefinition",
+            DefinitionTable::Place => "DestinyPlaceDefinition",
+            DefinitionTable::HistoricalStats => {
+                "DestinyHistoricalStatsDefinition"
+            }
+        }
+    }
+}
+
+impl FromStr for DefinitionTable {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //wrap in String so we can convert to lower case
+        let s = String::from(s).to_lowercase();
+
+        //get a slice to get a &str for the match
+        match &s[..] {
+            "inventory_item" => Ok(DefinitionTable::InventoryItem),
+            "activity" => Ok(DefinitionTable::Activity),
+            "activity_type" => Ok(DefinitionTable::ActivityType),
+            "destination" => Ok(DefinitionTable::Destination),
+            "place" => Ok(DefinitionTable::Place),
+            "historical_stats" => Ok(DefinitionTable::HistoricalStats),
+
+            _ => Err("Unknown DefinitionTable type"),
+        }
+    }
+}
+
+impl fmt::Display for DefinitionTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let out = match self {
+            DefinitionTable::InventoryItem => "inventory_item",
+            DefinitionTable::Activity => "activity",
+            DefinitionTable::ActivityType => "activity_type",
+            DefinitionTable::Destination => "destination",
+            DefinitionTable::Place => "place",
+            DefinitionTable::HistoricalStats => "historical_stats",
+        };
+
+        write!(f, "{}", out)
+    }
+}
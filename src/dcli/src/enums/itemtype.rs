@@ -21,6 +21,7 @@
 */
 
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::str::FromStr;
 
 #[derive(Eq, PartialEq, Debug, Clone, Deserialize_repr, Serialize_repr)]
 #[repr(i32)]
@@ -56,7 +57,9 @@ pub enum ItemType {
     Pattern = 30,
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Deserialize_repr, Serialize_repr)]
+#[derive(
+    Eq, PartialEq, Hash, Debug, Clone, Deserialize_repr, Serialize_repr,
+)]
 #[repr(i32)]
 pub enum ItemSubType {
     Unknown = -1,
@@ -122,3 +125,94 @@ impl std::fmt::Display for ItemSubType {
         write!(f, "{}", out)
     }
 }
+
+impl FromStr for ItemType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //wrap in String so we can convert to lower case
+        let s = String::from(s).to_lowercase();
+
+        //get a slice to get a &str for the match
+        match &s[..] {
+            "unknown" => Ok(ItemType::Unknown),
+            "none" => Ok(ItemType::None),
+            "currency" => Ok(ItemType::Currency),
+            "armor" => Ok(ItemType::Armor),
+            "weapon" => Ok(ItemType::Weapon),
+            "message" => Ok(ItemType::Message),
+            "engram" => Ok(ItemType::Engram),
+            "consumable" => Ok(ItemType::Consumable),
+            "exchange_material" => Ok(ItemType::ExchangeMaterial),
+            "mission_reward" => Ok(ItemType::MissionReward),
+            "quest_step" => Ok(ItemType::QuestStep),
+            "quest_step_complete" => Ok(ItemType::QuestStepComplete),
+            "emblem" => Ok(ItemType::Emblem),
+            "quest" => Ok(ItemType::Quest),
+            "subclass" => Ok(ItemType::Subclass),
+            "clan_banner" => Ok(ItemType::ClanBanner),
+            "aura" => Ok(ItemType::Aura),
+            "mod" => Ok(ItemType::Mod),
+            "dummy" => Ok(ItemType::Dummy),
+            "ship" => Ok(ItemType::Ship),
+            "vehicle" => Ok(ItemType::Vehicle),
+            "emote" => Ok(ItemType::Emote),
+            "ghost" => Ok(ItemType::Ghost),
+            "package" => Ok(ItemType::Package),
+            "bounty" => Ok(ItemType::Bounty),
+            "wrapper" => Ok(ItemType::Wrapper),
+            "seasonal_artifact" => Ok(ItemType::SeasonalArtifact),
+            "finisher" => Ok(ItemType::Finisher),
+            "pattern" => Ok(ItemType::Pattern),
+
+            _ => Err("Unknown ItemType type"),
+        }
+    }
+}
+
+impl FromStr for ItemSubType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //wrap in String so we can convert to lower case
+        let s = String::from(s).to_lowercase();
+
+        //get a slice to get a &str for the match
+        match &s[..] {
+            "unknown" => Ok(ItemSubType::Unknown),
+            "none" => Ok(ItemSubType::None),
+            "crucible" => Ok(ItemSubType::Crucible),
+            "vanguard" => Ok(ItemSubType::Vanguard),
+            "exotic" => Ok(ItemSubType::Exotic),
+            "auto_rifle" => Ok(ItemSubType::AutoRifle),
+            "shotgun" => Ok(ItemSubType::Shotgun),
+            "machine_gun" => Ok(ItemSubType::Machinegun),
+            "hand_cannon" => Ok(ItemSubType::HandCannon),
+            "rocket_launcher" => Ok(ItemSubType::RocketLauncher),
+            "fusion_rifle" => Ok(ItemSubType::FusionRifle),
+            "sniper_rifle" => Ok(ItemSubType::SniperRifle),
+            "pulse_rifle" => Ok(ItemSubType::PulseRifle),
+            "scout_rifle" => Ok(ItemSubType::ScoutRifle),
+            "crm" => Ok(ItemSubType::Crm),
+            "sidearm" => Ok(ItemSubType::Sidearm),
+            "sword" => Ok(ItemSubType::Sword),
+            "mask" => Ok(ItemSubType::Mask),
+            "shader" => Ok(ItemSubType::Shader),
+            "ornament" => Ok(ItemSubType::Ornament),
+            "linear_fusion_rifle" => Ok(ItemSubType::FusionRifleLine),
+            "grenade_launcher" => Ok(ItemSubType::GrenadeLauncher),
+            "submachine_gun" => Ok(ItemSubType::SubmachineGun),
+            "trace_rifle" => Ok(ItemSubType::TraceRifle),
+            "helmet_armor" => Ok(ItemSubType::HelmetArmor),
+            "gauntlets_armor" => Ok(ItemSubType::GauntletsArmor),
+            "chest_armor" => Ok(ItemSubType::ChestArmor),
+            "leg_armor" => Ok(ItemSubType::LegArmor),
+            "class_armor" => Ok(ItemSubType::ClassArmor),
+            "bow" => Ok(ItemSubType::Bow),
+            "dummy_repeatable_bounty" => Ok(ItemSubType::DummyRepeatableBounty),
+            "glaive" => Ok(ItemSubType::Glaive),
+
+            _ => Err("Unknown ItemSubType type"),
+        }
+    }
+}
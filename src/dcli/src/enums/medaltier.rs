@@ -48,3 +48,20 @@ impl MedalTier {
         }
     }
 }
+
+impl std::fmt::Display for MedalTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let out = match self {
+            MedalTier::Tier1 => "Tier 1",
+            MedalTier::Tier2 => "Tier 2",
+            MedalTier::Tier3 => "Tier 3",
+            MedalTier::Tier4 => "Tier 4",
+            MedalTier::Tier5 => "Tier 5",
+            MedalTier::Tier6 => "Tier 6",
+            MedalTier::Tier7 => "Tier 7",
+            MedalTier::Unknown => "Unknown",
+        };
+
+        write!(f, "{}", out)
+    }
+}
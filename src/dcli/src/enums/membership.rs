@@ -0,0 +1,78 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::enums::platform::Platform;
+
+/// Which of the Destiny memberships linked to a player's Bungie.net account
+/// to include when retrieving stored data.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MembershipSelection {
+    /// All linked memberships
+    All,
+
+    /// Only the primary membership (the cross save membership, or the most
+    /// recently played membership if cross save is not enabled)
+    Primary,
+
+    /// Only the membership for the specified platform
+    Platform(Platform),
+}
+
+impl Default for MembershipSelection {
+    fn default() -> Self {
+        MembershipSelection::All
+    }
+}
+
+impl FromStr for MembershipSelection {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //wrap in String so we can convert to lower case
+        let s = String::from(s).to_lowercase();
+
+        //get a slice to get a &str for the match
+        match &s[..] {
+            "all" => Ok(MembershipSelection::All),
+            "primary" => Ok(MembershipSelection::Primary),
+            _ => match Platform::from_str(&s) {
+                Ok(e) => Ok(MembershipSelection::Platform(e)),
+                Err(_) => Err("Unknown MembershipSelection type"),
+            },
+        }
+    }
+}
+
+impl fmt::Display for MembershipSelection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MembershipSelection::All => write!(f, "all"),
+            MembershipSelection::Primary => write!(f, "primary"),
+            MembershipSelection::Platform(e) => {
+                write!(f, "{}", e.to_string().to_lowercase())
+            }
+        }
+    }
+}
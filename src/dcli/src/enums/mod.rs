@@ -20,13 +20,20 @@
 * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
+pub mod ammotype;
 pub mod character;
+pub mod clansort;
 pub mod completionreason;
+pub mod damagetype;
+pub mod definitiontable;
 pub mod itemtype;
 pub mod medaltier;
+pub mod membership;
 pub mod mode;
 pub mod moment;
 pub mod platform;
 pub mod standing;
 pub mod stat;
+pub mod weapongrouping;
+pub mod weapongroupstat;
 pub mod weaponsort;
@@ -23,18 +23,19 @@
 use std::fmt;
 use std::str::FromStr;
 
-use chrono::prelude::*;
 use chrono::{DateTime, Duration, Utc};
 
 use crate::{
     error::Error,
+    season::{SeasonMoment, Seasons},
+    timeexpression::TimeExpression,
     utils::{
         get_destiny2_launch_date, get_last_daily_reset, get_last_friday_reset,
         get_last_weekly_reset,
     },
 };
 
-#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[derive(Eq, PartialEq, Debug, Clone)]
 pub enum Moment {
     Now,
     Daily,
@@ -52,36 +53,24 @@ pub enum Moment {
     AllTime,
     Custom,
 
-    Launch,
-    CurseOfOsiris,
-    Warmind,
-    SeasonOfTheOutlaw,
-    SeasonOfTheForge,
-    SeasonOfTheDrifter,
-    SeasonOfOpulence,
-    SeasonOfTheUndying,
-    SeasonOfDawn,
-    SeasonOfTheWorthy,
-    SeasonOfArrivals,
-    SeasonOfTheHunt,
-    SeasonOfTheChosen,
-    SeasonOfTheSplicer,
-    SeasonOfTheLost,
-    SeasonOfTheRisen,
-    WitchQueen,
-    SeasonOfTheHaunted,
-    SeasonOfPlunder,
-    SeasonOfTheSeraph,
-    Lightfall,
-    SeasonOfDefiance,
-    SeasonOfTheDeep,
-    SeasonOfTheWitch,
-    SeasonOfTheWish,
+    /// Season or expansion, which is resolved against Seasons
+    Season(SeasonMoment),
+
+    /// Relative or calendar based time expression (i.e. 3d, this-month)
+    Expression(TimeExpression),
 }
 
 impl Moment {
-    pub fn get_date_time(&self) -> DateTime<Utc> {
-        match self {
+    /// Returns the date / time for the moment. Season moments return the
+    /// start of the season.
+    ///
+    /// Returns an error for custom moments, since their time is specified
+    /// separately, and for seasons which cant be found.
+    pub fn get_date_time(
+        &self,
+        seasons: &Seasons,
+    ) -> Result<DateTime<Utc>, Error> {
+        let out = match self {
             Moment::Now => Utc::now(),
             Moment::Daily => get_last_daily_reset(),
             Moment::NextDaily => get_last_daily_reset() + Duration::days(1),
@@ -96,101 +85,52 @@ impl Moment {
             Moment::Month => Utc::now() - Duration::days(30),
             Moment::NextMonth => Utc::now() + Duration::days(30),
             Moment::AllTime => get_destiny2_launch_date(),
-            Moment::Custom => Utc.with_ymd_and_hms(0, 0, 0, 0, 0, 0).unwrap(),
-
-            Moment::Launch => {
-                Utc.with_ymd_and_hms(2017, 9, 6, 0, 0, 1).unwrap()
-            }
-            Moment::CurseOfOsiris => {
-                Utc.with_ymd_and_hms(2017, 12, 5, 17, 0, 0).unwrap()
-            }
-            Moment::Warmind => {
-                Utc.with_ymd_and_hms(2018, 5, 8, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheOutlaw => {
-                Utc.with_ymd_and_hms(2018, 9, 4, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheForge => {
-                Utc.with_ymd_and_hms(2018, 12, 4, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheDrifter => {
-                Utc.with_ymd_and_hms(2019, 3, 5, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfOpulence => {
-                Utc.with_ymd_and_hms(2019, 6, 4, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheUndying => {
-                Utc.with_ymd_and_hms(2019, 10, 1, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfDawn => {
-                Utc.with_ymd_and_hms(2019, 12, 10, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheWorthy => {
-                Utc.with_ymd_and_hms(2020, 3, 10, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfArrivals => {
-                Utc.with_ymd_and_hms(2020, 6, 9, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheHunt => {
-                Utc.with_ymd_and_hms(2020, 11, 10, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheChosen => {
-                Utc.with_ymd_and_hms(2021, 2, 9, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheSplicer => {
-                Utc.with_ymd_and_hms(2021, 5, 11, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheLost => {
-                Utc.with_ymd_and_hms(2021, 8, 24, 17, 0, 0).unwrap()
-            }
-            Moment::SeasonOfTheRisen => {
-                Utc.with_ymd_and_hms(2022, 2, 22, 17, 0, 0).unwrap()
-            }
-            Moment::WitchQueen => {
-                Utc.with_ymd_and_hms(2022, 2, 22, 17, 0, 0).unwrap()
-            }
-
-            Moment::SeasonOfTheHaunted => {
-                Utc.with_ymd_and_hms(2022, 5, 24, 17, 0, 0).unwrap()
-            }
-
-            Moment::SeasonOfPlunder => {
-                Utc.with_ymd_and_hms(2022, 8, 23, 17, 0, 0).unwrap()
-            }
-
-            Moment::SeasonOfTheSeraph => {
-                Utc.with_ymd_and_hms(2022, 12, 6, 17, 0, 0).unwrap()
-            }
-
-            Moment::Lightfall => {
-                Utc.with_ymd_and_hms(2023, 2, 28, 17, 0, 0).unwrap()
-            }
-
-            Moment::SeasonOfDefiance => {
-                Utc.with_ymd_and_hms(2023, 2, 28, 17, 0, 0).unwrap()
+            Moment::Custom => {
+                return Err(Error::InvalidArgument {
+                    description:
+                        "custom moments require a time to be specified."
+                            .to_string(),
+                })
             }
+            Moment::Season(e) => seasons.find(e)?.start,
+            Moment::Expression(e) => e.resolve()?.0,
+        };
 
-            Moment::SeasonOfTheDeep => {
-                Utc.with_ymd_and_hms(2023, 5, 22, 17, 0, 0).unwrap()
-            }
+        Ok(out)
+    }
 
-            Moment::SeasonOfTheWitch => {
-                Utc.with_ymd_and_hms(2023, 8, 22, 17, 0, 0).unwrap()
-            }
+    /// For season moments, returns when the season ended, or None if it
+    /// has not ended. For time expressions with a fixed end (i.e. 2023-05)
+    /// returns the end of the expression. Returns None for all other
+    /// moments.
+    pub fn get_end_date_time(
+        &self,
+        seasons: &Seasons,
+    ) -> Result<Option<DateTime<Utc>>, Error> {
+        match self {
+            Moment::Season(e) => Ok(seasons.get_end(seasons.find(e)?)),
+            Moment::Expression(e) if e.has_end() => Ok(Some(e.resolve()?.1)),
+            _ => Ok(None),
+        }
+    }
 
-            Moment::SeasonOfTheWish => {
-                Utc.with_ymd_and_hms(2023, 11, 28, 17, 0, 0).unwrap()
-            }
+    /// Whether the moment covers a period with a fixed end, such as a season
+    /// or calendar month, rather than a single point in time.
+    pub fn has_end(&self) -> bool {
+        match self {
+            Moment::Season(_) => true,
+            Moment::Expression(e) => e.has_end(),
+            _ => false,
         }
     }
 }
 
 impl FromStr for Moment {
-    type Err = &'static str;
+    type Err = String;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
         //wrap in String so we can convert to lower case
-        let s = String::from(s).to_lowercase();
+        let s = String::from(src).to_lowercase();
 
         //get a slice to get a &str for the match
         match &s[..] {
@@ -210,33 +150,33 @@ impl FromStr for Moment {
             "all_time" => Ok(Moment::AllTime),
             "custom" => Ok(Moment::Custom),
 
-            "launch" => Ok(Moment::Launch),
-            "curse_of_osiris" => Ok(Moment::CurseOfOsiris),
-            "warmind" => Ok(Moment::Warmind),
-            "season_of_the_outlaw" => Ok(Moment::SeasonOfTheOutlaw),
-            "season_of_the_forge" => Ok(Moment::SeasonOfTheForge),
-            "season_of_the_drifter" => Ok(Moment::SeasonOfTheDrifter),
-            "season_of_opulence" => Ok(Moment::SeasonOfOpulence),
-            "season_of_the_undying" => Ok(Moment::SeasonOfTheUndying),
-            "season_of_dawn" => Ok(Moment::SeasonOfDawn),
-            "season_of_the_worthy" => Ok(Moment::SeasonOfTheWorthy),
-            "season_of_arrivals" => Ok(Moment::SeasonOfArrivals),
-            "season_of_the_hunt" => Ok(Moment::SeasonOfTheHunt),
-            "season_of_the_chosen" => Ok(Moment::SeasonOfTheChosen),
-            "season_of_the_splicer" => Ok(Moment::SeasonOfTheSplicer),
-            "season_of_the_lost" => Ok(Moment::SeasonOfTheLost),
-            "season_of_the_risen" => Ok(Moment::SeasonOfTheRisen),
-            "witch_queen" => Ok(Moment::WitchQueen),
-            "season_of_the_haunted" => Ok(Moment::SeasonOfTheHaunted),
-            "season_of_plunder" => Ok(Moment::SeasonOfPlunder),
-            "season_of_the_seraph" => Ok(Moment::SeasonOfTheSeraph),
-            "lightfall" => Ok(Moment::Lightfall),
-            "season_of_defiance" => Ok(Moment::SeasonOfDefiance),
-            "season_of_the_deep" => Ok(Moment::SeasonOfTheDeep),
-            "season_of_the_witch" => Ok(Moment::SeasonOfTheWitch),
-            "season_of_the_wish" => Ok(Moment::SeasonOfTheWish),
-
-            _ => Err("Unknown Moment type"),
+            _ => {
+                if let Some(e) = SeasonMoment::parse(&s) {
+                    return Ok(Moment::Season(e));
+                }
+
+                if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+                    return match s.parse::<u32>() {
+                        Ok(e) => Ok(Moment::Season(SeasonMoment::Number(e))),
+                        Err(_) => Err(format!("Unknown Moment type : {}", s)),
+                    };
+                }
+
+                //use original, since rfc3339 times are case sensitive
+                if let Some(e) = TimeExpression::parse(src)? {
+                    return Ok(Moment::Expression(e));
+                }
+
+                //anything else is treated as a season or expansion name, which
+                //is checked once the season data has been loaded
+                if !s.is_empty()
+                    && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+                {
+                    return Ok(Moment::Season(SeasonMoment::Name(s)));
+                }
+
+                Err(format!("Unknown Moment type : {}", s))
+            }
         }
     }
 }
@@ -244,6 +184,8 @@ impl FromStr for Moment {
 impl fmt::Display for Moment {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let out = match self {
+            Moment::Season(e) => return write!(f, "{}", e),
+            Moment::Expression(e) => return write!(f, "{}", e),
             Moment::Now => "now",
             Moment::Daily => "last daily reset",
             Moment::NextDaily => "next daily reset",
@@ -260,32 +202,6 @@ impl fmt::Display for Moment {
             Moment::AllTime => "all time",
 
             Moment::Custom => "custom",
-
-            Moment::Launch => "launch",
-            Moment::CurseOfOsiris => "Curse of Osiris",
-            Moment::Warmind => "Warmind",
-            Moment::SeasonOfTheOutlaw => "Season of the Outlaw",
-            Moment::SeasonOfTheForge => "Season of the Forge",
-            Moment::SeasonOfTheDrifter => "Season of the Drifter",
-            Moment::SeasonOfOpulence => "Season of Opulence",
-            Moment::SeasonOfTheUndying => "Season of the Undying",
-            Moment::SeasonOfDawn => "Season of Dawn",
-            Moment::SeasonOfTheWorthy => "Season of the Worthy",
-            Moment::SeasonOfArrivals => "Season of Arrivals",
-            Moment::SeasonOfTheHunt => "Season of the Hunt",
-            Moment::SeasonOfTheChosen => "Season of the Chosen",
-            Moment::SeasonOfTheSplicer => "Season of the Splicer",
-            Moment::SeasonOfTheLost => "Season of the Lost",
-            Moment::SeasonOfTheRisen => "Season of the Risen",
-            Moment::WitchQueen => "The Witch Queen",
-            Moment::SeasonOfTheHaunted => "Season of the Haunted",
-            Moment::SeasonOfPlunder => "Season of Plunder",
-            Moment::SeasonOfTheSeraph => "Season of the Seraph",
-            Moment::Lightfall => "Lightfall",
-            Moment::SeasonOfDefiance => "Season of Defiance",
-            Moment::SeasonOfTheDeep => "Season of the Deep",
-            Moment::SeasonOfTheWitch => "Season of the Witch",
-            Moment::SeasonOfTheWish => "Season of the Wish",
         };
 
         write!(f, "{}", out)
@@ -329,4 +245,32 @@ impl DateTimePeriod {
 
         Ok(DateTimePeriod { start, end })
     }
+
+    /// Creates a period from the start and end moments. Custom times are
+    /// required when the corresponding moment is custom.
+    ///
+    /// If no end moment is specified, the period ends at the end of the
+    /// start moment if it is a season, otherwise now.
+    pub fn with_moments(
+        start: &Moment,
+        start_custom_time: Option<DateTime<Utc>>,
+        end: Option<&Moment>,
+        end_custom_time: Option<DateTime<Utc>>,
+        seasons: &Seasons,
+    ) -> Result<DateTimePeriod, Error> {
+        let start_time = match (start, start_custom_time) {
+            (Moment::Custom, Some(e)) => e,
+            _ => start.get_date_time(seasons)?,
+        };
+
+        let end_time = match (end, end_custom_time) {
+            (Some(Moment::Custom), Some(e)) => e,
+            (Some(e), _) => e.get_date_time(seasons)?,
+            (None, _) => {
+                start.get_end_date_time(seasons)?.unwrap_or_else(Utc::now)
+            }
+        };
+
+        DateTimePeriod::with_start_end_time(start_time, end_time)
+    }
 }
@@ -0,0 +1,71 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::str::FromStr;
+
+/// How weapons are grouped together.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum WeaponGrouping {
+    Archetype,
+    Ammo,
+    Element,
+}
+
+impl WeaponGrouping {
+    pub fn all() -> [WeaponGrouping; 3] {
+        [
+            WeaponGrouping::Archetype,
+            WeaponGrouping::Ammo,
+            WeaponGrouping::Element,
+        ]
+    }
+}
+
+impl FromStr for WeaponGrouping {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //wrap in String so we can convert to lower case
+        let s = String::from(s).to_lowercase();
+
+        //get a slice to get a &str for the match
+        match &s[..] {
+            "archetype" => Ok(WeaponGrouping::Archetype),
+            "ammo" => Ok(WeaponGrouping::Ammo),
+            "element" => Ok(WeaponGrouping::Element),
+
+            _ => Err("Unknown WeaponGrouping type"),
+        }
+    }
+}
+
+impl std::fmt::Display for WeaponGrouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let out = match self {
+            WeaponGrouping::Archetype => "Archetype",
+            WeaponGrouping::Ammo => "Ammo",
+            WeaponGrouping::Element => "Element",
+        };
+
+        write!(f, "{}", out)
+    }
+}
@@ -0,0 +1,64 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::str::FromStr;
+
+/// Stat used when comparing weapon groups across seasons.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum WeaponGroupStatType {
+    KillsShare,
+    PrecisionPercent,
+    KillsPerGame,
+    WinPercent,
+}
+
+impl FromStr for WeaponGroupStatType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //wrap in String so we can convert to lower case
+        let s = String::from(s).to_lowercase();
+
+        //get a slice to get a &str for the match
+        match &s[..] {
+            "kills_share" => Ok(WeaponGroupStatType::KillsShare),
+            "precision_percent" => Ok(WeaponGroupStatType::PrecisionPercent),
+            "kills_per_game" => Ok(WeaponGroupStatType::KillsPerGame),
+            "wins_percent" => Ok(WeaponGroupStatType::WinPercent),
+
+            _ => Err("Unknown WeaponGroupStatType type"),
+        }
+    }
+}
+
+impl std::fmt::Display for WeaponGroupStatType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let out = match self {
+            WeaponGroupStatType::KillsShare => "Kills Share",
+            WeaponGroupStatType::PrecisionPercent => "Precision %",
+            WeaponGroupStatType::KillsPerGame => "Kills / Game",
+            WeaponGroupStatType::WinPercent => "Win %",
+        };
+
+        write!(f, "{}", out)
+    }
+}
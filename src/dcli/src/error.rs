@@ -67,7 +67,11 @@ pub enum Error {
     DateTimePeriodOrder,
     BungieNameNotFound,
     NoProfilesFound,
+    MembershipNotFound { description: String },
+    GroupNotFound { description: String },
     InvalidArgument { description: String },
+    Config { description: String },
+    ManifestVerification { description: String },
 }
 
 impl Display for Error {
@@ -174,6 +178,18 @@ impl Display for Error {
             Error::NoProfilesFound  => {
                 write!(f, "No player profiles found.")
             },
+            Error::MembershipNotFound { description } => {
+                write!(f, "Membership not found : {}", description)
+            },
+            Error::GroupNotFound { description } => {
+                write!(f, "Group not found : {}", description)
+            },
+            Error::Config { description } => {
+                write!(f, "Error loading config file. {}", description)
+            },
+            Error::ManifestVerification { description } => {
+                write!(f, "Manifest failed verification. {}", description)
+            },
         }
     }
 }
@@ -0,0 +1,443 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use crate::crucible::{
+    ActivityRosterEntry, CruciblePlayerActivityPerformance, Player,
+};
+use crate::enums::standing::Standing;
+
+/// Number of matches, and wins, for a group of matches.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MatchResults {
+    pub matches: u32,
+    pub wins: u32,
+}
+
+impl MatchResults {
+    pub fn add(&mut self, is_win: bool) {
+        self.matches += 1;
+        if is_win {
+            self.wins += 1;
+        }
+    }
+
+    pub fn losses(&self) -> u32 {
+        self.matches - self.wins
+    }
+}
+
+/// A player the member has been matched with, and the number of times
+/// they have left matches early.
+#[derive(Debug, Clone)]
+pub struct LeaverRecord {
+    pub player: Player,
+
+    /// Number of matches played with or against the player
+    pub matches: u32,
+
+    /// Number of times the player left early while on the member's team
+    pub teammate_leaves: u32,
+
+    /// Number of times the player left early while on the opposing team
+    pub opponent_leaves: u32,
+}
+
+impl LeaverRecord {
+    pub fn total_leaves(&self) -> u32 {
+        self.teammate_leaves + self.opponent_leaves
+    }
+}
+
+/// Aggregate leaver and late joiner data for a member's activities.
+///
+/// Players who left are players who did not complete the match, and late
+/// joiners are players who joined after the match had started, usually to
+/// replace a player who left.
+#[derive(Debug, Default, Clone)]
+pub struct LeaverSummary {
+    pub matches: u32,
+    pub wins: u32,
+
+    pub member_leaves: u32,
+    pub member_late_joins: u32,
+
+    pub teammates: u32,
+    pub teammate_leaves: u32,
+    pub teammate_late_joins: u32,
+
+    pub opponents: u32,
+    pub opponent_leaves: u32,
+    pub opponent_late_joins: u32,
+
+    /// Matches where only the member's team lost a player
+    pub team_lost_player: MatchResults,
+
+    /// Matches where only the opposing team lost a player
+    pub opponent_lost_player: MatchResults,
+
+    /// Matches where both teams lost a player
+    pub both_lost_player: MatchResults,
+
+    /// Matches where no players, other than the member, left
+    pub no_player_lost: MatchResults,
+
+    /// All players matched with, keyed by member id
+    pub players: HashMap<i64, LeaverRecord>,
+}
+
+impl LeaverSummary {
+    pub fn with_activities(
+        performances: &[CruciblePlayerActivityPerformance],
+        rosters: &HashMap<i64, Vec<ActivityRosterEntry>>,
+    ) -> LeaverSummary {
+        let mut out = LeaverSummary::default();
+
+        for p in performances.iter() {
+            let roster = match rosters.get(&p.activity_detail.id) {
+                Some(e) => e,
+                None => continue,
+            };
+
+            let stats = &p.performance.stats;
+            let member_id = p.performance.player.member_id;
+            let is_win = stats.standing == Standing::Victory;
+
+            //everyone is on their own team in activities without teams
+            //(i.e. rumble)
+            let has_teams = p.activity_detail.has_teams;
+
+            out.matches += 1;
+            if is_win {
+                out.wins += 1;
+            }
+
+            if !stats.completed {
+                out.member_leaves += 1;
+            }
+
+            let mut team_leaves = 0;
+            let mut opponent_leaves = 0;
+
+            for e in roster.iter() {
+                if e.player.member_id == member_id {
+                    if e.joined_late() {
+                        out.member_late_joins += 1;
+                    }
+                    continue;
+                }
+
+                let is_teammate = has_teams && e.team == stats.team;
+                let left = e.left_early();
+
+                if is_teammate {
+                    out.teammates += 1;
+                    if left {
+                        out.teammate_leaves += 1;
+                        team_leaves += 1;
+                    }
+                    if e.joined_late() {
+                        out.teammate_late_joins += 1;
+                    }
+                } else {
+                    out.opponents += 1;
+                    if left {
+                        out.opponent_leaves += 1;
+                        opponent_leaves += 1;
+                    }
+                    if e.joined_late() {
+                        out.opponent_late_joins += 1;
+                    }
+                }
+
+                let record = out
+                    .players
+                    .entry(e.player.member_id)
+                    .or_insert_with(|| LeaverRecord {
+                        player: e.player.clone(),
+                        matches: 0,
+                        teammate_leaves: 0,
+                        opponent_leaves: 0,
+                    });
+
+                record.matches += 1;
+                if left {
+                    if is_teammate {
+                        record.teammate_leaves += 1;
+                    } else {
+                        record.opponent_leaves += 1;
+                    }
+                }
+            }
+
+            match (team_leaves > 0, opponent_leaves > 0) {
+                (true, false) => out.team_lost_player.add(is_win),
+                (false, true) => out.opponent_lost_player.add(is_win),
+                (true, true) => out.both_lost_player.add(is_win),
+                (false, false) => out.no_player_lost.add(is_win),
+            }
+        }
+
+        out
+    }
+
+    /// Players who have left at least min_leaves matches the member played
+    /// in, sorted by the number of times they left.
+    pub fn get_repeat_leavers(&self, min_leaves: u32) -> Vec<&LeaverRecord> {
+        let mut out: Vec<&LeaverRecord> = self
+            .players
+            .values()
+            .filter(|e| e.total_leaves() >= min_leaves)
+            .collect();
+
+        out.sort_by(|a, b| {
+            b.total_leaves()
+                .cmp(&a.total_leaves())
+                .then(b.matches.cmp(&a.matches))
+        });
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Utc;
+
+    use crate::crucible::{
+        ActivityDetail, CruciblePlayerPerformance, CrucibleStats, PlayerName,
+    };
+    use crate::enums::character::CharacterClass;
+    use crate::enums::completionreason::CompletionReason;
+    use crate::enums::mode::Mode;
+    use crate::enums::platform::Platform;
+
+    const TEAM: i32 = 16;
+    const OTHER_TEAM: i32 = 17;
+
+    fn player(member_id: i64) -> Player {
+        Player {
+            member_id,
+            character_id: member_id * 10,
+            platform: Platform::Steam,
+            name: PlayerName {
+                display_name: None,
+                bungie_display_name: Some(format!("player{}", member_id)),
+                bungie_display_name_code: Some("0001".to_string()),
+            },
+            light_level: 1810,
+            class_type: CharacterClass::Hunter,
+        }
+    }
+
+    fn entry(
+        activity_id: i64,
+        member_id: i64,
+        team: i32,
+        completed: bool,
+        start_seconds: u32,
+    ) -> ActivityRosterEntry {
+        ActivityRosterEntry {
+            activity_id,
+            player: player(member_id),
+            team,
+            fireteam_id: member_id.to_string(),
+            standing: Standing::Unknown,
+            completion_reason: CompletionReason::ObjectiveComplete,
+            completed,
+            start_seconds,
+            team_score: 0,
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+            combat_rating: None,
+        }
+    }
+
+    fn performance(
+        activity_id: i64,
+        standing: Standing,
+        has_teams: bool,
+    ) -> CruciblePlayerActivityPerformance {
+        CruciblePlayerActivityPerformance {
+            performance: CruciblePlayerPerformance {
+                player: player(1),
+                stats: CrucibleStats {
+                    assists: 0,
+                    score: 0,
+                    kills: 0,
+                    deaths: 0,
+                    average_score_per_kill: 0.0,
+                    average_score_per_life: 0.0,
+                    completed: true,
+                    opponents_defeated: 0,
+                    efficiency: 0.0,
+                    kills_deaths_ratio: 0.0,
+                    kills_deaths_assists: 0.0,
+                    activity_duration_seconds: 600,
+                    standing,
+                    team: TEAM,
+                    completion_reason: CompletionReason::ObjectiveComplete,
+                    start_seconds: 0,
+                    time_played_seconds: 600,
+                    player_count: 0,
+                    team_score: 0,
+                    emblem_hash: 0,
+                    extended: None,
+                },
+            },
+            activity_detail: ActivityDetail {
+                id: activity_id,
+                period: Utc::now(),
+                map_name: "".to_string(),
+                mode: if has_teams {
+                    Mode::ControlQuickplay
+                } else {
+                    Mode::Rumble
+                },
+                platform: Platform::Steam,
+                director_activity_hash: 0,
+                reference_id: 0,
+                has_teams,
+            },
+        }
+    }
+
+    #[test]
+    fn with_activities_counts_teammates_and_opponents() {
+        let performances = vec![
+            performance(100, Standing::Victory, true),
+            performance(200, Standing::Defeat, true),
+        ];
+
+        let mut rosters = HashMap::new();
+        rosters.insert(
+            100,
+            vec![
+                entry(100, 1, TEAM, true, 0),
+                entry(100, 2, TEAM, false, 0),
+                entry(100, 3, OTHER_TEAM, true, 120),
+                entry(100, 4, OTHER_TEAM, true, 0),
+            ],
+        );
+        rosters.insert(
+            200,
+            vec![
+                entry(200, 1, TEAM, true, 0),
+                entry(200, 2, OTHER_TEAM, false, 0),
+                entry(200, 3, OTHER_TEAM, true, 0),
+            ],
+        );
+
+        let s = LeaverSummary::with_activities(&performances, &rosters);
+
+        assert_eq!(s.matches, 2);
+        assert_eq!(s.wins, 1);
+        assert_eq!(s.member_leaves, 0);
+        assert_eq!((s.teammates, s.teammate_leaves), (1, 1));
+        assert_eq!((s.opponents, s.opponent_leaves), (4, 1));
+        assert_eq!(s.opponent_late_joins, 1);
+
+        assert_eq!(
+            (s.team_lost_player.matches, s.team_lost_player.wins),
+            (1, 1)
+        );
+        assert_eq!(s.opponent_lost_player.matches, 1);
+        assert_eq!(s.opponent_lost_player.losses(), 1);
+        assert_eq!(s.both_lost_player.matches, 0);
+        assert_eq!(s.no_player_lost.matches, 0);
+
+        let r = &s.players[&2];
+        assert_eq!(r.matches, 2);
+        assert_eq!((r.teammate_leaves, r.opponent_leaves), (1, 1));
+    }
+
+    #[test]
+    fn with_activities_treats_everyone_as_opponents_without_teams() {
+        let performances = vec![performance(100, Standing::Defeat, false)];
+
+        //players in free for all matches can share a team index
+        let mut rosters = HashMap::new();
+        rosters.insert(
+            100,
+            vec![
+                entry(100, 1, TEAM, true, 0),
+                entry(100, 2, TEAM, false, 0),
+                entry(100, 3, TEAM, true, 0),
+            ],
+        );
+
+        let s = LeaverSummary::with_activities(&performances, &rosters);
+
+        assert_eq!(s.teammates, 0);
+        assert_eq!((s.opponents, s.opponent_leaves), (2, 1));
+        assert_eq!(s.opponent_lost_player.matches, 1);
+        assert_eq!(s.players[&2].opponent_leaves, 1);
+    }
+
+    #[test]
+    fn with_activities_skips_activities_without_rosters() {
+        let performances = vec![performance(100, Standing::Victory, true)];
+        let s = LeaverSummary::with_activities(&performances, &HashMap::new());
+
+        assert_eq!(s.matches, 0);
+        assert!(s.players.is_empty());
+    }
+
+    #[test]
+    fn get_repeat_leavers_sorts_by_leaves() {
+        let performances = vec![
+            performance(100, Standing::Victory, true),
+            performance(200, Standing::Victory, true),
+            performance(300, Standing::Victory, true),
+        ];
+
+        let mut rosters = HashMap::new();
+        rosters.insert(
+            100,
+            vec![entry(100, 2, TEAM, false, 0), entry(100, 3, TEAM, false, 0)],
+        );
+        rosters.insert(
+            200,
+            vec![entry(200, 2, TEAM, false, 0), entry(200, 3, TEAM, false, 0)],
+        );
+        rosters.insert(
+            300,
+            vec![
+                entry(300, 3, OTHER_TEAM, false, 0),
+                entry(300, 4, TEAM, false, 0),
+            ],
+        );
+
+        let s = LeaverSummary::with_activities(&performances, &rosters);
+        let leavers: Vec<i64> = s
+            .get_repeat_leavers(2)
+            .iter()
+            .map(|e| e.player.member_id)
+            .collect();
+
+        assert_eq!(leavers, vec![3, 2]);
+    }
+}
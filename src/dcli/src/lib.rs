@@ -25,15 +25,30 @@ pub mod apiclient;
 pub mod apiinterface;
 pub mod apiutils;
 pub mod character;
+pub mod clan;
+pub mod comparison;
+pub mod config;
 pub mod crucible;
+pub mod currentactivity;
 pub mod cruciblestats;
+pub mod document;
 pub mod emblem;
 pub mod enums;
 pub mod error;
+pub mod leavers;
 pub mod manifest;
+pub mod manifesthistory;
 pub mod manifestinterface;
+pub mod manifestsearch;
+pub mod medalsummary;
 pub mod output;
 pub mod playeractivitiessummary;
 pub mod response;
+pub mod season;
 pub mod statscontainer;
+pub mod teambalance;
+pub mod timeexpression;
+pub mod timezone;
+pub mod trials;
 pub mod utils;
+pub mod weapongroups;
@@ -1,8 +1,13 @@
+use chrono::{DateTime, Utc};
 use serde_derive::{Deserialize, Serialize};
 
+use crate::enums::ammotype::AmmoType;
+use crate::enums::damagetype::DamageType;
 use crate::enums::itemtype::{ItemSubType, ItemType};
 use crate::enums::medaltier::MedalTier;
-use crate::response::utils::prepend_base_url_option;
+use crate::response::utils::{
+    ammo_type_from_id, damage_type_from_id, prepend_base_url_option,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DisplayPropertiesData {
@@ -37,6 +42,23 @@ pub struct InventoryItemDefinitionData {
 
     #[serde(rename = "itemSubType")]
     pub item_sub_type: ItemSubType,
+
+    #[serde(default)]
+    #[serde(
+        rename = "defaultDamageType",
+        deserialize_with = "damage_type_from_id"
+    )]
+    pub default_damage_type: DamageType,
+
+    #[serde(rename = "equippingBlock")]
+    pub equipping_block: Option<EquippingBlockData>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EquippingBlockData {
+    #[serde(default)]
+    #[serde(rename = "ammoType", deserialize_with = "ammo_type_from_id")]
+    pub ammo_type: AmmoType,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -113,3 +135,21 @@ pub struct ActivityTypeDefinitionData {
     #[serde(rename = "displayProperties")]
     pub display_properties: DisplayPropertiesData,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SeasonDefinitionData {
+    #[serde(rename = "hash")]
+    pub id: u32,
+
+    #[serde(rename = "displayProperties")]
+    pub display_properties: DisplayPropertiesData,
+
+    #[serde(default, rename = "seasonNumber")]
+    pub season_number: u32,
+
+    #[serde(rename = "startDate")]
+    pub start_date: Option<DateTime<Utc>>,
+
+    #[serde(rename = "endDate")]
+    pub end_date: Option<DateTime<Utc>>,
+}
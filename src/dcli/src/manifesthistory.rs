@@ -0,0 +1,239 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+pub const MANIFEST_HISTORY_DIR: &str = "manifests";
+pub const MANIFEST_HISTORY_FILE_NAME: &str = "manifest_history.json";
+pub const DEFAULT_MANIFEST_LOCALE: &str = "en";
+pub const DEFAULT_MANIFEST_KEEP_COUNT: usize = 3;
+
+/// A manifest version that has been downloaded and stored locally.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManifestVersion {
+    pub version: String,
+    pub locale: String,
+    pub url: String,
+    pub file_name: String,
+    pub downloaded: DateTime<Utc>,
+}
+
+impl ManifestVersion {
+    pub fn new(version: &str, locale: &str, url: &str) -> ManifestVersion {
+        //versions look like 223620.24.10.29.1700-1-bnet.57347, so we make
+        //sure we only use characters that are safe in file names
+        let safe_version: String = version
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+
+        ManifestVersion {
+            version: version.to_string(),
+            locale: locale.to_string(),
+            url: url.to_string(),
+            file_name: format!("manifest_{}_{}.sqlite3", safe_version, locale),
+            downloaded: Utc::now(),
+        }
+    }
+
+    /// Returns the date the manifest was released by Bungie, parsed from the
+    /// version string (i.e. 223620.24.10.29.1700-1-bnet.57347). Falls back to
+    /// the date it was downloaded if the version can't be parsed.
+    pub fn get_release_date(&self) -> DateTime<Utc> {
+        parse_version_date(&self.version).unwrap_or(self.downloaded)
+    }
+}
+
+fn parse_version_date(version: &str) -> Option<DateTime<Utc>> {
+    let tokens: Vec<&str> = version.split('.').collect();
+
+    if tokens.len() < 5 {
+        return None;
+    }
+
+    let year: i32 = tokens[1].parse().ok()?;
+    let month: u32 = tokens[2].parse().ok()?;
+    let day: u32 = tokens[3].parse().ok()?;
+
+    let time: String = tokens[4].chars().take(4).collect();
+    if time.len() != 4 {
+        return None;
+    }
+
+    let hour: u32 = time[0..2].parse().ok()?;
+    let minute: u32 = time[2..4].parse().ok()?;
+
+    Utc.with_ymd_and_hms(2000 + year, month, day, hour, minute, 0)
+        .single()
+}
+
+/// Index of the manifest versions stored in the data directory.
+///
+/// Versions are stored in the manifests directory, and the active version
+/// is copied to manifest.sqlite3, which is what the apps load by default.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ManifestHistory {
+    pub versions: Vec<ManifestVersion>,
+
+    /// file name of the version currently copied to manifest.sqlite3
+    pub active: Option<String>,
+
+    /// file name of the version which is pinned. If set, dclim will not
+    /// automatically update the manifest
+    pub pinned: Option<String>,
+}
+
+impl ManifestHistory {
+    pub fn load(data_dir: &Path) -> Result<ManifestHistory, Error> {
+        let path = data_dir.join(MANIFEST_HISTORY_FILE_NAME);
+
+        if !path.exists() {
+            return Ok(ManifestHistory::default());
+        }
+
+        let json = fs::read_to_string(path)?;
+        let history: ManifestHistory = serde_json::from_str(&json)?;
+
+        Ok(history)
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(data_dir.join(MANIFEST_HISTORY_FILE_NAME), json)?;
+
+        Ok(())
+    }
+
+    pub fn get_dir(data_dir: &Path) -> PathBuf {
+        data_dir.join(MANIFEST_HISTORY_DIR)
+    }
+
+    pub fn get_path(data_dir: &Path, version: &ManifestVersion) -> PathBuf {
+        ManifestHistory::get_dir(data_dir).join(&version.file_name)
+    }
+
+    pub fn get_active(&self) -> Option<&ManifestVersion> {
+        self.find_by_file_name(self.active.as_ref()?)
+    }
+
+    pub fn get_pinned(&self) -> Option<&ManifestVersion> {
+        self.find_by_file_name(self.pinned.as_ref()?)
+    }
+
+    fn find_by_file_name(&self, file_name: &str) -> Option<&ManifestVersion> {
+        self.versions.iter().find(|e| e.file_name == file_name)
+    }
+
+    pub fn find(
+        &self,
+        version: &str,
+        locale: &str,
+    ) -> Option<&ManifestVersion> {
+        self.versions
+            .iter()
+            .find(|e| e.version == version && e.locale == locale)
+    }
+
+    /// Returns versions for the locale, sorted from newest to oldest
+    pub fn get_versions(&self, locale: &str) -> Vec<&ManifestVersion> {
+        let mut out: Vec<&ManifestVersion> = self
+            .versions
+            .iter()
+            .filter(|e| e.locale == locale)
+            .collect();
+
+        out.sort_by_key(|e| std::cmp::Reverse(e.get_release_date()));
+        out
+    }
+
+    /// Returns the newest stored version which was released on or before
+    /// the specified date.
+    pub fn find_for_date(
+        &self,
+        date: &DateTime<Utc>,
+        locale: &str,
+    ) -> Option<&ManifestVersion> {
+        self.get_versions(locale)
+            .into_iter()
+            .find(|e| e.get_release_date() <= *date)
+    }
+
+    /// Returns the version stored just before the active version
+    pub fn find_previous(&self) -> Option<&ManifestVersion> {
+        let active = self.get_active()?;
+
+        self.get_versions(&active.locale)
+            .into_iter()
+            .skip_while(|e| e.file_name != active.file_name)
+            .nth(1)
+    }
+
+    pub fn add(&mut self, version: ManifestVersion) {
+        self.versions.retain(|e| e.file_name != version.file_name);
+        self.versions.push(version);
+    }
+
+    /// Removes the oldest versions for each locale, so only keep_count
+    /// versions remain. Active and pinned versions are never removed.
+    /// Returns the versions that were removed.
+    pub fn prune(
+        &mut self,
+        data_dir: &Path,
+        keep_count: usize,
+    ) -> Result<Vec<ManifestVersion>, Error> {
+        let mut locales: Vec<String> =
+            self.versions.iter().map(|e| e.locale.clone()).collect();
+        locales.sort();
+        locales.dedup();
+
+        let mut removed: Vec<ManifestVersion> = Vec::new();
+        for locale in locales.iter() {
+            for v in self.get_versions(locale).into_iter().skip(keep_count) {
+                if Some(&v.file_name) == self.active.as_ref()
+                    || Some(&v.file_name) == self.pinned.as_ref()
+                {
+                    continue;
+                }
+
+                removed.push(v.clone());
+            }
+        }
+
+        for v in removed.iter() {
+            let path = ManifestHistory::get_path(data_dir, v);
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+
+        self.versions.retain(|e| !removed.contains(e));
+
+        Ok(removed)
+    }
+}
@@ -23,6 +23,7 @@
 use std::path::Path;
 use std::str::FromStr;
 
+use chrono::{DateTime, Utc};
 use futures::TryStreamExt;
 use serde_derive::{Deserialize, Serialize};
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
@@ -30,16 +31,37 @@ use sqlx::Row;
 use sqlx::{ConnectOptions, Connection, SqliteConnection};
 use std::collections::HashMap;
 
+use crate::enums::definitiontable::DefinitionTable;
 use crate::error::Error;
 use crate::manifest::definitions::{
     ActivityDefinitionData, ActivityTypeDefinitionData,
     DestinationDefinitionData, DisplayPropertiesData,
     HistoricalStatsDefinition, InventoryItemDefinitionData,
-    PlaceDefinitionData,
+    PlaceDefinitionData, SeasonDefinitionData,
+};
+use crate::manifesthistory::{ManifestHistory, DEFAULT_MANIFEST_LOCALE};
+use crate::manifestsearch::{
+    SearchDefinitionData, SearchHistoricalStatsData, SearchQuery, SearchResult,
 };
 
 pub const MANIFEST_FILE_NAME: &str = "manifest.sqlite3";
 
+/// Tables loaded into memory when the manifest is opened with cache set to
+/// true. Other tables will not be available (i.e. via find).
+pub const CACHED_TABLES: [&str; 7] = [
+    "DestinyActivityDefinition",
+    "DestinyActivityTypeDefinition",
+    "DestinyDestinationDefinition",
+    "DestinyHistoricalStatsDefinition",
+    "DestinyInventoryItemDefinition",
+    "DestinyPlaceDefinition",
+    "DestinySeasonDefinition",
+];
+
+/// Tables in CACHED_TABLES which a manifest is still valid without. The apps
+/// fall back to other data (such as seasons.json) when they are missing.
+pub const OPTIONAL_TABLES: [&str; 1] = ["DestinySeasonDefinition"];
+
 /// Takes a Destiny 2 API has and converts it to a Destiny 2 manifest db index value
 pub fn convert_hash_to_id(hash: u32) -> i64 {
     let mut id: i64 = hash as i64;
@@ -60,12 +82,53 @@ pub struct ManifestInterface {
 }
 
 impl ManifestInterface {
+    /// Opens the manifest in the specified directory.
+    ///
+    /// If cache is true, the definition tables in CACHED_TABLES are copied
+    /// into an in memory database when opened. This takes longer to open, but
+    /// speeds up lookups, so is useful when doing a lot of lookups against the
+    /// same instance.
     pub async fn new(
         manifest_dir: &Path,
         cache: bool,
     ) -> Result<ManifestInterface, Error> {
         let manifest_path = manifest_dir.join(MANIFEST_FILE_NAME);
+        ManifestInterface::with_path(&manifest_path, cache).await
+    }
+
+    /// Loads the locally stored manifest version that was current at the
+    /// specified date (i.e. when an activity was played). If no stored
+    /// version matches, the active manifest is loaded.
+    ///
+    /// Manifest versions are stored by dclim.
+    pub async fn new_for_date(
+        manifest_dir: &Path,
+        date: &DateTime<Utc>,
+        cache: bool,
+    ) -> Result<ManifestInterface, Error> {
+        let history = ManifestHistory::load(manifest_dir)?;
+
+        let locale = match history.get_active() {
+            Some(e) => e.locale.clone(),
+            None => DEFAULT_MANIFEST_LOCALE.to_string(),
+        };
+
+        if let Some(e) = history.find_for_date(date, &locale) {
+            let path = ManifestHistory::get_path(manifest_dir, e);
+
+            if path.exists() {
+                log::info!("Using manifest version {} for {}", e.version, date);
+                return ManifestInterface::with_path(&path, cache).await;
+            }
+        }
 
+        ManifestInterface::new(manifest_dir, cache).await
+    }
+
+    pub async fn with_path(
+        manifest_path: &Path,
+        cache: bool,
+    ) -> Result<ManifestInterface, Error> {
         if !manifest_path.exists() {
             return Err(Error::IoFileDoesNotExist {
                 description: format!(
@@ -76,64 +139,33 @@ impl ManifestInterface {
         }
 
         let path: String = format!("{}", manifest_path.display());
-        let mut read_only = true;
-        let connection_string: &str = if cache {
-            read_only = false;
-            "sqlite:file::memory:"
+
+        let options = if cache {
+            //we dont use sqlite::memory: here, as sqlx then opens the
+            //connection with SQLITE_OPEN_MEMORY, which also causes the
+            //attached manifest to be opened as an empty in memory db
+            SqliteConnectOptions::new().filename(":memory:")
         } else {
-            &path
+            SqliteConnectOptions::from_str(&path)?.read_only(true)
         };
 
         //note, we cant use WAL journal mode, which is default
         //as it can causes errors when opening a DB in readonly mode
         //We use Memory which should provide better performance
         //since we never write to the DB
-        let db = SqliteConnectOptions::from_str(connection_string)?
+        let mut db = options
             .journal_mode(SqliteJournalMode::Memory)
-            .read_only(read_only)
             .connect()
             .await?;
 
-        /*
         if cache {
-            match sqlx::query("ATTACH DATABASE '?' as 'tmpDb'")
-                .bind(path)
-                .execute(&mut db)
-                .await
-            {
-                Ok(e) => e,
-                Err(e) => {
-                    db.close().await?;
-                    return Err(Error::from(e));
-                }
-            };
-
-
-            //TODO: Need to impliment this to dynamically pull table names
-            //"SELECT name FROM sqlite_master WHERE type='table'"
-            let table_name: String = "InventoryItemDefinition".to_string();
-            //todo: do we need to pass table_name twice?
-            match sqlx::query("CREATE TABLE ? AS SELECT * FROM tmpDb.?")
-                .bind(table_name)
-                .execute(&mut db)
-                .await
+            if let Err(e) =
+                ManifestInterface::load_cache_tables(&mut db, &path).await
             {
-                Ok(e) => e,
-                Err(e) => {
-                    db.close().await?;
-                    return Err(Error::from(e));
-                }
-            };
-
-            match sqlx::query("DETACH DATABASE tmpDb").execute(&mut db).await {
-                Ok(e) => e,
-                Err(e) => {
-                    db.close().await?;
-                    return Err(Error::from(e));
-                }
+                db.close().await?;
+                return Err(e);
             }
         }
-        */
 
         Ok(ManifestInterface {
             manifest_db: db,
@@ -143,6 +175,50 @@ impl ManifestInterface {
         })
     }
 
+    //copies the definition tables used by the apps from the manifest file
+    //into the in memory database. We copy the original table definitions so
+    //the primary key indexes are recreated
+    async fn load_cache_tables(
+        db: &mut SqliteConnection,
+        path: &str,
+    ) -> Result<(), Error> {
+        sqlx::query("ATTACH DATABASE ? AS manifest")
+            .bind(path)
+            .execute(&mut *db)
+            .await?;
+
+        for table in CACHED_TABLES.iter() {
+            let row = sqlx::query(
+                "SELECT sql FROM manifest.sqlite_master WHERE type='table' AND name=?",
+            )
+            .bind(table)
+            .fetch_optional(&mut *db)
+            .await?;
+
+            //table might not exist in older manifest versions
+            let sql: String = match row {
+                Some(e) => e.try_get("sql")?,
+                None => continue,
+            };
+
+            sqlx::query(&sql).execute(&mut *db).await?;
+
+            //for some reason sqlx doesnt let you bind table names
+            sqlx::query(&format!(
+                "INSERT INTO main.{0} SELECT * FROM manifest.{0}",
+                table
+            ))
+            .execute(&mut *db)
+            .await?;
+        }
+
+        sqlx::query("DETACH DATABASE manifest")
+            .execute(&mut *db)
+            .await?;
+
+        Ok(())
+    }
+
     ///closes the database connection and takes ownership of self
     pub async fn close(self) -> Result<(), Error> {
         //can call ping to see if its still open? but that throws an error if it
@@ -152,6 +228,64 @@ impl ManifestInterface {
         Ok(())
     }
 
+    /// Checks that the manifest database is not corrupt, and that the
+    /// definition tables used by the apps exist and are not empty.
+    ///
+    /// Missing tables in OPTIONAL_TABLES are not treated as an error. Instead,
+    /// their names are returned so they can be reported as a warning.
+    pub async fn verify(&mut self) -> Result<Vec<String>, Error> {
+        let rows = sqlx::query("PRAGMA integrity_check")
+            .fetch_all(&mut self.manifest_db)
+            .await?;
+
+        let mut results: Vec<String> = Vec::new();
+        for row in rows.iter() {
+            results.push(row.try_get(0)?);
+        }
+
+        if results.len() != 1 || results[0] != "ok" {
+            return Err(Error::ManifestVerification {
+                description: format!(
+                    "Integrity check failed : {}",
+                    results.join(", ")
+                ),
+            });
+        }
+
+        let tables = self.get_tables().await?;
+
+        let mut missing: Vec<String> = Vec::new();
+        for table in CACHED_TABLES.iter() {
+            if !tables.iter().any(|e| e == table) {
+                if !OPTIONAL_TABLES.contains(table) {
+                    return Err(Error::ManifestVerification {
+                        description: format!("Table is missing : {}", table),
+                    });
+                }
+
+                missing.push(table.to_string());
+                continue;
+            }
+
+            //for some reason sqlx doesnt let you bind table names
+            let row = sqlx::query(&format!(
+                "SELECT COUNT(*) AS count FROM {}",
+                table
+            ))
+            .fetch_one(&mut self.manifest_db)
+            .await?;
+
+            let count: i64 = row.try_get("count")?;
+            if count == 0 {
+                return Err(Error::ManifestVerification {
+                    description: format!("Table is empty : {}", table),
+                });
+            }
+        }
+
+        Ok(missing)
+    }
+
     /// Searches entire manifest for id, and returns associated data for it.
     /// returns an error if more that one result found.
     //TODO: should we return a vector in case there are multiple results?
@@ -181,6 +315,110 @@ impl ManifestInterface {
         Ok(out)
     }
 
+    /// Searches the definition tables specified in the query for definitions
+    /// whose name matches the query name. Results are sorted by how closely
+    /// they match.
+    pub async fn search(
+        &mut self,
+        query: &SearchQuery,
+    ) -> Result<Vec<SearchResult>, Error> {
+        let mut out: Vec<SearchResult> = Vec::new();
+
+        let tables: Vec<String> = self.get_tables().await?;
+
+        for table in query.tables.iter() {
+            if query.has_item_filter()
+                && *table != DefinitionTable::InventoryItem
+            {
+                continue;
+            }
+
+            //older manifest versions might not have all of the tables
+            if !tables.iter().any(|e| e == table.table_name()) {
+                continue;
+            }
+
+            //for non fuzzy searches, we can let sqlite filter out most of the
+            //rows (LIKE is case insensitive) so we dont have to parse them all
+            let q = if query.fuzzy {
+                format!("SELECT json FROM {}", table.table_name())
+            } else {
+                format!(
+                    "SELECT json FROM {} WHERE json LIKE ? ESCAPE '\\'",
+                    table.table_name()
+                )
+            };
+
+            let pattern = query.like_pattern();
+            let mut rows = if query.fuzzy {
+                sqlx::query(&q).fetch(&mut self.manifest_db)
+            } else {
+                sqlx::query(&q).bind(pattern).fetch(&mut self.manifest_db)
+            };
+
+            while let Some(row) = rows.try_next().await? {
+                let json: &str = row.try_get("json")?;
+
+                let result = if *table == DefinitionTable::HistoricalStats {
+                    let d: SearchHistoricalStatsData =
+                        serde_json::from_str(json)?;
+
+                    SearchResult {
+                        table: *table,
+                        id: d.id,
+                        name: d.name,
+                        description: d.description,
+                        icon_path: d.icon_path,
+                        item_type_display_name: None,
+                        score: 0.0,
+                    }
+                } else {
+                    let d: SearchDefinitionData = serde_json::from_str(json)?;
+
+                    if let Some(e) = &query.item_type {
+                        if d.item_type != e.clone() as i32 {
+                            continue;
+                        }
+                    }
+
+                    if let Some(e) = &query.item_sub_type {
+                        if d.item_sub_type != e.clone() as i32 {
+                            continue;
+                        }
+                    }
+
+                    let display = match d.display_properties {
+                        Some(e) => e,
+                        None => continue,
+                    };
+
+                    SearchResult {
+                        table: *table,
+                        id: d.hash.to_string(),
+                        name: display.name,
+                        description: display.description,
+                        icon_path: display.icon_path,
+                        item_type_display_name: d.item_type_display_name,
+                        score: 0.0,
+                    }
+                };
+
+                if let Some(score) = query.score(&result.name) {
+                    out.push(SearchResult { score, ..result });
+                }
+            }
+        }
+
+        out.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        Ok(out)
+    }
+
     pub async fn get_tables_with_id_column(
         &mut self,
     ) -> Result<Vec<String>, Error> {
@@ -343,6 +581,29 @@ impl ManifestInterface {
         Ok(data)
     }
 
+    /// Returns all of the season definitions in the manifest, or an empty
+    /// vector if the manifest does not contain season definitions.
+    pub async fn get_season_definitions(
+        &mut self,
+    ) -> Result<Vec<SeasonDefinitionData>, Error> {
+        let mut out: Vec<SeasonDefinitionData> = Vec::new();
+
+        let tables = self.get_tables().await?;
+        if !tables.iter().any(|e| e == "DestinySeasonDefinition") {
+            return Ok(out);
+        }
+
+        let mut rows = sqlx::query("SELECT json FROM DestinySeasonDefinition")
+            .fetch(&mut self.manifest_db);
+
+        while let Some(row) = rows.try_next().await? {
+            let json: &str = row.try_get("json")?;
+            out.push(serde_json::from_str(json)?);
+        }
+
+        Ok(out)
+    }
+
     async fn get_definition<T: serde::de::DeserializeOwned>(
         &mut self,
         query: &str,
@@ -0,0 +1,227 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::enums::definitiontable::DefinitionTable;
+use crate::enums::itemtype::{ItemSubType, ItemType};
+use crate::manifest::definitions::DisplayPropertiesData;
+use crate::response::utils::prepend_base_url_option;
+
+/// Minimum similarity (0 - 1) for a name to be considered a fuzzy match
+pub const FUZZY_MATCH_THRESHOLD: f32 = 0.75;
+
+/// Options for searching manifest definitions by name via
+/// ManifestInterface::search
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pub name: String,
+    pub tables: Vec<DefinitionTable>,
+
+    /// If true, names which are similar to, but do not contain, name
+    /// will also be matched (i.e. misspellings).
+    pub fuzzy: bool,
+
+    /// Only applies to InventoryItem definitions. If set, all other tables
+    /// are skipped.
+    pub item_type: Option<ItemType>,
+
+    /// Only applies to InventoryItem definitions. If set, all other tables
+    /// are skipped.
+    pub item_sub_type: Option<ItemSubType>,
+}
+
+impl SearchQuery {
+    pub fn new(name: &str) -> SearchQuery {
+        SearchQuery {
+            name: name.to_string(),
+            tables: DefinitionTable::all(),
+            fuzzy: false,
+            item_type: None,
+            item_sub_type: None,
+        }
+    }
+
+    pub fn has_item_filter(&self) -> bool {
+        self.item_type.is_some() || self.item_sub_type.is_some()
+    }
+
+    /// Returns a LIKE pattern which matches text containing the name. LIKE
+    /// wildcards in the name are escaped with a backslash, so the pattern
+    /// must be used with ESCAPE '\'.
+    pub fn like_pattern(&self) -> String {
+        let mut out = String::from("%");
+        for c in self.name.trim().chars() {
+            if c == '%' || c == '_' || c == '\\' {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out.push('%');
+        out
+    }
+
+    /// Returns how closely the name matches the query, between 0 and 1, or
+    /// None if it does not match.
+    ///
+    /// Exact and substring matches always score higher than fuzzy matches.
+    pub fn score(&self, name: &str) -> Option<f32> {
+        let query = self.name.trim().to_lowercase();
+        let name = name.to_lowercase();
+
+        if query.is_empty() || name.is_empty() {
+            return None;
+        }
+
+        if name == query {
+            return Some(1.0);
+        }
+
+        if name.contains(&query) {
+            let ratio =
+                query.chars().count() as f32 / name.chars().count() as f32;
+            return Some(0.5 + ratio * 0.45);
+        }
+
+        if !self.fuzzy {
+            return None;
+        }
+
+        let similarity = best_word_similarity(&query, &name);
+
+        if similarity < FUZZY_MATCH_THRESHOLD {
+            return None;
+        }
+
+        Some(similarity * 0.5)
+    }
+}
+
+/// Definition found by ManifestInterface::search
+#[derive(Serialize, Debug, Clone)]
+pub struct SearchResult {
+    #[serde(serialize_with = "serialize_table")]
+    pub table: DefinitionTable,
+
+    /// Hash of the definition, or stat id for HistoricalStats definitions
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon_path: Option<String>,
+    pub item_type_display_name: Option<String>,
+
+    #[serde(skip)]
+    pub score: f32,
+}
+
+fn serialize_table<S>(
+    table: &DefinitionTable,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&table.to_string())
+}
+
+//we only pull the fields we need, and keep item types as ints, since there
+//are values in the manifest that our enums dont know about
+#[derive(Deserialize, Debug)]
+pub(crate) struct SearchDefinitionData {
+    pub hash: u32,
+
+    #[serde(rename = "displayProperties")]
+    pub display_properties: Option<DisplayPropertiesData>,
+
+    #[serde(rename = "itemTypeDisplayName")]
+    pub item_type_display_name: Option<String>,
+
+    #[serde(default, rename = "itemType")]
+    pub item_type: i32,
+
+    #[serde(default, rename = "itemSubType")]
+    pub item_sub_type: i32,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct SearchHistoricalStatsData {
+    #[serde(rename = "statId")]
+    pub id: String,
+
+    #[serde(default, rename = "statName")]
+    pub name: String,
+
+    #[serde(rename = "statDescription")]
+    pub description: Option<String>,
+
+    #[serde(
+        default,
+        rename = "iconImage",
+        deserialize_with = "prepend_base_url_option"
+    )]
+    pub icon_path: Option<String>,
+}
+
+//compares the query against each run of words in name with the same number
+//of words, so "ace of spaeds" will match "Ace of Spades Catalyst"
+fn best_word_similarity(query: &str, name: &str) -> f32 {
+    let q_count = query.split_whitespace().count();
+    let words: Vec<&str> = name.split_whitespace().collect();
+
+    let mut best = similarity(query, name);
+
+    if words.len() > q_count {
+        for w in words.windows(q_count) {
+            let s = similarity(query, &w.join(" "));
+            if s > best {
+                best = s;
+            }
+        }
+    }
+
+    best
+}
+
+//normalized levenshtein similarity. 1.0 means the strings are the same
+fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    1.0 - (prev[b.len()] as f32 / max_len as f32)
+}
@@ -0,0 +1,177 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use crate::crucible::{Medal, MedalHistory, MedalTotals};
+use crate::enums::medaltier::MedalTier;
+use crate::utils::calculate_average;
+
+/// A member's rate for a medal, compared to the other players in their
+/// activities.
+#[derive(Debug, Clone)]
+pub struct MedalRate {
+    pub medal: Medal,
+    pub count: u32,
+    pub per_game: f32,
+
+    /// Number of times the medal was earned by other players
+    pub lobby_count: u32,
+    pub lobby_per_game: f32,
+
+    /// The member's history for the medal across all stored activities,
+    /// or None if they have never earned it
+    pub history: Option<MedalHistory>,
+}
+
+impl MedalRate {
+    /// Difference between the member's rate and the lobby rate. Positive
+    /// values mean the member earns the medal more often.
+    pub fn per_game_delta(&self) -> f32 {
+        self.per_game - self.lobby_per_game
+    }
+}
+
+/// Medal rates for all medals of a tier.
+#[derive(Debug, Clone)]
+pub struct TierRate {
+    pub tier: MedalTier,
+    pub count: u32,
+    pub per_game: f32,
+    pub lobby_per_game: f32,
+}
+
+/// The member's medal rates, by medal and by tier, compared to the other
+/// players in their activities.
+#[derive(Debug, Clone)]
+pub struct MedalSummary {
+    pub activity_count: u32,
+    pub lobby_activity_count: u32,
+
+    /// Sorted by tier, and then by count
+    pub medals: Vec<MedalRate>,
+
+    /// Sorted by tier, highest first
+    pub tiers: Vec<TierRate>,
+}
+
+impl MedalSummary {
+    /// Stats without a medal tier (such as precision kills) are stored with
+    /// medals, and are not included.
+    pub fn with_totals(
+        totals: &MedalTotals,
+        lobby_totals: &MedalTotals,
+        history: &[MedalHistory],
+    ) -> MedalSummary {
+        let mut medals: HashMap<&str, MedalRate> = HashMap::new();
+
+        for m in totals.medals.iter() {
+            if m.medal.tier == MedalTier::Unknown {
+                continue;
+            }
+
+            medals.insert(
+                &m.medal.id,
+                MedalRate {
+                    medal: m.medal.clone(),
+                    count: m.count,
+                    per_game: calculate_average(m.count, totals.activity_count),
+                    lobby_count: 0,
+                    lobby_per_game: 0.0,
+                    history: None,
+                },
+            );
+        }
+
+        //include medals earned by other players, but not by the member
+        for m in lobby_totals.medals.iter() {
+            if m.medal.tier == MedalTier::Unknown {
+                continue;
+            }
+
+            let rate = medals.entry(&m.medal.id).or_insert_with(|| MedalRate {
+                medal: m.medal.clone(),
+                count: 0,
+                per_game: 0.0,
+                lobby_count: 0,
+                lobby_per_game: 0.0,
+                history: None,
+            });
+
+            rate.lobby_count = m.count;
+            rate.lobby_per_game =
+                calculate_average(m.count, lobby_totals.activity_count);
+        }
+
+        for h in history.iter() {
+            if let Some(e) = medals.get_mut(h.medal.id.as_str()) {
+                e.history = Some(h.clone());
+            }
+        }
+
+        let mut medals: Vec<MedalRate> = medals.into_values().collect();
+        medals.sort_by(|a, b| {
+            b.medal
+                .tier
+                .get_order()
+                .cmp(&a.medal.tier.get_order())
+                .then(b.count.cmp(&a.count))
+                .then(a.medal.name.cmp(&b.medal.name))
+        });
+
+        //medals are sorted by tier, so all medals for a tier are together
+        let mut tier_counts: Vec<(MedalTier, u32, u32)> = Vec::new();
+        for m in medals.iter() {
+            match tier_counts.last_mut() {
+                Some(e) if e.0 == m.medal.tier => {
+                    e.1 += m.count;
+                    e.2 += m.lobby_count;
+                }
+                _ => tier_counts.push((
+                    m.medal.tier.clone(),
+                    m.count,
+                    m.lobby_count,
+                )),
+            }
+        }
+
+        let tiers: Vec<TierRate> = tier_counts
+            .into_iter()
+            .map(|(tier, count, lobby_count)| TierRate {
+                tier,
+                count,
+                per_game: calculate_average(count, totals.activity_count),
+                lobby_per_game: calculate_average(
+                    lobby_count,
+                    lobby_totals.activity_count,
+                ),
+            })
+            .collect();
+
+        MedalSummary {
+            activity_count: totals.activity_count,
+            lobby_activity_count: lobby_totals.activity_count,
+            medals,
+            tiers,
+        }
+    }
+}
@@ -20,12 +20,27 @@
 * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
+use std::fmt;
 use std::str::FromStr;
 
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
 pub enum Output {
     Tsv,
     Default,
+    Markdown,
+    Html,
+    Jsonl,
+    Json,
+}
+
+impl Output {
+    /// Output formats supported by tools which only print to the terminal.
+    pub const TERMINAL: [Output; 2] = [Output::Default, Output::Tsv];
+
+    /// Output formats supported by the report tools, which can render as
+    /// Markdown or as an HTML fragment.
+    pub const REPORT: [Output; 3] =
+        [Output::Default, Output::Markdown, Output::Html];
 }
 
 impl FromStr for Output {
@@ -39,7 +54,26 @@ impl FromStr for Output {
         match &s[..] {
             "tsv" => Ok(Output::Tsv),
             "default" => Ok(Output::Default),
+            "markdown" => Ok(Output::Markdown),
+            "html" => Ok(Output::Html),
+            "jsonl" => Ok(Output::Jsonl),
+            "json" => Ok(Output::Json),
             _ => Err("Unknown Output type"),
         }
     }
 }
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let out = match self {
+            Output::Tsv => "tsv",
+            Output::Default => "default",
+            Output::Markdown => "markdown",
+            Output::Html => "html",
+            Output::Jsonl => "jsonl",
+            Output::Json => "json",
+        };
+
+        write!(f, "{}", out)
+    }
+}
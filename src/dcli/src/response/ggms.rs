@@ -22,6 +22,8 @@
 
 use crate::response::drs::{DestinyResponseStatus, IsDestinyAPIResponse};
 use crate::response::pgcr::UserInfoCard;
+use crate::response::utils::string_to_i64;
+use chrono::{DateTime, Utc};
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -48,4 +50,36 @@ pub struct GroupMemberResponse {
 pub struct GroupMemberInfo {
     #[serde(rename = "destinyUserInfo")]
     pub destiny_user_info: UserInfoCard,
+
+    #[serde(rename = "joinDate")]
+    pub join_date: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetGroupResponse {
+    #[serde(rename = "Response")]
+    pub response: Option<GroupResponse>,
+
+    #[serde(flatten)]
+    pub status: DestinyResponseStatus,
+}
+
+impl IsDestinyAPIResponse for GetGroupResponse {
+    fn get_status(&self) -> &DestinyResponseStatus {
+        &self.status
+    }
+}
+
+//https://bungie-net.github.io/multi/schema_GroupsV2-GroupResponse.html
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupResponse {
+    pub detail: GroupV2,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupV2 {
+    #[serde(rename = "groupId", deserialize_with = "string_to_i64")]
+    pub group_id: i64,
+
+    pub name: String,
 }
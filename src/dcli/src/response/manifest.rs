@@ -20,10 +20,12 @@
 * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
+use std::collections::HashMap;
+
 use serde_derive::{Deserialize, Serialize};
 
 use crate::response::drs::{DestinyResponseStatus, IsDestinyAPIResponse};
-use crate::response::utils::prepend_base_url;
+use crate::response::utils::prepend_base_url_to_values;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ManifestResponse {
@@ -44,12 +46,10 @@ impl IsDestinyAPIResponse for ManifestResponse {
 pub struct ManifestData {
     pub version: String,
 
-    #[serde(rename = "mobileWorldContentPaths")]
-    pub mobile_world_content_paths: MobileWorldContentPathsData,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct MobileWorldContentPathsData {
-    #[serde(deserialize_with = "prepend_base_url")]
-    pub en: String,
+    //keyed by locale (en, de, fr, ja, etc...)
+    #[serde(
+        rename = "mobileWorldContentPaths",
+        deserialize_with = "prepend_base_url_to_values"
+    )]
+    pub mobile_world_content_paths: HashMap<String, String>,
 }
@@ -208,6 +208,7 @@ impl UserInfoCard {
             name,
             platform: self.membership_type,
             id: self.membership_id,
+            linked_ids: Vec::new(),
         }
     }
 }
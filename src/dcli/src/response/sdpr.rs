@@ -25,7 +25,9 @@ use serde_derive::{Deserialize, Serialize};
 use super::{
     drs::{DestinyResponseStatus, IsDestinyAPIResponse},
     pgcr::{DestinyProfileUserInfoCard, UserInfoCard},
+    utils::string_to_i64,
 };
+use crate::enums::platform::Platform;
 
 #[derive(Serialize, Debug)]
 pub struct SearchDestinyPlayerPostData {
@@ -76,4 +78,37 @@ impl IsDestinyAPIResponse for LinkedProfilesResponse {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DestinyLinkedProfilesResponse {
     pub profiles: Vec<DestinyProfileUserInfoCard>,
+
+    #[serde(rename = "bnetMembership")]
+    #[serde(default)]
+    pub bnet_membership: Option<BungieNetMembership>,
+}
+
+impl DestinyLinkedProfilesResponse {
+    /// Returns the primary profile, which is the cross save profile if cross
+    /// save is enabled, otherwise the most recently played profile.
+    pub fn primary_profile(&self) -> Option<&DestinyProfileUserInfoCard> {
+        let cross_save = self.profiles.iter().find(|e| {
+            e.cross_save_override != Platform::Unknown
+                && e.membership_type == e.cross_save_override
+        });
+
+        if cross_save.is_some() {
+            return cross_save;
+        }
+
+        //max_by_key returns the last max, so reverse to prefer the first
+        self.profiles
+            .iter()
+            .rev()
+            .max_by_key(|e| e.date_last_played)
+    }
+}
+
+//https://bungie-net.github.io/multi/schema_User-UserInfoCard.html#schema_User-UserInfoCard
+//we only need the id for the Bungie.net account
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BungieNetMembership {
+    #[serde(rename = "membershipId", deserialize_with = "string_to_i64")]
+    pub membership_id: i64,
 }
@@ -25,8 +25,11 @@ use serde::Deserialize;
 //use serde_derive::Deserialize;
 
 use crate::apiutils::RESOURCE_BASE_URL;
+use crate::enums::ammotype::AmmoType;
+use crate::enums::damagetype::DamageType;
 use crate::enums::standing::STANDING_UNKNOWN_MAGIC_NUMBER;
 
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::str::FromStr;
 
@@ -155,6 +158,24 @@ where
     })
 }
 
+//new damage and ammo types may be added to the API, so we dont fail
+//deserializing the entire definition if we see one we dont know about
+pub fn damage_type_from_id<'de, D>(
+    deserializer: D,
+) -> Result<DamageType, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    i32::deserialize(deserializer).map(DamageType::from_id)
+}
+
+pub fn ammo_type_from_id<'de, D>(deserializer: D) -> Result<AmmoType, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    i32::deserialize(deserializer).map(AmmoType::from_id)
+}
+
 pub fn prepend_base_url_option<'de, D>(
     deserializer: D,
 ) -> Result<Option<String>, D::Error>
@@ -172,6 +193,19 @@ where
     })
 }
 
+pub fn prepend_base_url_to_values<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, String>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    HashMap::<String, String>::deserialize(deserializer).map(|m| {
+        m.into_iter()
+            .map(|(k, v)| (k, format!("{}{}", RESOURCE_BASE_URL, v)))
+            .collect()
+    })
+}
+
 pub fn string_to_i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
 where
     D: serde::de::Deserializer<'de>,
@@ -0,0 +1,352 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::manifestinterface::{ManifestInterface, MANIFEST_FILE_NAME};
+
+/// Name of the optional, user updatable file in the data directory which can
+/// be used to add or override season and expansion dates.
+pub const SEASONS_FILE_NAME: &str = "seasons.json";
+
+//seasons built into dcli. uses the same format as the seasons.json file, and
+//is used when the manifest is not available, or doesnt have season dates
+const BUILT_IN_SEASONS: &str = include_str!("../data/seasons.json");
+
+//prefixes ignored when matching names, so season_of_the_deep can be
+//specified as deep, and episode_echoes as echoes
+const NAME_PREFIXES: [&str; 4] =
+    ["season_of_the_", "season_of_", "episode_", "the_"];
+
+/// A season moment specified by the user, which is resolved against Seasons.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum SeasonMoment {
+    /// Season or expansion name, such as season_of_the_deep or lightfall
+    Name(String),
+
+    /// Season number, such as 21
+    Number(u32),
+
+    /// Number of seasons before the current season. 0 is the current season.
+    Relative(u32),
+}
+
+impl SeasonMoment {
+    /// Parses season numbers (season_21), and relative seasons (season,
+    /// season-1). Returns None if the input is not in one of those forms.
+    pub fn parse(s: &str) -> Option<SeasonMoment> {
+        let s = s.to_lowercase();
+
+        if s == "season" || s == "current_season" {
+            return Some(SeasonMoment::Relative(0));
+        }
+
+        if s == "previous_season" {
+            return Some(SeasonMoment::Relative(1));
+        }
+
+        if let Some(e) = s.strip_prefix("season-") {
+            return e.parse::<u32>().ok().map(SeasonMoment::Relative);
+        }
+
+        if let Some(e) = s.strip_prefix("season_") {
+            if let Ok(n) = e.parse::<u32>() {
+                return Some(SeasonMoment::Number(n));
+            }
+        }
+
+        None
+    }
+}
+
+impl fmt::Display for SeasonMoment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SeasonMoment::Name(e) => write!(f, "{}", e.replace('_', " ")),
+            SeasonMoment::Number(e) => write!(f, "season {}", e),
+            SeasonMoment::Relative(0) => write!(f, "current season"),
+            SeasonMoment::Relative(1) => write!(f, "previous season"),
+            SeasonMoment::Relative(e) => write!(f, "{} seasons ago", e),
+        }
+    }
+}
+
+/// A Destiny 2 season or expansion release.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Season {
+    /// Name used to specify the season from the command line. If not set,
+    /// it is created from the name (i.e. season_of_the_deep)
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+
+    /// Season number. Expansions which are not also seasons don't have a
+    /// season number.
+    #[serde(default)]
+    pub number: Option<u32>,
+
+    #[serde(default)]
+    pub expansion: bool,
+    pub start: DateTime<Utc>,
+
+    /// If not set, the season ends when the next season (or expansion)
+    /// starts
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl Season {
+    fn matches(&self, name: &str) -> bool {
+        let name = create_id(name);
+        let ids = [self.id.clone(), create_id(&self.name)];
+
+        ids.iter().any(|e| *e == name)
+            || ids
+                .iter()
+                .any(|e| strip_name_prefix(e) == strip_name_prefix(&name))
+    }
+}
+
+/// List of Destiny 2 seasons and expansions, and their dates.
+#[derive(Debug, Clone, Default)]
+pub struct Seasons {
+    seasons: Vec<Season>,
+}
+
+impl Seasons {
+    /// Seasons built into dcli. These are used for any seasons not found in
+    /// the manifest or seasons.json file.
+    pub fn built_in() -> Seasons {
+        //data is included in the source, so this should never fail
+        let seasons: Vec<Season> =
+            serde_json::from_str(BUILT_IN_SEASONS).unwrap_or_default();
+
+        let mut out = Seasons { seasons };
+        out.sort();
+        out
+    }
+
+    /// Loads the built in seasons, and then updates them with the season
+    /// definitions from the manifest (if it has been downloaded), and the
+    /// seasons.json file in the data directory (if it exists).
+    pub async fn load(data_dir: &Path) -> Result<Seasons, Error> {
+        let mut seasons = Seasons::built_in();
+
+        if data_dir.join(MANIFEST_FILE_NAME).exists() {
+            //we still want moments to work if the manifest cant be read, so we
+            //just log and fall back to the built in data
+            match Seasons::load_from_manifest(data_dir).await {
+                Ok(e) => {
+                    for s in e.into_iter() {
+                        seasons.merge(s);
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Could not load seasons from manifest : {}", e)
+                }
+            }
+        }
+
+        let path = data_dir.join(SEASONS_FILE_NAME);
+        if path.exists() {
+            let json = fs::read_to_string(&path)?;
+            let user: Vec<Season> = serde_json::from_str(&json)?;
+
+            for s in user.into_iter() {
+                seasons.merge(s);
+            }
+        }
+
+        seasons.sort();
+        Ok(seasons)
+    }
+
+    async fn load_from_manifest(data_dir: &Path) -> Result<Vec<Season>, Error> {
+        let mut manifest = ManifestInterface::new(data_dir, false).await?;
+        let definitions = manifest.get_season_definitions().await;
+        manifest.close().await?;
+
+        let mut out: Vec<Season> = Vec::new();
+        for d in definitions?.into_iter() {
+            let start = match d.start_date {
+                Some(e) => e,
+                None => continue,
+            };
+
+            if d.season_number == 0 || d.display_properties.name.is_empty() {
+                continue;
+            }
+
+            out.push(Season {
+                id: String::new(),
+                name: d.display_properties.name,
+                number: Some(d.season_number),
+                expansion: false,
+                start,
+                end: d.end_date,
+            });
+        }
+
+        Ok(out)
+    }
+
+    //replaces the season with the same number (or name if it doesnt have a
+    //number), or adds it if it doesnt exist. Built in ids are kept, so
+    //existing names continue to work
+    fn merge(&mut self, mut season: Season) {
+        let existing = self.seasons.iter_mut().find(|e| match season.number {
+            Some(n) => e.number == Some(n),
+            None => {
+                e.id == season.id
+                    || e.id == create_id(&season.name)
+                    || e.matches(&season.name)
+            }
+        });
+
+        match existing {
+            Some(e) => {
+                if season.id.is_empty() {
+                    season.id = e.id.clone();
+                }
+                season.expansion = season.expansion || e.expansion;
+                *e = season;
+            }
+            None => {
+                if season.id.is_empty() {
+                    season.id = create_id(&season.name);
+                }
+                self.seasons.push(season);
+            }
+        }
+    }
+
+    fn sort(&mut self) {
+        self.seasons.sort_by_key(|e| (e.start, e.number));
+    }
+
+    /// All seasons and expansions, sorted by start date.
+    pub fn get_all(&self) -> &[Season] {
+        &self.seasons
+    }
+
+    /// Finds the season for the specified moment.
+    pub fn find(&self, moment: &SeasonMoment) -> Result<&Season, Error> {
+        let out = match moment {
+            SeasonMoment::Name(e) => {
+                //check ids first, so short names dont match the wrong season
+                let id = create_id(e);
+                self.seasons
+                    .iter()
+                    .find(|s| s.id == id)
+                    .or_else(|| self.seasons.iter().find(|s| s.matches(e)))
+            }
+            SeasonMoment::Number(n) => {
+                self.seasons.iter().find(|s| s.number == Some(*n))
+            }
+            SeasonMoment::Relative(n) => {
+                let now = Utc::now();
+                let numbered: Vec<&Season> = self
+                    .seasons
+                    .iter()
+                    .filter(|s| s.number.is_some() && s.start <= now)
+                    .collect();
+
+                let n = *n as usize;
+                if n < numbered.len() {
+                    Some(numbered[numbered.len() - 1 - n])
+                } else {
+                    None
+                }
+            }
+        };
+
+        out.ok_or_else(|| Error::InvalidArgument {
+            description: format!(
+                "Could not find season or expansion : {}",
+                moment
+            ),
+        })
+    }
+
+    /// Finds the numbered season that was active at the specified date /
+    /// time.
+    pub fn find_by_date(&self, date_time: &DateTime<Utc>) -> Option<&Season> {
+        self.seasons
+            .iter()
+            .filter(|s| s.number.is_some() && &s.start <= date_time)
+            .last()
+            .filter(|s| match self.get_end(s) {
+                Some(e) => date_time < &e,
+                None => true,
+            })
+    }
+
+    /// Returns when the season ends. Seasons end when the next season or
+    /// expansion starts, and expansions when the next expansion is released,
+    /// unless an end date has been specified. Returns None if the season has
+    /// not ended.
+    pub fn get_end(&self, season: &Season) -> Option<DateTime<Utc>> {
+        if season.end.is_some() {
+            return season.end;
+        }
+
+        self.seasons
+            .iter()
+            .filter(|e| e.start > season.start)
+            .find(|e| match season.number {
+                Some(_) => e.number.is_some() || e.expansion,
+                None => e.expansion,
+            })
+            .map(|e| e.start)
+    }
+}
+
+//Season of the Deep -> season_of_the_deep
+fn create_id(name: &str) -> String {
+    let mut out = String::new();
+
+    for c in name.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+
+    out.trim_end_matches('_').to_string()
+}
+
+fn strip_name_prefix(id: &str) -> &str {
+    for p in NAME_PREFIXES.iter() {
+        if let Some(e) = id.strip_prefix(p) {
+            return e;
+        }
+    }
+
+    id
+}
@@ -0,0 +1,611 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use crate::crucible::{
+    ActivityDetail, ActivityRosterEntry, CruciblePlayerActivityPerformance,
+    PlayerHistory,
+};
+use crate::enums::standing::Standing;
+use crate::leavers::MatchResults;
+
+/// Upper bounds for the team strength delta ranges used to group matches,
+/// with a final range for everything above the last bound.
+const STRENGTH_DELTA_BOUNDS: [f32; 6] = [-0.5, -0.25, -0.1, 0.1, 0.25, 0.5];
+
+/// Team strength delta above which a match is considered lopsided
+const LOPSIDED_STRENGTH_DELTA: f32 = 0.25;
+
+/// Averages for one team in a match.
+#[derive(Debug, Default, Clone)]
+pub struct TeamBalance {
+    pub player_count: u32,
+
+    /// Average of the players' kills deaths ratios across their stored
+    /// activities for the mode played before the match. Players without
+    /// earlier activities are not included. None if no players on the team
+    /// have earlier activities.
+    pub average_kills_deaths_ratio: Option<f32>,
+
+    /// Average of the players' stored combat ratings from when the match
+    /// was played. Players without a stored rating are not included. None if
+    /// no players on the team have a stored rating.
+    pub average_combat_rating: Option<f32>,
+    pub average_light_level: f32,
+
+    /// Size of each fireteam on the team, largest first
+    pub fireteams: Vec<u32>,
+}
+
+impl TeamBalance {
+    fn with_entries(
+        entries: &[&ActivityRosterEntry],
+        histories: Option<&HashMap<i64, PlayerHistory>>,
+    ) -> TeamBalance {
+        if entries.is_empty() {
+            return TeamBalance::default();
+        }
+
+        let mut kds: Vec<f32> = Vec::new();
+        let mut ratings: Vec<f32> = Vec::new();
+        let mut light_total = 0;
+        let mut fireteams: HashMap<&str, u32> = HashMap::new();
+
+        for e in entries.iter() {
+            if let Some(h) = histories.and_then(|h| h.get(&e.player.member_id))
+            {
+                kds.push(h.kills_deaths_ratio());
+            }
+
+            if let Some(r) = e.combat_rating {
+                ratings.push(r);
+            }
+
+            light_total += e.player.light_level;
+            *fireteams.entry(&e.fireteam_id).or_insert(0) += 1;
+        }
+
+        let mut fireteams: Vec<u32> = fireteams.into_values().collect();
+        fireteams.sort_unstable_by(|a, b| b.cmp(a));
+
+        let count = entries.len() as u32;
+        TeamBalance {
+            player_count: count,
+            average_kills_deaths_ratio: average(&kds),
+            average_combat_rating: average(&ratings),
+            average_light_level: light_total as f32 / count as f32,
+            fireteams,
+        }
+    }
+
+    pub fn largest_fireteam(&self) -> u32 {
+        self.fireteams.first().copied().unwrap_or(0)
+    }
+}
+
+fn average(values: &[f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+
+    Some(values.iter().sum::<f32>() / values.len() as f32)
+}
+
+//difference between the values, relative to their average, so that stats
+//with different scales (kills deaths ratio and combat rating) can be
+//combined
+fn relative_delta(value: Option<f32>, other: Option<f32>) -> Option<f32> {
+    let (value, other) = (value?, other?);
+    let avg = (value + other) / 2.0;
+    if avg <= 0.0 {
+        return None;
+    }
+
+    Some((value - other) / avg)
+}
+
+/// How evenly matched the teams in a match were, based on the team strength
+/// delta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchBalanceType {
+    Even,
+    Favored,
+    Unfavored,
+
+    /// There isn't enough stored data to compare the teams' strength
+    Unknown,
+}
+
+impl std::fmt::Display for MatchBalanceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let out = match self {
+            MatchBalanceType::Even => "Even",
+            MatchBalanceType::Favored => "Favored",
+            MatchBalanceType::Unfavored => "Unfavored",
+            MatchBalanceType::Unknown => "Unknown",
+        };
+
+        write!(f, "{}", out)
+    }
+}
+
+/// The member's team and the opposing team for a single match.
+#[derive(Debug, Clone)]
+pub struct MatchBalance {
+    pub activity_detail: ActivityDetail,
+    pub standing: Standing,
+    pub team_score: u32,
+    pub opponent_score: u32,
+
+    /// The member's team, including the member
+    pub team: TeamBalance,
+    pub opponents: TeamBalance,
+}
+
+impl MatchBalance {
+    /// Returns None for activities without teams (i.e. Rumble) or where
+    /// the roster is not available.
+    pub fn with_roster(
+        performance: &CruciblePlayerActivityPerformance,
+        roster: &[ActivityRosterEntry],
+        histories: Option<&HashMap<i64, PlayerHistory>>,
+    ) -> Option<MatchBalance> {
+        if !performance.activity_detail.has_teams {
+            return None;
+        }
+
+        let stats = &performance.performance.stats;
+        let (team, opponents): (Vec<&ActivityRosterEntry>, Vec<_>) =
+            roster.iter().partition(|e| e.team == stats.team);
+
+        if team.is_empty() || opponents.is_empty() {
+            return None;
+        }
+
+        Some(MatchBalance {
+            activity_detail: performance.activity_detail.clone(),
+            standing: stats.standing,
+            team_score: stats.team_score,
+            opponent_score: opponents[0].team_score,
+            team: TeamBalance::with_entries(&team, histories),
+            opponents: TeamBalance::with_entries(&opponents, histories),
+        })
+    }
+
+    pub fn is_win(&self) -> bool {
+        self.standing == Standing::Victory
+    }
+
+    /// Difference in strength between the member's team and the opposing
+    /// team. Positive values favor the member's team.
+    ///
+    /// Strength is based on the teams' average kills deaths ratio and
+    /// average combat rating. The difference for each is relative to the
+    /// average of the two teams' values (i.e. 0.25 means the member's team
+    /// is 25% stronger), and the delta is the average of the differences
+    /// which are available for both teams. Returns None if neither is
+    /// available.
+    pub fn strength_delta(&self) -> Option<f32> {
+        let deltas: Vec<f32> = [
+            relative_delta(
+                self.team.average_kills_deaths_ratio,
+                self.opponents.average_kills_deaths_ratio,
+            ),
+            relative_delta(
+                self.team.average_combat_rating,
+                self.opponents.average_combat_rating,
+            ),
+        ]
+        .iter()
+        .flatten()
+        .copied()
+        .collect();
+
+        average(&deltas)
+    }
+
+    pub fn light_level_delta(&self) -> f32 {
+        self.team.average_light_level - self.opponents.average_light_level
+    }
+
+    /// Difference in score as a percent of the winning score. Positive
+    /// values favor the member's team.
+    pub fn score_margin(&self) -> f32 {
+        let max = self.team_score.max(self.opponent_score);
+        if max == 0 {
+            return 0.0;
+        }
+
+        (self.team_score as f32 - self.opponent_score as f32) / max as f32
+            * 100.0
+    }
+
+    pub fn get_balance_type(&self) -> MatchBalanceType {
+        let delta = match self.strength_delta() {
+            Some(e) => e,
+            None => return MatchBalanceType::Unknown,
+        };
+
+        if delta > LOPSIDED_STRENGTH_DELTA {
+            MatchBalanceType::Favored
+        } else if delta < -LOPSIDED_STRENGTH_DELTA {
+            MatchBalanceType::Unfavored
+        } else {
+            MatchBalanceType::Even
+        }
+    }
+}
+
+/// Match results for a range of team strength deltas.
+#[derive(Debug, Clone)]
+pub struct StrengthDeltaRange {
+    /// Lower bound (exclusive), or None if there is no lower bound
+    pub min: Option<f32>,
+
+    /// Upper bound (inclusive), or None if there is no upper bound
+    pub max: Option<f32>,
+    pub results: MatchResults,
+}
+
+impl StrengthDeltaRange {
+    fn contains(&self, delta: f32) -> bool {
+        self.min.map_or(true, |e| delta > e)
+            && self.max.map_or(true, |e| delta <= e)
+    }
+}
+
+/// Aggregate team balance data across a number of matches.
+#[derive(Debug, Clone)]
+pub struct BalanceSummary {
+    pub results: MatchResults,
+    pub even: MatchResults,
+    pub favored: MatchResults,
+    pub unfavored: MatchResults,
+
+    /// Matches without enough stored data to compare the teams' strength
+    pub unknown: MatchResults,
+
+    /// Averages for matches where the strength delta is known
+    pub average_strength_delta: f32,
+    pub average_absolute_strength_delta: f32,
+    pub average_light_level_delta: f32,
+    pub average_largest_fireteam: f32,
+    pub average_opponent_largest_fireteam: f32,
+
+    pub ranges: Vec<StrengthDeltaRange>,
+}
+
+impl BalanceSummary {
+    pub fn with_matches(matches: &[MatchBalance]) -> BalanceSummary {
+        let mut ranges: Vec<StrengthDeltaRange> = Vec::new();
+        let mut min: Option<f32> = None;
+        for b in STRENGTH_DELTA_BOUNDS.iter() {
+            ranges.push(StrengthDeltaRange {
+                min,
+                max: Some(*b),
+                results: MatchResults::default(),
+            });
+            min = Some(*b);
+        }
+        ranges.push(StrengthDeltaRange {
+            min,
+            max: None,
+            results: MatchResults::default(),
+        });
+
+        let mut out = BalanceSummary {
+            results: MatchResults::default(),
+            even: MatchResults::default(),
+            favored: MatchResults::default(),
+            unfavored: MatchResults::default(),
+            unknown: MatchResults::default(),
+            average_strength_delta: 0.0,
+            average_absolute_strength_delta: 0.0,
+            average_light_level_delta: 0.0,
+            average_largest_fireteam: 0.0,
+            average_opponent_largest_fireteam: 0.0,
+            ranges,
+        };
+
+        if matches.is_empty() {
+            return out;
+        }
+
+        let mut delta_count = 0;
+        for m in matches.iter() {
+            let is_win = m.is_win();
+
+            out.results.add(is_win);
+            match m.get_balance_type() {
+                MatchBalanceType::Even => out.even.add(is_win),
+                MatchBalanceType::Favored => out.favored.add(is_win),
+                MatchBalanceType::Unfavored => out.unfavored.add(is_win),
+                MatchBalanceType::Unknown => out.unknown.add(is_win),
+            }
+
+            if let Some(delta) = m.strength_delta() {
+                if let Some(r) =
+                    out.ranges.iter_mut().find(|e| e.contains(delta))
+                {
+                    r.results.add(is_win);
+                }
+
+                delta_count += 1;
+                out.average_strength_delta += delta;
+                out.average_absolute_strength_delta += delta.abs();
+            }
+
+            out.average_light_level_delta += m.light_level_delta();
+            out.average_largest_fireteam += m.team.largest_fireteam() as f32;
+            out.average_opponent_largest_fireteam +=
+                m.opponents.largest_fireteam() as f32;
+        }
+
+        if delta_count > 0 {
+            out.average_strength_delta /= delta_count as f32;
+            out.average_absolute_strength_delta /= delta_count as f32;
+        }
+
+        let count = matches.len() as f32;
+        out.average_light_level_delta /= count;
+        out.average_largest_fireteam /= count;
+        out.average_opponent_largest_fireteam /= count;
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Utc;
+
+    use crate::crucible::{
+        CruciblePlayerPerformance, CrucibleStats, Player, PlayerName,
+    };
+    use crate::enums::character::CharacterClass;
+    use crate::enums::completionreason::CompletionReason;
+    use crate::enums::mode::Mode;
+    use crate::enums::platform::Platform;
+
+    const TEAM: i32 = 16;
+    const OTHER_TEAM: i32 = 17;
+
+    fn entry(
+        member_id: i64,
+        team: i32,
+        fireteam_id: &str,
+        combat_rating: Option<f32>,
+    ) -> ActivityRosterEntry {
+        ActivityRosterEntry {
+            activity_id: 100,
+            player: Player {
+                member_id,
+                character_id: member_id * 10,
+                platform: Platform::Steam,
+                name: PlayerName {
+                    display_name: None,
+                    bungie_display_name: Some(format!("player{}", member_id)),
+                    bungie_display_name_code: Some("0001".to_string()),
+                },
+                light_level: 1800 + member_id as i32,
+                class_type: CharacterClass::Hunter,
+            },
+            team,
+            fireteam_id: fireteam_id.to_string(),
+            standing: Standing::Unknown,
+            completion_reason: CompletionReason::ObjectiveComplete,
+            completed: true,
+            start_seconds: 0,
+            team_score: if team == TEAM { 100 } else { 80 },
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+            combat_rating,
+        }
+    }
+
+    fn performance(
+        standing: Standing,
+        has_teams: bool,
+    ) -> CruciblePlayerActivityPerformance {
+        let player = entry(1, TEAM, "a", None).player;
+        CruciblePlayerActivityPerformance {
+            performance: CruciblePlayerPerformance {
+                player,
+                stats: CrucibleStats {
+                    assists: 0,
+                    score: 0,
+                    kills: 0,
+                    deaths: 0,
+                    average_score_per_kill: 0.0,
+                    average_score_per_life: 0.0,
+                    completed: true,
+                    opponents_defeated: 0,
+                    efficiency: 0.0,
+                    kills_deaths_ratio: 0.0,
+                    kills_deaths_assists: 0.0,
+                    activity_duration_seconds: 600,
+                    standing,
+                    team: TEAM,
+                    completion_reason: CompletionReason::ObjectiveComplete,
+                    start_seconds: 0,
+                    time_played_seconds: 600,
+                    player_count: 4,
+                    team_score: 100,
+                    emblem_hash: 0,
+                    extended: None,
+                },
+            },
+            activity_detail: ActivityDetail {
+                id: 100,
+                period: Utc::now(),
+                map_name: "".to_string(),
+                mode: if has_teams {
+                    Mode::ControlQuickplay
+                } else {
+                    Mode::Rumble
+                },
+                platform: Platform::Steam,
+                director_activity_hash: 0,
+                reference_id: 0,
+                has_teams,
+            },
+        }
+    }
+
+    fn history(member_id: i64, kills: u32, deaths: u32) -> PlayerHistory {
+        PlayerHistory {
+            member_id,
+            activity_count: 10,
+            kills,
+            deaths,
+            assists: 0,
+        }
+    }
+
+    fn roster() -> Vec<ActivityRosterEntry> {
+        vec![
+            entry(1, TEAM, "a", Some(120.0)),
+            entry(2, TEAM, "a", Some(100.0)),
+            entry(3, OTHER_TEAM, "b", Some(90.0)),
+            entry(4, OTHER_TEAM, "c", Some(90.0)),
+        ]
+    }
+
+    #[test]
+    fn relative_delta_is_relative_to_average() {
+        assert_eq!(relative_delta(Some(110.0), Some(90.0)), Some(0.2));
+        assert_eq!(relative_delta(Some(90.0), Some(110.0)), Some(-0.2));
+        assert_eq!(relative_delta(Some(1.0), None), None);
+        assert_eq!(relative_delta(Some(0.0), Some(0.0)), None);
+    }
+
+    #[test]
+    fn with_roster_averages_teams() {
+        let mut histories = HashMap::new();
+        histories.insert(1, history(1, 20, 10));
+        histories.insert(2, history(2, 10, 10));
+        histories.insert(3, history(3, 10, 20));
+
+        let p = performance(Standing::Victory, true);
+        let m =
+            MatchBalance::with_roster(&p, &roster(), Some(&histories)).unwrap();
+
+        assert_eq!(m.team.player_count, 2);
+        assert_eq!(m.team.average_kills_deaths_ratio, Some(1.5));
+        assert_eq!(m.team.average_combat_rating, Some(110.0));
+        assert_eq!(m.team.average_light_level, 1801.5);
+        assert_eq!(m.team.fireteams, vec![2]);
+        assert_eq!(m.team.largest_fireteam(), 2);
+
+        //players without history are not included in the average
+        assert_eq!(m.opponents.average_kills_deaths_ratio, Some(0.5));
+        assert_eq!(m.opponents.average_combat_rating, Some(90.0));
+        assert_eq!(m.opponents.fireteams, vec![1, 1]);
+
+        assert_eq!((m.team_score, m.opponent_score), (100, 80));
+        assert_eq!(m.score_margin(), 20.0);
+        assert_eq!(m.light_level_delta(), -2.0);
+
+        //kd delta of 1.0 and combat rating delta of 0.2
+        assert!((m.strength_delta().unwrap() - 0.6).abs() < 0.0001);
+        assert_eq!(m.get_balance_type(), MatchBalanceType::Favored);
+    }
+
+    #[test]
+    fn with_roster_ignores_matches_without_teams() {
+        let p = performance(Standing::Victory, false);
+        assert!(MatchBalance::with_roster(&p, &roster(), None).is_none());
+    }
+
+    #[test]
+    fn strength_delta_uses_available_data() {
+        let p = performance(Standing::Defeat, true);
+
+        //only combat ratings are available
+        let m = MatchBalance::with_roster(&p, &roster(), None).unwrap();
+        assert_eq!(m.team.average_kills_deaths_ratio, None);
+        assert!((m.strength_delta().unwrap() - 0.2).abs() < 0.0001);
+        assert_eq!(m.get_balance_type(), MatchBalanceType::Even);
+
+        let roster: Vec<ActivityRosterEntry> = roster()
+            .into_iter()
+            .map(|mut e| {
+                e.combat_rating = None;
+                e
+            })
+            .collect();
+
+        let m = MatchBalance::with_roster(&p, &roster, None).unwrap();
+        assert_eq!(m.strength_delta(), None);
+        assert_eq!(m.get_balance_type(), MatchBalanceType::Unknown);
+    }
+
+    #[test]
+    fn balance_summary_groups_matches() {
+        let no_ratings: Vec<ActivityRosterEntry> = roster()
+            .into_iter()
+            .map(|mut e| {
+                e.combat_rating = None;
+                e
+            })
+            .collect();
+
+        let matches = vec![
+            //delta of 0.2
+            MatchBalance::with_roster(
+                &performance(Standing::Victory, true),
+                &roster(),
+                None,
+            )
+            .unwrap(),
+            //unknown delta
+            MatchBalance::with_roster(
+                &performance(Standing::Defeat, true),
+                &no_ratings,
+                None,
+            )
+            .unwrap(),
+        ];
+
+        let s = BalanceSummary::with_matches(&matches);
+
+        assert_eq!((s.results.matches, s.results.wins), (2, 1));
+        assert_eq!((s.even.matches, s.even.wins), (1, 1));
+        assert_eq!((s.unknown.matches, s.unknown.wins), (1, 0));
+        assert_eq!(s.favored.matches + s.unfavored.matches, 0);
+
+        //unknown deltas are not included in the delta averages
+        assert!((s.average_strength_delta - 0.2).abs() < 0.0001);
+        assert!((s.average_absolute_strength_delta - 0.2).abs() < 0.0001);
+        assert_eq!(s.average_light_level_delta, -2.0);
+        assert_eq!(s.average_largest_fireteam, 2.0);
+        assert_eq!(s.average_opponent_largest_fireteam, 1.0);
+
+        let range: Vec<u32> =
+            s.ranges.iter().map(|e| e.results.matches).collect();
+        assert_eq!(range, vec![0, 0, 0, 0, 1, 0, 0]);
+    }
+}
@@ -0,0 +1,527 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fmt;
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, TimeZone, Utc,
+    Weekday,
+};
+
+use crate::error::Error;
+use crate::timezone::{get_timezone, to_local};
+use crate::utils::{get_last_daily_reset, get_last_weekly_reset};
+
+const RANGE_DELIMITER: &str = "..";
+
+/// A relative or calendar based time expression, such as 3d, this-month,
+/// 2023-05, 2023-W14 or 2023-01-01..2023-02-01, which resolves to a start
+/// and (optional) end time.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum TimeExpression {
+    /// Amount of time before now (i.e. 3d, 12h)
+    Ago(Duration),
+    Instant(DateTime<Utc>),
+    Day(NaiveDate),
+    Month(i32, u32),
+    IsoWeek(i32, u32),
+    Today,
+    Yesterday,
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    LastMonth,
+    ThisYear,
+    LastYear,
+    SinceLastReset,
+    SinceWeeklyReset,
+    Range(Box<TimeExpression>, Box<TimeExpression>),
+}
+
+impl TimeExpression {
+    /// Parses a time expression.
+    ///
+    /// Returns Ok(None) if the input does not look like a time expression
+    /// (so it can be parsed as something else, such as a season name), and
+    /// an error if it looks like a time expression, but is not valid.
+    pub fn parse(src: &str) -> Result<Option<TimeExpression>, String> {
+        let s = src.trim();
+
+        if s.contains(RANGE_DELIMITER) {
+            return TimeExpression::parse_range(s).map(Some);
+        }
+
+        if let Some(e) = TimeExpression::parse_keyword(s) {
+            return Ok(Some(e));
+        }
+
+        //names (such as seasons) only contain letters and underscores
+        if s.chars().all(|c| c.is_alphabetic() || c == '_') {
+            return Ok(None);
+        }
+
+        TimeExpression::parse_value(s).map(Some)
+    }
+
+    fn parse_range(s: &str) -> Result<TimeExpression, String> {
+        let tokens: Vec<&str> = s.split(RANGE_DELIMITER).collect();
+
+        if tokens.len() != 2 || tokens[0].is_empty() || tokens[1].is_empty() {
+            return Err(format!(
+                "Invalid range : {}. Ranges must be in the form start..end (i.e. 2023-01-01..2023-02-01)",
+                s
+            ));
+        }
+
+        let parse = |e: &str| -> Result<TimeExpression, String> {
+            match TimeExpression::parse_keyword(e) {
+                Some(e) => Ok(e),
+                None => TimeExpression::parse_value(e),
+            }
+        };
+
+        Ok(TimeExpression::Range(
+            Box::new(parse(tokens[0])?),
+            Box::new(parse(tokens[1])?),
+        ))
+    }
+
+    fn parse_keyword(s: &str) -> Option<TimeExpression> {
+        let s = s.to_lowercase().replace('_', "-");
+
+        let out = match &s[..] {
+            "today" => TimeExpression::Today,
+            "yesterday" => TimeExpression::Yesterday,
+            "this-week" => TimeExpression::ThisWeek,
+            "last-week" => TimeExpression::LastWeek,
+            "this-month" => TimeExpression::ThisMonth,
+            "last-month" => TimeExpression::LastMonth,
+            "this-year" => TimeExpression::ThisYear,
+            "last-year" => TimeExpression::LastYear,
+            "since-last-reset" => TimeExpression::SinceLastReset,
+            "since-weekly-reset" => TimeExpression::SinceWeeklyReset,
+            _ => return None,
+        };
+
+        Some(out)
+    }
+
+    fn parse_value(s: &str) -> Result<TimeExpression, String> {
+        if let Ok(e) = DateTime::parse_from_rfc3339(s) {
+            return Ok(TimeExpression::Instant(e.with_timezone(&Utc)));
+        }
+
+        let lower = s.to_lowercase();
+
+        //3d, 12h, 30m, 2w
+        if let Some(unit) = lower.chars().last() {
+            let amount = &lower[..lower.len() - unit.len_utf8()];
+
+            if !amount.is_empty() && amount.chars().all(|c| c.is_ascii_digit())
+            {
+                let amount: i64 = amount
+                    .parse()
+                    .map_err(|_| format!("Invalid duration : {}", s))?;
+
+                let unit_seconds: i64 = match unit {
+                    'm' => 60,
+                    'h' => 60 * 60,
+                    'd' => 60 * 60 * 24,
+                    'w' => 60 * 60 * 24 * 7,
+                    _ => {
+                        return Err(format!(
+                            "Invalid duration unit : {}. Valid units are m (minutes), h (hours), d (days) and w (weeks).",
+                            unit
+                        ))
+                    }
+                };
+
+                //Duration::minutes etc panic if the value is out of range
+                let ms = amount
+                    .checked_mul(unit_seconds)
+                    .and_then(|e| e.checked_mul(1000))
+                    .ok_or_else(|| format!("Duration is too large : {}", s))?;
+
+                return Ok(TimeExpression::Ago(Duration::milliseconds(ms)));
+            }
+        }
+
+        //2023-W14
+        if let Some((year, week)) = lower.split_once("-w") {
+            let year: i32 = year
+                .parse()
+                .map_err(|_| format!("Invalid year in week : {}", s))?;
+            let week: u32 = week
+                .parse()
+                .map_err(|_| format!("Invalid week number : {}", s))?;
+
+            if NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).is_none() {
+                return Err(format!("Invalid week number : {}", s));
+            }
+
+            return Ok(TimeExpression::IsoWeek(year, week));
+        }
+
+        if let Ok(e) = NaiveDate::parse_from_str(&lower, "%Y-%m-%d") {
+            return Ok(TimeExpression::Day(e));
+        }
+
+        //2023-05
+        let tokens: Vec<&str> = lower.split('-').collect();
+        if tokens.len() == 2 && tokens[0].len() == 4 {
+            if let (Ok(year), Ok(month)) =
+                (tokens[0].parse::<i32>(), tokens[1].parse::<u32>())
+            {
+                if NaiveDate::from_ymd_opt(year, month, 1).is_some() {
+                    return Ok(TimeExpression::Month(year, month));
+                }
+            }
+        }
+
+        Err(format!(
+            "Invalid time expression : {}. Examples : 3d, 12h, last-week, this-month, 2023-05, 2023-W14, 2023-05-01, since-last-reset, 2023-01-01..2023-02-01",
+            s
+        ))
+    }
+
+    /// Whether the expression has a fixed end (i.e. 2023-05), rather than
+    /// running up until now.
+    pub fn has_end(&self) -> bool {
+        !matches!(
+            self,
+            TimeExpression::Ago(_)
+                | TimeExpression::Instant(_)
+                | TimeExpression::Today
+                | TimeExpression::ThisWeek
+                | TimeExpression::ThisMonth
+                | TimeExpression::ThisYear
+                | TimeExpression::SinceLastReset
+                | TimeExpression::SinceWeeklyReset
+        )
+    }
+
+    /// Returns the start and end of the time period for the expression.
+    /// Expressions without a fixed end (see has_end) end now.
+    pub fn resolve(&self) -> Result<(DateTime<Utc>, DateTime<Utc>), Error> {
+        let now = Utc::now();
+
+        //calendar based expressions use the timezone set by the user
+        let today = to_local(&now).date_naive();
+
+        let out = match self {
+            TimeExpression::Ago(e) => {
+                let start = now.checked_sub_signed(*e).ok_or_else(|| {
+                    Error::InvalidArgument {
+                        description: format!(
+                            "Time expression is out of range : {}",
+                            self
+                        ),
+                    }
+                })?;
+                (start, now)
+            }
+            TimeExpression::Instant(e) => (*e, now),
+            TimeExpression::Day(e) => day_range(*e, 1)?,
+            TimeExpression::Month(y, m) => month_range(*y, *m)?,
+            TimeExpression::IsoWeek(y, w) => {
+                let start = NaiveDate::from_isoywd_opt(*y, *w, Weekday::Mon)
+                    .ok_or_else(|| invalid(self))?;
+                day_range(start, 7)?
+            }
+            TimeExpression::Today => (to_utc(today)?, now),
+            TimeExpression::Yesterday => {
+                day_range(today - Duration::days(1), 1)?
+            }
+            TimeExpression::ThisWeek => (week_start(today)?, now),
+            TimeExpression::LastWeek => {
+                day_range(week_start_date(today) - Duration::weeks(1), 7)?
+            }
+            TimeExpression::ThisMonth => {
+                (month_range(today.year(), today.month())?.0, now)
+            }
+            TimeExpression::LastMonth => {
+                let (y, m) = if today.month() == 1 {
+                    (today.year() - 1, 12)
+                } else {
+                    (today.year(), today.month() - 1)
+                };
+                month_range(y, m)?
+            }
+            TimeExpression::ThisYear => (year_start(today.year())?, now),
+            TimeExpression::LastYear => {
+                (year_start(today.year() - 1)?, year_start(today.year())?)
+            }
+            TimeExpression::SinceLastReset => (get_last_daily_reset(), now),
+            TimeExpression::SinceWeeklyReset => (get_last_weekly_reset(), now),
+
+            //ranges go from the start of the first expression up to the
+            //start of the second
+            TimeExpression::Range(start, end) => {
+                (start.resolve()?.0, end.resolve()?.0)
+            }
+        };
+
+        if out.0 > out.1 {
+            return Err(Error::DateTimePeriodOrder);
+        }
+
+        Ok(out)
+    }
+}
+
+impl fmt::Display for TimeExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeExpression::Ago(e) => write!(f, "last {}", format_duration(e)),
+            TimeExpression::Instant(e) => write!(f, "{}", e.to_rfc3339()),
+            TimeExpression::Day(e) => write!(f, "{}", e.format("%Y-%m-%d")),
+            TimeExpression::Month(y, m) => write!(f, "{}-{:02}", y, m),
+            TimeExpression::IsoWeek(y, w) => write!(f, "{}-W{:02}", y, w),
+            TimeExpression::Today => write!(f, "today"),
+            TimeExpression::Yesterday => write!(f, "yesterday"),
+            TimeExpression::ThisWeek => write!(f, "this week"),
+            TimeExpression::LastWeek => write!(f, "last week"),
+            TimeExpression::ThisMonth => write!(f, "this month"),
+            TimeExpression::LastMonth => write!(f, "last month"),
+            TimeExpression::ThisYear => write!(f, "this year"),
+            TimeExpression::LastYear => write!(f, "last year"),
+            TimeExpression::SinceLastReset => write!(f, "last reset"),
+            TimeExpression::SinceWeeklyReset => write!(f, "last weekly reset"),
+            TimeExpression::Range(start, end) => {
+                write!(f, "{} to {}", start, end)
+            }
+        }
+    }
+}
+
+fn format_duration(d: &Duration) -> String {
+    let (amount, unit) = if d.num_weeks() > 0 && d.num_days() % 7 == 0 {
+        (d.num_weeks(), "week")
+    } else if d.num_days() > 0 && d.num_hours() % 24 == 0 {
+        (d.num_days(), "day")
+    } else if d.num_hours() > 0 && d.num_minutes() % 60 == 0 {
+        (d.num_hours(), "hour")
+    } else {
+        (d.num_minutes(), "minute")
+    };
+
+    if amount == 1 {
+        unit.to_string()
+    } else {
+        format!("{} {}s", amount, unit)
+    }
+}
+
+fn invalid(e: &TimeExpression) -> Error {
+    Error::InvalidArgument {
+        description: format!("Invalid time expression : {}", e),
+    }
+}
+
+//returns midnight at the start of the date, in the current timezone
+fn to_utc(date: NaiveDate) -> Result<DateTime<Utc>, Error> {
+    let tz = get_timezone();
+    let midnight =
+        date.and_hms_opt(0, 0, 0)
+            .ok_or_else(|| Error::InvalidArgument {
+                description: format!("Invalid date : {}", date),
+            })?;
+
+    //midnight can be ambiguous or not exist when daylight savings time
+    //changes, in which case we use the earliest valid time
+    let local = match tz.from_local_datetime(&midnight) {
+        LocalResult::Single(e) => e,
+        LocalResult::Ambiguous(e, _) => e,
+        LocalResult::None => tz.from_utc_datetime(&midnight),
+    };
+
+    Ok(local.with_timezone(&Utc))
+}
+
+//returns midnight at the start of the date, and at the start of the date
+//the number of days after it, in the current timezone. The end is
+//calculated from the local date, so that ranges which cross a daylight
+//savings time change are not off by an hour
+fn day_range(
+    date: NaiveDate,
+    days: i64,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), Error> {
+    let end =
+        date.checked_add_signed(Duration::days(days))
+            .ok_or_else(|| Error::InvalidArgument {
+                description: format!("Invalid date : {}", date),
+            })?;
+
+    Ok((to_utc(date)?, to_utc(end)?))
+}
+
+fn week_start_date(date: NaiveDate) -> NaiveDate {
+    let days = date.weekday().num_days_from_monday() as i64;
+    date - Duration::days(days)
+}
+
+fn week_start(date: NaiveDate) -> Result<DateTime<Utc>, Error> {
+    to_utc(week_start_date(date))
+}
+
+fn year_start(year: i32) -> Result<DateTime<Utc>, Error> {
+    month_range(year, 1).map(|e| e.0)
+}
+
+fn month_range(
+    year: i32,
+    month: u32,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), Error> {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+
+    let start = NaiveDate::from_ymd_opt(year, month, 1);
+    let end = NaiveDate::from_ymd_opt(next_year, next_month, 1);
+
+    match (start, end) {
+        (Some(s), Some(e)) => Ok((to_utc(s)?, to_utc(e)?)),
+        _ => Err(Error::InvalidArgument {
+            description: format!("Invalid month : {}-{:02}", year, month),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timezone::{parse_timezone, set_timezone};
+
+    fn parse(s: &str) -> TimeExpression {
+        TimeExpression::parse(s).unwrap().unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse("30m"), TimeExpression::Ago(Duration::minutes(30)));
+        assert_eq!(parse("12h"), TimeExpression::Ago(Duration::hours(12)));
+        assert_eq!(parse("3D"), TimeExpression::Ago(Duration::days(3)));
+        assert_eq!(parse("2w"), TimeExpression::Ago(Duration::weeks(2)));
+
+        assert!(TimeExpression::parse("3y").is_err());
+        assert!(TimeExpression::parse("9223372036854775807w").is_err());
+    }
+
+    #[test]
+    fn parse_calendar_values() {
+        assert_eq!(
+            parse("2023-05-01"),
+            TimeExpression::Day(NaiveDate::from_ymd_opt(2023, 5, 1).unwrap())
+        );
+        assert_eq!(parse("2023-05"), TimeExpression::Month(2023, 5));
+        assert_eq!(parse("2023-W14"), TimeExpression::IsoWeek(2023, 14));
+        assert_eq!(
+            parse("2023-05-01T12:00:00Z"),
+            TimeExpression::Instant(utc("2023-05-01T12:00:00Z"))
+        );
+
+        assert!(TimeExpression::parse("2023-13").is_err());
+        assert!(TimeExpression::parse("2023-W54").is_err());
+    }
+
+    #[test]
+    fn parse_keywords_and_ranges() {
+        assert_eq!(parse("last-week"), TimeExpression::LastWeek);
+        assert_eq!(parse("this_month"), TimeExpression::ThisMonth);
+        assert_eq!(
+            parse("2023-01..this-month"),
+            TimeExpression::Range(
+                Box::new(TimeExpression::Month(2023, 1)),
+                Box::new(TimeExpression::ThisMonth)
+            )
+        );
+
+        assert!(TimeExpression::parse("2023-01..").is_err());
+        assert!(TimeExpression::parse("a..b..c").is_err());
+    }
+
+    #[test]
+    fn parse_names_are_not_expressions() {
+        assert_eq!(TimeExpression::parse("season_of_the_deep"), Ok(None));
+        assert_eq!(TimeExpression::parse("weekly"), Ok(None));
+    }
+
+    #[test]
+    fn has_end() {
+        assert!(!parse("3d").has_end());
+        assert!(!parse("this-week").has_end());
+        assert!(parse("last-week").has_end());
+        assert!(parse("2023-05").has_end());
+    }
+
+    #[test]
+    fn resolve_ago() {
+        let (start, end) = parse("2h").resolve().unwrap();
+        assert_eq!(end - start, Duration::hours(2));
+
+        assert!(TimeExpression::Ago(Duration::max_value())
+            .resolve()
+            .is_err());
+    }
+
+    //all of the tests which depend on the timezone are in a single test,
+    //since the timezone is shared by all tests running in parallel
+    #[test]
+    fn resolve_calendar_values_in_timezone() {
+        set_timezone(parse_timezone("America/Los_Angeles").unwrap());
+
+        //daylight savings time started on 2023-03-12
+        let result = [
+            ("2023-03-12", "2023-03-12T08:00:00Z", "2023-03-13T07:00:00Z"),
+            ("2023-03-13", "2023-03-13T07:00:00Z", "2023-03-14T07:00:00Z"),
+            ("2023-W10", "2023-03-06T08:00:00Z", "2023-03-13T07:00:00Z"),
+            ("2023-03", "2023-03-01T08:00:00Z", "2023-04-01T07:00:00Z"),
+            ("2023-12", "2023-12-01T08:00:00Z", "2024-01-01T08:00:00Z"),
+            (
+                "2023-03-01..2023-03-15",
+                "2023-03-01T08:00:00Z",
+                "2023-03-15T07:00:00Z",
+            ),
+        ]
+        .iter()
+        .map(|(e, start, end)| {
+            (parse(e).resolve().map_err(|e| e.to_string()), *start, *end)
+        })
+        .collect::<Vec<_>>();
+
+        let range = parse("2023-03-15..2023-03-01").resolve();
+
+        set_timezone(chrono_tz::UTC);
+
+        for (r, start, end) in result.into_iter() {
+            assert_eq!(r, Ok((utc(start), utc(end))));
+        }
+
+        assert!(range.is_err());
+    }
+}
@@ -0,0 +1,88 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+
+use crate::error::Error;
+
+/// Environment variable checked for the timezone if it is not set via the
+/// command line or config file.
+pub const TIMEZONE_ENV_VAR: &str = "TZ";
+
+lazy_static! {
+    static ref TIMEZONE: RwLock<Tz> = RwLock::new(Tz::UTC);
+}
+
+/// Sets the timezone used to display dates / times, and to calculate
+/// calendar based moments (i.e. day and month boundaries).
+pub fn set_timezone(tz: Tz) {
+    let mut t = TIMEZONE.write().unwrap();
+    *t = tz;
+}
+
+/// Returns the timezone set via set_timezone. Defaults to UTC.
+pub fn get_timezone() -> Tz {
+    *TIMEZONE.read().unwrap()
+}
+
+/// Converts the date / time to the current timezone
+pub fn to_local(dt: &DateTime<Utc>) -> DateTime<Tz> {
+    dt.with_timezone(&get_timezone())
+}
+
+/// Parses an IANA timezone name, such as America/Los_Angeles or UTC
+pub fn parse_timezone(name: &str) -> Result<Tz, Error> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| Error::InvalidArgument {
+            description: format!(
+                "Unknown timezone : {}. Timezones must be IANA names, such as America/Los_Angeles or Europe/Berlin.",
+                name
+            ),
+        })
+}
+
+/// Determines the timezone from the TZ environment variable, or the system
+/// timezone. Falls back to UTC if neither can be determined.
+pub fn get_system_timezone() -> Tz {
+    if let Ok(e) = std::env::var(TIMEZONE_ENV_VAR) {
+        //TZ can also contain paths (i.e. :/etc/localtime), which we ignore
+        match parse_timezone(e.trim_start_matches(':')) {
+            Ok(tz) => return tz,
+            Err(_) => {
+                log::info!("Could not parse {} : {}", TIMEZONE_ENV_VAR, e)
+            }
+        }
+    }
+
+    match iana_time_zone::get_timezone() {
+        Ok(e) => parse_timezone(&e).unwrap_or(Tz::UTC),
+        Err(e) => {
+            log::info!("Could not determine system timezone : {:?}", e);
+            Tz::UTC
+        }
+    }
+}
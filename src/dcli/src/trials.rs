@@ -0,0 +1,277 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::crucible::{
+    ActivityRosterEntry, CruciblePlayerActivityPerformance, Player,
+};
+use crate::enums::completionreason::CompletionReason;
+use crate::enums::standing::Standing;
+use crate::utils::get_friday_reset_before;
+
+/// Number of wins needed to complete a Trials card
+pub const TRIALS_CARD_MAX_WINS: u32 = 7;
+
+/// Number of losses after which a Trials card is complete
+pub const TRIALS_CARD_MAX_LOSSES: u32 = 3;
+
+//Trials runs from the Friday reset to the Tuesday weekly reset
+const TRIALS_WEEKEND_DAYS: i64 = 4;
+
+/// A single Trials match, along with the players on both teams.
+#[derive(Debug, Clone)]
+pub struct TrialsMatch {
+    pub performance: CruciblePlayerActivityPerformance,
+
+    /// Players on the member's team, not including the member
+    pub teammates: Vec<Player>,
+    pub opponents: Vec<Player>,
+
+    pub opponent_score: u32,
+
+    //member ids of players in the member's fireteam, used to determine when
+    //the fireteam changes
+    fireteam: Vec<i64>,
+}
+
+impl TrialsMatch {
+    fn with_roster(
+        performance: CruciblePlayerActivityPerformance,
+        roster: &[ActivityRosterEntry],
+    ) -> TrialsMatch {
+        let stats = &performance.performance.stats;
+        let member_id = performance.performance.player.member_id;
+
+        let fireteam_id = roster
+            .iter()
+            .find(|e| e.player.member_id == member_id)
+            .map(|e| e.fireteam_id.to_string())
+            .unwrap_or_default();
+
+        let mut teammates: Vec<Player> = Vec::new();
+        let mut opponents: Vec<Player> = Vec::new();
+        let mut fireteam: Vec<i64> = Vec::new();
+        let mut opponent_score = 0;
+
+        for e in roster.iter().filter(|e| e.player.member_id != member_id) {
+            if e.team == stats.team {
+                if !fireteam_id.is_empty() && e.fireteam_id == fireteam_id {
+                    fireteam.push(e.player.member_id);
+                }
+                teammates.push(e.player.clone());
+            } else {
+                opponent_score = e.team_score;
+                opponents.push(e.player.clone());
+            }
+        }
+
+        fireteam.sort_unstable();
+
+        TrialsMatch {
+            performance,
+            teammates,
+            opponents,
+            opponent_score,
+            fireteam,
+        }
+    }
+
+    pub fn is_win(&self) -> bool {
+        self.performance.performance.stats.standing == Standing::Victory
+    }
+
+    pub fn is_mercy(&self) -> bool {
+        self.performance.performance.stats.completion_reason
+            == CompletionReason::Mercy
+    }
+
+    /// Whether the match ended because a team left, or the member left
+    /// before the match completed
+    pub fn is_forfeit(&self) -> bool {
+        let stats = &self.performance.performance.stats;
+        stats.completion_reason == CompletionReason::NoOpponents
+            || !stats.completed
+    }
+
+    pub fn get_period(&self) -> DateTime<Utc> {
+        self.performance.activity_detail.period
+    }
+}
+
+/// Consecutive Trials matches played with the same fireteam, until the card
+/// has 7 wins or 3 losses.
+///
+/// Cards are reconstructed from stored activities, so cards reset in game,
+/// and passages which change the card rules, are not taken into account.
+#[derive(Debug, Clone)]
+pub struct TrialsCard {
+    pub matches: Vec<TrialsMatch>,
+}
+
+impl TrialsCard {
+    pub fn wins(&self) -> u32 {
+        self.matches.iter().filter(|e| e.is_win()).count() as u32
+    }
+
+    pub fn losses(&self) -> u32 {
+        self.matches.iter().filter(|e| !e.is_win()).count() as u32
+    }
+
+    pub fn mercy_count(&self) -> u32 {
+        self.matches.iter().filter(|e| e.is_mercy()).count() as u32
+    }
+
+    pub fn forfeit_count(&self) -> u32 {
+        self.matches.iter().filter(|e| e.is_forfeit()).count() as u32
+    }
+
+    pub fn is_flawless(&self) -> bool {
+        self.wins() >= TRIALS_CARD_MAX_WINS && self.losses() == 0
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.wins() >= TRIALS_CARD_MAX_WINS
+            || self.losses() >= TRIALS_CARD_MAX_LOSSES
+    }
+
+    /// All players who played on the member's team while on the card
+    pub fn get_teammates(&self) -> Vec<&Player> {
+        let mut out: Vec<&Player> = Vec::new();
+        for m in self.matches.iter() {
+            for p in m.teammates.iter() {
+                if !out.iter().any(|e| e.member_id == p.member_id) {
+                    out.push(p);
+                }
+            }
+        }
+
+        out
+    }
+
+    fn get_fireteam(&self) -> &[i64] {
+        match self.matches.first() {
+            Some(e) => &e.fireteam,
+            None => &[],
+        }
+    }
+}
+
+/// Trials cards played during a weekend, from the Friday reset until the
+/// Tuesday weekly reset.
+#[derive(Debug, Clone)]
+pub struct TrialsWeekend {
+    pub start: DateTime<Utc>,
+    pub cards: Vec<TrialsCard>,
+}
+
+impl TrialsWeekend {
+    pub fn get_end(&self) -> DateTime<Utc> {
+        self.start + Duration::days(TRIALS_WEEKEND_DAYS)
+    }
+
+    /// Reconstructs Trials weekends and cards from the member's Trials
+    /// activities and the rosters for those activities.
+    pub fn with_activities(
+        performances: &[CruciblePlayerActivityPerformance],
+        rosters: &HashMap<i64, Vec<ActivityRosterEntry>>,
+    ) -> Vec<TrialsWeekend> {
+        let mut performances = performances.to_vec();
+        performances.sort_by(|a, b| {
+            a.activity_detail.period.cmp(&b.activity_detail.period)
+        });
+
+        let mut out: Vec<TrialsWeekend> = Vec::new();
+        for p in performances {
+            let start = get_friday_reset_before(&p.activity_detail.period);
+            let roster = match rosters.get(&p.activity_detail.id) {
+                Some(e) => e.as_slice(),
+                None => &[],
+            };
+
+            let m = TrialsMatch::with_roster(p, roster);
+
+            let weekend = match out.last_mut() {
+                Some(e) if e.start == start => e,
+                _ => {
+                    out.push(TrialsWeekend {
+                        start,
+                        cards: Vec::new(),
+                    });
+                    out.last_mut().unwrap()
+                }
+            };
+
+            match weekend.cards.last_mut() {
+                Some(e)
+                    if !e.is_complete() && e.get_fireteam() == m.fireteam =>
+                {
+                    e.matches.push(m)
+                }
+                _ => weekend.cards.push(TrialsCard { matches: vec![m] }),
+            }
+        }
+
+        out
+    }
+}
+
+/// Totals across a number of Trials weekends.
+#[derive(Debug, Default, Clone)]
+pub struct TrialsSummary {
+    pub weekends: u32,
+    pub cards: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub flawless: u32,
+    pub mercy: u32,
+    pub forfeits: u32,
+}
+
+impl TrialsSummary {
+    pub fn with_weekends(weekends: &[&TrialsWeekend]) -> TrialsSummary {
+        let mut out = TrialsSummary {
+            weekends: weekends.len() as u32,
+            ..Default::default()
+        };
+
+        for c in weekends.iter().flat_map(|e| e.cards.iter()) {
+            out.cards += 1;
+            out.wins += c.wins();
+            out.losses += c.losses();
+            out.mercy += c.mercy_count();
+            out.forfeits += c.forfeit_count();
+
+            if c.is_flawless() {
+                out.flawless += 1;
+            }
+        }
+
+        out
+    }
+
+    pub fn total_matches(&self) -> u32 {
+        self.wins + self.losses
+    }
+}
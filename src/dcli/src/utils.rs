@@ -27,11 +27,12 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use crate::enums::mode::Mode;
-use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Timelike, Utc};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
 use crossterm::{execute, terminal};
 use std::str::FromStr;
 
 use crate::error::Error;
+use crate::timezone::to_local;
 
 //use chrono::prelude::*;
 
@@ -168,6 +169,15 @@ pub fn clear_scr() {
     let _ = execute!(stdout, terminal::Clear(terminal::ClearType::All));
 }
 
+/// Returns the width of the terminal in columns, or default_width if it
+/// cannot be determined (for example, when output is piped).
+pub fn get_terminal_width(default_width: usize) -> usize {
+    match terminal::size() {
+        Ok((w, _h)) if w > 0 => w as usize,
+        _ => default_width,
+    }
+}
+
 pub fn clear_terminal() {
     print!("{}[2J", 27 as char);
 }
@@ -181,11 +191,13 @@ pub fn uppercase_first_char(s: &str) -> String {
     }
 }
 
+/// Formats the date / time for display, in the timezone set via
+/// timezone::set_timezone
 pub fn human_date_format(start_time: &DateTime<Utc>) -> String {
-    let local = start_time.with_timezone(&Local);
+    let local = to_local(start_time);
     let format_str = if Utc::now() - *start_time > Duration::days(6) {
         "%B %-d, %Y"
-    } else if local.day() == Local::now().day() {
+    } else if local.day() == to_local(&Utc::now()).day() {
         "Today at %-I:%M %p"
     } else {
         "%A at %-I:%M %p"
@@ -246,6 +258,9 @@ pub fn get_destiny2_launch_date() -> DateTime<Utc> {
     Utc.with_ymd_and_hms(2017, 9, 6, 17, 0, 0).unwrap()
 }
 
+//Destiny 2 resets happen at 17:00 UTC year round (they do not move with
+//daylight savings time), so resets are always calculated in UTC, and only
+//converted to the users timezone for display
 pub fn get_last_weekly_reset() -> DateTime<Utc> {
     //get a hardcoded past reset date / time (17:00 UTC every tuesday)
     let past_reset: DateTime<Utc> =
@@ -268,16 +283,33 @@ pub fn get_last_daily_reset() -> DateTime<Utc> {
     find_previous_moment(past_reset, DAY_IN_SECONDS)
 }
 
+/// Returns the Friday reset (start of the Trials / Xur weekend) at or before
+/// the specified date / time
+pub fn get_friday_reset_before(date_time: &DateTime<Utc>) -> DateTime<Utc> {
+    let past_reset: DateTime<Utc> =
+        Utc.with_ymd_and_hms(2020, 12, 4, 17, 0, 0).unwrap();
+    find_moment_before(*date_time, past_reset, WEEK_IN_SECONDS)
+}
+
 fn find_previous_moment(
     past_reset: DateTime<Utc>,
     interval: i64,
 ) -> DateTime<Utc> {
-    let now: DateTime<Utc> = Utc::now();
+    find_moment_before(Utc::now(), past_reset, interval)
+}
 
-    //get total seconds between now and the past reset
-    //take the mod of that divided by a week in seconds
-    //subtract that amount from current date / time to find previous reset
-    now - Duration::seconds((now - past_reset).num_seconds() % interval)
+fn find_moment_before(
+    date_time: DateTime<Utc>,
+    past_reset: DateTime<Utc>,
+    interval: i64,
+) -> DateTime<Utc> {
+    //get total seconds between the date and the past reset
+    //take the mod of that divided by the interval in seconds
+    //subtract that amount from the date / time to find previous reset
+    date_time
+        - Duration::seconds(
+            (date_time - past_reset).num_seconds().rem_euclid(interval),
+        )
 }
 
 pub fn determine_data_dir(dir: Option<PathBuf>) -> Result<PathBuf, Error> {
@@ -0,0 +1,231 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use crate::crucible::{CruciblePlayerActivityPerformance, Item};
+use crate::enums::ammotype::AmmoType;
+use crate::enums::damagetype::DamageType;
+use crate::enums::itemtype::ItemSubType;
+use crate::enums::standing::Standing;
+use crate::enums::weapongrouping::WeaponGrouping;
+use crate::enums::weapongroupstat::WeaponGroupStatType;
+use crate::season::{Season, Seasons};
+use crate::utils::{calculate_average, calculate_percent};
+
+/// A group of weapons, such as all Hand Cannons, all Special ammo weapons
+/// or all Void weapons.
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
+pub enum WeaponGroup {
+    Archetype(ItemSubType),
+    Ammo(AmmoType),
+    Element(DamageType),
+}
+
+impl WeaponGroup {
+    pub fn with_item(grouping: WeaponGrouping, item: &Item) -> WeaponGroup {
+        match grouping {
+            WeaponGrouping::Archetype => {
+                WeaponGroup::Archetype(item.item_sub_type.clone())
+            }
+            WeaponGrouping::Ammo => WeaponGroup::Ammo(item.ammo_type),
+            WeaponGrouping::Element => WeaponGroup::Element(item.damage_type),
+        }
+    }
+}
+
+impl std::fmt::Display for WeaponGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WeaponGroup::Archetype(e) => write!(f, "{}", e),
+            WeaponGroup::Ammo(e) => write!(f, "{}", e),
+            WeaponGroup::Element(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Stats for a group of weapons. Games and wins are only counted for
+/// activities where a weapon in the group got at least one kill.
+#[derive(Debug, Clone)]
+pub struct WeaponGroupStat {
+    pub group: WeaponGroup,
+    pub kills: u32,
+    pub precision_kills: u32,
+
+    /// Number of activities a weapon in the group was used in
+    pub activity_count: u32,
+    pub wins: u32,
+}
+
+impl WeaponGroupStat {
+    pub fn precision_kills_percent(&self) -> f32 {
+        calculate_percent(self.precision_kills, self.kills)
+    }
+
+    pub fn kills_per_game(&self) -> f32 {
+        calculate_average(self.kills, self.activity_count)
+    }
+
+    pub fn win_rate(&self) -> f32 {
+        calculate_percent(self.wins, self.activity_count)
+    }
+}
+
+/// Weapon kills for a set of activities, grouped by archetype, ammo type or
+/// element.
+#[derive(Debug, Clone)]
+pub struct WeaponGroupSummary {
+    pub grouping: WeaponGrouping,
+    pub activity_count: u32,
+
+    /// Total weapon kills across all groups
+    pub kills: u32,
+
+    /// Groups sorted by kills
+    pub groups: Vec<WeaponGroupStat>,
+}
+
+impl WeaponGroupSummary {
+    pub fn with_activities(
+        performances: &[&CruciblePlayerActivityPerformance],
+        grouping: WeaponGrouping,
+    ) -> WeaponGroupSummary {
+        let mut group_hash: HashMap<WeaponGroup, WeaponGroupStat> =
+            HashMap::new();
+        let mut kills = 0;
+
+        for p in performances.iter() {
+            let stats = &p.performance.stats;
+            let extended = match &stats.extended {
+                Some(e) => e,
+                None => continue,
+            };
+
+            let is_win = stats.standing == Standing::Victory;
+
+            //kills for each group in the activity
+            let mut activity_groups: HashMap<WeaponGroup, (u32, u32)> =
+                HashMap::new();
+            for w in extended.weapons.iter() {
+                let e = activity_groups
+                    .entry(WeaponGroup::with_item(grouping, &w.weapon))
+                    .or_insert((0, 0));
+                e.0 += w.kills;
+                e.1 += w.precision_kills;
+            }
+
+            for (group, (k, pk)) in activity_groups.into_iter() {
+                kills += k;
+
+                let e = group_hash.entry(group.clone()).or_insert_with(|| {
+                    WeaponGroupStat {
+                        group,
+                        kills: 0,
+                        precision_kills: 0,
+                        activity_count: 0,
+                        wins: 0,
+                    }
+                });
+
+                e.kills += k;
+                e.precision_kills += pk;
+                e.activity_count += 1;
+                if is_win {
+                    e.wins += 1;
+                }
+            }
+        }
+
+        let mut groups: Vec<WeaponGroupStat> =
+            group_hash.into_iter().map(|(_k, v)| v).collect();
+        groups.sort_by(|a, b| {
+            b.kills
+                .cmp(&a.kills)
+                .then(b.activity_count.cmp(&a.activity_count))
+        });
+
+        WeaponGroupSummary {
+            grouping,
+            activity_count: performances.len() as u32,
+            kills,
+            groups,
+        }
+    }
+
+    pub fn get_group(&self, group: &WeaponGroup) -> Option<&WeaponGroupStat> {
+        self.groups.iter().find(|e| &e.group == group)
+    }
+
+    /// Percent of all weapon kills made with the group
+    pub fn kills_share(&self, group: &WeaponGroupStat) -> f32 {
+        calculate_percent(group.kills, self.kills)
+    }
+
+    pub fn get_stat(
+        &self,
+        group: &WeaponGroupStat,
+        stat: WeaponGroupStatType,
+    ) -> f32 {
+        match stat {
+            WeaponGroupStatType::KillsShare => self.kills_share(group),
+            WeaponGroupStatType::PrecisionPercent => {
+                group.precision_kills_percent()
+            }
+            WeaponGroupStatType::KillsPerGame => group.kills_per_game(),
+            WeaponGroupStatType::WinPercent => group.win_rate(),
+        }
+    }
+}
+
+/// Weapon group summaries for each season activities were played in, sorted
+/// by season start.
+pub fn summarize_by_season(
+    performances: &[CruciblePlayerActivityPerformance],
+    grouping: WeaponGrouping,
+    seasons: &Seasons,
+) -> Vec<(Season, WeaponGroupSummary)> {
+    let mut season_activities: Vec<(
+        &Season,
+        Vec<&CruciblePlayerActivityPerformance>,
+    )> = Vec::new();
+
+    for p in performances.iter() {
+        let season = match seasons.find_by_date(&p.activity_detail.period) {
+            Some(e) => e,
+            None => continue,
+        };
+
+        match season_activities.iter_mut().find(|e| e.0 == season) {
+            Some(e) => e.1.push(p),
+            None => season_activities.push((season, vec![p])),
+        }
+    }
+
+    season_activities.sort_by(|a, b| a.0.start.cmp(&b.0.start));
+
+    season_activities
+        .into_iter()
+        .map(|(s, a)| {
+            (s.clone(), WeaponGroupSummary::with_activities(&a, grouping))
+        })
+        .collect()
+}
@@ -20,26 +20,29 @@
 * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
+mod watch;
+
 use tell::{Tell, TellLevel};
 
 use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::Utc;
 
 use dcli::activitystoreinterface::ActivityStoreInterface;
 use dcli::apiinterface::ApiInterface;
-use dcli::crucible::{Member, PlayerName};
-use dcli::manifest::definitions::{
-    ActivityDefinitionData, DestinationDefinitionData, PlaceDefinitionData,
-};
-//use dcli::error::Error;
-use dcli::enums::mode::Mode;
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::currentactivity::{retrieve_activity_status, ActivityStatus};
+use dcli::error::Error;
 use dcli::manifestinterface::ManifestInterface;
 use dcli::output::Output;
-use dcli::response::gpr::CharacterActivitiesData;
+use dcli::timezone::set_timezone;
 use dcli::utils::{build_tsv, determine_data_dir};
 use dcli::utils::{format_error, EXIT_FAILURE};
 use structopt::StructOpt;
 
-const ORBIT_PLACE_HASH: u32 = 2961497387;
+use crate::watch::{find_transitions, print_event, run_hook};
 
 #[derive(StructOpt, Debug)]
 #[structopt(verbatim_doc_comment)]
@@ -61,8 +64,11 @@ struct Opt {
     /// Name must be in the format of NAME#CODE. Example: foo#3280
     /// You can find your name in game, or on Bungie's site at:
     /// https://www.bungie.net/7/en/User/Account/IdentitySettings
-    #[structopt(long = "name", short = "n", required = true)]
-    name: PlayerName,
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
 
     ///Print out additional information
 
@@ -79,16 +85,55 @@ struct Opt {
 
     /// Format for command output
     ///
-    /// Valid values are default (Default) and tsv.
+    /// Valid values are default (Default), tsv and jsonl.
     ///
     /// tsv outputs in a tab (\t) separated format of name / value pairs with lines
-    /// ending in a new line character (\n).
-    #[structopt(
-        short = "O",
-        long = "output-format",
-        default_value = "default"
-    )]
-    output: Output,
+    /// ending in a new line character (\n). When watching, each event is
+    /// followed by an empty line.
+    ///
+    /// jsonl outputs each event as a JSON object on a single line, and is
+    /// only supported with --watch.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Continually check activity status, and print an event each time it
+    /// changes
+    ///
+    /// Events are started_activity, left_activity, entered_orbit and
+    /// not_in_activity. Moving from one activity to another prints a
+    /// left_activity event followed by an event for the new status.
+    ///
+    /// Runs until stopped with CTRL-C.
+    #[structopt(short = "w", long = "watch")]
+    watch: bool,
+
+    /// Number of seconds between status checks when watching
+    #[structopt(short = "i", long = "interval", default_value = "15")]
+    interval: u64,
+
+    /// Command to run for each event when watching
+    ///
+    /// The command is run with the system shell, with the event fields set
+    /// in environment variables: DCLIA_EVENT, DCLIA_TIMESTAMP, DCLIA_NAME,
+    /// DCLIA_HUMAN_STATUS, DCLIA_IS_CRUCIBLE, DCLIA_ACTIVITY_TYPE_NAME,
+    /// DCLIA_ACTIVITY_NAME, DCLIA_PLACE_NAME, DCLIA_DESTINATION_NAME,
+    /// DCLIA_DESCRIPTION and DCLIA_STARTED.
+    ///
+    /// Each hook completes before the next event is handled.
+    #[structopt(long = "hook", requires = "watch")]
+    hook: Option<String>,
+
+    /// Timezone used to display event times when watching
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
 
     /// API key from Bungie required for some actions.
     ///
@@ -112,357 +157,243 @@ async fn main() {
 
     tell::verbose!("{:#?}", opt);
 
-    let data_dir = match determine_data_dir(opt.data_dir) {
+    let config = match Config::load(&opt.data_dir) {
         Ok(e) => e,
         Err(e) => {
-            tell::error!(
-                "{}",
-                format_error("Error initializing data directory.", e)
-            );
+            tell::error!("{}", format_error("Could not load config file.", e));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    let mut store =
-        match ActivityStoreInterface::init_with_path(&data_dir, opt.api_key)
-            .await
-        {
-            Ok(e) => e,
-            Err(e) => {
-                tell::error!("{}", format_error(
-                "Could not initialize activity store. Have you run dclisync?",
-                e,
-            ));
-                std::process::exit(EXIT_FAILURE);
-            }
-        };
-
-    let member: Member = match store.find_member(&opt.name, true).await {
+    let name = match config.player_name(&opt.name) {
         Ok(e) => e,
         Err(e) => {
-            tell::error!("{}", format_error(
-                "Could not find Bungie ID. Please check name and try again. {}",
-                e
-            ));
+            tell::error!("{}", format_error("Invalid player name.", e));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    let client = match ApiInterface::new() {
-        Ok(e) => e,
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
         Err(e) => {
-            tell::error!(
-                "{}",
-                format_error("Error initializing API Interface", e)
-            );
+            tell::error!("{}", format_error("Invalid timezone.", e));
             std::process::exit(EXIT_FAILURE);
         }
+    }
+
+    let supported: &[Output] = if opt.watch {
+        &[Output::Default, Output::Tsv, Output::Jsonl]
+    } else {
+        &Output::TERMINAL
     };
 
-    let activities_data: Option<CharacterActivitiesData> = match client
-        .retrieve_current_activity(member.id, member.platform)
-        .await
+    let output = match config.output_supported(opt.output, "default", supported)
     {
         Ok(e) => e,
         Err(e) => {
-            tell::error!(
-                "{}",
-                format_error("Error retrieving data from API", e)
-            );
+            tell::error!("{}", format_error("Invalid output format.", e));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    let activity_data_a = match activities_data {
-        Some(e) => e,
-        None => {
-            match opt.output {
-                Output::Default => {
-                    tell::update!("Not currently in an activity");
-                }
-                Output::Tsv => {
-                    print_tsv_no_activity();
-                }
-            };
-            return;
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
         }
     };
 
-    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
         Ok(e) => e,
         Err(e) => {
-            tell::error!("{}", format_error("Manifest Error", e));
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    tell::verbose!(
-        "Getting activity definition data from manifest : {}",
-        activity_data_a.current_activity_hash
-    );
-    let activity_data_m: Option<ActivityDefinitionData> = match manifest
-        .get_activity_definition(activity_data_a.current_activity_hash)
-        .await
-    {
+    let member: Member = match store.find_member(&name, true).await {
         Ok(e) => e,
         Err(e) => {
-            tell::error!(
-                "{}",
-                format_error("Error Retrieving Data from Manifest", e)
-            );
+            tell::error!("{}", format_error(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            ));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    if activity_data_m.is_none() {
-        tell::update!("Unknown activity. Make sure you have synced the latest version of the manifest using dclim.");
-        return;
-    }
-
-    let activity_data_m = activity_data_m.unwrap();
-
-    if activity_data_m.place_hash == ORBIT_PLACE_HASH {
-        match opt.output {
-            Output::Default => {
-                tell::update!("{}", get_in_orbit_human());
-            }
-            Output::Tsv => {
-                print_tsv_orbit();
-            }
-        };
-
-        return;
-    }
-
-    tell::update!(
-        "Getting place definition data from manifest : {}",
-        activity_data_m.place_hash
-    );
-    let place_data_m: Option<PlaceDefinitionData> = match manifest
-        .get_place_definition(activity_data_m.place_hash)
-        .await
-    {
+    let client = match ApiInterface::new() {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
                 "{}",
-                format_error("Error Retrieving Data from Manifest", e)
+                format_error("Error initializing API Interface", e)
             );
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    if place_data_m.is_none() {
-        tell::update!("Unknown location. Make sure you have synced the latest version of the manifest using dclim.");
-        return;
-    }
-    let place_data_m = place_data_m.unwrap();
-
-    tell::update!(
-        "Getting destination definition data from manifest : {}",
-        activity_data_m.destination_hash
-    );
-    let destination_data_m: Option<DestinationDefinitionData> = match manifest
-        .get_destination_definition(activity_data_m.destination_hash)
-        .await
-    {
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
         Ok(e) => e,
         Err(e) => {
-            tell::error!(
-                "{}",
-                format_error("Error Retrieving Data from Manifest", e)
-            );
+            tell::error!("{}", format_error("Manifest Error", e));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    if destination_data_m.is_none() {
-        tell::update!("Unknown destination. Make sure you have synced the latest version of the manifest using dclim.");
+    if opt.watch {
+        if opt.interval == 0 {
+            tell::error!("--interval must be greater than 0.");
+            std::process::exit(EXIT_FAILURE);
+        }
+
+        watch(
+            &client,
+            &mut manifest,
+            &member,
+            output,
+            Duration::from_secs(opt.interval),
+            &opt.hook,
+        )
+        .await;
         return;
     }
 
-    let destination_data_m = destination_data_m.unwrap();
-
-    let mut mode = Mode::None;
-
-    //lets find out the mode / activity type name
-    tell::verbose!("Determining activity mode");
-    let activity_type_name: String =
-        match activity_data_a.current_activity_mode_type {
-            // if its set in the API data, we use that
-            // this is due to this bug:
-            // https://github.com/Bungie-net/api/issues/1341
-            Some(e) => {
-                mode = e;
-                format!("{}", e)
+    let status =
+        match retrieve_activity_status(&client, &mut manifest, &member).await {
+            Ok(e) => e,
+            Err(Error::ManifestItemNotFound { description }) => {
+                tell::update!("{}", description);
+                return;
             }
-            None => {
-                tell::verbose!(
-                "Activity mode not returned from API. Checking Manifest : {}",
-                activity_data_m.activity_type_hash
-            );
-                //otherwise, we go into the manifest to find it
-                match manifest
-                    .get_activity_type_definition(
-                        activity_data_m.activity_type_hash,
-                    )
-                    .await
-                {
-                    Ok(e) => match e {
-                        Some(e) => e.display_properties.name,
-                        None => "Unknown".to_string(),
-                    },
-                    Err(e) => {
-                        tell::verbose!(
-                            "Activity Mode not found in Manifest : {:?}",
-                            e
-                        );
-                        //Todo: this either means an error, unknown activity, or they are in orbit
-                        "Unknown".to_string()
-                    }
-                }
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Error retrieving data from API", e)
+                );
+                std::process::exit(EXIT_FAILURE);
             }
         };
 
-    // note if for some reason correct activities are not displayed for some
-    // crucible modes, then this may be false (i've only seen this as an issue
-    // for raids thought (see link above for bug (raid)))
-    let description = activity_data_m
-        .display_properties
-        .description
-        .unwrap_or_default();
-    let activity_name = activity_data_m.display_properties.name;
-    let place_name = place_data_m.display_properties.name;
-    let destination_name = destination_data_m.display_properties.name;
-
-    match opt.output {
-        Output::Default => {
-            print_default(
-                mode,
-                &activity_type_name,
-                &activity_name,
-                &place_name,
-                &destination_name,
-                &description,
-            );
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => {
+            tell::update!("{}", status.human_status());
         }
         Output::Tsv => {
-            print_tsv(
-                mode,
-                &activity_type_name,
-                &activity_name,
-                &place_name,
-                &destination_name,
-                &description,
-                true,
-            );
+            print_tsv(&status);
         }
     };
 }
 
-fn print_tsv_orbit() {
-    print_tsv(Mode::None, "", "", "Orbit", "", "", true);
-}
-
-fn print_tsv_no_activity() {
-    print_tsv(Mode::None, "", "", "", "", "", false);
-}
-
-fn print_tsv(
-    mode: Mode,
-    activity_type_name: &str,
-    activity_name: &str,
-    place_name: &str,
-    destination_name: &str,
-    description: &str,
-    in_activity: bool,
+async fn watch(
+    client: &ApiInterface,
+    manifest: &mut ManifestInterface,
+    member: &Member,
+    output: Output,
+    interval: Duration,
+    hook: &Option<String>,
 ) {
-    //figure out if they are in orbit since bungie doesn't give us
-    //a mode for it
-    let human_status = if mode == Mode::None && in_activity {
-        get_in_orbit_human()
-    } else {
-        build_human_status(
-            mode,
-            activity_type_name,
-            activity_name,
-            place_name,
-            destination_name,
-            description,
-        )
-    };
-
-    let name_values: Vec<(&str, String)> = vec![
-        ("in_activity", in_activity.to_string()),
-        ("activity_type_name", activity_type_name.to_string()),
-        ("activity_name", activity_name.to_string()),
-        ("place_name", place_name.to_string()),
-        ("destination_name", destination_name.to_string()),
-        ("description", description.to_string()),
-        ("human_status", human_status),
-        ("is_crucible", mode.is_crucible().to_string()),
-    ];
+    let name = member.name.get_bungie_name();
 
-    tell::update!("{}", build_tsv(name_values));
-}
+    if output == Output::Default {
+        tell::progress!(
+            "Watching activity status for {}. CTRL-C to stop.",
+            name
+        );
+    }
 
-fn print_default(
-    mode: Mode,
-    activity_type_name: &str,
-    activity_name: &str,
-    place_name: &str,
-    _destination_name: &str,
-    description: &str,
-) {
-    let out = build_human_status(
-        mode,
-        activity_type_name,
-        activity_name,
-        place_name,
-        _destination_name,
-        description,
-    );
-
-    tell::update!("{}", out);
-}
+    let mut previous: Option<ActivityStatus> = None;
+    loop {
+        match retrieve_activity_status(client, manifest, member).await {
+            Ok(status) => {
+                match &previous {
+                    //no event for the initial status, since it is not a change
+                    None => {
+                        if output == Output::Default {
+                            tell::progress!("{}", status.human_status());
+                        }
+                    }
+                    Some(e) => {
+                        let events =
+                            find_transitions(e, &status, &name, Utc::now());
+                        for event in &events {
+                            print_event(event, output);
+                            if let Some(h) = hook {
+                                run_hook(h, event).await;
+                            }
+                        }
+                    }
+                }
+                previous = Some(status);
+            }
+            Err(Error::ManifestItemNotFound { description }) => {
+                tell::error!("{}", description);
+            }
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Error retrieving data from API", e)
+                );
+            }
+        };
 
-fn build_human_status(
-    mode: Mode,
-    activity_type_name: &str,
-    activity_name: &str,
-    place_name: &str,
-    _destination_name: &str,
-    description: &str,
-) -> String {
-    if mode == Mode::Patrol {
-        format!("Exploring on {}", place_name)
-    } else if mode.is_gambit() || mode.is_crucible() {
-        format!(
-            "Playing {} on {} ({})",
-            activity_type_name, activity_name, description
-        )
-    } else if mode == Mode::Strike {
-        format!(
-            "Running {} {} on {}",
-            activity_name, activity_type_name, place_name
-        )
-    } else if mode == Mode::Social {
-        format!("Hanging out in the {} on {}", activity_name, place_name)
-    } else if mode == Mode::Story {
-        format!("Playing {} story on {}", activity_name, place_name)
-    } else if mode.is_nightfall() {
-        format!(
-            "Playing {} {} on {}",
-            description, activity_name, place_name
-        )
-    } else {
-        format!(
-            "Playing {} {} on {}",
-            activity_name, activity_type_name, place_name
-        )
+        tokio::time::sleep(interval).await;
     }
 }
 
-fn get_in_orbit_human() -> String {
-    "Currently sitting in Orbit".to_string()
+fn print_tsv(status: &ActivityStatus) {
+    let empty = String::new();
+    let (in_activity, human_status) = match status {
+        ActivityStatus::NotInActivity => (false, String::new()),
+        _ => (true, status.human_status()),
+    };
+
+    let name_values: Vec<(&str, String)> = match status {
+        ActivityStatus::InActivity(e) => vec![
+            ("in_activity", in_activity.to_string()),
+            ("activity_type_name", e.activity_type_name.to_string()),
+            ("activity_name", e.activity_name.to_string()),
+            ("place_name", e.place_name.to_string()),
+            ("destination_name", e.destination_name.to_string()),
+            ("description", e.description.to_string()),
+            ("human_status", human_status),
+            ("is_crucible", e.mode.is_crucible().to_string()),
+        ],
+        _ => {
+            //bungie doesnt give us a place name for orbit
+            let place_name = match status {
+                ActivityStatus::Orbit => "Orbit".to_string(),
+                _ => empty.clone(),
+            };
+
+            vec![
+                ("in_activity", in_activity.to_string()),
+                ("activity_type_name", empty.clone()),
+                ("activity_name", empty.clone()),
+                ("place_name", place_name),
+                ("destination_name", empty.clone()),
+                ("description", empty),
+                ("human_status", human_status),
+                ("is_crucible", false.to_string()),
+            ]
+        }
+    };
+
+    tell::update!("{}", build_tsv(name_values));
 }
@@ -0,0 +1,247 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fmt;
+use std::process::Stdio;
+
+use chrono::{DateTime, Utc};
+use serde_derive::Serialize;
+use tokio::process::Command;
+
+use dcli::currentactivity::{ActivityStatus, CurrentActivity};
+use dcli::output::Output;
+use dcli::timezone::to_local;
+use dcli::utils::build_tsv;
+
+/// Prefix for the environment variables passed to the hook command.
+const HOOK_ENV_PREFIX: &str = "DCLIA_";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    StartedActivity,
+    LeftActivity,
+    EnteredOrbit,
+    NotInActivity,
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let out = match self {
+            EventType::StartedActivity => "started_activity",
+            EventType::LeftActivity => "left_activity",
+            EventType::EnteredOrbit => "entered_orbit",
+            EventType::NotInActivity => "not_in_activity",
+        };
+
+        write!(f, "{}", out)
+    }
+}
+
+/// A change in the player's activity status. Activity fields are for the
+/// activity the event is about (i.e. the activity which was left for
+/// left_activity), and are empty for orbit and not_in_activity events.
+#[derive(Serialize, Debug, Clone)]
+pub struct ActivityEvent {
+    pub event: EventType,
+    pub timestamp: DateTime<Utc>,
+    pub name: String,
+    pub human_status: String,
+    pub is_crucible: bool,
+    pub activity_type_name: String,
+    pub activity_name: String,
+    pub place_name: String,
+    pub destination_name: String,
+    pub description: String,
+    pub started: Option<DateTime<Utc>>,
+}
+
+impl ActivityEvent {
+    fn new(
+        event: EventType,
+        name: &str,
+        timestamp: DateTime<Utc>,
+        activity: Option<&CurrentActivity>,
+    ) -> ActivityEvent {
+        let human_status = match (event, activity) {
+            (EventType::EnteredOrbit, _) => {
+                ActivityStatus::Orbit.human_status()
+            }
+            (EventType::NotInActivity, _) => {
+                ActivityStatus::NotInActivity.human_status()
+            }
+            (_, Some(e)) => e.human_status(),
+            (_, None) => String::new(),
+        };
+
+        let empty = String::new();
+        ActivityEvent {
+            event,
+            timestamp,
+            name: name.to_string(),
+            human_status,
+            is_crucible: activity.map_or(false, |e| e.mode.is_crucible()),
+            activity_type_name: activity
+                .map_or(empty.clone(), |e| e.activity_type_name.clone()),
+            activity_name: activity
+                .map_or(empty.clone(), |e| e.activity_name.clone()),
+            place_name: match event {
+                //bungie doesnt give us a place name for orbit
+                EventType::EnteredOrbit => "Orbit".to_string(),
+                _ => activity.map_or(empty.clone(), |e| e.place_name.clone()),
+            },
+            destination_name: activity
+                .map_or(empty.clone(), |e| e.destination_name.clone()),
+            description: activity.map_or(empty, |e| e.description.clone()),
+            started: activity.map(|e| e.started),
+        }
+    }
+
+    /// Short human readable description of the event.
+    pub fn summary(&self) -> String {
+        match self.event {
+            EventType::StartedActivity => self.human_status.clone(),
+            EventType::LeftActivity => format!("Left {}", self.activity_name),
+            EventType::EnteredOrbit => "Entered Orbit".to_string(),
+            EventType::NotInActivity => self.human_status.clone(),
+        }
+    }
+
+    pub fn name_values(&self) -> Vec<(&str, String)> {
+        vec![
+            ("event", self.event.to_string()),
+            ("timestamp", self.timestamp.to_rfc3339()),
+            ("name", self.name.clone()),
+            ("human_status", self.human_status.clone()),
+            ("is_crucible", self.is_crucible.to_string()),
+            ("activity_type_name", self.activity_type_name.clone()),
+            ("activity_name", self.activity_name.clone()),
+            ("place_name", self.place_name.clone()),
+            ("destination_name", self.destination_name.clone()),
+            ("description", self.description.clone()),
+            (
+                "started",
+                self.started.map_or(String::new(), |e| e.to_rfc3339()),
+            ),
+        ]
+    }
+}
+
+fn is_same_status(previous: &ActivityStatus, current: &ActivityStatus) -> bool {
+    match (previous, current) {
+        (ActivityStatus::NotInActivity, ActivityStatus::NotInActivity) => true,
+        (ActivityStatus::Orbit, ActivityStatus::Orbit) => true,
+        (ActivityStatus::InActivity(a), ActivityStatus::InActivity(b)) => {
+            //a new match has a new start time, even on the same map
+            a.started == b.started && a.activity_name == b.activity_name
+        }
+        _ => false,
+    }
+}
+
+/// Returns the events for the change from the previous to the current
+/// status. Returns an empty Vec if the status has not changed.
+pub fn find_transitions(
+    previous: &ActivityStatus,
+    current: &ActivityStatus,
+    name: &str,
+    timestamp: DateTime<Utc>,
+) -> Vec<ActivityEvent> {
+    let mut out = Vec::new();
+    if is_same_status(previous, current) {
+        return out;
+    }
+
+    if let ActivityStatus::InActivity(e) = previous {
+        out.push(ActivityEvent::new(
+            EventType::LeftActivity,
+            name,
+            timestamp,
+            Some(e),
+        ));
+    }
+
+    let event = match current {
+        ActivityStatus::NotInActivity => {
+            ActivityEvent::new(EventType::NotInActivity, name, timestamp, None)
+        }
+        ActivityStatus::Orbit => {
+            ActivityEvent::new(EventType::EnteredOrbit, name, timestamp, None)
+        }
+        ActivityStatus::InActivity(e) => ActivityEvent::new(
+            EventType::StartedActivity,
+            name,
+            timestamp,
+            Some(e),
+        ),
+    };
+    out.push(event);
+
+    out
+}
+
+pub fn print_event(event: &ActivityEvent, output: Output) {
+    match output {
+        Output::Jsonl => match serde_json::to_string(event) {
+            Ok(e) => tell::update!("{}", e),
+            Err(e) => tell::error!("Could not serialize event : {}", e),
+        },
+        Output::Tsv => {
+            tell::update!("{}", build_tsv(event.name_values()));
+        }
+        Output::Default | Output::Markdown | Output::Html | Output::Json => {
+            tell::update!(
+                "[{}] {}",
+                to_local(&event.timestamp).format("%Y-%m-%d %H:%M:%S"),
+                event.summary()
+            );
+        }
+    }
+}
+
+/// Runs the hook command through the system shell, with the event fields
+/// set in DCLIA_ prefixed environment variables (i.e. DCLIA_EVENT), and
+/// waits for it to complete so hooks run in the same order as the events.
+pub async fn run_hook(command: &str, event: &ActivityEvent) {
+    let mut cmd = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(command);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c").arg(command);
+        c
+    };
+
+    for (name, value) in event.name_values() {
+        cmd.env(format!("{}{}", HOOK_ENV_PREFIX, name.to_uppercase()), value);
+    }
+
+    tell::verbose!("Running hook for {} : {}", event.event, command);
+    match cmd.stdin(Stdio::null()).status().await {
+        Ok(e) if !e.success() => {
+            tell::error!("Hook exited with an error : {}", e);
+        }
+        Ok(_) => {}
+        Err(e) => tell::error!("Could not run hook : {}", e),
+    }
+}
@@ -25,10 +25,13 @@ use tell::{Tell, TellLevel};
 use std::str::FromStr;
 use std::{collections::HashMap, path::PathBuf};
 
-use dcli::crucible::{Member, PlayerName};
+use dcli::character::CharacterSnapshot;
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::document::{Alignment, Document, Table};
+use dcli::timezone::set_timezone;
 use dcli::utils::{format_error, truncate_ascii_string};
 use dcli::{
-    apiinterface::ApiInterface,
     crucible::{
         AggregateCruciblePerformances, CrucibleActivity,
         CruciblePlayerPerformance, Player,
@@ -39,8 +42,10 @@ use dcli::{
 
 use dcli::enums::mode::Mode;
 use dcli::manifestinterface::ManifestInterface;
+use dcli::output::Output;
 
 use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
 use dcli::error::Error;
 
 use dcli::activitystoreinterface::ActivityStoreInterface;
@@ -50,6 +55,7 @@ use dcli::utils::{
     repeat_str,
 };
 
+use chrono::Duration;
 use dcli::utils::EXIT_FAILURE;
 use structopt::StructOpt;
 
@@ -78,46 +84,15 @@ fn generate_score(data: &CrucibleActivity) -> String {
     tokens.join("")
 }
 
-async fn get_combat_ratings(data: &CrucibleActivity) -> HashMap<u64, f32> {
-    let mut players: Vec<&Player> = Vec::new();
-
-    for t in data.teams.values() {
-        for p in &t.player_performances {
-            players.push(&p.player);
-        }
-    }
-
-    let elo_hash: HashMap<u64, f32> = match ApiInterface::new() {
-        Ok(e) => {
-            let mut player_refs: Vec<&Player> = Vec::new();
-            for t in data.teams.values() {
-                for p in &t.player_performances {
-                    player_refs.push(&p.player);
-                }
-            }
-
-            match e
-                .retrieve_combat_ratings(&player_refs, &data.details.mode)
-                .await
-            {
-                Ok(e) => e,
-                Err(_e) => HashMap::new(),
-            }
-        }
-        Err(_e) => HashMap::new(),
-    };
-    elo_hash
-}
-
 fn print_default(
     data: &CrucibleActivity,
     elo_hash: &HashMap<u64, f32>,
     member: &Member,
     details: bool,
     weapon_count: u32,
+    snapshot: Option<&CharacterSnapshot>,
+    match_ratings: bool,
 ) {
-    let member_id = &member.id;
-
     let col_w = 8;
     let name_col_w = 24;
 
@@ -125,7 +100,7 @@ fn print_default(
     let mut completion_reason = "".to_string();
     let mut standing_str = "".to_string();
 
-    if let Some(e) = data.get_member_performance(member_id) {
+    if let Some(e) = data.get_member_performance(member) {
         completion_reason =
             if e.stats.completion_reason == CompletionReason::Unknown {
                 "".to_string()
@@ -157,6 +132,25 @@ fn print_default(
     tell::update!("{}", standing_str);
     tell::update!("{} {}\n", generate_score(data), completion_reason);
 
+    if let Some(e) = snapshot {
+        tell::update!(
+            "{} {} (Stats from {})",
+            format!("{}", e.class_type).to_uppercase(),
+            e.stats.power,
+            human_date_format(&e.created),
+        );
+        tell::update!("{}", team_title_border);
+        tell::update!(
+            "MOB {} : RES {} : REC {} : DIS {} : INT {} : STR {}\n",
+            e.stats.mobility,
+            e.stats.resilience,
+            e.stats.recovery,
+            e.stats.discipline,
+            e.stats.intellect,
+            e.stats.strength,
+        );
+    }
+
     let header = format!("{:<0name_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
     "PLAYER",
     "KILLS",
@@ -466,9 +460,281 @@ fn print_default(
 
     tell::update!();
     tell::update!("STATUS : L - Joined late, E - Left early");
+    if match_ratings {
+        tell::update!(
+            "RATING : Stored combat ratings from when the activity was played"
+        );
+    }
     tell::update!();
 }
 
+const PLAYER_COLUMNS: [(&str, Alignment); 14] = [
+    ("Player", Alignment::Left),
+    ("Kills", Alignment::Right),
+    ("Asts", Alignment::Right),
+    ("K+A", Alignment::Right),
+    ("Deaths", Alignment::Right),
+    ("K/D", Alignment::Right),
+    ("KD/A", Alignment::Right),
+    ("Eff", Alignment::Right),
+    ("Sup", Alignment::Right),
+    ("Gren", Alignment::Right),
+    ("Mel", Alignment::Right),
+    ("Med", Alignment::Right),
+    ("Rating", Alignment::Right),
+    ("Status", Alignment::Left),
+];
+
+//returns the total and average rows for a group of performances
+fn aggregate_rows(
+    performances: &[&CruciblePlayerPerformance],
+    rating: f32,
+) -> Vec<Vec<String>> {
+    let aggregate =
+        AggregateCruciblePerformances::with_performances(performances);
+    let extended = aggregate.extended.as_ref().unwrap();
+    let count = performances.len() as f32;
+
+    let rating_str = if f32_are_equal(rating, 0.0) {
+        "".to_string()
+    } else {
+        format_f32(rating, 0)
+    };
+
+    vec![
+        vec![
+            "Total".to_string(),
+            aggregate.kills.to_string(),
+            aggregate.assists.to_string(),
+            aggregate.opponents_defeated.to_string(),
+            aggregate.deaths.to_string(),
+            format_f32(aggregate.kills_deaths_ratio, 2),
+            format_f32(aggregate.kills_deaths_assists, 2),
+            format_f32(aggregate.efficiency, 2),
+            extended.weapon_kills_super.to_string(),
+            extended.weapon_kills_grenade.to_string(),
+            extended.weapon_kills_melee.to_string(),
+            extended.all_medals_earned.to_string(),
+        ],
+        vec![
+            "Avg".to_string(),
+            format_f32(aggregate.kills as f32 / count, 2),
+            format_f32(aggregate.assists as f32 / count, 2),
+            format_f32(aggregate.opponents_defeated as f32 / count, 2),
+            format_f32(aggregate.deaths as f32 / count, 2),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+            format_f32(extended.weapon_kills_super as f32 / count, 2),
+            format_f32(extended.weapon_kills_grenade as f32 / count, 2),
+            format_f32(extended.weapon_kills_melee as f32 / count, 2),
+            format_f32(extended.all_medals_earned as f32 / count, 2),
+            rating_str,
+        ],
+    ]
+}
+
+#[allow(clippy::too_many_arguments)]
+fn print_document(
+    data: &CrucibleActivity,
+    elo_hash: &HashMap<u64, f32>,
+    member: &Member,
+    details: bool,
+    weapon_count: u32,
+    snapshot: Option<&CharacterSnapshot>,
+    match_ratings: bool,
+    output: Output,
+) {
+    let mut doc = Document::new();
+    doc.add_heading(
+        2,
+        &format!(
+            "{} on {} : {}",
+            data.details.mode,
+            data.details.map_name,
+            human_date_format(&data.details.period)
+        ),
+    );
+
+    if let Some(e) = data.get_member_performance(member) {
+        let completion_reason =
+            if e.stats.completion_reason == CompletionReason::Unknown {
+                "".to_string()
+            } else {
+                format!(" ({})", e.stats.completion_reason)
+            };
+
+        doc.add_paragraph(&format!(
+            "{}! {}{} in {}",
+            e.stats.standing,
+            generate_score(data),
+            completion_reason,
+            human_duration(e.stats.activity_duration_seconds)
+        ));
+    }
+
+    if let Some(e) = snapshot {
+        doc.add_paragraph(&format!(
+            "{} {} (Stats from {}) : MOB {} : RES {} : REC {} : DIS {} : INT {} : STR {}",
+            e.class_type,
+            e.stats.power,
+            human_date_format(&e.created),
+            e.stats.mobility,
+            e.stats.resilience,
+            e.stats.recovery,
+            e.stats.discipline,
+            e.stats.intellect,
+            e.stats.strength,
+        ));
+    }
+
+    let mut all_performances: Vec<&CruciblePlayerPerformance> = Vec::new();
+    let mut elo_total_count = 0;
+    let mut elo_total_total = 0.0;
+    let mut weapon_table = Table::new(&[
+        ("Player", Alignment::Left),
+        ("Weapon", Alignment::Left),
+        ("Kills", Alignment::Right),
+        ("Prec", Alignment::Right),
+        ("%", Alignment::Right),
+        ("Type", Alignment::Left),
+    ]);
+
+    for v in data.teams.values() {
+        doc.add_heading(
+            3,
+            &format!("[{}] {} Team {}!", v.score, v.display_name, v.standing),
+        );
+
+        let mut table = Table::new(&PLAYER_COLUMNS);
+        let mut elo_team_count = 0;
+        let mut elo_team_total = 0.0;
+
+        let mut player_performances: Vec<&CruciblePlayerPerformance> =
+            v.player_performances.iter().collect();
+        player_performances.sort_by(|a, b| {
+            b.stats.opponents_defeated.cmp(&a.stats.opponents_defeated)
+        });
+
+        for p in &player_performances {
+            let elo = *elo_hash.get(&p.player.calculate_hash()).unwrap_or(&0.0)
+                * ELO_SCALE;
+
+            let mut elo_str = "".to_string();
+            if !f32_are_equal(elo, 0.0) {
+                elo_team_count += 1;
+                elo_team_total += elo;
+                elo_total_count += 1;
+                elo_total_total += elo;
+                elo_str = format_f32(elo, 0);
+            }
+
+            let extended = p.stats.extended.as_ref().unwrap();
+            table.add_row(vec![
+                p.player.name.get_bungie_name(),
+                p.stats.kills.to_string(),
+                p.stats.assists.to_string(),
+                p.stats.opponents_defeated.to_string(),
+                p.stats.deaths.to_string(),
+                format_f32(p.stats.kills_deaths_ratio, 2),
+                format_f32(p.stats.kills_deaths_assists, 2),
+                format_f32(p.stats.efficiency, 2),
+                extended.weapon_kills_super.to_string(),
+                extended.weapon_kills_grenade.to_string(),
+                extended.weapon_kills_melee.to_string(),
+                extended.all_medals_earned.to_string(),
+                elo_str,
+                p.stats.generate_status(),
+            ]);
+
+            if details {
+                let mut weapons = extended.weapons.clone();
+                weapons.sort_by(|a, b| b.kills.cmp(&a.kills));
+                for w in &weapons {
+                    weapon_table.add_row(vec![
+                        p.player.name.get_bungie_name(),
+                        w.weapon.name.to_string(),
+                        w.kills.to_string(),
+                        w.precision_kills.to_string(),
+                        format_f32(w.precision_kills_percent * 100.0, 0),
+                        format!("{}", w.weapon.item_sub_type),
+                    ]);
+                }
+            }
+        }
+
+        for row in aggregate_rows(
+            &player_performances,
+            calculate_avg(elo_team_total, elo_team_count),
+        ) {
+            table.add_row(row);
+        }
+        doc.add_table(table);
+
+        all_performances.extend(player_performances);
+    }
+
+    doc.add_heading(3, "Combined");
+    let mut table = Table::new(&PLAYER_COLUMNS);
+    for row in aggregate_rows(
+        &all_performances,
+        calculate_avg(elo_total_total, elo_total_count),
+    ) {
+        table.add_row(row);
+    }
+    doc.add_table(table);
+
+    if details && !weapon_table.is_empty() {
+        doc.add_heading(3, "Player Weapons");
+        doc.add_table(weapon_table);
+    }
+
+    let aggregate =
+        AggregateCruciblePerformances::with_performances(&all_performances);
+    let weapons = &aggregate.extended.as_ref().unwrap().weapons;
+    let max_weps = std::cmp::min(weapon_count as usize, weapons.len());
+
+    if max_weps > 0 {
+        doc.add_heading(3, "Weapons");
+        let mut table = Table::new(&[
+            ("Weapon", Alignment::Left),
+            ("Kills", Alignment::Right),
+            ("% Total", Alignment::Right),
+            ("Prec", Alignment::Right),
+            ("% Prec", Alignment::Right),
+            ("Type", Alignment::Left),
+        ]);
+
+        for w in &weapons[..max_weps] {
+            table.add_row(vec![
+                w.weapon.name.to_string(),
+                w.kills.to_string(),
+                format!(
+                    "{}%",
+                    format_f32(
+                        (w.kills as f32 / aggregate.kills as f32) * 100.0,
+                        2
+                    )
+                ),
+                w.precision_kills.to_string(),
+                format!("{}%", format_f32(w.precision_kills_percent, 2)),
+                format!("{}", w.weapon.item_sub_type),
+            ]);
+        }
+        doc.add_table(table);
+    }
+
+    let mut key = "Status : L - Joined late, E - Left early.".to_string();
+    if match_ratings {
+        key.push_str(
+            " Rating : Stored combat ratings from when the activity was played.",
+        );
+    }
+    doc.add_paragraph(&key);
+
+    tell::update!("{}", doc.render(output));
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(verbatim_doc_comment)]
 /// Command line tool for retrieving and viewing Destiny 2 Crucible activity details.
@@ -494,8 +760,11 @@ struct Opt {
     /// Name must be in the format of NAME#CODE. Example: foo#3280
     /// You can find your name in game, or on Bungie's site at:
     /// https://www.bungie.net/7/en/User/Account/IdentitySettings
-    #[structopt(long = "name", short = "n", required = true)]
-    name: PlayerName,
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
 
     /// Activity mode from which to return last activity
     ///
@@ -509,15 +778,40 @@ struct Opt {
     /// rift_competitive, showdown, lockdown, iron_banner_rift,
     /// zone_control, iron_banner_zone_control, rift,
     /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
     #[structopt(long = "mode", short = "M", 
-        parse(try_from_str=parse_and_validate_mode), default_value = "all_pvp")]
-    mode: Mode,
+        parse(try_from_str=parse_and_validate_mode))]
+    mode: Option<Mode>,
 
     /// Character class to retrieve data for
     ///
     /// Valid values include hunter, titan, warlock, last_active and all.
-    #[structopt(short = "C", long = "class", default_value = "all")]
-    character_class_selection: CharacterClassSelection,
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
 
     ///Print out additional information
 
@@ -531,7 +825,8 @@ struct Opt {
     /// Display extended activity details
     ///
     /// If flag is set, additional information will be displayed, including per
-    /// user weapon stats.
+    /// user weapon stats, and the character stats stored closest to when the
+    /// activity was played.
     #[structopt(short = "d", long = "details")]
     details: bool,
 
@@ -539,6 +834,18 @@ struct Opt {
     #[structopt(long = "weapon-count", short = "w", default_value = "5")]
     weapon_count: u32,
 
+    /// Format for command output
+    ///
+    /// Valid values are default (Default), markdown and html.
+    ///
+    /// markdown renders the activity with Markdown headings and tables, which
+    /// can be pasted into Discord or GitHub. html renders the activity as an
+    /// HTML fragment with inline styles, which can be included in an email.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
     /// The activity id of the activity to display data about
     ///
     /// By default, the last activity will be displayed. The index can be retrieved
@@ -546,6 +853,32 @@ struct Opt {
     #[structopt(long = "activity-id", short = "a")]
     activity_id: Option<i64>,
 
+    /// Use the manifest version that was current when the activity was played
+    ///
+    /// Useful for older activities where weapons or maps have since been
+    /// renamed or removed. Requires that the version was previously downloaded
+    /// and stored by dclim. If not available, the current manifest is used.
+    #[structopt(long = "period-manifest", short = "P")]
+    period_manifest: bool,
+
+    /// Display the combat ratings stored when the activity was played
+    ///
+    /// Uses the last rating stored before the activity was played, or the
+    /// first rating stored after it if there isn't one. Combat ratings for
+    /// the activity's mode are stored when activity details are viewed, and
+    /// all pvp ratings are stored for your own characters when syncing, which
+    /// are used if there is no rating stored for the mode. If not specified,
+    /// current combat ratings are displayed.
+    #[structopt(long = "match-ratings", short = "R")]
+    match_ratings: bool,
+
+    /// Number of hours after which stored combat ratings are refreshed
+    ///
+    /// Stored ratings older than this are retrieved again from the API.
+    /// Not used when --match-ratings is specified.
+    #[structopt(long = "rating-max-age", default_value = "24")]
+    rating_max_age: i64,
+
     /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
     ///
     /// This will normally be downloaded using the dclim tool, and uses
@@ -574,7 +907,57 @@ async fn main() {
 
     tell::verbose!("{:#?}", opt);
 
-    let data_dir = match determine_data_dir(opt.data_dir) {
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = match config.mode(opt.mode, "all_pvp", parse_and_validate_mode) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::REPORT) {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
@@ -585,19 +968,21 @@ async fn main() {
         }
     };
 
-    let mut store =
-        match ActivityStoreInterface::init_with_path(&data_dir, opt.api_key)
-            .await
-        {
-            Ok(e) => e,
-            Err(e) => {
-                tell::error!("{}",format_error(
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}",format_error(
                 "Could not initialize activity store. Have you run dclisync?",
                 e,
             ));
-                std::process::exit(EXIT_FAILURE);
-            }
-        };
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
 
     let mut manifest = match ManifestInterface::new(&data_dir, false).await {
         Ok(e) => e,
@@ -613,7 +998,7 @@ async fn main() {
         }
     };
 
-    let member = match store.find_member(&opt.name, true).await {
+    let member = match store.find_member(&name, true).await {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
@@ -634,14 +1019,33 @@ async fn main() {
         };
     }
 
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
     let data_result = match opt.activity_id {
         Some(e) => store.retrieve_activity(e, &mut manifest).await,
         None => {
             store
                 .retrieve_last_activity(
                     &member,
-                    &opt.character_class_selection,
-                    &opt.mode,
+                    &character_class_selection,
+                    &mode,
                     &mut manifest,
                 )
                 .await
@@ -664,7 +1068,117 @@ async fn main() {
         }
     };
 
-    let elo_hash = get_combat_ratings(&data).await;
+    let data = if opt.period_manifest {
+        let mut period_manifest = match ManifestInterface::new_for_date(
+            &data_dir,
+            &data.details.period,
+            false,
+        )
+        .await
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Could not initialize manifest.", e)
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+        match store
+            .retrieve_activity(data.details.id, &mut period_manifest)
+            .await
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error(
+                        "Could not retrieve data from activity store.",
+                        e
+                    )
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+    } else {
+        data
+    };
+
+    let mut players: Vec<&Player> = Vec::new();
+    for t in data.teams.values() {
+        for p in &t.player_performances {
+            players.push(&p.player);
+        }
+    }
 
-    print_default(&data, &elo_hash, &member, opt.details, opt.weapon_count);
+    let elo_result = if opt.match_ratings {
+        store
+            .retrieve_combat_ratings_at(
+                &players,
+                &data.details.mode,
+                &data.details.period,
+            )
+            .await
+    } else {
+        store
+            .retrieve_combat_ratings(
+                &players,
+                &data.details.mode,
+                &Duration::hours(opt.rating_max_age),
+            )
+            .await
+    };
+
+    let elo_hash = match elo_result {
+        Ok(e) => e,
+        Err(e) => {
+            tell::verbose!("Could not retrieve combat ratings : {}", e);
+            HashMap::new()
+        }
+    };
+
+    //character snapshots are stored when syncing, so may not exist for
+    //older activities
+    let snapshot = match data.get_member_performance(&member) {
+        Some(p) if opt.details => match store
+            .retrieve_closest_character_snapshot(
+                &p.player.character_id,
+                &data.details.period,
+            )
+            .await
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::verbose!("Could not retrieve character snapshot : {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+
+    match output {
+        Output::Markdown | Output::Html => print_document(
+            &data,
+            &elo_hash,
+            &member,
+            opt.details,
+            opt.weapon_count,
+            snapshot.as_ref(),
+            opt.match_ratings,
+            output,
+        ),
+        Output::Default | Output::Tsv | Output::Jsonl | Output::Json => {
+            print_default(
+                &data,
+                &elo_hash,
+                &member,
+                opt.details,
+                opt.weapon_count,
+                snapshot.as_ref(),
+                opt.match_ratings,
+            )
+        }
+    }
 }
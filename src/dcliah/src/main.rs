@@ -25,13 +25,19 @@ use std::cmp::Ordering;
 use std::path::PathBuf;
 use tell::{Tell, TellLevel};
 
-use dcli::crucible::{Member, PlayerName};
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::document::{Alignment, Document, Table};
 use dcli::enums::standing::Standing;
 use dcli::enums::{
     completionreason::CompletionReason,
     moment::{DateTimePeriod, Moment},
 };
+use dcli::error::Error;
 use dcli::manifestinterface::ManifestInterface;
+use dcli::output::Output;
+use dcli::season::Seasons;
+use dcli::timezone::set_timezone;
 use dcli::utils::{
     calculate_average, calculate_percent, format_error,
     parse_and_validate_crucible_mode, parse_rfc3339, truncate_ascii_string,
@@ -39,13 +45,14 @@ use dcli::utils::{
 use dcli::{
     crucible::{
         AggregateCruciblePerformances, CruciblePlayerActivityPerformance,
-        CruciblePlayerPerformance,
+        CruciblePlayerPerformance, MedalStat, WeaponStat,
     },
     enums::mode::Mode,
     utils::{calculate_ratio, human_duration},
 };
 
 use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
 use dcli::enums::weaponsort::WeaponSort;
 
 use dcli::activitystoreinterface::ActivityStoreInterface;
@@ -59,41 +66,16 @@ use dcli::utils::EXIT_FAILURE;
 use num_format::{Locale, ToFormattedString};
 use structopt::StructOpt;
 
-#[allow(clippy::too_many_arguments)]
-fn print_default(
-    member: &Member,
-    data: &[CruciblePlayerActivityPerformance],
-    activity_limit: &u32,
+fn generate_title(
+    player_name: &str,
     mode: &Mode,
     time_period: &DateTimePeriod,
     moment: &Moment,
-    end_moment: &Moment,
-    weapon_count: &u32,
-    weapon_sort: &WeaponSort,
-    medal_count: &u32,
+    end_moment: &Option<Moment>,
     character_class_selection: &CharacterClassSelection,
-) {
-    let player_name = member.name.get_bungie_name();
-
-    let start_time = time_period.get_start();
-    let end_time = time_period.get_end();
-
-    let performances = data;
-
-    let cpp: Vec<&CruciblePlayerPerformance> =
-        performances.iter().map(|x| &x.performance).collect();
-    let aggregate = AggregateCruciblePerformances::with_performances(&cpp);
-
-    let activity_count = performances.len();
-
-    let display_count = std::cmp::min(activity_count, *activity_limit as usize);
-    let is_limited = activity_count != display_count;
-
-    let start_time_label = human_date_format(&start_time);
-    let end_time_label = human_date_format(&end_time);
-
-    tell::update!();
-    tell::update!();
+) -> String {
+    let start_time_label = human_date_format(&time_period.get_start());
+    let end_time_label = human_date_format(&time_period.get_end());
 
     let char_class = match character_class_selection {
         CharacterClassSelection::Hunter => "Hunter",
@@ -103,8 +85,20 @@ fn print_default(
         CharacterClassSelection::LastActive => "last active character",
     };
 
+    //seasons and calendar periods end at the end of the period when no end
+    //moment is specified
+    let is_open_ended = match end_moment {
+        Some(e) => e == &Moment::Now,
+        None => !moment.has_end(),
+    };
+
+    let end_moment = match end_moment {
+        Some(e) => e.to_string(),
+        None => format!("end of {}", moment),
+    };
+
     //todo: if player name is empty, then the sentence below will be a little weird
-    let title = if end_moment == &Moment::Now {
+    if is_open_ended {
         format!(
             "{mode} activities for {player_name} on {char_class} since {start_time} ({moment})",
             mode = uppercase_first_char(&format!("{}", mode)),
@@ -124,7 +118,133 @@ fn print_default(
             char_class = char_class,
             player_name = player_name,
         )
-    };
+    }
+}
+
+fn sort_weapons(
+    weapons: &[WeaponStat],
+    weapon_sort: &WeaponSort,
+) -> Vec<WeaponStat> {
+    let mut weapons = weapons.to_vec();
+    match weapon_sort {
+        WeaponSort::Name => {
+            weapons.sort_by(|a, b| {
+                a.weapon
+                    .name
+                    .to_lowercase()
+                    .cmp(&b.weapon.name.to_lowercase())
+            });
+        }
+        WeaponSort::Kills => {
+            //sorted by kills by default so we dont need to sort again
+            //weapons.sort_by(|a, b| b.kills.cmp(&a.kills));
+        }
+        WeaponSort::Games => {
+            weapons.sort_by(|a, b| b.activity_count.cmp(&a.activity_count));
+        }
+        WeaponSort::KillsPerGameKills => {
+            weapons.sort_by(|a, b| {
+                let a_kpk = calculate_ratio(a.kills, a.activity_count);
+                let b_kpk = calculate_ratio(b.kills, b.activity_count);
+                b_kpk.partial_cmp(&a_kpk).unwrap()
+            });
+        }
+        WeaponSort::PrecisionTotal => {
+            weapons.sort_by(|a, b| {
+                b.precision_kills.partial_cmp(&a.precision_kills).unwrap()
+            });
+        }
+        WeaponSort::PrecisionPercent => {
+            weapons.sort_by(|a, b| {
+                b.precision_kills_percent
+                    .partial_cmp(&a.precision_kills_percent)
+                    .unwrap()
+            });
+        }
+        WeaponSort::WinPercent => {
+            weapons.sort_by(|a, b| {
+                let a_wp = calculate_percent(a.wins, a.activity_count);
+                let b_wp = calculate_percent(b.wins, b.activity_count);
+                b_wp.partial_cmp(&a_wp).unwrap()
+            });
+        }
+        WeaponSort::Type => {
+            weapons.sort_by(|a, b| {
+                let a_type =
+                    format!("{}", a.weapon.item_sub_type).to_lowercase();
+                let b_type =
+                    format!("{}", b.weapon.item_sub_type).to_lowercase();
+
+                a_type.cmp(&b_type)
+            });
+        }
+    }
+
+    weapons
+}
+
+//gold medals are listed first, then sorted by count
+fn sort_medals(medals: &[MedalStat]) -> Vec<MedalStat> {
+    let mut medals = medals.to_vec();
+    medals
+        .retain(|m| m.medal.tier != dcli::enums::medaltier::MedalTier::Unknown);
+
+    medals.sort_by(|a, b| {
+        let a_gold = a.medal.is_gold();
+        let b_gold = b.medal.is_gold();
+
+        if b_gold == a_gold {
+            return b.count.cmp(&a.count);
+        }
+
+        if b_gold && !a_gold {
+            return Ordering::Greater;
+        }
+
+        Ordering::Less
+    });
+
+    medals
+}
+
+#[allow(clippy::too_many_arguments)]
+fn print_default(
+    member: &Member,
+    data: &[CruciblePlayerActivityPerformance],
+    activity_limit: &u32,
+    mode: &Mode,
+    time_period: &DateTimePeriod,
+    moment: &Moment,
+    end_moment: &Option<Moment>,
+    weapon_count: &u32,
+    weapon_sort: &WeaponSort,
+    medal_count: &u32,
+    character_class_selection: &CharacterClassSelection,
+) {
+    let player_name = member.name.get_bungie_name();
+
+    let performances = data;
+
+    let cpp: Vec<&CruciblePlayerPerformance> =
+        performances.iter().map(|x| &x.performance).collect();
+    let aggregate = AggregateCruciblePerformances::with_performances(&cpp);
+
+    let activity_count = performances.len();
+
+    let display_count = std::cmp::min(activity_count, *activity_limit as usize);
+    let is_limited = activity_count != display_count;
+
+    tell::update!();
+    tell::update!();
+
+    let title = generate_title(
+        &player_name,
+        mode,
+        time_period,
+        moment,
+        end_moment,
+        character_class_selection,
+    );
 
     tell::update!();
     tell::update!("ACTIVITIES");
@@ -389,60 +509,7 @@ fn print_default(
         tell::update!("{}", wep_header_str);
         tell::update!("{}", wep_divider);
 
-        let mut weapons = extended.weapons.clone();
-        match weapon_sort {
-            WeaponSort::Name => {
-                weapons.sort_by(|a, b| {
-                    a.weapon
-                        .name
-                        .to_lowercase()
-                        .cmp(&b.weapon.name.to_lowercase())
-                });
-            }
-            WeaponSort::Kills => {
-                //sorted by kills by default so we dont need to sort again
-                //weapons.sort_by(|a, b| b.kills.cmp(&a.kills));
-            }
-            WeaponSort::Games => {
-                weapons.sort_by(|a, b| b.activity_count.cmp(&a.activity_count));
-            }
-            WeaponSort::KillsPerGameKills => {
-                weapons.sort_by(|a, b| {
-                    let a_kpk = calculate_ratio(a.kills, a.activity_count);
-                    let b_kpk = calculate_ratio(b.kills, b.activity_count);
-                    b_kpk.partial_cmp(&a_kpk).unwrap()
-                });
-            }
-            WeaponSort::PrecisionTotal => {
-                weapons.sort_by(|a, b| {
-                    b.precision_kills.partial_cmp(&a.precision_kills).unwrap()
-                });
-            }
-            WeaponSort::PrecisionPercent => {
-                weapons.sort_by(|a, b| {
-                    b.precision_kills_percent
-                        .partial_cmp(&a.precision_kills_percent)
-                        .unwrap()
-                });
-            }
-            WeaponSort::WinPercent => {
-                weapons.sort_by(|a, b| {
-                    let a_wp = calculate_percent(a.wins, a.activity_count);
-                    let b_wp = calculate_percent(b.wins, b.activity_count);
-                    b_wp.partial_cmp(&a_wp).unwrap()
-                });
-            }
-            WeaponSort::Type => {
-                weapons.sort_by(|a, b| {
-                    let a_type =
-                        format!("{}", a.weapon.item_sub_type).to_lowercase();
-                    let b_type =
-                        format!("{}", b.weapon.item_sub_type).to_lowercase();
-
-                    a_type.cmp(&b_type)
-                });
-            }
-        }
+        let weapons = sort_weapons(&extended.weapons, weapon_sort);
 
         let max_weps = std::cmp::min(*weapon_count as usize, weapons.len());
 
@@ -495,30 +562,12 @@ fn print_default(
         let med_divider_len = wep_divider_len;
         let med_divider = repeat_str("=", med_divider_len);
 
-        let mut medals = extended.medals.clone();
-        medals.retain(|m| {
-            m.medal.tier != dcli::enums::medaltier::MedalTier::Unknown
-        });
+        let medals = sort_medals(&extended.medals);
 
         tell::update!("{}", med_header_str);
         tell::update!("{}", med_divider);
 
         if !medals.is_empty() {
-            medals.sort_by(|a, b| {
-                let a_gold = a.medal.is_gold();
-                let b_gold = b.medal.is_gold();
-
-                if b_gold == a_gold {
-                    return b.count.cmp(&a.count);
-                }
-
-                if b_gold && !a_gold {
-                    return Ordering::Greater;
-                }
-
-                Ordering::Less
-            });
-
             //TODO: can make this an option
             let max_medals = std::cmp::min(*medal_count as usize, medals.len());
 
@@ -554,6 +603,284 @@ fn print_default(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn print_document(
+    member: &Member,
+    data: &[CruciblePlayerActivityPerformance],
+    activity_limit: &u32,
+    mode: &Mode,
+    time_period: &DateTimePeriod,
+    moment: &Moment,
+    end_moment: &Option<Moment>,
+    weapon_count: &u32,
+    weapon_sort: &WeaponSort,
+    medal_count: &u32,
+    character_class_selection: &CharacterClassSelection,
+    output: Output,
+) {
+    let player_name = member.name.get_bungie_name();
+
+    let cpp: Vec<&CruciblePlayerPerformance> =
+        data.iter().map(|x| &x.performance).collect();
+    let aggregate = AggregateCruciblePerformances::with_performances(&cpp);
+    let extended = aggregate.extended.as_ref().unwrap();
+
+    let activity_count = data.len();
+    let display_count = std::cmp::min(activity_count, *activity_limit as usize);
+
+    let mut doc = Document::new();
+    doc.add_heading(
+        2,
+        &format!(
+            "{} : {}",
+            uppercase_first_char(&format!("{}", mode)),
+            uppercase_first_char(&format!("{}", moment))
+        ),
+    );
+    doc.add_paragraph(&generate_title(
+        &player_name,
+        mode,
+        time_period,
+        moment,
+        end_moment,
+        character_class_selection,
+    ));
+    doc.add_paragraph(&format!(
+        "Total time played is {}",
+        human_duration(aggregate.time_played_seconds)
+    ));
+
+    if display_count > 0 {
+        doc.add_heading(3, "Activities");
+        doc.add_paragraph(&format!(
+            "Displaying details for the last {} of {} activities",
+            display_count, activity_count
+        ));
+
+        let mut table = Table::new(&[
+            ("Map", Alignment::Left),
+            ("Mode", Alignment::Left),
+            ("W/L", Alignment::Left),
+            ("Streak", Alignment::Right),
+            ("Kills", Alignment::Right),
+            ("Asts", Alignment::Right),
+            ("K+A", Alignment::Right),
+            ("Deaths", Alignment::Right),
+            ("K/D", Alignment::Right),
+            ("KD/A", Alignment::Right),
+            ("Eff", Alignment::Right),
+            ("Mercy", Alignment::Right),
+            ("Index", Alignment::Right),
+        ]);
+
+        //activities are listed oldest to newest, matching the default output
+        let mut streak: i32 = 0;
+        let mut last_standing = Standing::Unknown;
+        for activity in data[..display_count].iter().rev() {
+            let stats = &activity.performance.stats;
+            streak = match stats.standing {
+                Standing::Unknown => 0,
+                Standing::Victory if last_standing == Standing::Victory => {
+                    streak + 1
+                }
+                Standing::Victory => 1,
+                Standing::Defeat if last_standing == Standing::Defeat => {
+                    streak - 1
+                }
+                Standing::Defeat => -1,
+            };
+            last_standing = stats.standing;
+
+            let mercy = if stats.completion_reason == CompletionReason::Mercy {
+                "X"
+            } else {
+                ""
+            };
+
+            table.add_row(vec![
+                activity.activity_detail.map_name.clone(),
+                format!("{}", activity.activity_detail.mode),
+                stats.standing.to_string(),
+                streak.to_string(),
+                stats.kills.to_string(),
+                stats.assists.to_string(),
+                stats.opponents_defeated.to_string(),
+                stats.deaths.to_string(),
+                format_f32(stats.kills_deaths_ratio, 2),
+                format_f32(stats.kills_deaths_assists, 2),
+                format_f32(stats.efficiency, 2),
+                mercy.to_string(),
+                activity.activity_detail.id.to_string(),
+            ]);
+        }
+
+        doc.add_table(table);
+    }
+
+    doc.add_heading(3, "Summary");
+    let mut table = Table::new(&[
+        ("", Alignment::Left),
+        ("W/L", Alignment::Left),
+        ("Streak", Alignment::Right),
+        ("Kills", Alignment::Right),
+        ("Asts", Alignment::Right),
+        ("K+A", Alignment::Right),
+        ("Deaths", Alignment::Right),
+        ("K/D", Alignment::Right),
+        ("KD/A", Alignment::Right),
+        ("Eff", Alignment::Right),
+        ("Sup", Alignment::Right),
+        ("Gren", Alignment::Right),
+        ("Mel", Alignment::Right),
+        ("Mercy", Alignment::Right),
+    ]);
+
+    let total = aggregate.total_activities;
+    table.add_row(vec![
+        "Total".to_string(),
+        total.to_formatted_string(&Locale::en),
+        "".to_string(),
+        aggregate.kills.to_formatted_string(&Locale::en),
+        aggregate.assists.to_formatted_string(&Locale::en),
+        aggregate
+            .opponents_defeated
+            .to_formatted_string(&Locale::en),
+        aggregate.deaths.to_formatted_string(&Locale::en),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        extended.weapon_kills_super.to_formatted_string(&Locale::en),
+        extended
+            .weapon_kills_grenade
+            .to_formatted_string(&Locale::en),
+        extended.weapon_kills_melee.to_formatted_string(&Locale::en),
+        aggregate.total_mercy.to_string(),
+    ]);
+    table.add_row(vec![
+        "High".to_string(),
+        format!(
+            "{}-{}",
+            aggregate.wins.to_formatted_string(&Locale::en),
+            aggregate.losses.to_formatted_string(&Locale::en)
+        ),
+        format!(
+            "{}W {}L",
+            aggregate.longest_win_streak, aggregate.longest_loss_streak
+        ),
+        aggregate.highest_kills.to_string(),
+        aggregate.highest_assists.to_string(),
+        aggregate.highest_opponents_defeated.to_string(),
+        aggregate.highest_deaths.to_string(),
+        format_f32(aggregate.highest_kills_deaths_ratio, 2),
+        format_f32(aggregate.highest_kills_deaths_assists, 2),
+        format_f32(aggregate.highest_efficiency, 2),
+        extended.highest_weapon_kills_super.to_string(),
+        extended.highest_weapon_kills_grenade.to_string(),
+        extended.highest_weapon_kills_melee.to_string(),
+        "".to_string(),
+    ]);
+    table.add_row(vec![
+        "Per Game".to_string(),
+        format!("{}%", format_f32(aggregate.win_rate, 2)),
+        "".to_string(),
+        format_f32(calculate_average(aggregate.kills, total), 2),
+        format_f32(calculate_average(aggregate.assists, total), 2),
+        format_f32(calculate_average(aggregate.opponents_defeated, total), 2),
+        format_f32(calculate_average(aggregate.deaths, total), 2),
+        format_f32(aggregate.kills_deaths_ratio, 2),
+        format_f32(aggregate.kills_deaths_assists, 2),
+        format_f32(aggregate.efficiency, 2),
+        format_f32(calculate_average(extended.weapon_kills_super, total), 2),
+        format_f32(calculate_average(extended.weapon_kills_grenade, total), 2),
+        format_f32(calculate_average(extended.weapon_kills_melee, total), 2),
+        format!(
+            "{}%",
+            format_f32(calculate_percent(aggregate.total_mercy, total), 2)
+        ),
+    ]);
+    doc.add_table(table);
+
+    if *weapon_count > 0 {
+        doc.add_heading(3, "Weapons");
+        let mut table = Table::new(&[
+            ("Weapon", Alignment::Left),
+            ("Games", Alignment::Right),
+            ("Kills", Alignment::Right),
+            ("K/Gk", Alignment::Right),
+            ("% Total", Alignment::Right),
+            ("Win %", Alignment::Right),
+            ("Prec", Alignment::Right),
+            ("% Prec", Alignment::Right),
+            ("Type", Alignment::Left),
+        ]);
+
+        let weapons = sort_weapons(&extended.weapons, weapon_sort);
+        let max_weps = std::cmp::min(*weapon_count as usize, weapons.len());
+        for w in &weapons[..max_weps] {
+            table.add_row(vec![
+                w.weapon.name.clone(),
+                w.activity_count.to_formatted_string(&Locale::en),
+                w.kills.to_formatted_string(&Locale::en),
+                format_f32(calculate_ratio(w.kills, w.activity_count), 2),
+                format!(
+                    "{}%",
+                    format_f32(calculate_percent(w.kills, aggregate.kills), 2)
+                ),
+                format!(
+                    "{}%",
+                    format_f32(calculate_percent(w.wins, w.activity_count), 2)
+                ),
+                w.precision_kills.to_formatted_string(&Locale::en),
+                format!("{}%", format_f32(w.precision_kills_percent, 2)),
+                format!("{}", w.weapon.item_sub_type),
+            ]);
+        }
+        doc.add_table(table);
+        doc.add_paragraph(
+            "% Total - Percentage of all kills. K/Gk - Kills per game in \
+            games with a kill with the weapon. Win % - Win percentage in \
+            games with a kill with the weapon.",
+        );
+    }
+
+    if *medal_count > 0 {
+        doc.add_heading(3, "Medals");
+
+        let medals = sort_medals(&extended.medals);
+        if medals.is_empty() {
+            doc.add_paragraph("No medals");
+        } else {
+            let mut table = Table::new(&[
+                ("Medal", Alignment::Left),
+                ("", Alignment::Left),
+                ("Count", Alignment::Right),
+                ("M/G", Alignment::Right),
+                ("G/M", Alignment::Right),
+                ("Description", Alignment::Left),
+            ]);
+
+            let max_medals = std::cmp::min(*medal_count as usize, medals.len());
+            for m in &medals[..max_medals] {
+                let gold = if m.medal.is_gold() { "Gold" } else { "" };
+                table.add_row(vec![
+                    m.medal.name.clone(),
+                    gold.to_string(),
+                    m.count.to_string(),
+                    format_f32(m.count as f32 / activity_count as f32, 2),
+                    (activity_count as f32 / m.count as f32).ceil().to_string(),
+                    m.medal.description.clone(),
+                ]);
+            }
+            doc.add_table(table);
+            doc.add_paragraph(
+                "M/G - Medals per game. G/M - Number of games to get one medal.",
+            );
+        }
+    }
+
+    tell::update!("{}", doc.render(output));
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(verbatim_doc_comment)]
 /// Command line tool for retrieving and viewing Destiny 2 Crucible activity history.
@@ -577,8 +904,11 @@ struct Opt {
     /// Name must be in the format of NAME#CODE. Example: foo#3280
     /// You can find your name in game, or on Bungie's site at:
     /// https://www.bungie.net/7/en/User/Account/IdentitySettings
-    #[structopt(long = "name", short = "n", required = true)]
-    name: PlayerName,
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
 
     /// Custom start time in RFC 3339 date / time format
     ///
@@ -610,20 +940,33 @@ struct Opt {
     /// Valid values include daily (last daily reset), weekend
     /// (last weekend reset on Friday), weekly (last weekly reset on Tuesday),
     /// day (last day), week (last week), month (last month), all_time and custom
-    /// as well as the following season moments launch, curse_of_osiris, warmind,
-    /// season_of_the_outlaw, season_of_the_forge, season_of_the_drifter,
-    /// season_of_opulence, season_of_the_undying, season_of_dawn,
-    /// season_of_the_worthy, season_of_arrivals, season_of_the_hunt,
-    /// season_of_the_chosen, season_of_the_splicer, season_of_the_lost, season_of_the_risen,
-    /// witch_queen, season_of_the_haunted, season_of_plunder, season_of_the_seraph, lightfall, season_of_defiance, season_of_the_deep, season_of_the_witch, season_of_the_wish.
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season). Season dates are loaded
+    /// from the manifest, and can be updated via a seasons.json file in the
+    /// data directory.
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 12h, 2w, today, yesterday, this-week, last-week,
+    /// this-month, last-month, this-year, last-year, since-last-reset,
+    /// since-weekly-reset, months (2023-05), ISO weeks (2023-W14), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When a season, expansion or calendar period (such as 2023-05) is
+    /// specified without --end-moment, only activities within that period
+    /// are returned.
     ///
     /// When custom is specified, the custom start date in RFC3339 format must
     /// be specified with the --custom-time argument.
     ///
     /// For example:
     /// --moment custom --custom-time 2020-12-08T17:00:00.774187+00:00
-    #[structopt(long = "moment", short = "T", default_value = "week")]
-    moment: Moment,
+    ///
+    /// Defaults to week, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
 
     /// End moment from which to pull activities from
     ///
@@ -637,20 +980,30 @@ struct Opt {
     /// Valid values include daily (last daily reset), weekend
     /// (last weekend reset on Friday), weekly (last weekly reset on Tuesday),
     /// day (last day), week (last week), month (last month), all_time and custom
-    /// as well as the following season moments launch, curse_of_osiris, warmind,
-    /// season_of_the_outlaw, season_of_the_forge, season_of_the_drifter,
-    /// season_of_opulence, season_of_the_undying, season_of_dawn,
-    /// season_of_the_worthy, season_of_arrivals, season_of_the_hunt,
-    /// season_of_the_chosen, season_of_the_splicer, season_of_the_lost, season_of_the_risen,
-    /// witch_queen, season_of_the_haunted, season_of_plunder, season_of_the_seraph, lightfall, season_of_defiance, season_of_the_deep, season_of_the_witch, season_of_the_wish.
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season). Season dates are loaded
+    /// from the manifest, and can be updated via a seasons.json file in the
+    /// data directory.
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 12h, 2w, today, yesterday, this-week, last-week,
+    /// this-month, last-month, this-year, last-year, since-last-reset,
+    /// since-weekly-reset, months (2023-05), ISO weeks (2023-W14), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
     ///
     /// When custom is specified, the custom start date in RFC3339 format must
     /// be specified with the --end-custom-time argument.
     ///
     /// For example:
     /// --moment custom --end-custom-time 2020-12-08T17:00:00.774187+00:00
-    #[structopt(long = "end-moment", short = "E", default_value = "now")]
-    end_moment: Moment,
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
 
     /// Activity mode to return stats for
     ///
@@ -664,9 +1017,11 @@ struct Opt {
     /// rift_competitive, showdown, lockdown, iron_banner_rift,
     /// zone_control, iron_banner_zone_control, rift,
     /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
     #[structopt(long = "mode", short = "M", 
-        parse(try_from_str=parse_and_validate_crucible_mode), default_value = "all_pvp")]
-    mode: Mode,
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
 
     /// Limit the number of activity details that will be displayed
     ///
@@ -685,8 +1040,21 @@ struct Opt {
     /// Character to retrieve data for
     ///
     /// Valid values include hunter, titan, warlock, last_active and all.
-    #[structopt(short = "C", long = "class", default_value = "all")]
-    character_class_selection: CharacterClassSelection,
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
 
     /// Specify weapon stats sort order
     ///
@@ -695,6 +1063,28 @@ struct Opt {
     #[structopt(short = "W", long = "weapon-sort", default_value = "kills")]
     weapon_sort: WeaponSort,
 
+    /// Format for command output
+    ///
+    /// Valid values are default (Default), markdown and html.
+    ///
+    /// markdown renders the report with Markdown headings and tables, which
+    /// can be pasted into Discord or GitHub. html renders the report as an
+    /// HTML fragment with inline styles, which can be included in an email.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
     ///Print out additional information
 
     #[structopt(short = "v", long = "verbose")]
@@ -733,54 +1123,123 @@ async fn main() {
     tell::verbose!("{:#?}", opt);
     log::info!("{:#?}", opt);
 
-    let data_dir = match determine_data_dir(opt.data_dir) {
+    let config = match Config::load(&opt.data_dir) {
         Ok(e) => e,
         Err(e) => {
-            tell::error!(
-                "{}",
-                format_error("Error initializing data directory.", e)
-            );
+            tell::error!("{}", format_error("Could not load config file.", e));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    let start_time = match opt.moment {
-        Moment::Custom => {
-            opt.custom_time.unwrap() //note, this should be ok, because struct opt should ensure valid value
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
         }
-        _ => opt.moment.get_date_time(),
     };
 
-    let end_time = match opt.end_moment {
-        Moment::Custom => {
-            opt.end_custom_time.unwrap() //note, this should be ok, because struct opt should ensure valid value
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
         }
-        _ => opt.end_moment.get_date_time(),
     };
 
-    let time_period =
-        match DateTimePeriod::with_start_end_time(start_time, end_time) {
+    let moment = match config.moment(opt.moment, "week") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
             Ok(e) => e,
-            Err(_e) => {
-                tell::error!("--end-moment must be greater than --moment");
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
                 std::process::exit(EXIT_FAILURE);
             }
         };
 
-    let mut store =
-        match ActivityStoreInterface::init_with_path(&data_dir, opt.api_key)
-            .await
-        {
+    let output =
+        match config.output_supported(opt.output, "default", &Output::REPORT) {
             Ok(e) => e,
             Err(e) => {
-                tell::error!("{}", format_error(
-                "Could not initialize activity store. Have you run dclisync?",
-                e,
-            ));
+                tell::error!("{}", format_error("Invalid output format.", e));
                 std::process::exit(EXIT_FAILURE);
             }
         };
 
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    //note, custom times should be ok, because struct opt ensures they are
+    //set when the moments are custom
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
     let mut manifest = match ManifestInterface::new(&data_dir, false).await {
         Ok(e) => e,
         Err(e) => {
@@ -795,7 +1254,7 @@ async fn main() {
         }
     };
 
-    let member: Member = match store.find_member(&opt.name, true).await {
+    let member: Member = match store.find_member(&name, true).await {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
@@ -816,11 +1275,30 @@ async fn main() {
         };
     }
 
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
     let data = match store
         .retrieve_activities_since(
             &member,
-            &opt.character_class_selection,
-            &opt.mode,
+            &character_class_selection,
+            &mode,
             &time_period,
             &mut manifest,
         )
@@ -848,17 +1326,35 @@ async fn main() {
         return;
     }
 
-    print_default(
-        &member,
-        &data,
-        &opt.activity_limit,
-        &opt.mode,
-        &time_period,
-        &opt.moment,
-        &opt.end_moment,
-        &opt.weapon_count,
-        &opt.weapon_sort,
-        &opt.medal_count,
-        &opt.character_class_selection,
-    );
+    match output {
+        Output::Markdown | Output::Html => print_document(
+            &member,
+            &data,
+            &opt.activity_limit,
+            &mode,
+            &time_period,
+            &moment,
+            &opt.end_moment,
+            &opt.weapon_count,
+            &opt.weapon_sort,
+            &opt.medal_count,
+            &character_class_selection,
+            output,
+        ),
+        Output::Default | Output::Tsv | Output::Jsonl | Output::Json => {
+            print_default(
+                &member,
+                &data,
+                &opt.activity_limit,
+                &mode,
+                &time_period,
+                &moment,
+                &opt.end_moment,
+                &opt.weapon_count,
+                &opt.weapon_sort,
+                &opt.medal_count,
+                &character_class_selection,
+            )
+        }
+    }
 }
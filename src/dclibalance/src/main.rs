@@ -0,0 +1,709 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::error::Error;
+use dcli::leavers::MatchResults;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::output::Output;
+use dcli::season::Seasons;
+use dcli::teambalance::{BalanceSummary, MatchBalance, TeamBalance};
+use dcli::timezone::{set_timezone, to_local};
+use dcli::utils::{
+    calculate_percent, determine_data_dir, format_error, format_f32,
+    parse_and_validate_crucible_mode, parse_rfc3339, repeat_str,
+    truncate_ascii_string, EXIT_FAILURE, TSV_DELIM,
+};
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const MAP_COL_W: usize = 20;
+
+fn format_percent(value: u32, total: u32) -> String {
+    format!("{}%", format_f32(calculate_percent(value, total), 2))
+}
+
+fn format_delta(value: f32) -> String {
+    format!("{:+.2}", value)
+}
+
+fn format_optional(value: Option<f32>, precision: usize) -> String {
+    match value {
+        Some(e) => format_f32(e, precision),
+        None => "-".to_string(),
+    }
+}
+
+fn to_tsv_value(value: Option<f32>) -> String {
+    value.map(|e| e.to_string()).unwrap_or_default()
+}
+
+fn format_fireteams(team: &TeamBalance) -> String {
+    team.fireteams
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>()
+        .join("+")
+}
+
+fn format_range(min: Option<f32>, max: Option<f32>) -> String {
+    match (min, max) {
+        (None, Some(max)) => format!("<= {:+.2}", max),
+        (Some(min), None) => format!("> {:+.2}", min),
+        (Some(min), Some(max)) => format!("{:+.2} to {:+.2}", min, max),
+        (None, None) => "All".to_string(),
+    }
+}
+
+fn print_results_row(label: &str, r: &MatchResults, label_col_w: usize) {
+    let col_w = 10;
+    tell::update!(
+        "{:<0label_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+        label,
+        r.matches.to_string(),
+        r.wins.to_string(),
+        r.losses().to_string(),
+        format_percent(r.wins, r.matches),
+        col_w = col_w,
+        label_col_w = label_col_w,
+    );
+}
+
+fn print_results_header(label: &str, label_col_w: usize) {
+    let col_w = 10;
+    let header = format!(
+        "{:<0label_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+        label,
+        "MATCHES",
+        "WINS",
+        "LOSSES",
+        "WIN %",
+        col_w = col_w,
+        label_col_w = label_col_w,
+    );
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+}
+
+fn print_matches(matches: &[MatchBalance]) {
+    let col_w = 10;
+    let header = format!(
+        "{:<0date_w$}{:<0map_w$}{:<0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}   {:<0ft_w$}{}",
+        "DATE",
+        "MAP",
+        "RESULT",
+        "SCORE",
+        "TEAM KD",
+        "OPP KD",
+        "TEAM RTG",
+        "OPP RTG",
+        "DELTA",
+        "LIGHT",
+        "FIRETEAMS",
+        "BALANCE",
+        col_w = col_w,
+        date_w = 18,
+        map_w = MAP_COL_W,
+        ft_w = 28,
+    );
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count() + 2));
+
+    for m in matches.iter() {
+        tell::update!(
+            "{:<0date_w$}{:<0map_w$}{:<0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}   {:<0ft_w$}{}",
+            to_local(&m.activity_detail.period)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            truncate_ascii_string(&m.activity_detail.map_name, MAP_COL_W - 2),
+            format!("{}", m.standing),
+            format!("{}-{}", m.team_score, m.opponent_score),
+            format_optional(m.team.average_kills_deaths_ratio, 2),
+            format_optional(m.opponents.average_kills_deaths_ratio, 2),
+            format_optional(m.team.average_combat_rating, 0),
+            format_optional(m.opponents.average_combat_rating, 0),
+            m.strength_delta()
+                .map(format_delta)
+                .unwrap_or_else(|| "-".to_string()),
+            format!("{:+.1}", m.light_level_delta()),
+            format!(
+                "{} v {}",
+                format_fireteams(&m.team),
+                format_fireteams(&m.opponents)
+            ),
+            m.get_balance_type(),
+            col_w = col_w,
+            date_w = 18,
+            map_w = MAP_COL_W,
+            ft_w = 28,
+        );
+    }
+
+    tell::update!();
+}
+
+fn print_default(
+    member: &Member,
+    mode: &Mode,
+    matches: &[MatchBalance],
+    summary: &BalanceSummary,
+    details: bool,
+) {
+    tell::update!(
+        "{} team balance for {} ({} matches)",
+        mode,
+        member.name.get_bungie_name(),
+        summary.results.matches
+    );
+    tell::update!();
+
+    if details {
+        print_matches(matches);
+    }
+
+    let label_col_w = 18;
+    print_results_header("BALANCE", label_col_w);
+    print_results_row("Favored", &summary.favored, label_col_w);
+    print_results_row("Even", &summary.even, label_col_w);
+    print_results_row("Unfavored", &summary.unfavored, label_col_w);
+    if summary.unknown.matches > 0 {
+        print_results_row("Unknown", &summary.unknown, label_col_w);
+    }
+    print_results_row("All", &summary.results, label_col_w);
+    tell::update!();
+
+    print_results_header("STRENGTH DELTA", label_col_w);
+    for r in summary.ranges.iter() {
+        print_results_row(&format_range(r.min, r.max), &r.results, label_col_w);
+    }
+    tell::update!();
+
+    tell::update!(
+        "Average team strength delta : {} ({} absolute)",
+        format_delta(summary.average_strength_delta),
+        format_f32(summary.average_absolute_strength_delta, 2)
+    );
+    tell::update!(
+        "Average light level delta   : {:+.1}",
+        summary.average_light_level_delta
+    );
+    tell::update!(
+        "Average largest fireteam    : {} (opponents {})",
+        format_f32(summary.average_largest_fireteam, 2),
+        format_f32(summary.average_opponent_largest_fireteam, 2)
+    );
+    tell::update!();
+    tell::update!("Team strength is based on the average kills deaths ratio of the players on a team, across their activities stored in the activity database from before the match, and their average stored combat rating (RTG) from when the match was played.");
+    tell::update!("Strength delta is how much stronger your team was than the opposing team, relative to the average of the two teams (0.25 is 25% stronger). Matches with a delta greater than 0.25 are considered favored or unfavored, and matches without stored data for both teams are unknown.");
+}
+
+fn print_tsv(matches: &[MatchBalance]) {
+    for m in matches.iter() {
+        let values = vec![
+            m.activity_detail.id.to_string(),
+            m.activity_detail.period.to_rfc3339(),
+            format!("{}", m.activity_detail.mode),
+            m.activity_detail.map_name.to_string(),
+            format!("{}", m.standing),
+            m.team_score.to_string(),
+            m.opponent_score.to_string(),
+            to_tsv_value(m.team.average_kills_deaths_ratio),
+            to_tsv_value(m.opponents.average_kills_deaths_ratio),
+            to_tsv_value(m.team.average_combat_rating),
+            to_tsv_value(m.opponents.average_combat_rating),
+            to_tsv_value(m.strength_delta()),
+            m.team.average_light_level.to_string(),
+            m.opponents.average_light_level.to_string(),
+            format_fireteams(&m.team),
+            format_fireteams(&m.opponents),
+            format!("{}", m.get_balance_type()),
+        ];
+
+        tell::update!("{}", values.join(TSV_DELIM));
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for analyzing Destiny 2 Crucible team balance and
+/// matchmaking.
+///
+/// Compares the average strength, light level and fireteam sizes of your team
+/// and the opposing team in each match, and displays your win rate against
+/// the difference in team strength.
+///
+/// Team strength is based on the average kills deaths ratio of the players on
+/// a team, across their activities for the mode stored in the activity
+/// database from before the match, and their average stored combat rating
+/// from when the match was played. Since only activities which include synced
+/// players are stored, this is based on a sample of each player's history.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie name for player
+    ///
+    /// Name must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
+
+    /// Custom start time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "t", long = "custom-time", parse(try_from_str = parse_rfc3339), required_if("moment", "custom"))]
+    custom_time: Option<DateTime<Utc>>,
+
+    /// Custom end time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --end-moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "e", long = "end-custom-time", parse(try_from_str = parse_rfc3339), required_if("end-moment", "custom"))]
+    end_custom_time: Option<DateTime<Utc>>,
+
+    /// Start moment from which to pull activities
+    ///
+    /// Valid values include weekend (last weekend reset on Friday), weekly
+    /// (last weekly reset on Tuesday), day, week, month, all_time and custom
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season).
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 2w, this-month, last-month, months (2023-05), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When custom is specified, the custom start date in RFC3339 format must
+    /// be specified with the --custom-time argument.
+    ///
+    /// Defaults to month, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// End moment from which to pull activities
+    ///
+    /// Accepts the same values as --moment.
+    ///
+    /// When custom is specified, the custom end date in RFC3339 format must
+    /// be specified with the --end-custom-time argument.
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
+
+    /// Activity mode to return stats for
+    ///
+    /// Supported values are all_pvp (default), control, clash, elimination,
+    /// mayhem, iron_banner, all_private, rumble, pvp_competitive,
+    /// quickplay and trials_of_osiris.
+    ///
+    /// Addition values available are crimsom_doubles, supremacy, survival,
+    /// countdown, all_doubles, doubles, private_clash, private_control,
+    /// private_survival, private_rumble, showdown_competitive, survival_competitive,
+    /// rift_competitive, showdown, lockdown, iron_banner_rift,
+    /// zone_control, iron_banner_zone_control, rift,
+    /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
+    #[structopt(long = "mode", short = "M",
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
+
+    /// Character to retrieve data for
+    ///
+    /// Valid values include hunter, titan, warlock, last_active and all.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// Display the teams' strength, light level and fireteams for each match
+    #[structopt(short = "d", long = "details")]
+    details: bool,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default) and tsv.
+    ///
+    /// tsv outputs one line per match, in a tab (\t) separated format with
+    /// lines ending in a new line character (\n). Columns are activity id,
+    /// date, mode, map, result, team score, opponent score, team kills
+    /// deaths ratio, opponent kills deaths ratio, team combat rating,
+    /// opponent combat rating, strength delta, team light level, opponent
+    /// light level, team fireteams, opponent fireteams and balance. Values
+    /// which are not available are empty.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Sync player activities
+    #[structopt(long = "sync", short = "s")]
+    sync: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let moment = match config.moment(opt.moment, "month") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::TERMINAL)
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if opt.sync {
+        match store.sync_member(&member).await {
+            Ok(_e) => (),
+            Err(e) => {
+                tell::error!("Could not sync activity store {}", e);
+                tell::update!("Using existing data");
+            }
+        };
+    }
+
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let performances = match store
+        .retrieve_activities_since(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+            &mut manifest,
+        )
+        .await
+    {
+        Ok(e) => e.unwrap_or_default(),
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if performances.is_empty() {
+        tell::update!("No activities found");
+        return;
+    }
+
+    let rosters = match store
+        .retrieve_activity_rosters(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let histories = match store
+        .retrieve_roster_histories(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut matches: Vec<MatchBalance> = performances
+        .iter()
+        .filter_map(|p| {
+            let roster = rosters.get(&p.activity_detail.id)?;
+            MatchBalance::with_roster(
+                p,
+                roster,
+                histories.get(&p.activity_detail.id),
+            )
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        a.activity_detail.period.cmp(&b.activity_detail.period)
+    });
+
+    let summary = BalanceSummary::with_matches(&matches);
+
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => {
+            print_default(&member, &mode, &matches, &summary, opt.details)
+        }
+        Output::Tsv => print_tsv(&matches),
+    }
+}
@@ -0,0 +1,451 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+mod profileoutput;
+mod profilesummary;
+
+use std::path::PathBuf;
+
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::apiinterface::ApiInterface;
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::output::Output;
+use dcli::timezone::set_timezone;
+use dcli::utils::{
+    determine_data_dir, format_error, human_date_format, human_duration,
+    repeat_str, truncate_ascii_string, EXIT_FAILURE, TSV_DELIM,
+};
+use profileoutput::ProfileOutput;
+use profilesummary::ProfileSummary;
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const EMBLEM_COL_W: usize = 28;
+
+fn print_default(summary: &ProfileSummary) {
+    let col_w = 6;
+    let name_col_w = 14;
+    let date_col_w = 24;
+
+    tell::update!("{} ({})", summary.name, summary.platform);
+
+    match &summary.cross_save_primary {
+        Some(e) => tell::update!("Cross Save : Enabled ({} is primary)", e),
+        None => tell::update!("Cross Save : Not enabled"),
+    }
+    tell::update!();
+
+    let header = format!(
+        "{:<0name_col_w$}{:<0id_col_w$}{:<0date_col_w$}{}",
+        "PLATFORM",
+        "MEMBERSHIP ID",
+        "LAST PLAYED",
+        "STATUS",
+        name_col_w = name_col_w,
+        id_col_w = name_col_w + col_w + 2,
+        date_col_w = date_col_w,
+    );
+
+    tell::update!("LINKED PLATFORMS");
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count() + 10));
+
+    for p in summary.linked_profiles.iter() {
+        let status = if p.is_overridden {
+            "Overridden by Cross Save"
+        } else if summary.is_cross_save_active() {
+            "Primary"
+        } else if !p.is_public {
+            "Private"
+        } else {
+            ""
+        };
+
+        tell::update!(
+            "{:<0name_col_w$}{:<0id_col_w$}{:<0date_col_w$}{}",
+            p.platform,
+            p.membership_id.to_string(),
+            human_date_format(&p.date_last_played),
+            status,
+            name_col_w = name_col_w,
+            id_col_w = name_col_w + col_w + 2,
+            date_col_w = date_col_w,
+        );
+    }
+    tell::update!();
+
+    let header = format!(
+        "{:<0name_col_w$}{:<0name_col_w$}{:<0name_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}   {:<0emblem_col_w$}{:<0date_col_w$}{}",
+        "CLASS",
+        "RACE",
+        "GENDER",
+        "POWER",
+        "MOB",
+        "RES",
+        "REC",
+        "DIS",
+        "INT",
+        "STR",
+        "EMBLEM",
+        "LAST PLAYED",
+        "TIME PLAYED",
+        name_col_w = name_col_w,
+        col_w = col_w,
+        emblem_col_w = EMBLEM_COL_W,
+        date_col_w = date_col_w,
+    );
+
+    tell::update!("CHARACTERS");
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count() + 10));
+
+    for c in summary.characters.iter() {
+        tell::update!(
+            "{:<0name_col_w$}{:<0name_col_w$}{:<0name_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}   {:<0emblem_col_w$}{:<0date_col_w$}{}",
+            c.class,
+            c.race,
+            c.gender,
+            c.power.to_string(),
+            c.mobility.to_string(),
+            c.resilience.to_string(),
+            c.recovery.to_string(),
+            c.discipline.to_string(),
+            c.intellect.to_string(),
+            c.strength.to_string(),
+            truncate_ascii_string(&c.emblem_name, EMBLEM_COL_W - 2),
+            human_date_format(&c.date_last_played),
+            human_duration(c.minutes_played_total * 60),
+            name_col_w = name_col_w,
+            col_w = col_w,
+            emblem_col_w = EMBLEM_COL_W,
+            date_col_w = date_col_w,
+        );
+    }
+
+    let total_minutes: u32 = summary
+        .characters
+        .iter()
+        .map(|e| e.minutes_played_total)
+        .sum();
+    tell::update!();
+    tell::update!("Total time played : {}", human_duration(total_minutes * 60));
+}
+
+//each line starts with the type of record, either platform or character
+fn print_tsv(summary: &ProfileSummary) {
+    for p in summary.linked_profiles.iter() {
+        let values: Vec<String> = vec![
+            "platform".to_string(),
+            p.platform.to_string(),
+            p.membership_id.to_string(),
+            p.date_last_played.to_rfc3339(),
+            p.is_public.to_string(),
+            p.is_overridden.to_string(),
+            summary.is_cross_save_active().to_string(),
+        ];
+
+        tell::update!("{}", values.join(TSV_DELIM));
+    }
+
+    for c in summary.characters.iter() {
+        let values: Vec<String> = vec![
+            "character".to_string(),
+            c.id.to_string(),
+            c.class.to_string(),
+            c.race.to_string(),
+            c.gender.to_string(),
+            c.power.to_string(),
+            c.mobility.to_string(),
+            c.resilience.to_string(),
+            c.recovery.to_string(),
+            c.discipline.to_string(),
+            c.intellect.to_string(),
+            c.strength.to_string(),
+            c.emblem_hash.to_string(),
+            c.emblem_name.to_string(),
+            c.date_last_played.to_rfc3339(),
+            c.minutes_played_total.to_string(),
+        ];
+
+        tell::update!("{}", values.join(TSV_DELIM));
+    }
+}
+
+fn print_json(summary: &ProfileSummary) {
+    match serde_json::to_string_pretty(summary) {
+        Ok(e) => tell::update!("{}", e),
+        Err(e) => {
+            tell::error!("Could not serialize profile data : {}", e);
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for viewing Destiny 2 character and profile information.
+///
+/// Displays class, race, gender, power, stats, emblem, last played date and
+/// time played for each character, along with the platforms linked to the
+/// profile and their cross save status.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie name for player
+    ///
+    /// Name must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default), tsv and json.
+    ///
+    /// tsv outputs in a tab (\t) separated format with lines ending in a new
+    /// line character (\n). The first column of each line is the type of
+    /// record, either platform or character.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<ProfileOutput>,
+
+    /// Timezone used to display dates / times
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let output: ProfileOutput = match opt.output {
+        Some(e) => e,
+        None => match config.output_supported(
+            None,
+            "default",
+            &[Output::Default, Output::Tsv, Output::Json],
+        ) {
+            Ok(e) => e.into(),
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        },
+    };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let api_key = config.api_key(opt.api_key);
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        api_key.clone(),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let client = match api_key {
+        Some(e) => ApiInterface::new_with_key(&e),
+        None => ApiInterface::new(),
+    };
+
+    let client = match client {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing API Interface", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let player_info =
+        match client.get_player_info(&member.id, &member.platform).await {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Error retrieving data from API", e)
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let linked_profiles = match client
+        .retrieve_linked_profiles(&member.id, &member.platform)
+        .await
+    {
+        Ok(e) => e.profiles,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error retrieving data from API", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut summary = ProfileSummary::with_data(
+        &member,
+        &player_info.characters,
+        &linked_profiles,
+    );
+
+    for c in summary.characters.iter_mut() {
+        c.emblem_name = match manifest
+            .get_iventory_item_definition(c.emblem_hash)
+            .await
+        {
+            Ok(Some(e)) => e.display_properties.name,
+            Ok(None) => "Unknown".to_string(),
+            Err(e) => {
+                tell::verbose!("Could not retrieve emblem definition : {}", e);
+                "Unknown".to_string()
+            }
+        };
+    }
+
+    match output {
+        ProfileOutput::Default => print_default(&summary),
+        ProfileOutput::Tsv => print_tsv(&summary),
+        ProfileOutput::Json => print_json(&summary),
+    }
+}
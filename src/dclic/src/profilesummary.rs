@@ -0,0 +1,143 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use chrono::{DateTime, Utc};
+use dcli::character::Characters;
+use dcli::crucible::Member;
+use dcli::enums::platform::Platform;
+use dcli::response::pgcr::DestinyProfileUserInfoCard;
+use serde_derive::Serialize;
+
+#[derive(Serialize, Debug)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub membership_id: i64,
+    pub platform: String,
+
+    /// Platform whose profile is used for all platforms, if cross save is
+    /// enabled
+    pub cross_save_primary: Option<String>,
+    pub linked_profiles: Vec<LinkedProfileSummary>,
+    pub characters: Vec<CharacterSummary>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LinkedProfileSummary {
+    pub platform: String,
+    pub membership_id: i64,
+    pub display_name: Option<String>,
+    pub is_public: bool,
+    pub date_last_played: DateTime<Utc>,
+
+    /// Whether the profile is not used, as another platform is the cross
+    /// save primary
+    pub is_overridden: bool,
+}
+
+impl LinkedProfileSummary {
+    pub fn with_card(
+        card: &DestinyProfileUserInfoCard,
+    ) -> LinkedProfileSummary {
+        LinkedProfileSummary {
+            platform: format!("{}", card.membership_type),
+            membership_id: card.membership_id,
+            display_name: card.display_name.clone(),
+            is_public: card.is_public,
+            date_last_played: card.date_last_played,
+            is_overridden: card.cross_save_override != Platform::Unknown
+                && card.cross_save_override != card.membership_type,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct CharacterSummary {
+    pub id: i64,
+    pub class: String,
+    pub race: String,
+    pub gender: String,
+    pub power: i32,
+    pub mobility: i32,
+    pub resilience: i32,
+    pub recovery: i32,
+    pub discipline: i32,
+    pub intellect: i32,
+    pub strength: i32,
+    pub emblem_hash: u32,
+    pub emblem_name: String,
+    pub date_last_played: DateTime<Utc>,
+    pub minutes_played_total: u32,
+}
+
+impl ProfileSummary {
+    /// Creates the summary from the profile and linked profiles. Emblem names
+    /// are left empty, and must be set from the manifest.
+    pub fn with_data(
+        member: &Member,
+        characters: &Characters,
+        linked_profiles: &[DestinyProfileUserInfoCard],
+    ) -> ProfileSummary {
+        let cross_save_primary = linked_profiles
+            .iter()
+            .map(|e| e.cross_save_override)
+            .find(|e| e != &Platform::Unknown)
+            .map(|e| format!("{}", e));
+
+        let characters = characters
+            .characters
+            .iter()
+            .map(|c| CharacterSummary {
+                id: c.id,
+                class: format!("{}", c.class_type),
+                race: format!("{}", c.race),
+                gender: format!("{}", c.gender),
+                power: c.stats.power,
+                mobility: c.stats.mobility,
+                resilience: c.stats.resilience,
+                recovery: c.stats.recovery,
+                discipline: c.stats.discipline,
+                intellect: c.stats.intellect,
+                strength: c.stats.strength,
+                emblem_hash: c.emblem_hash,
+                emblem_name: "".to_string(),
+                date_last_played: c.date_last_played,
+                minutes_played_total: c.minutes_played_total,
+            })
+            .collect();
+
+        ProfileSummary {
+            name: member.name.get_bungie_name(),
+            membership_id: member.id,
+            platform: format!("{}", member.platform),
+            cross_save_primary,
+            linked_profiles: linked_profiles
+                .iter()
+                .map(LinkedProfileSummary::with_card)
+                .collect(),
+            characters,
+        }
+    }
+
+    pub fn is_cross_save_active(&self) -> bool {
+        self.cross_save_primary.is_some()
+    }
+}
@@ -0,0 +1,616 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+mod snapshotgrouping;
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::character::CharacterSnapshot;
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::error::Error;
+use dcli::output::Output;
+use dcli::season::Seasons;
+use dcli::timezone::{set_timezone, to_local};
+use dcli::utils::{
+    determine_data_dir, format_error, human_duration, parse_rfc3339,
+    repeat_str, truncate_ascii_string, EXIT_FAILURE, TSV_DELIM,
+};
+use snapshotgrouping::SnapshotGrouping;
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const LABEL_COL_W: usize = 24;
+
+/// Snapshots for a single character within a period (day, week or season),
+/// along with the time played in that period.
+struct SnapshotPeriod<'a> {
+    label: String,
+    last: &'a CharacterSnapshot,
+    minutes_played: u32,
+}
+
+fn get_label(
+    snapshot: &CharacterSnapshot,
+    grouping: SnapshotGrouping,
+    seasons: &Seasons,
+) -> String {
+    let local = to_local(&snapshot.created);
+    match grouping {
+        SnapshotGrouping::Day => local.format("%Y-%m-%d").to_string(),
+        SnapshotGrouping::Week => local.format("%G-W%V").to_string(),
+        SnapshotGrouping::Season => {
+            match seasons.find_by_date(&snapshot.created) {
+                Some(e) => e.name.to_string(),
+                None => "Unknown".to_string(),
+            }
+        }
+    }
+}
+
+//expects snapshots for a single character, sorted by date
+fn group_snapshots<'a>(
+    snapshots: &[&'a CharacterSnapshot],
+    grouping: SnapshotGrouping,
+    seasons: &Seasons,
+) -> Vec<SnapshotPeriod<'a>> {
+    let mut out: Vec<SnapshotPeriod> = Vec::new();
+
+    //time played is the difference in total minutes played from the end of
+    //the previous period (or the first snapshot)
+    let mut last_minutes_played = match snapshots.first() {
+        Some(e) => e.minutes_played_total,
+        None => return out,
+    };
+
+    for s in snapshots {
+        let label = get_label(s, grouping, seasons);
+        let minutes_played =
+            s.minutes_played_total.saturating_sub(last_minutes_played);
+        last_minutes_played = s.minutes_played_total;
+
+        match out.last_mut() {
+            Some(e) if e.label == label => {
+                e.last = s;
+                e.minutes_played += minutes_played;
+            }
+            _ => out.push(SnapshotPeriod {
+                label,
+                last: s,
+                minutes_played,
+            }),
+        }
+    }
+
+    out
+}
+
+//splits the snapshots by character, keeping the order returned from the
+//store
+fn split_by_character(
+    snapshots: &[CharacterSnapshot],
+) -> Vec<Vec<&CharacterSnapshot>> {
+    let mut out: Vec<Vec<&CharacterSnapshot>> = Vec::new();
+
+    for s in snapshots {
+        match out.last_mut() {
+            Some(e) if e[0].character_id == s.character_id => e.push(s),
+            _ => out.push(vec![s]),
+        }
+    }
+
+    out
+}
+
+fn format_minutes(minutes: u32) -> String {
+    if minutes == 0 {
+        return "".to_string();
+    }
+
+    human_duration(minutes * 60)
+}
+
+fn print_default(
+    member: &Member,
+    snapshots: &[CharacterSnapshot],
+    grouping: SnapshotGrouping,
+    seasons: &Seasons,
+    time_period: &DateTimePeriod,
+) {
+    let col_w = 7;
+
+    tell::update!(
+        "Character history for {} from {} to {}",
+        member.name.get_bungie_name(),
+        to_local(&time_period.get_start()).format("%B %-d, %Y"),
+        to_local(&time_period.get_end()).format("%B %-d, %Y"),
+    );
+    tell::update!();
+
+    let header = format!(
+        "{:<0label_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}   {}",
+        grouping.get_header(),
+        "POWER",
+        "MOB",
+        "RES",
+        "REC",
+        "DIS",
+        "INT",
+        "STR",
+        "TIME PLAYED",
+        label_w = LABEL_COL_W,
+        col_w = col_w,
+    );
+
+    let header_border = repeat_str("=", header.chars().count() + 10);
+    let footer_border = repeat_str("-", header.chars().count() + 10);
+
+    for character in split_by_character(snapshots) {
+        let periods = group_snapshots(&character, grouping, seasons);
+
+        let first = character[0];
+        let last = character[character.len() - 1];
+
+        tell::update!("{}", format!("{}", first.class_type).to_uppercase());
+        tell::update!("{}", header);
+        tell::update!("{}", header_border);
+
+        for p in periods.iter() {
+            let s = &p.last.stats;
+            tell::update!(
+                "{:<0label_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}   {}",
+                truncate_ascii_string(&p.label, LABEL_COL_W - 2),
+                s.power.to_string(),
+                s.mobility.to_string(),
+                s.resilience.to_string(),
+                s.recovery.to_string(),
+                s.discipline.to_string(),
+                s.intellect.to_string(),
+                s.strength.to_string(),
+                format_minutes(p.minutes_played),
+                label_w = LABEL_COL_W,
+                col_w = col_w,
+            );
+        }
+
+        tell::update!("{}", footer_border);
+
+        let minutes_played: u32 =
+            periods.iter().map(|e| e.minutes_played).sum();
+        tell::update!(
+            "{:<0label_w$}{:>0col_w$}{:>0rest_w$}   {}",
+            "CHANGE",
+            format!("{:+}", last.stats.power - first.stats.power),
+            "",
+            format_minutes(minutes_played),
+            label_w = LABEL_COL_W,
+            col_w = col_w,
+            rest_w = col_w * 6,
+        );
+        tell::update!(
+            "Total time played : {}",
+            human_duration(last.minutes_played_total * 60)
+        );
+        tell::update!();
+    }
+
+    tell::update!("Time played is calculated from the difference between snapshots, which are stored each time the player is synced.");
+}
+
+fn print_tsv(
+    snapshots: &[CharacterSnapshot],
+    grouping: SnapshotGrouping,
+    seasons: &Seasons,
+) {
+    for character in split_by_character(snapshots) {
+        for p in group_snapshots(&character, grouping, seasons) {
+            let s = &p.last.stats;
+            tell::update!(
+                "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
+                p.last.character_id,
+                TSV_DELIM,
+                p.last.class_type,
+                TSV_DELIM,
+                p.label,
+                TSV_DELIM,
+                s.power,
+                TSV_DELIM,
+                s.mobility,
+                TSV_DELIM,
+                s.resilience,
+                TSV_DELIM,
+                s.recovery,
+                TSV_DELIM,
+                s.discipline,
+                TSV_DELIM,
+                s.intellect,
+                TSV_DELIM,
+                s.strength,
+                TSV_DELIM,
+                p.minutes_played,
+                TSV_DELIM,
+                p.last.minutes_played_total,
+            );
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for viewing Destiny 2 character power, stat and time
+/// played history.
+///
+/// A snapshot of each character's power, stats and time played is stored
+/// each time the player is synced (via dclisync, or the --sync flag), and
+/// this tool displays how they have changed per day, week or season.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie name for player
+    ///
+    /// Name must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
+
+    /// Custom start time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "t", long = "custom-time", parse(try_from_str = parse_rfc3339), required_if("moment", "custom"))]
+    custom_time: Option<DateTime<Utc>>,
+
+    /// Custom end time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --end-moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "e", long = "end-custom-time", parse(try_from_str = parse_rfc3339), required_if("end-moment", "custom"))]
+    end_custom_time: Option<DateTime<Utc>>,
+
+    /// Start moment from which to display character history
+    ///
+    /// Valid values include daily (last daily reset), weekend
+    /// (last weekend reset on Friday), weekly (last weekly reset on Tuesday),
+    /// day (last day), week (last week), month (last month), all_time and custom
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season).
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 12h, 2w, today, yesterday, this-week, last-week,
+    /// this-month, last-month, this-year, last-year, months (2023-05), ISO
+    /// weeks (2023-W14), dates (2023-05-01) and ranges
+    /// (2023-01-01..2023-02-01). Calendar based expressions use the timezone
+    /// set with --timezone.
+    ///
+    /// When custom is specified, the custom start date in RFC3339 format must
+    /// be specified with the --custom-time argument.
+    ///
+    /// Defaults to all_time, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// End moment from which to display character history
+    ///
+    /// Accepts the same values as --moment.
+    ///
+    /// When custom is specified, the custom end date in RFC3339 format must
+    /// be specified with the --end-custom-time argument.
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
+
+    /// Character to display history for
+    ///
+    /// Valid values include hunter, titan, warlock, last_active and all.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// How to group character snapshots
+    ///
+    /// Valid values are day (default), week and season.
+    #[structopt(short = "g", long = "group-by", default_value = "day")]
+    group_by: SnapshotGrouping,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default) and tsv.
+    ///
+    /// tsv outputs in a tab (\t) separated format of columns with lines
+    /// ending in a new line character (\n). Columns are character id, class,
+    /// period, power, mobility, resilience, recovery, discipline, intellect,
+    /// strength, minutes played in period and total minutes played.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Sync player activities and store a new character snapshot
+    #[structopt(long = "sync", short = "s")]
+    sync: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let moment = match config.moment(opt.moment, "all_time") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::TERMINAL)
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if opt.sync {
+        match store.sync_member(&member).await {
+            Ok(_e) => (),
+            Err(e) => {
+                tell::error!("Could not sync activity store {}", e);
+                tell::update!("Using existing data");
+            }
+        };
+    }
+
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let snapshots = match store
+        .retrieve_character_snapshots(
+            &member,
+            &character_class_selection,
+            &time_period,
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if snapshots.is_empty() {
+        tell::update!("No character snapshots found. Snapshots are stored each time the player is synced.");
+        return;
+    }
+
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => print_default(
+            &member,
+            &snapshots,
+            opt.group_by,
+            &seasons,
+            &time_period,
+        ),
+        Output::Tsv => print_tsv(&snapshots, opt.group_by, &seasons),
+    }
+}
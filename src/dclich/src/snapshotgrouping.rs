@@ -0,0 +1,71 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum SnapshotGrouping {
+    Day,
+    Week,
+    Season,
+}
+
+impl SnapshotGrouping {
+    pub fn get_header(&self) -> &'static str {
+        match self {
+            SnapshotGrouping::Day => "DATE",
+            SnapshotGrouping::Week => "WEEK",
+            SnapshotGrouping::Season => "SEASON",
+        }
+    }
+}
+
+impl FromStr for SnapshotGrouping {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //wrap in String so we can convert to lower case
+        let s = String::from(s).to_lowercase();
+
+        //get a slice to get a &str for the match
+        match &s[..] {
+            "day" => Ok(SnapshotGrouping::Day),
+            "week" => Ok(SnapshotGrouping::Week),
+            "season" => Ok(SnapshotGrouping::Season),
+
+            _ => Err("Unknown SnapshotGrouping type"),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotGrouping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let out = match self {
+            SnapshotGrouping::Day => "day",
+            SnapshotGrouping::Week => "week",
+            SnapshotGrouping::Season => "season",
+        };
+
+        write!(f, "{}", out)
+    }
+}
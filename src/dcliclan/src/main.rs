@@ -0,0 +1,714 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::clan::{Clan, ClanMatchup, ClanPlayerStats};
+use dcli::config::Config;
+use dcli::enums::clansort::ClanSort;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::error::Error;
+use dcli::output::Output;
+use dcli::season::Seasons;
+use dcli::timezone::{set_timezone, to_local};
+use dcli::utils::{
+    determine_data_dir, format_error, format_f32,
+    parse_and_validate_crucible_mode, parse_rfc3339, repeat_str,
+    truncate_ascii_string, EXIT_FAILURE, TSV_DELIM,
+};
+use serde_derive::Serialize;
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const NAME_COL_W: usize = 24;
+
+#[derive(Serialize, Debug)]
+struct LeaderboardEntry {
+    rank: usize,
+    name: String,
+    games: u32,
+    wins: u32,
+    losses: u32,
+    win_percent: f32,
+    kills: u32,
+    deaths: u32,
+    assists: u32,
+    kills_deaths_ratio: f32,
+    efficiency: f32,
+    medals: u32,
+    time_played_seconds: u32,
+}
+
+impl LeaderboardEntry {
+    fn with_stats(rank: usize, s: &ClanPlayerStats) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank,
+            name: s.member.name.get_bungie_name(),
+            games: s.games,
+            wins: s.wins,
+            losses: s.losses(),
+            win_percent: s.win_percent(),
+            kills: s.kills,
+            deaths: s.deaths,
+            assists: s.assists,
+            kills_deaths_ratio: s.kills_deaths_ratio(),
+            efficiency: s.efficiency(),
+            medals: s.medals,
+            time_played_seconds: s.time_played_seconds,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct MatchupEntry {
+    name: String,
+    other_name: String,
+    games_together: u32,
+    wins_together: u32,
+    win_percent_together: f32,
+    games_against: u32,
+    wins: u32,
+    other_wins: u32,
+}
+
+impl MatchupEntry {
+    fn with_matchup(m: &ClanMatchup) -> MatchupEntry {
+        MatchupEntry {
+            name: m.member.name.get_bungie_name(),
+            other_name: m.other.name.get_bungie_name(),
+            games_together: m.games_together,
+            wins_together: m.wins_together,
+            win_percent_together: m.win_percent_together(),
+            games_against: m.games_against,
+            wins: m.member_wins,
+            other_wins: m.other_wins,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ClanReport {
+    group_id: i64,
+    name: String,
+    mode: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    sort: String,
+    leaderboard: Vec<LeaderboardEntry>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    matchups: Option<Vec<MatchupEntry>>,
+}
+
+fn format_percent(value: f32) -> String {
+    format!("{}%", format_f32(value, 2))
+}
+
+fn print_leaderboard(leaderboard: &[ClanPlayerStats]) {
+    let col_w = 9;
+    let header = format!(
+        "{:<4}{:<0name_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+        "#",
+        "PLAYER",
+        "GAMES",
+        "WIN %",
+        "KILLS",
+        "DEATHS",
+        "ASSISTS",
+        "K/D",
+        "EFF",
+        "MEDALS",
+        col_w = col_w,
+        name_w = NAME_COL_W,
+    );
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    for (i, s) in leaderboard.iter().enumerate() {
+        tell::update!(
+            "{:<4}{:<0name_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+            i + 1,
+            truncate_ascii_string(
+                &s.member.name.get_bungie_name(),
+                NAME_COL_W - 2
+            ),
+            s.games.to_string(),
+            format_percent(s.win_percent()),
+            s.kills.to_string(),
+            s.deaths.to_string(),
+            s.assists.to_string(),
+            format_f32(s.kills_deaths_ratio(), 2),
+            format_f32(s.efficiency(), 2),
+            s.medals.to_string(),
+            col_w = col_w,
+            name_w = NAME_COL_W,
+        );
+    }
+    tell::update!();
+}
+
+fn print_matchups(matchups: &[ClanMatchup]) {
+    let col_w = 10;
+    let header = format!(
+        "{:<0name_w$}{:<0name_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+        "PLAYER",
+        "PLAYER",
+        "TOGETHER",
+        "WIN %",
+        "AGAINST",
+        "RECORD",
+        col_w = col_w,
+        name_w = NAME_COL_W,
+    );
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    for m in matchups.iter() {
+        tell::update!(
+            "{:<0name_w$}{:<0name_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+            truncate_ascii_string(
+                &m.member.name.get_bungie_name(),
+                NAME_COL_W - 2
+            ),
+            truncate_ascii_string(&m.other.name.get_bungie_name(), NAME_COL_W - 2),
+            m.games_together.to_string(),
+            format_percent(m.win_percent_together()),
+            m.games_against.to_string(),
+            format!("{}-{}", m.member_wins, m.other_wins),
+            col_w = col_w,
+            name_w = NAME_COL_W,
+        );
+    }
+    tell::update!();
+    tell::update!(
+        "RECORD - Wins for each player when playing against each other"
+    );
+}
+
+fn print_default(
+    clan: &Clan,
+    mode: &Mode,
+    time_period: &DateTimePeriod,
+    sort: &ClanSort,
+    leaderboard: &[ClanPlayerStats],
+    matchups: Option<&[ClanMatchup]>,
+) {
+    tell::update!(
+        "{} leaderboard for {} sorted by {} ({} players)",
+        mode,
+        clan.name,
+        sort,
+        leaderboard.len()
+    );
+    tell::update!(
+        "{} to {}",
+        to_local(&time_period.get_start()).format("%Y-%m-%d %H:%M"),
+        to_local(&time_period.get_end()).format("%Y-%m-%d %H:%M")
+    );
+    tell::update!();
+
+    if leaderboard.is_empty() {
+        tell::update!("No activities found");
+        return;
+    }
+
+    print_leaderboard(leaderboard);
+
+    if let Some(matchups) = matchups {
+        if matchups.is_empty() {
+            tell::update!("No matchups found");
+        } else {
+            print_matchups(matchups);
+        }
+    }
+}
+
+fn print_tsv(
+    leaderboard: &[ClanPlayerStats],
+    matchups: Option<&[ClanMatchup]>,
+) {
+    //matchups replace the leaderboard, so each line has the same columns
+    if let Some(matchups) = matchups {
+        for m in matchups.iter() {
+            let values = vec![
+                m.member.name.get_bungie_name(),
+                m.other.name.get_bungie_name(),
+                m.games_together.to_string(),
+                m.wins_together.to_string(),
+                m.games_against.to_string(),
+                m.member_wins.to_string(),
+                m.other_wins.to_string(),
+            ];
+
+            tell::update!("{}", values.join(TSV_DELIM));
+        }
+        return;
+    }
+
+    for (i, s) in leaderboard.iter().enumerate() {
+        let values = vec![
+            (i + 1).to_string(),
+            s.member.name.get_bungie_name(),
+            s.games.to_string(),
+            s.wins.to_string(),
+            s.win_percent().to_string(),
+            s.kills.to_string(),
+            s.deaths.to_string(),
+            s.assists.to_string(),
+            s.kills_deaths_ratio().to_string(),
+            s.efficiency().to_string(),
+            s.medals.to_string(),
+        ];
+
+        tell::update!("{}", values.join(TSV_DELIM));
+    }
+}
+
+fn print_json(
+    clan: &Clan,
+    mode: &Mode,
+    time_period: &DateTimePeriod,
+    sort: &ClanSort,
+    leaderboard: &[ClanPlayerStats],
+    matchups: Option<&[ClanMatchup]>,
+) {
+    let report = ClanReport {
+        group_id: clan.group_id,
+        name: clan.name.to_string(),
+        mode: mode.to_string(),
+        start: time_period.get_start(),
+        end: time_period.get_end(),
+        sort: sort.to_string(),
+        leaderboard: leaderboard
+            .iter()
+            .enumerate()
+            .map(|(i, s)| LeaderboardEntry::with_stats(i + 1, s))
+            .collect(),
+        matchups: matchups
+            .map(|e| e.iter().map(MatchupEntry::with_matchup).collect()),
+    };
+
+    match serde_json::to_string_pretty(&report) {
+        Ok(e) => tell::update!("{}", e),
+        Err(e) => {
+            tell::error!("Could not serialize leaderboard : {}", e);
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for viewing Destiny 2 Crucible leaderboards and matchups
+/// for members of a clan.
+///
+/// Displays stats for the current members of a clan tracked with
+/// dclisync --import-group, ranked by kills deaths ratio, efficiency, win
+/// percentage, games, kills or medals. Matchups show how clan members have
+/// done playing together, and against each other.
+///
+/// Stats are based on the activities stored in the activity database, so
+/// clan members must be synced with dclisync.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Group id for the clan
+    ///
+    /// The clan must be tracked with dclisync --import-group. Not required
+    /// if only one clan is tracked.
+    #[structopt(long = "group", short = "g")]
+    group: Option<i64>,
+
+    /// Custom start time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "t", long = "custom-time", parse(try_from_str = parse_rfc3339), required_if("moment", "custom"))]
+    custom_time: Option<DateTime<Utc>>,
+
+    /// Custom end time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --end-moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "e", long = "end-custom-time", parse(try_from_str = parse_rfc3339), required_if("end-moment", "custom"))]
+    end_custom_time: Option<DateTime<Utc>>,
+
+    /// Start moment from which to pull activities
+    ///
+    /// Valid values include weekend (last weekend reset on Friday), weekly
+    /// (last weekly reset on Tuesday), day, week, month, all_time and custom
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season).
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 2w, this-month, last-month, months (2023-05), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When custom is specified, the custom start date in RFC3339 format must
+    /// be specified with the --custom-time argument.
+    ///
+    /// Defaults to month, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// End moment from which to pull activities
+    ///
+    /// Accepts the same values as --moment.
+    ///
+    /// When custom is specified, the custom end date in RFC3339 format must
+    /// be specified with the --end-custom-time argument.
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
+
+    /// Activity mode to return stats for
+    ///
+    /// Supported values are all_pvp (default), control, clash, elimination,
+    /// mayhem, iron_banner, all_private, rumble, pvp_competitive,
+    /// quickplay and trials_of_osiris.
+    ///
+    /// Addition values available are crimsom_doubles, supremacy, survival,
+    /// countdown, all_doubles, doubles, private_clash, private_control,
+    /// private_survival, private_rumble, showdown_competitive, survival_competitive,
+    /// rift_competitive, showdown, lockdown, iron_banner_rift,
+    /// zone_control, iron_banner_zone_control, rift,
+    /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
+    #[structopt(long = "mode", short = "M",
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
+
+    /// Stat to rank players by
+    ///
+    /// Valid values are kd (default), efficiency, win_percent, games, kills
+    /// and medals.
+    #[structopt(long = "sort", short = "S", default_value = "kd")]
+    sort: ClanSort,
+
+    /// Minimum number of games a player must have played to be ranked
+    #[structopt(long = "min-games", short = "m", default_value = "1")]
+    min_games: u32,
+
+    /// Display results for clan members playing together, and against each
+    /// other
+    #[structopt(long = "matchups")]
+    matchups: bool,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default), tsv and json.
+    ///
+    /// tsv outputs one line per player, in a tab (\t) separated format with
+    /// lines ending in a new line character (\n). Columns are rank, name,
+    /// games, wins, win percent, kills, deaths, assists, kills deaths ratio,
+    /// efficiency and medals. When --matchups is specified, outputs one line
+    /// per matchup instead, with columns for name, other name, games
+    /// together, wins together, games against, wins and other wins.
+    ///
+    /// json outputs the leaderboard, and matchups when --matchups is
+    /// specified, as a single JSON object.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Sync the clan roster, and activities for all clan members
+    #[structopt(long = "sync", short = "s")]
+    sync: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let moment = match config.moment(opt.moment, "month") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let output = match config.output_supported(
+        opt.output,
+        "default",
+        &[Output::Default, Output::Tsv, Output::Json],
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid output format.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let clan = match store.retrieve_clan(opt.group).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not find clan.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if opt.sync {
+        match store.sync_group(clan.group_id).await {
+            Ok(_e) => (),
+            Err(e) => {
+                tell::error!("Could not sync clan roster {}", e);
+                tell::update!("Using existing roster");
+            }
+        };
+
+        let members =
+            match store.retrieve_clan_members(clan.group_id, false).await {
+                Ok(e) => e,
+                Err(e) => {
+                    tell::error!(
+                        "{}",
+                        format_error("Could not retrieve clan roster.", e)
+                    );
+                    std::process::exit(EXIT_FAILURE);
+                }
+            };
+
+        for m in members.iter() {
+            if let Err(e) = store.sync_member(&m.member).await {
+                tell::error!(
+                    "Could not sync activity store for {} {}",
+                    m.member.name.get_bungie_name(),
+                    e
+                );
+            }
+        }
+    }
+
+    let mut leaderboard = match store
+        .retrieve_clan_leaderboard(&clan, &mode, &time_period)
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let sort = opt.sort;
+    let min_games = opt.min_games;
+    leaderboard.retain(|e| e.games >= min_games);
+    leaderboard.sort_by(|a, b| a.cmp_by(b, &sort));
+
+    let matchups = if opt.matchups {
+        match store
+            .retrieve_clan_matchups(&clan, &mode, &time_period)
+            .await
+        {
+            Ok(mut e) => {
+                e.sort_by(|a, b| {
+                    (b.games_together + b.games_against)
+                        .cmp(&(a.games_together + a.games_against))
+                });
+                Some(e)
+            }
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error(
+                        "Could not retrieve data from activity store.",
+                        e
+                    )
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+    } else {
+        None
+    };
+
+    match output {
+        Output::Tsv => print_tsv(&leaderboard, matchups.as_deref()),
+        Output::Json => print_json(
+            &clan,
+            &mode,
+            &time_period,
+            &sort,
+            &leaderboard,
+            matchups.as_deref(),
+        ),
+        Output::Default | Output::Markdown | Output::Html | Output::Jsonl => {
+            print_default(
+                &clan,
+                &mode,
+                &time_period,
+                &sort,
+                &leaderboard,
+                matchups.as_deref(),
+            )
+        }
+    }
+}
@@ -0,0 +1,981 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use crossterm::style::Stylize;
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::comparison::{find_common_weapons, find_weapon_stat, SharedMatches};
+use dcli::config::Config;
+use dcli::crucible::{
+    AggregateCruciblePerformances, CruciblePlayerActivityPerformance,
+    CruciblePlayerPerformance, Member,
+};
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::error::Error;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::output::Output;
+use dcli::playeractivitiessummary::PlayerActivitiesSummary;
+use dcli::season::Seasons;
+use dcli::timezone::{set_timezone, to_local};
+use dcli::utils::{
+    calculate_average, calculate_percent, determine_data_dir, format_error,
+    format_f32, parse_and_validate_crucible_mode, parse_rfc3339, repeat_str,
+    truncate_ascii_string, EXIT_FAILURE, TSV_DELIM,
+};
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const LABEL_COL_W: usize = 24;
+const MIN_COL_W: usize = 16;
+
+/// Stats and activities for one of the players being compared
+struct ComparedPlayer {
+    member: Member,
+    performances: Vec<CruciblePlayerActivityPerformance>,
+    aggregate: AggregateCruciblePerformances,
+    summary: PlayerActivitiesSummary,
+}
+
+/// Which value wins when comparing a stat
+#[derive(Clone, Copy, PartialEq)]
+enum Better {
+    Higher,
+    Lower,
+
+    /// The stat is displayed, but has no winner. Used for totals (i.e. games
+    /// played or kills), which depend on how many games each player played
+    Neither,
+}
+
+/// A stat for each of the players, in the order they were specified
+struct StatRow {
+    label: String,
+    values: Vec<f32>,
+    formatted: Vec<String>,
+    better: Better,
+}
+
+impl StatRow {
+    fn with_values(
+        label: &str,
+        players: &[ComparedPlayer],
+        better: Better,
+        value: impl Fn(&ComparedPlayer) -> f32,
+        format: impl Fn(f32) -> String,
+    ) -> StatRow {
+        let values: Vec<f32> = players.iter().map(value).collect();
+        let formatted = values.iter().map(|e| format(*e)).collect();
+
+        StatRow {
+            label: label.to_string(),
+            values,
+            formatted,
+            better,
+        }
+    }
+
+    /// Returns whether the value at the index is the best value. Nothing
+    /// wins if all of the values are the same.
+    fn is_winner(&self, index: usize) -> bool {
+        let best = match self.better {
+            Better::Higher => {
+                self.values.iter().cloned().fold(f32::MIN, f32::max)
+            }
+            Better::Lower => {
+                self.values.iter().cloned().fold(f32::MAX, f32::min)
+            }
+            Better::Neither => return false,
+        };
+
+        let all_same = self.values.iter().all(|e| *e == best);
+        !all_same && self.values[index] == best
+    }
+}
+
+fn format_int(value: f32) -> String {
+    format!("{}", value as u32)
+}
+
+fn format_ratio(value: f32) -> String {
+    format_f32(value, 2)
+}
+
+fn format_percent(value: f32) -> String {
+    format!("{}%", format_f32(value, 2))
+}
+
+fn format_hours(value: f32) -> String {
+    let minutes = value as u32 / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+fn per_game(value: u32, p: &ComparedPlayer) -> f32 {
+    calculate_average(value, p.aggregate.total_activities)
+}
+
+fn build_performance_rows(players: &[ComparedPlayer]) -> Vec<StatRow> {
+    vec![
+        StatRow::with_values(
+            "GAMES",
+            players,
+            Better::Neither,
+            |p| p.aggregate.total_activities as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "TIME PLAYED",
+            players,
+            Better::Neither,
+            |p| p.aggregate.time_played_seconds as f32,
+            format_hours,
+        ),
+        StatRow::with_values(
+            "WINS",
+            players,
+            Better::Neither,
+            |p| p.aggregate.wins as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "WIN %",
+            players,
+            Better::Higher,
+            |p| p.aggregate.win_rate,
+            format_percent,
+        ),
+        StatRow::with_values(
+            "KILLS",
+            players,
+            Better::Neither,
+            |p| p.aggregate.kills as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "ASSISTS",
+            players,
+            Better::Neither,
+            |p| p.aggregate.assists as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "DEATHS",
+            players,
+            Better::Neither,
+            |p| p.aggregate.deaths as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "KILLS / GAME",
+            players,
+            Better::Higher,
+            |p| per_game(p.aggregate.kills, p),
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "DEATHS / GAME",
+            players,
+            Better::Lower,
+            |p| per_game(p.aggregate.deaths, p),
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "K/D",
+            players,
+            Better::Higher,
+            |p| p.aggregate.kills_deaths_ratio,
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "KD/A",
+            players,
+            Better::Higher,
+            |p| p.aggregate.kills_deaths_assists,
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "EFFICIENCY",
+            players,
+            Better::Higher,
+            |p| p.aggregate.efficiency,
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "LONGEST WIN STREAK",
+            players,
+            Better::Higher,
+            |p| p.aggregate.longest_win_streak as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "LONGEST LOSS STREAK",
+            players,
+            Better::Lower,
+            |p| p.aggregate.longest_loss_streak as f32,
+            format_int,
+        ),
+    ]
+}
+
+fn build_summary_rows(players: &[ComparedPlayer]) -> Vec<StatRow> {
+    vec![
+        StatRow::with_values(
+            "COMPLETED %",
+            players,
+            Better::Higher,
+            |p| {
+                calculate_percent(
+                    p.summary.completed,
+                    p.summary.total_activities,
+                )
+            },
+            format_percent,
+        ),
+        StatRow::with_values(
+            "MERCY",
+            players,
+            Better::Neither,
+            |p| p.summary.completion_reason_mercy as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "PRECISION / GAME",
+            players,
+            Better::Higher,
+            |p| per_game(p.summary.precision, p),
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "SUPER KILLS / GAME",
+            players,
+            Better::Higher,
+            |p| per_game(p.summary.super_kills, p),
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "GRENADE KILLS / GAME",
+            players,
+            Better::Higher,
+            |p| per_game(p.summary.grenade_kills, p),
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "MELEE KILLS / GAME",
+            players,
+            Better::Higher,
+            |p| per_game(p.summary.melee_kills, p),
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "HIGHEST KILLS",
+            players,
+            Better::Higher,
+            |p| p.summary.highest_kills as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "HIGHEST ASSISTS",
+            players,
+            Better::Higher,
+            |p| p.summary.highest_assists as f32,
+            format_int,
+        ),
+        StatRow::with_values(
+            "HIGHEST K/D",
+            players,
+            Better::Higher,
+            |p| p.summary.highest_kills_deaths_ratio,
+            format_ratio,
+        ),
+        StatRow::with_values(
+            "HIGHEST EFFICIENCY",
+            players,
+            Better::Higher,
+            |p| p.summary.highest_efficiency,
+            format_ratio,
+        ),
+    ]
+}
+
+fn build_weapon_rows(players: &[ComparedPlayer], count: usize) -> Vec<StatRow> {
+    let aggregates: Vec<&AggregateCruciblePerformances> =
+        players.iter().map(|p| &p.aggregate).collect();
+
+    let mut out: Vec<StatRow> = Vec::new();
+    for id in find_common_weapons(&aggregates, count).iter() {
+        let stats: Vec<_> = players
+            .iter()
+            .map(|p| find_weapon_stat(&p.aggregate, *id))
+            .collect();
+
+        let name = match stats.iter().flatten().next() {
+            Some(e) => e.weapon.name.to_string(),
+            None => continue,
+        };
+
+        //weapons are ranked by kills per game, since players may have
+        //played a different number of games
+        let values: Vec<f32> = stats
+            .iter()
+            .zip(players.iter())
+            .map(|(s, p)| per_game(s.map(|e| e.kills).unwrap_or(0), p))
+            .collect();
+
+        let formatted = stats
+            .iter()
+            .zip(values.iter())
+            .map(|(s, v)| {
+                format!(
+                    "{} ({})",
+                    s.map(|e| e.kills).unwrap_or(0),
+                    format_ratio(*v)
+                )
+            })
+            .collect();
+
+        out.push(StatRow {
+            label: name,
+            values,
+            formatted,
+            better: Better::Higher,
+        });
+    }
+
+    out
+}
+
+fn print_rows(title: &str, rows: &[StatRow], players: &[ComparedPlayer]) {
+    let col_w = column_width(players);
+
+    let mut header = format!("{:<0label_w$}", title, label_w = LABEL_COL_W);
+    for p in players.iter() {
+        header.push_str(&format!(
+            "{:>0col_w$}",
+            truncate_ascii_string(&p.member.name.get_bungie_name(), col_w - 2),
+            col_w = col_w
+        ));
+    }
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    for row in rows.iter() {
+        let mut line = format!(
+            "{:<0label_w$}",
+            truncate_ascii_string(&row.label, LABEL_COL_W - 2),
+            label_w = LABEL_COL_W
+        );
+
+        for (i, value) in row.formatted.iter().enumerate() {
+            //pad before styling, since the escape codes would be counted in
+            //the width
+            let cell = format!("{:>0col_w$}", value, col_w = col_w);
+            if row.is_winner(i) {
+                line.push_str(&format!("{}", cell.green().bold()));
+            } else {
+                line.push_str(&cell);
+            }
+        }
+
+        tell::update!("{}", line);
+    }
+    tell::update!();
+}
+
+fn print_shared_matches(players: &[ComparedPlayer]) {
+    let col_w = 10;
+    let name_w = column_width(players);
+
+    let header = format!(
+        "{:<0name_w$}{:<0name_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+        "PLAYER",
+        "PLAYER",
+        "TOGETHER",
+        "WIN %",
+        "AGAINST",
+        "RECORD",
+        col_w = col_w,
+        name_w = name_w,
+    );
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    for (i, a) in players.iter().enumerate() {
+        for b in players[i + 1..].iter() {
+            let shared = SharedMatches::with_performances(
+                &a.performances,
+                &b.performances,
+            );
+
+            tell::update!(
+                "{:<0name_w$}{:<0name_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+                truncate_ascii_string(
+                    &a.member.name.get_bungie_name(),
+                    name_w - 2
+                ),
+                truncate_ascii_string(
+                    &b.member.name.get_bungie_name(),
+                    name_w - 2
+                ),
+                shared.together.matches.to_string(),
+                format_percent(calculate_percent(
+                    shared.together.wins,
+                    shared.together.matches
+                )),
+                shared.against.matches.to_string(),
+                format!("{}-{}", shared.against.wins, shared.other_wins),
+                col_w = col_w,
+                name_w = name_w,
+            );
+        }
+    }
+    tell::update!();
+    tell::update!(
+        "RECORD - Wins for each player when playing against each other"
+    );
+}
+
+fn column_width(players: &[ComparedPlayer]) -> usize {
+    players
+        .iter()
+        .map(|p| p.member.name.get_bungie_name().chars().count() + 2)
+        .max()
+        .unwrap_or(0)
+        .max(MIN_COL_W)
+}
+
+fn print_default(
+    players: &[ComparedPlayer],
+    mode: &Mode,
+    time_period: &DateTimePeriod,
+    weapon_count: usize,
+) {
+    let names: Vec<String> = players
+        .iter()
+        .map(|p| p.member.name.get_bungie_name())
+        .collect();
+
+    tell::update!("{} comparison for {}", mode, names.join(" vs "));
+    tell::update!(
+        "{} to {}",
+        to_local(&time_period.get_start()).format("%Y-%m-%d %H:%M"),
+        to_local(&time_period.get_end()).format("%Y-%m-%d %H:%M")
+    );
+    tell::update!();
+
+    print_rows("PERFORMANCE", &build_performance_rows(players), players);
+
+    print_rows("ACTIVITY SUMMARY", &build_summary_rows(players), players);
+
+    let weapon_rows = build_weapon_rows(players, weapon_count);
+    if weapon_rows.is_empty() {
+        tell::update!(
+            "No weapons in common in the top {} weapons for each player",
+            weapon_count
+        );
+        tell::update!();
+    } else {
+        print_rows("WEAPONS IN COMMON", &weapon_rows, players);
+        tell::update!("WEAPONS IN COMMON - Weapons in the top {} (by kills) for every player. Values are kills (kills per game)", weapon_count);
+        tell::update!();
+    }
+
+    print_shared_matches(players);
+}
+
+fn print_tsv(players: &[ComparedPlayer]) {
+    let rows = build_performance_rows(players)
+        .into_iter()
+        .chain(build_summary_rows(players).into_iter());
+
+    for row in rows {
+        let mut values = vec![row.label.to_lowercase()];
+        values.extend(row.values.iter().map(|e| e.to_string()));
+
+        tell::update!("{}", values.join(TSV_DELIM));
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for comparing Destiny 2 Crucible stats for two or more
+/// players side by side.
+///
+/// Displays each player's stats in aligned columns, with the best value for
+/// each stat highlighted, along with the weapons which are in every player's
+/// top weapons, and the players' records in shared matches, both as
+/// teammates and as opponents.
+///
+/// Stats are based on the activities stored in the activity database, so
+/// each player should be synced with dclisync. Shared matches are only
+/// found when both players are in a stored activity.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie names for the players to compare
+    ///
+    /// At least two names must be specified, either space separated or by
+    /// passing --name multiple times.
+    ///
+    /// Names must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be aliases from the dcli.toml config file.
+    #[structopt(long = "name", short = "n", required = true)]
+    names: Vec<String>,
+
+    /// Custom start time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "t", long = "custom-time", parse(try_from_str = parse_rfc3339), required_if("moment", "custom"))]
+    custom_time: Option<DateTime<Utc>>,
+
+    /// Custom end time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --end-moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "e", long = "end-custom-time", parse(try_from_str = parse_rfc3339), required_if("end-moment", "custom"))]
+    end_custom_time: Option<DateTime<Utc>>,
+
+    /// Start moment from which to pull activities
+    ///
+    /// Valid values include weekend (last weekend reset on Friday), weekly
+    /// (last weekly reset on Tuesday), day, week, month, all_time and custom
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season).
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 2w, this-month, last-month, months (2023-05), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When custom is specified, the custom start date in RFC3339 format must
+    /// be specified with the --custom-time argument.
+    ///
+    /// Defaults to month, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// End moment from which to pull activities
+    ///
+    /// Accepts the same values as --moment.
+    ///
+    /// When custom is specified, the custom end date in RFC3339 format must
+    /// be specified with the --end-custom-time argument.
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
+
+    /// Activity mode to return stats for
+    ///
+    /// Supported values are all_pvp (default), control, clash, elimination,
+    /// mayhem, iron_banner, all_private, rumble, pvp_competitive,
+    /// quickplay and trials_of_osiris.
+    ///
+    /// Addition values available are crimsom_doubles, supremacy, survival,
+    /// countdown, all_doubles, doubles, private_clash, private_control,
+    /// private_survival, private_rumble, showdown_competitive, survival_competitive,
+    /// rift_competitive, showdown, lockdown, iron_banner_rift,
+    /// zone_control, iron_banner_zone_control, rift,
+    /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
+    #[structopt(long = "mode", short = "M",
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
+
+    /// Character to retrieve data for
+    ///
+    /// Valid values include hunter, titan, warlock, last_active and all.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// The number of top weapons (by kills) for each player to look for
+    /// weapons in common
+    #[structopt(long = "weapon-count", short = "w", default_value = "10")]
+    weapon_count: u32,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default) and tsv.
+    ///
+    /// tsv outputs one line per stat, in a tab (\t) separated format with
+    /// lines ending in a new line character (\n). The first column is the
+    /// stat, followed by a column with the value for each player, in the
+    /// order the players were specified.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Sync activities for the players
+    #[structopt(long = "sync", short = "s")]
+    sync: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    if opt.names.len() < 2 {
+        tell::error!("At least two players must be specified with --name");
+        std::process::exit(EXIT_FAILURE);
+    }
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let mut names = Vec::new();
+    for n in opt.names.iter() {
+        match config.player_name(&Some(n.to_string())) {
+            Ok(e) => names.push(e),
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid player name.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+    }
+
+    let moment = match config.moment(opt.moment, "month") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::TERMINAL)
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut players: Vec<ComparedPlayer> = Vec::new();
+    for name in names.iter() {
+        let member: Member = match store.find_member(name, true).await {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!(
+                    "Could not find Bungie ID ({}). Please check name and try again. {}",
+                    name.get_bungie_name(),
+                    e
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+        if opt.sync {
+            match store.sync_member(&member).await {
+                Ok(_e) => (),
+                Err(e) => {
+                    tell::error!("Could not sync activity store {}", e);
+                    tell::update!("Using existing data");
+                }
+            };
+        }
+
+        let member = match store.select_membership(&member, &membership).await {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Could not find selected membership.", e)
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+        let performances = match store
+            .retrieve_activities_since(
+                &member,
+                &character_class_selection,
+                &mode,
+                &time_period,
+                &mut manifest,
+            )
+            .await
+        {
+            Ok(e) => e.unwrap_or_default(),
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error(
+                        "Could not retrieve data from activity store.",
+                        e
+                    )
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+        if performances.is_empty() {
+            tell::update!(
+                "No activities found for {}",
+                member.name.get_bungie_name()
+            );
+            return;
+        }
+
+        let summary = match store
+            .retrieve_activities_summary(
+                &member,
+                &character_class_selection,
+                &mode,
+                &time_period,
+            )
+            .await
+        {
+            Ok(Some(e)) => e,
+            Ok(None) => {
+                tell::update!(
+                    "No activities found for {}",
+                    member.name.get_bungie_name()
+                );
+                return;
+            }
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error(
+                        "Could not retrieve data from activity store.",
+                        e
+                    )
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+        let cpp: Vec<&CruciblePlayerPerformance> =
+            performances.iter().map(|x| &x.performance).collect();
+        let aggregate = AggregateCruciblePerformances::with_performances(&cpp);
+
+        players.push(ComparedPlayer {
+            member,
+            performances,
+            aggregate,
+            summary,
+        });
+    }
+
+    match output {
+        Output::Tsv => print_tsv(&players),
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => print_default(
+            &players,
+            &mode,
+            &time_period,
+            opt.weapon_count as usize,
+        ),
+    }
+}
@@ -1,5 +1,31 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use dcli::enums::character::CharacterClass;
+
 pub struct ClassAscii {
     pub hunter: Vec<String>,
+    pub titan: Vec<String>,
+    pub warlock: Vec<String>,
 }
 
 impl ClassAscii {
@@ -32,6 +58,76 @@ impl ClassAscii {
             "                                                     ".to_string(),
         ]);
 
-        ClassAscii { hunter }
+        let titan = Vec::from([
+            "                                                     ".to_string(),
+            "                                                     ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%                                   %%       ".to_string(),
+            "       %%                                   %%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@%%       ".to_string(),
+            "          %%@@@@@@@@@@@@@@ @@@@@@@@@@@@@@%%          ".to_string(),
+            "            %%@@@@@@@@@@@@ @@@@@@@@@@@@%%            ".to_string(),
+            "               %%@@@@@@@@@ @@@@@@@@@%%               ".to_string(),
+            "                 %%@@@@@@@ @@@@@@@%%                 ".to_string(),
+            "                   %%@@@@@ @@@@@%%                   ".to_string(),
+            "                      %%@@ @@%%                      ".to_string(),
+            "                        %% %%                        ".to_string(),
+            "                                                     ".to_string(),
+            "                                                     ".to_string(),
+            "                                                     ".to_string(),
+        ]);
+
+        let warlock = Vec::from([
+            "                                                     ".to_string(),
+            "                          %                          ".to_string(),
+            "                         %%%                         ".to_string(),
+            "                        %%@%%                        ".to_string(),
+            "                       %%@@@%%                       ".to_string(),
+            "                      %%@@@@@%%                      ".to_string(),
+            "                     %%@@@@@@@%%                     ".to_string(),
+            "                    %%@@@@@@@@@%%                    ".to_string(),
+            "                   %%@@@@@@@@@@@%%                   ".to_string(),
+            "                  %%@@@@@@@@@@@@@%%                  ".to_string(),
+            "                 %%@@@@@@@@@@@@@@@%%                 ".to_string(),
+            "                %%@@@@@@@@@@@@@@@@@%%                ".to_string(),
+            "               %%                   %%               ".to_string(),
+            "              %%@@                 @@%%              ".to_string(),
+            "             %%@@@@               @@@@%%             ".to_string(),
+            "            %%@@@@@@             @@@@@@%%            ".to_string(),
+            "           %%@@@@@@@@           @@@@@@@@%%           ".to_string(),
+            "          %%@@@@@@@@@@         @@@@@@@@@@%%          ".to_string(),
+            "         %%@@@@@@@@@@@@       @@@@@@@@@@@@%%         ".to_string(),
+            "        %%@@@@@@@@@@@@@@     @@@@@@@@@@@@@@%%        ".to_string(),
+            "       %%@@@@@@@@@@@@@@@@   @@@@@@@@@@@@@@@@%%       ".to_string(),
+            "      %%@@@@@@@@@@@@@@@@@@ @@@@@@@@@@@@@@@@@@%%      ".to_string(),
+            "     %%@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@%%     ".to_string(),
+            "   %%@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@%%   ".to_string(),
+            "                                                     ".to_string(),
+        ]);
+
+        ClassAscii {
+            hunter,
+            titan,
+            warlock,
+        }
+    }
+
+    /// Returns the art for the class, or None if the class is unknown
+    pub fn get(&self, class: &CharacterClass) -> Option<&[String]> {
+        match class {
+            CharacterClass::Hunter => Some(&self.hunter),
+            CharacterClass::Titan => Some(&self.titan),
+            CharacterClass::Warlock => Some(&self.warlock),
+            CharacterClass::Unknown => None,
+        }
     }
 }
@@ -25,18 +25,26 @@ use chrono::{DateTime, Utc};
 mod classascii;
 use classascii::ClassAscii;
 
-use dcli::crucible::{Member, PlayerName};
+use dcli::config::Config;
+use dcli::crucible::{
+    AggregateCruciblePerformances, CruciblePlayerPerformance, Member,
+};
+use dcli::document::{Alignment, Document, Table};
+use dcli::enums::character::{CharacterClass, CharacterClassSelection};
+use dcli::enums::membership::MembershipSelection;
 use dcli::enums::mode::Mode;
 use dcli::enums::moment::{DateTimePeriod, Moment};
-use dcli::playeractivitiessummary::PlayerActivitiesSummary;
+use dcli::error::Error;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::output::Output;
+use dcli::season::Seasons;
+use dcli::timezone::set_timezone;
 use dcli::utils::{
-    calculate_efficiency, calculate_kills_deaths_ratio, format_error,
-    parse_rfc3339,
+    calculate_average, format_error, get_terminal_width, human_date_format,
+    parse_rfc3339, repeat_str, truncate_ascii_string, uppercase_first_char,
 };
 use std::path::PathBuf;
 
-use dcli::enums::character::CharacterClassSelection;
-
 use dcli::activitystoreinterface::ActivityStoreInterface;
 
 use dcli::utils::{
@@ -45,65 +53,198 @@ use dcli::utils::{
 
 use dcli::utils::EXIT_FAILURE;
 use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const LABEL_COL_W: usize = 14;
+const MODE_COL_W: usize = 18;
 
-fn print_header(header: &str) {
-    tell::update!("{:^44}", header);
+//used when the terminal width cannot be determined
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+const DEFAULT_MODES: [Mode; 5] = [
+    Mode::AllPvP,
+    Mode::TrialsOfOsiris,
+    Mode::IronBanner,
+    Mode::Control,
+    Mode::PrivateMatchesAll,
+];
+
+struct ModeSummary {
+    mode: Mode,
+    aggregate: Option<AggregateCruciblePerformances>,
 }
 
-fn print_row(left: &str, center: &str, right: &str) {
-    tell::update!("{:>20}{:^4}{:<20}", left, center, right);
+fn print_centered(s: &str, width: usize) {
+    tell::update!("{:^0width$}", s, width = width);
 }
 
-#[allow(clippy::too_many_arguments)]
-fn _print_default(data: &PlayerActivitiesSummary) {
+fn print_card_header(
+    member: &Member,
+    class: &CharacterClass,
+    moment: &Moment,
+    time_period: &DateTimePeriod,
+    width: usize,
+) {
     let ascii = ClassAscii::init();
 
-    for s in ascii.hunter.iter() {
-        tell::update!("{}", s);
+    //art is only displayed if it fits in the terminal
+    let art_width = match ascii.get(class) {
+        Some(art) if art[0].chars().count() <= width => {
+            for s in art.iter() {
+                tell::update!("{}", s);
+            }
+            art[0].chars().count()
+        }
+        _ => std::cmp::min(width, LABEL_COL_W + MODE_COL_W * 2),
+    };
+
+    print_centered(&member.name.get_bungie_name(), art_width);
+    print_centered(&class.to_string().to_uppercase(), art_width);
+    print_centered(
+        &format!(
+            "{} ({} to {})",
+            moment,
+            human_date_format(&time_period.get_start()),
+            human_date_format(&time_period.get_end())
+        ),
+        art_width,
+    );
+    tell::update!();
+}
+
+fn format_mode_stat(summary: &ModeSummary, f: StatFormatter) -> String {
+    match &summary.aggregate {
+        Some(e) => f(e),
+        None => "-".to_string(),
     }
+}
 
-    //3 columns
+type StatFormatter = fn(&AggregateCruciblePerformances) -> String;
+
+//rows displayed for each mode on the card
+fn card_rows() -> Vec<(&'static str, StatFormatter)> {
+    vec![
+        ("Matches", |e| e.total_activities.to_string()),
+        ("Win %", |e| format!("{}%", format_f32(e.win_rate, 2))),
+        ("KD", |e| format_f32(e.kills_deaths_ratio, 2)),
+        ("Efficiency", |e| format_f32(e.efficiency, 2)),
+        ("Kills / Game", |e| {
+            format_f32(calculate_average(e.kills, e.total_activities), 2)
+        }),
+        ("Top Weapon", |e| {
+            e.extended
+                .as_ref()
+                .and_then(|x| x.weapons.first())
+                .map(|w| w.weapon.name.to_string())
+                .unwrap_or_default()
+        }),
+        ("Win Streak", |e| e.longest_win_streak.to_string()),
+        ("Loss Streak", |e| e.longest_loss_streak.to_string()),
+    ]
+}
 
-    //tell::update!("{:>20}{:^14}{:<20}", "left", "center", "right");
+fn print_row(label: &str, summaries: &[ModeSummary], f: StatFormatter) {
+    let mut line = format!("{:<0col_w$}", label, col_w = LABEL_COL_W);
+    for s in summaries.iter() {
+        line.push_str(&format!(
+            "{:>0col_w$}",
+            truncate_ascii_string(&format_mode_stat(s, f), MODE_COL_W - 2),
+            col_w = MODE_COL_W
+        ));
+    }
 
-    print_row("left", "center", "right");
+    tell::update!("{}", line);
+}
 
-    //mode
-    //moment
-    //win %
-    //kd
-    //eff
+fn print_mode_table(summaries: &[ModeSummary]) {
+    let mut header = format!("{:<0col_w$}", "", col_w = LABEL_COL_W);
+    for s in summaries.iter() {
+        header.push_str(&format!(
+            "{:>0col_w$}",
+            truncate_ascii_string(
+                &s.mode.to_string().to_uppercase(),
+                MODE_COL_W - 2
+            ),
+            col_w = MODE_COL_W
+        ));
+    }
+
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    for (label, f) in card_rows() {
+        print_row(label, summaries, f);
+    }
 }
 
-fn print_mode_data(
-    mode: &Mode,
+//the card as a document, with all modes in a single table since the
+//width is not limited by the terminal
+fn print_document(
+    member: &Member,
+    class: &CharacterClass,
     moment: &Moment,
-    data: &PlayerActivitiesSummary,
+    time_period: &DateTimePeriod,
+    summaries: &[ModeSummary],
+    output: Output,
 ) {
-    print_header(&mode.to_string());
-    print_row("Moment", "", &moment.to_string());
-    print_row("Win %", "", "");
-    print_row(
-        "KD",
-        "",
-        &format_f32(calculate_kills_deaths_ratio(data.kills, data.deaths), 2),
-    );
-    print_row(
-        "Efficiency",
-        "",
-        &format_f32(
-            calculate_efficiency(data.kills, data.deaths, data.assists),
-            2,
-        ),
+    let mut doc = Document::new();
+    doc.add_heading(
+        2,
+        &format!("{} : {}", member.name.get_bungie_name(), class),
     );
+    doc.add_paragraph(&format!(
+        "{} ({} to {})",
+        uppercase_first_char(&moment.to_string()),
+        human_date_format(&time_period.get_start()),
+        human_date_format(&time_period.get_end())
+    ));
+
+    let mode_names: Vec<String> =
+        summaries.iter().map(|s| s.mode.to_string()).collect();
+    let mut columns = vec![("", Alignment::Left)];
+    columns.extend(mode_names.iter().map(|m| (m.as_str(), Alignment::Right)));
+
+    let mut table = Table::new(&columns);
+    for (label, f) in card_rows() {
+        let mut row = vec![label.to_string()];
+        row.extend(summaries.iter().map(|s| format_mode_stat(s, f)));
+        table.add_row(row);
+    }
+    doc.add_table(table);
+
+    tell::update!("{}", doc.render(output));
+}
+
+fn print_default(
+    member: &Member,
+    class: &CharacterClass,
+    moment: &Moment,
+    time_period: &DateTimePeriod,
+    summaries: &[ModeSummary],
+) {
+    let width = get_terminal_width(DEFAULT_TERMINAL_WIDTH);
+
+    print_card_header(member, class, moment, time_period, width);
+
+    //split modes across multiple tables if they dont fit across the terminal
+    let columns =
+        std::cmp::max(1, width.saturating_sub(LABEL_COL_W) / MODE_COL_W);
+
+    for chunk in summaries.chunks(columns) {
+        print_mode_table(chunk);
+        tell::update!();
+    }
 }
 
 #[derive(StructOpt, Debug)]
 #[structopt(verbatim_doc_comment)]
-/// Command line tool for retrieving and viewing Destiny 2 Crucible activity history.
+/// Command line tool for viewing a Destiny 2 Crucible player card.
 ///
-/// Enables control of which stats are displayed based on game mode, moment range
-/// from which to retrieve them and character.
+/// Displays art for the player's last active class, along with a summary of
+/// stats across multiple Crucible modes for the specified moment, including
+/// win %, KD, efficiency, kills per game, top weapon and longest win and loss
+/// streaks. Modes are split across multiple tables if they do not fit within
+/// the width of the terminal.
 ///
 /// Created by Mike Chambers.
 /// https://www.mikechambers.com
@@ -121,8 +262,11 @@ struct Opt {
     /// Name must be in the format of NAME#CODE. Example: foo#3280
     /// You can find your name in game, or on Bungie's site at:
     /// https://www.bungie.net/7/en/User/Account/IdentitySettings
-    #[structopt(long = "name", short = "n", required = true)]
-    name: PlayerName,
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
 
     /// Custom start time in RFC 3339 date / time format
     ///
@@ -154,20 +298,33 @@ struct Opt {
     /// Valid values include daily (last daily reset), weekend
     /// (last weekend reset on Friday), weekly (last weekly reset on Tuesday),
     /// day (last day), week (last week), month (last month), all_time and custom
-    /// as well as the following season moments launch, curse_of_osiris, warmind,
-    /// season_of_the_outlaw, season_of_the_forge, season_of_the_drifter,
-    /// season_of_opulence, season_of_the_undying, season_of_dawn,
-    /// season_of_the_worthy, season_of_arrivals, season_of_the_hunt,
-    /// season_of_the_chosen, season_of_the_splicer, season_of_the_lost, season_of_the_risen,
-    /// witch_queen, season_of_the_haunted, season_of_plunder, season_of_the_seraph, lightfall, season_of_defiance, season_of_the_deep, season_of_the_witch, season_of_the_wish.
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season). Season dates are loaded
+    /// from the manifest, and can be updated via a seasons.json file in the
+    /// data directory.
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 12h, 2w, today, yesterday, this-week, last-week,
+    /// this-month, last-month, this-year, last-year, since-last-reset,
+    /// since-weekly-reset, months (2023-05), ISO weeks (2023-W14), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When a season, expansion or calendar period (such as 2023-05) is
+    /// specified without --end-moment, only activities within that period
+    /// are returned.
     ///
     /// When custom is specified, the custom start date in RFC3339 format must
     /// be specified with the --custom-time argument.
     ///
     /// For example:
     /// --moment custom --custom-time 2020-12-08T17:00:00.774187+00:00
-    #[structopt(long = "moment", short = "T", default_value = "week")]
-    moment: Moment,
+    ///
+    /// Defaults to week, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
 
     /// End moment from which to pull activities from
     ///
@@ -181,24 +338,38 @@ struct Opt {
     /// Valid values include daily (last daily reset), weekend
     /// (last weekend reset on Friday), weekly (last weekly reset on Tuesday),
     /// day (last day), week (last week), month (last month), all_time and custom
-    /// as well as the following season moments launch, curse_of_osiris, warmind,
-    /// season_of_the_outlaw, season_of_the_forge, season_of_the_drifter,
-    /// season_of_opulence, season_of_the_undying, season_of_dawn,
-    /// season_of_the_worthy, season_of_arrivals, season_of_the_hunt,
-    /// season_of_the_chosen, season_of_the_splicer, season_of_the_lost, season_of_the_risen,
-    /// witch_queen, season_of_the_haunted, season_of_plunder, season_of_the_seraph, lightfall, season_of_defiance, season_of_the_deep, season_of_the_witch, season_of_the_wish.
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season). Season dates are loaded
+    /// from the manifest, and can be updated via a seasons.json file in the
+    /// data directory.
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 12h, 2w, today, yesterday, this-week, last-week,
+    /// this-month, last-month, this-year, last-year, since-last-reset,
+    /// since-weekly-reset, months (2023-05), ISO weeks (2023-W14), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
     ///
     /// When custom is specified, the custom start date in RFC3339 format must
     /// be specified with the --end-custom-time argument.
     ///
     /// For example:
     /// --moment custom --end-custom-time 2020-12-08T17:00:00.774187+00:00
-    #[structopt(long = "end-moment", short = "E", default_value = "now")]
-    end_moment: Moment,
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
 
-    /// Activity mode to return stats for
+    /// Activity modes to display stats for
+    ///
+    /// Can be specified multiple times to display multiple modes, for
+    /// example: --mode trials_of_osiris --mode control
+    ///
     ///
-    /// Supported values are all_pvp (default), control, clash, elimination,
+    /// Supported values are all_pvp, control, clash, elimination,
     /// mayhem, iron_banner, all_private, rumble, pvp_competitive,
     /// quickplay and trials_of_osiris.
     ///
@@ -208,18 +379,59 @@ struct Opt {
     /// rift_competitive, showdown, lockdown, iron_banner_rift,
     /// zone_control, iron_banner_zone_control, rift,
     /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
-    #[structopt(long = "mode", short = "M", 
-        parse(try_from_str=parse_and_validate_crucible_mode), default_value = "all_pvp")]
-    mode: Mode,
+    ///
+    /// Defaults to all_pvp, trials_of_osiris, iron_banner, control and
+    /// all_private, unless mode is set in the dcli.toml config file.
+    #[structopt(long = "mode", short = "M",
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    modes: Vec<Mode>,
 
     /// Character to retrieve data for
     ///
     /// Valid values include hunter, titan, warlock, last_active and all.
-    #[structopt(short = "C", long = "class", default_value = "all")]
-    character_class_selection: CharacterClassSelection,
+    ///
+    /// Art for the last active class is displayed unless a specific class
+    /// is selected.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
 
-    ///Print out additional information
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
 
+    /// Format for command output
+    ///
+    /// Valid values are default (Default), markdown and html.
+    ///
+    /// markdown renders the card as a Markdown table with a column for each
+    /// mode, which can be pasted into Discord or GitHub. html renders the
+    /// card as an HTML fragment with inline styles, which can be included in
+    /// an email. Class art is only included in the default output.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
     #[structopt(short = "v", long = "verbose")]
     verbose: bool,
 
@@ -245,57 +457,123 @@ struct Opt {
 #[tokio::main]
 async fn main() {
     let opt = Opt::from_args();
-    tell::error!("{:#?}", opt);
 
-    let data_dir = match determine_data_dir(opt.data_dir) {
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
         Ok(e) => e,
         Err(e) => {
-            tell::error!(
-                "{}",
-                format_error("Error initializing data directory.", e)
-            );
+            tell::error!("{}", format_error("Could not load config file.", e));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    let start_time = match opt.moment {
-        Moment::Custom => {
-            opt.custom_time.unwrap() //note, this should be ok, because struct opt should ensure valid value
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
         }
-        _ => opt.moment.get_date_time(),
     };
 
-    let end_time = match opt.end_moment {
-        Moment::Custom => {
-            opt.end_custom_time.unwrap() //note, this should be ok, because struct opt should ensure valid value
+    let moment = match config.moment(opt.moment, "week") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
         }
-        _ => opt.end_moment.get_date_time(),
     };
 
-    let time_period =
-        match DateTimePeriod::with_start_end_time(start_time, end_time) {
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
             Ok(e) => e,
-            Err(_e) => {
-                tell::error!("--end-moment must be greater than --moment");
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
                 std::process::exit(EXIT_FAILURE);
             }
         };
 
-    let mut store =
-        match ActivityStoreInterface::init_with_path(&data_dir, opt.api_key)
-            .await
-        {
+    let output =
+        match config.output_supported(opt.output, "default", &Output::REPORT) {
             Ok(e) => e,
             Err(e) => {
-                tell::error!("{}", format_error(
-                "Could not initialize activity store. Have you run dclisync?",
-                e,
-            ));
+                tell::error!("{}", format_error("Invalid output format.", e));
                 std::process::exit(EXIT_FAILURE);
             }
         };
 
-    let member: Member = match store.find_member(&opt.name, true).await {
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    //note, custom times should be ok, because struct opt ensures they are
+    //set when the moments are custom
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
@@ -316,33 +594,117 @@ async fn main() {
         };
     }
 
-    let data = match store
-        .retrieve_activities_summary(
-            &member,
-            &opt.character_class_selection,
-            &opt.mode,
-            &time_period,
-        )
-        .await
-    {
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
                 "{}",
-                format_error("Could not retrieve data from activity store.", e)
+                format_error("Could not find selected membership.", e)
             );
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    if data.is_none() {
-        tell::update!("No data found");
-        return;
-    }
+    let class = match character_class_selection {
+        CharacterClassSelection::Titan => CharacterClass::Titan,
+        CharacterClassSelection::Hunter => CharacterClass::Hunter,
+        CharacterClassSelection::Warlock => CharacterClass::Warlock,
+        _ => match store.retrieve_last_active_class(&member).await {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Could not retrieve last active class.", e)
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        },
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
 
-    let data: PlayerActivitiesSummary = data.unwrap();
+    //a mode set in the config file replaces the default modes
+    let modes = if !opt.modes.is_empty() {
+        opt.modes
+    } else if config.mode.is_some() {
+        match config.mode(None, "all_pvp", parse_and_validate_crucible_mode) {
+            Ok(e) => vec![e],
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid mode.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+    } else {
+        DEFAULT_MODES.to_vec()
+    };
+
+    let mut summaries: Vec<ModeSummary> = Vec::with_capacity(modes.len());
+    for mode in modes.into_iter() {
+        let performances = match store
+            .retrieve_activities_since(
+                &member,
+                &character_class_selection,
+                &mode,
+                &time_period,
+                &mut manifest,
+            )
+            .await
+        {
+            Ok(e) => e.unwrap_or_default(),
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error(
+                        "Could not retrieve data from activity store.",
+                        e
+                    )
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
 
-    //print_default(&data);
+        let aggregate = if performances.is_empty() {
+            None
+        } else {
+            let cpp: Vec<&CruciblePlayerPerformance> =
+                performances.iter().map(|x| &x.performance).collect();
+            Some(AggregateCruciblePerformances::with_performances(&cpp))
+        };
+
+        summaries.push(ModeSummary { mode, aggregate });
+    }
 
-    print_mode_data(&opt.mode, &opt.moment, &data);
+    match output {
+        Output::Markdown | Output::Html => print_document(
+            &member,
+            &class,
+            &moment,
+            &time_period,
+            &summaries,
+            output,
+        ),
+        Output::Default | Output::Tsv | Output::Jsonl | Output::Json => {
+            print_default(&member, &class, &moment, &time_period, &summaries)
+        }
+    }
 }
@@ -26,11 +26,16 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use dcli::apiclient::ApiClient;
+use dcli::config::Config;
 use dcli::error::Error;
-use dcli::manifestinterface::MANIFEST_FILE_NAME;
+use dcli::manifesthistory::{ManifestHistory, ManifestVersion};
+use dcli::manifestinterface::{ManifestInterface, MANIFEST_FILE_NAME};
 use dcli::output::Output;
 use dcli::response::manifest::ManifestResponse;
-use dcli::utils::{build_tsv, determine_data_dir};
+use dcli::timezone::{set_timezone, to_local};
+use dcli::utils::{
+    build_tsv, determine_data_dir, repeat_str, TSV_DELIM, TSV_EOL,
+};
 use dcli::utils::{format_error, EXIT_FAILURE};
 use manifest_info::ManifestInfo;
 use structopt::StructOpt;
@@ -39,7 +44,7 @@ use tokio::io::AsyncWriteExt;
 
 pub const MANIFEST_INFO_FILE_NAME: &str = "manifest_info.json";
 
-async fn retrieve_manifest_info() -> Result<ManifestInfo, Error> {
+async fn retrieve_manifest_info(locale: &str) -> Result<ManifestInfo, Error> {
     let client: ApiClient = ApiClient::new()?;
     let url = "https://www.bungie.net/Platform/Destiny2/Manifest/";
 
@@ -50,7 +55,7 @@ async fn retrieve_manifest_info() -> Result<ManifestInfo, Error> {
         None => return Err(Error::ApiResponseMissing), //we should never get here as this will be caught earlier
     };
 
-    let m_info: ManifestInfo = ManifestInfo::from_manifest(manifest);
+    let m_info: ManifestInfo = ManifestInfo::from_manifest(manifest, locale)?;
 
     Ok(m_info)
 }
@@ -75,39 +80,247 @@ fn load_manifest_info(path: &Path) -> Result<ManifestInfo, Error> {
     Ok(m)
 }
 
-//should this move to ApiClient?
+//downloads the zipped manifest to a temp file, then extracts and verifies it
+//before moving it into place. If anything fails, the temp files are removed,
+//and any existing file at path is left untouched.
 async fn download_manifest(url: &str, path: &Path) -> Result<(), Error> {
+    let zip_path = path.with_extension("zip.part");
+    let db_path = path.with_extension("sqlite3.part");
+
+    let mut result = download_to_file(url, &zip_path).await;
+
+    if result.is_ok() {
+        tell::verbose!("Extracting manifest.");
+        result = extract_manifest(&zip_path, &db_path);
+    }
+
+    if result.is_ok() {
+        tell::verbose!("Verifying manifest.");
+        result = verify_manifest(&db_path).await;
+    }
+
+    if result.is_ok() {
+        //rename is atomic when on the same file system, so other apps will
+        //never see a partially written manifest
+        result = fs::rename(&db_path, path).map_err(|e| e.into());
+    }
+
+    let _ = fs::remove_file(&zip_path);
+
+    if result.is_err() {
+        let _ = fs::remove_file(&db_path);
+    }
+
+    result
+}
+
+//streams the download to the file, rather than buffering it in memory
+async fn download_to_file(url: &str, path: &Path) -> Result<(), Error> {
     let client: ApiClient = ApiClient::new()?;
 
     //Download the manifest
     let mut response = client.call(url).await?;
+    let total = response.content_length();
 
-    //create a Vector to store the bytes for the download
-    let mut out: Vec<u8> = Vec::new();
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut downloaded: u64 = 0;
+    let mut last_percent: u64 = 0;
 
-    //read of the bytes into the vector
     while let Some(chunk) = response.chunk().await? {
-        out.write_all(&chunk).await?;
+        file.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+
+        if let Some(t) = total {
+            let percent = downloaded * 100 / t.max(1);
+
+            if percent >= last_percent + 10 {
+                last_percent = percent - percent % 10;
+                tell::progress!(
+                    "Downloaded {} of {} ({}%)",
+                    format_mb(downloaded),
+                    format_mb(t),
+                    last_percent
+                );
+            }
+        }
+    }
+
+    file.flush().await?;
+
+    if let Some(t) = total {
+        if downloaded != t {
+            return Err(Error::ManifestVerification {
+                description: format!(
+                    "Download incomplete. Expected {} bytes, received {}.",
+                    t, downloaded
+                ),
+            });
+        }
     }
 
-    //create a cursor so we can move through the bytes
-    let cursor = std::io::Cursor::new(out);
+    Ok(())
+}
 
-    //create a new zip archive from the bytes (since the download is compressed)
-    let mut zip = zip::ZipArchive::new(cursor)?;
+fn extract_manifest(zip_path: &Path, path: &Path) -> Result<(), Error> {
+    let file = fs::File::open(zip_path)?;
+    let mut zip = zip::ZipArchive::new(file)?;
+
+    //there should only be one file in the zip
+    if zip.len() != 1 {
+        return Err(Error::ManifestVerification {
+            description: format!(
+                "Expected 1 file in manifest archive, found {}.",
+                zip.len()
+            ),
+        });
+    }
 
-    //get a reference to the first file in the zip (there should only be one)
     let mut manifest = zip.by_index(0)?;
+    let expected = manifest.size();
 
-    //reference to file we are going to write the ucompressed manifest to
     let mut outfile = fs::File::create(path)?;
 
-    //save the uncompressed / unzipped manifest to the file system
-    std::io::copy(&mut manifest, &mut outfile)?;
+    //zip checks the crc32 checksum once all of the data has been read, and
+    //returns an error if it doesnt match
+    let written = std::io::copy(&mut manifest, &mut outfile)?;
+    outfile.sync_all()?;
+
+    if written != expected {
+        return Err(Error::ManifestVerification {
+            description: format!(
+                "Expected {} bytes from archive, extracted {}.",
+                expected, written
+            ),
+        });
+    }
 
     Ok(())
 }
 
+async fn verify_manifest(path: &Path) -> Result<(), Error> {
+    let mut manifest = ManifestInterface::with_path(path, false).await?;
+    let result = manifest.verify().await;
+    manifest.close().await?;
+
+    for table in result?.iter() {
+        tell::update!(
+            "Warning : Manifest does not contain the {} table. Some data may not be available.",
+            table
+        );
+    }
+
+    Ok(())
+}
+
+fn format_mb(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / 1_048_576.0)
+}
+
+//copies the stored version to manifest.sqlite3 so it is used by all of the apps
+fn activate_manifest(
+    data_dir: &Path,
+    history: &mut ManifestHistory,
+    version: &ManifestVersion,
+) -> Result<(), Error> {
+    let path = ManifestHistory::get_path(data_dir, version);
+
+    if !path.exists() {
+        return Err(Error::IoFileDoesNotExist {
+            description: format!("{}", path.display()),
+        });
+    }
+
+    //copy to a temp file first, so the active manifest is only replaced once
+    //the copy is complete
+    let m_path = data_dir.join(MANIFEST_FILE_NAME);
+    let tmp_path = m_path.with_extension("sqlite3.part");
+
+    if let Err(e) = fs::copy(&path, &tmp_path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    fs::rename(&tmp_path, &m_path)?;
+
+    save_manifest_info(
+        &ManifestInfo::from_version(version),
+        &data_dir.join(MANIFEST_INFO_FILE_NAME),
+    )?;
+
+    history.active = Some(version.file_name.clone());
+
+    Ok(())
+}
+
+fn print_history(history: &ManifestHistory, output: Output) {
+    let mut versions: Vec<&ManifestVersion> = history.versions.iter().collect();
+    versions.sort_by_key(|e| std::cmp::Reverse(e.get_release_date()));
+
+    let get_status = |v: &ManifestVersion| -> &str {
+        if Some(&v.file_name) == history.pinned.as_ref() {
+            "pinned"
+        } else if Some(&v.file_name) == history.active.as_ref() {
+            "active"
+        } else {
+            ""
+        }
+    };
+
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => {
+            if versions.is_empty() {
+                tell::update!("No manifest versions stored.");
+                return;
+            }
+
+            let version_w =
+                versions.iter().map(|e| e.version.len()).max().unwrap_or(0) + 2;
+
+            let header = format!(
+                "{:<0version_w$}{:<8}{:<14}{}",
+                "VERSION",
+                "LOCALE",
+                "RELEASED",
+                "STATUS",
+                version_w = version_w
+            );
+            tell::update!("{}", header);
+            tell::update!("{}", repeat_str("-", header.len() + 2));
+
+            for v in versions.iter() {
+                tell::update!(
+                    "{:<0version_w$}{:<8}{:<14}{}",
+                    v.version,
+                    v.locale,
+                    to_local(&v.get_release_date())
+                        .format("%Y-%m-%d")
+                        .to_string(),
+                    get_status(v),
+                    version_w = version_w
+                );
+            }
+        }
+        Output::Tsv => {
+            for v in versions.iter() {
+                tell::update!(
+                    "{}{}{}{}{}{}{}{}",
+                    v.version,
+                    TSV_DELIM,
+                    v.locale,
+                    TSV_DELIM,
+                    to_local(&v.get_release_date()).to_rfc3339(),
+                    TSV_DELIM,
+                    get_status(v),
+                    TSV_EOL
+                );
+            }
+        }
+    }
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(verbatim_doc_comment)]
 /// Command line tool for retrieving and managing the Destiny 2 manifest database.
@@ -116,6 +329,9 @@ async fn download_manifest(url: &str, path: &Path) -> Result<(), Error> {
 /// manifest.sqlite3, along with meta-data with information about the downloaded
 /// version. This is used to to determine whether the remote version has been updated.
 ///
+/// Previously downloaded versions are kept in the manifests directory, and can
+/// be listed, pinned and rolled back to.
+///
 /// Created by Mike Chambers.
 /// https://www.mikechambers.com
 ///
@@ -135,6 +351,16 @@ struct Opt {
     #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
     data_dir: Option<PathBuf>,
 
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
     ///Print out additional information
 
     #[structopt(short = "v", long = "verbose")]
@@ -148,18 +374,68 @@ struct Opt {
     #[structopt(short = "K", long = "check")]
     check: bool,
 
+    /// Locale of the manifest to download
+    ///
+    /// Determines the language used for item, activity and map names.
+    ///
+    /// Valid values include en, fr, es, es-mx, de, it, ja, pt-br, ru, pl, ko,
+    /// zh-cht and zh-chs.
+    ///
+    /// Defaults to en, unless locale is set in the dcli.toml config file.
+    #[structopt(short = "l", long = "locale")]
+    locale: Option<String>,
+
+    /// Number of manifest versions to keep stored locally for each locale
+    ///
+    /// Active and pinned versions are always kept.
+    #[structopt(short = "N", long = "keep", default_value = "3")]
+    keep: usize,
+
+    /// List locally stored manifest versions
+    #[structopt(
+        short = "L",
+        long = "list",
+        conflicts_with_all = &["force", "check", "pin", "unpin", "rollback"]
+    )]
+    list: bool,
+
+    /// Make the specified stored manifest version active, and stop
+    /// automatically updating to new versions
+    ///
+    /// Stored versions can be listed with --list.
+    #[structopt(
+        short = "P",
+        long = "pin",
+        conflicts_with_all = &["force", "check", "unpin", "rollback"]
+    )]
+    pin: Option<String>,
+
+    /// Remove pin, and resume updating to new manifest versions
+    #[structopt(
+        short = "U",
+        long = "unpin",
+        conflicts_with_all = &["force", "check", "rollback"]
+    )]
+    unpin: bool,
+
+    /// Make the previously stored manifest version active, and pin it
+    #[structopt(
+        short = "R",
+        long = "rollback",
+        conflicts_with_all = &["force", "check"]
+    )]
+    rollback: bool,
+
     /// Format for command output
     ///
     /// Valid values are default (Default) and tsv.
     ///
     /// tsv outputs in a tab (\t) separated format of name / value pairs with lines
     /// ending in a new line character (\n).
-    #[structopt(
-        short = "O",
-        long = "output-format",
-        default_value = "default"
-    )]
-    output: Output,
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
 }
 #[tokio::main]
 async fn main() {
@@ -175,7 +451,33 @@ async fn main() {
     tell::verbose!("{:#?}", opt);
     log::info!("{:#?}", opt);
 
-    let data_dir = match determine_data_dir(opt.data_dir) {
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::TERMINAL)
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
@@ -188,8 +490,81 @@ async fn main() {
 
     let m_path = data_dir.join(MANIFEST_FILE_NAME);
     let m_info_path = data_dir.join(MANIFEST_INFO_FILE_NAME);
+    let locale = config.locale(opt.locale);
 
-    let remote_manifest_info = match retrieve_manifest_info().await {
+    let mut history = match ManifestHistory::load(&data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not load manifest history.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if opt.list {
+        print_history(&history, output);
+        return;
+    }
+
+    if opt.pin.is_some() || opt.rollback {
+        let version = if opt.rollback {
+            history.find_previous()
+        } else {
+            history.find(opt.pin.as_ref().unwrap(), &locale)
+        };
+
+        let version = match version {
+            Some(e) => e.clone(),
+            None => {
+                tell::error!("Could not find stored manifest version. Run with --list to view stored versions.");
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+        if let Err(e) = activate_manifest(&data_dir, &mut history, &version) {
+            tell::error!(
+                "{}",
+                format_error("Could not activate manifest version.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+
+        history.pinned = Some(version.file_name.clone());
+
+        if let Err(e) = history.save(&data_dir) {
+            tell::error!(
+                "{}",
+                format_error("Could not save manifest history.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+
+        tell::update!(
+            "Manifest pinned to version {} ({})",
+            version.version,
+            version.locale
+        );
+        return;
+    }
+
+    if opt.unpin {
+        history.pinned = None;
+
+        if let Err(e) = history.save(&data_dir) {
+            tell::error!(
+                "{}",
+                format_error("Could not save manifest history.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+
+        tell::update!("Manifest unpinned.");
+        return;
+    }
+
+    let remote_manifest_info = match retrieve_manifest_info(&locale).await {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
@@ -204,7 +579,7 @@ async fn main() {
     };
 
     let col_w: usize = 30;
-    if opt.output == Output::Default {
+    if output == Output::Default {
         tell::update!(
             "{:<0col_w$}{}",
             "Remote Manifest version",
@@ -225,7 +600,7 @@ async fn main() {
         if let Ok(e) = load_manifest_info(&m_info_path) {
             let local_manifest_info: ManifestInfo = e;
 
-            if opt.output == Output::Default {
+            if output == Output::Default {
                 tell::update!(
                     "{:<0col_w$}{}",
                     "Local Manifest version",
@@ -252,7 +627,22 @@ async fn main() {
         }
     }
 
-    if manifest_needs_updating && opt.output == Output::Default {
+    if let Some(e) = history.get_pinned() {
+        if !opt.force {
+            manifest_needs_updating = false;
+
+            if output == Output::Default {
+                tell::update!(
+                    "{:<0col_w$}{}",
+                    "Manifest pinned to version",
+                    e.version,
+                    col_w = col_w
+                );
+            }
+        }
+    }
+
+    if manifest_needs_updating && output == Output::Default {
         tell::update!(
             "{:<0col_w$}{}",
             "Updated manifest available",
@@ -262,8 +652,12 @@ async fn main() {
     }
 
     if opt.check {
-        match opt.output {
-            Output::Default => {
+        match output {
+            Output::Default
+            | Output::Markdown
+            | Output::Html
+            | Output::Jsonl
+            | Output::Json => {
                 if !manifest_needs_updating {
                     tell::update!("No new manifest available.");
                 }
@@ -285,23 +679,50 @@ async fn main() {
     }
 
     if opt.force || manifest_needs_updating {
-        //print to stderr so user can redirect other output (such as tsv) to stdout
-        tell::update!("Downloading manifest. This may take a bit of time.");
-        match download_manifest(&remote_manifest_info.url, &m_path).await {
-            Ok(e) => e,
-            Err(e) => {
+        let version = ManifestVersion::new(
+            &remote_manifest_info.version,
+            &locale,
+            &remote_manifest_info.url,
+        );
+        let v_path = ManifestHistory::get_path(&data_dir, &version);
+
+        //only download if we dont already have the version stored (i.e.
+        //if switching back to a previously downloaded locale)
+        if opt.force || !v_path.exists() {
+            if let Err(e) =
+                fs::create_dir_all(ManifestHistory::get_dir(&data_dir))
+            {
                 tell::error!(
                     "{}",
-                    format_error("Could not download and save manifest", e)
+                    format_error(
+                        "Could not create manifest directory",
+                        e.into()
+                    )
                 );
                 std::process::exit(EXIT_FAILURE);
             }
-        };
 
-        tell::verbose!("Download and save complete.");
+            //print to stderr so user can redirect other output (such as tsv) to stdout
+            tell::update!("Downloading manifest. This may take a bit of time.");
+            match download_manifest(&remote_manifest_info.url, &v_path).await {
+                Ok(e) => e,
+                Err(e) => {
+                    tell::error!(
+                        "{}",
+                        format_error("Could not download and save manifest", e)
+                    );
+                    std::process::exit(EXIT_FAILURE);
+                }
+            };
+
+            tell::verbose!("Download and save complete.");
+        } else {
+            tell::verbose!("Using stored manifest version.");
+        }
+
         tell::verbose!("Saving manifest info.");
 
-        match save_manifest_info(&remote_manifest_info, &m_info_path) {
+        match activate_manifest(&data_dir, &mut history, &version) {
             Ok(e) => e,
             Err(e) => {
                 tell::error!("{}", format_error("Could not save manifest.", e));
@@ -309,15 +730,44 @@ async fn main() {
             }
         }
 
-        if opt.output == Output::Default {
+        history.add(version);
+        history.pinned = None;
+
+        match history.prune(&data_dir, opt.keep.max(1)) {
+            Ok(e) => {
+                for v in e.iter() {
+                    tell::verbose!("Removed manifest version {}", v.version);
+                }
+            }
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Could not remove old manifest versions.", e)
+                );
+            }
+        }
+
+        if let Err(e) = history.save(&data_dir) {
+            tell::error!(
+                "{}",
+                format_error("Could not save manifest history.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+
+        if output == Output::Default {
             tell::update!("Manifest info saved.");
         }
-    } else if opt.output == Output::Default {
+    } else if output == Output::Default {
         tell::update!("No new manifest available");
     }
 
-    match opt.output {
-        Output::Default => {
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => {
             tell::update!("{}", m_path.display());
         }
         Output::Tsv => {
@@ -327,6 +777,7 @@ async fn main() {
                 .push(("updated", format!("{}", manifest_needs_updating)));
             name_values.push(("version", remote_manifest_info.version));
             name_values.push(("url", remote_manifest_info.url));
+            name_values.push(("locale", remote_manifest_info.locale));
 
             tell::update!("{}", build_tsv(name_values));
         }
@@ -21,6 +21,7 @@
 */
 
 use dcli::error::Error;
+use dcli::manifesthistory::{ManifestVersion, DEFAULT_MANIFEST_LOCALE};
 use dcli::response::manifest::ManifestData;
 use serde_derive::{Deserialize, Serialize};
 
@@ -28,13 +29,53 @@ use serde_derive::{Deserialize, Serialize};
 pub struct ManifestInfo {
     pub version: String,
     pub url: String,
+
+    //older info files wont have a locale, but were always en
+    #[serde(default = "default_locale")]
+    pub locale: String,
+}
+
+fn default_locale() -> String {
+    DEFAULT_MANIFEST_LOCALE.to_string()
 }
 
 impl ManifestInfo {
-    pub fn from_manifest(manifest: &ManifestData) -> ManifestInfo {
-        ManifestInfo {
+    pub fn from_manifest(
+        manifest: &ManifestData,
+        locale: &str,
+    ) -> Result<ManifestInfo, Error> {
+        let url = match manifest.mobile_world_content_paths.get(locale) {
+            Some(e) => e,
+            None => {
+                let mut locales: Vec<&String> =
+                    manifest.mobile_world_content_paths.keys().collect();
+                locales.sort();
+
+                let locales: Vec<&str> =
+                    locales.iter().map(|e| e.as_str()).collect();
+
+                return Err(Error::InvalidArgument {
+                    description: format!(
+                        "Unsupported manifest locale : {}. Valid values include {}.",
+                        locale,
+                        locales.join(", ")
+                    ),
+                });
+            }
+        };
+
+        Ok(ManifestInfo {
             version: String::from(&manifest.version),
-            url: String::from(&manifest.mobile_world_content_paths.en),
+            url: String::from(url),
+            locale: locale.to_string(),
+        })
+    }
+
+    pub fn from_version(version: &ManifestVersion) -> ManifestInfo {
+        ManifestInfo {
+            version: version.version.clone(),
+            url: version.url.clone(),
+            locale: version.locale.clone(),
         }
     }
 
@@ -0,0 +1,630 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::error::Error;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::medalsummary::{MedalRate, MedalSummary};
+use dcli::output::Output;
+use dcli::season::Seasons;
+use dcli::timezone::{set_timezone, to_local};
+use dcli::utils::{
+    determine_data_dir, format_error, format_f32,
+    parse_and_validate_crucible_mode, parse_rfc3339, repeat_str,
+    truncate_ascii_string, EXIT_FAILURE, TSV_DELIM,
+};
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+fn format_date(date: &DateTime<Utc>) -> String {
+    to_local(date).format("%Y-%m-%d").to_string()
+}
+
+fn format_delta(value: f32) -> String {
+    format!("{:+.2}", value)
+}
+
+fn print_default(
+    member: &Member,
+    mode: &Mode,
+    summary: &MedalSummary,
+    medals: &[&MedalRate],
+    details: bool,
+) {
+    let col_w = 10;
+    let name_col_w = 28;
+
+    tell::update!(
+        "{} medals for {} ({} games)",
+        mode,
+        member.name.get_bungie_name(),
+        summary.activity_count
+    );
+    tell::update!();
+
+    let header = format!(
+        "{:<0name_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+        "TIER",
+        "MEDALS",
+        "PER GAME",
+        "LOBBY",
+        "+/-",
+        col_w = col_w,
+        name_col_w = name_col_w,
+    );
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    for t in summary.tiers.iter() {
+        tell::update!(
+            "{:<0name_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+            format!("{}", t.tier),
+            t.count.to_string(),
+            format_f32(t.per_game, 2),
+            format_f32(t.lobby_per_game, 2),
+            format_delta(t.per_game - t.lobby_per_game),
+            col_w = col_w,
+            name_col_w = name_col_w,
+        );
+    }
+
+    tell::update!();
+
+    let header = format!(
+        "{:<0name_col_w$}{:<0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>14}{:>14}{:>0col_w$}",
+        "MEDAL",
+        "TIER",
+        "COUNT",
+        "PER GAME",
+        "LOBBY",
+        "+/-",
+        "FIRST",
+        "LAST",
+        "SINCE",
+        col_w = col_w,
+        name_col_w = name_col_w,
+    );
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    for m in medals.iter() {
+        let (first, last, since) = match m.history.as_ref() {
+            Some(e) => (
+                format_date(&e.first_earned),
+                format_date(&e.last_earned),
+                e.activities_since.to_string(),
+            ),
+            None => ("".to_string(), "".to_string(), "".to_string()),
+        };
+
+        tell::update!(
+            "{:<0name_col_w$}{:<0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>14}{:>14}{:>0col_w$}",
+            truncate_ascii_string(&m.medal.name, name_col_w - 2),
+            format!("{}", m.medal.tier),
+            m.count.to_string(),
+            format_f32(m.per_game, 2),
+            format_f32(m.lobby_per_game, 2),
+            format_delta(m.per_game_delta()),
+            first,
+            last,
+            since,
+            col_w = col_w,
+            name_col_w = name_col_w,
+        );
+
+        if details && !m.medal.description.is_empty() {
+            tell::update!("    {}", m.medal.description);
+        }
+    }
+
+    tell::update!();
+    tell::update!("PER GAME - Average number of medals earned per game");
+    tell::update!(
+        "LOBBY - Average earned per game by the other players in your games"
+    );
+    tell::update!("FIRST / LAST - Date first and last earned (all time)");
+    tell::update!("SINCE - Number of games played since last earned");
+}
+
+fn print_tsv(medals: &[&MedalRate]) {
+    for m in medals.iter() {
+        let (first, last, since) = match m.history.as_ref() {
+            Some(e) => (
+                e.first_earned.to_rfc3339(),
+                e.last_earned.to_rfc3339(),
+                e.activities_since.to_string(),
+            ),
+            None => ("".to_string(), "".to_string(), "".to_string()),
+        };
+
+        let values = vec![
+            m.medal.id.to_string(),
+            m.medal.name.to_string(),
+            format!("{}", m.medal.tier),
+            m.count.to_string(),
+            m.per_game.to_string(),
+            m.lobby_per_game.to_string(),
+            first,
+            last,
+            since,
+            m.medal.description.to_string(),
+        ];
+
+        tell::update!("{}", values.join(TSV_DELIM));
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for viewing Destiny 2 Crucible medal stats and rates.
+///
+/// Displays the medals earned per game for each medal and medal tier, and
+/// compares them to the average for the other players in your games. Also
+/// displays when each medal was first and last earned, and the number of
+/// games played since it was last earned, across all stored activities for
+/// the mode.
+///
+/// Medal names, descriptions and tiers are loaded from the manifest.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie name for player
+    ///
+    /// Name must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
+
+    /// Custom start time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "t", long = "custom-time", parse(try_from_str = parse_rfc3339), required_if("moment", "custom"))]
+    custom_time: Option<DateTime<Utc>>,
+
+    /// Custom end time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --end-moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "e", long = "end-custom-time", parse(try_from_str = parse_rfc3339), required_if("end-moment", "custom"))]
+    end_custom_time: Option<DateTime<Utc>>,
+
+    /// Start moment from which to pull activities
+    ///
+    /// Valid values include weekend (last weekend reset on Friday), weekly
+    /// (last weekly reset on Tuesday), day, week, month, all_time and custom
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season).
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 2w, this-month, last-month, months (2023-05), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When custom is specified, the custom start date in RFC3339 format must
+    /// be specified with the --custom-time argument.
+    ///
+    /// Defaults to month, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// End moment from which to pull activities
+    ///
+    /// Accepts the same values as --moment.
+    ///
+    /// When custom is specified, the custom end date in RFC3339 format must
+    /// be specified with the --end-custom-time argument.
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
+
+    /// Activity mode to return stats for
+    ///
+    /// Supported values are all_pvp (default), control, clash, elimination,
+    /// mayhem, iron_banner, all_private, rumble, pvp_competitive,
+    /// quickplay and trials_of_osiris.
+    ///
+    /// Addition values available are crimsom_doubles, supremacy, survival,
+    /// countdown, all_doubles, doubles, private_clash, private_control,
+    /// private_survival, private_rumble, showdown_competitive, survival_competitive,
+    /// rift_competitive, showdown, lockdown, iron_banner_rift,
+    /// zone_control, iron_banner_zone_control, rift,
+    /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
+    #[structopt(long = "mode", short = "M",
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
+
+    /// Character to retrieve data for
+    ///
+    /// Valid values include hunter, titan, warlock, last_active and all.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// Display the description for each medal
+    #[structopt(short = "d", long = "details")]
+    details: bool,
+
+    /// Limit the number of medals displayed
+    ///
+    /// Use 0 to display all medals.
+    #[structopt(long = "limit", short = "L", default_value = "25")]
+    limit: usize,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default) and tsv.
+    ///
+    /// tsv outputs one line per medal, in a tab (\t) separated format with
+    /// lines ending in a new line character (\n). Columns are medal id, name,
+    /// tier, count, per game, lobby per game, first earned, last earned, games
+    /// since last earned and description.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Sync player activities
+    #[structopt(long = "sync", short = "s")]
+    sync: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let moment = match config.moment(opt.moment, "month") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::TERMINAL)
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if opt.sync {
+        match store.sync_member(&member).await {
+            Ok(_e) => (),
+            Err(e) => {
+                tell::error!("Could not sync activity store {}", e);
+                tell::update!("Using existing data");
+            }
+        };
+    }
+
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let totals = match store
+        .retrieve_medal_totals(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+            &mut manifest,
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if totals.activity_count == 0 {
+        tell::update!("No activities found");
+        return;
+    }
+
+    let lobby_totals = match store
+        .retrieve_lobby_medal_totals(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+            &mut manifest,
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let history = match store
+        .retrieve_medal_history(
+            &member,
+            &character_class_selection,
+            &mode,
+            &mut manifest,
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let summary = MedalSummary::with_totals(&totals, &lobby_totals, &history);
+
+    let mut medals: Vec<&MedalRate> = summary.medals.iter().collect();
+    if opt.limit > 0 {
+        medals.truncate(opt.limit);
+    }
+
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => {
+            print_default(&member, &mode, &summary, &medals, opt.details)
+        }
+        Output::Tsv => print_tsv(&medals),
+    }
+}
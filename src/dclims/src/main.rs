@@ -0,0 +1,385 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+mod searchoutput;
+
+use std::path::PathBuf;
+
+use dcli::config::Config;
+use dcli::enums::definitiontable::DefinitionTable;
+use dcli::enums::itemtype::{ItemSubType, ItemType};
+use dcli::manifestinterface::{FindResult, ManifestInterface};
+use dcli::manifestsearch::{SearchQuery, SearchResult};
+use dcli::output::Output;
+use dcli::utils::{
+    determine_data_dir, format_error, repeat_str, truncate_ascii_string,
+    EXIT_FAILURE, TSV_DELIM, TSV_EOL,
+};
+use searchoutput::SearchOutput;
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const DESCRIPTION_MAX_LEN: usize = 50;
+
+fn print_results(results: &[SearchResult], output: SearchOutput) {
+    match output {
+        SearchOutput::Default => {
+            if results.is_empty() {
+                tell::update!("No matching definitions found.");
+                return;
+            }
+
+            let id_w =
+                results.iter().map(|e| e.id.len()).max().unwrap_or(0) + 2;
+            let name_w = results
+                .iter()
+                .map(|e| e.name.chars().count())
+                .max()
+                .unwrap_or(0)
+                + 2;
+            let type_w = results
+                .iter()
+                .map(|e| match &e.item_type_display_name {
+                    Some(e) => e.chars().count(),
+                    None => 0,
+                })
+                .max()
+                .unwrap_or(0)
+                .max(4)
+                + 2;
+
+            let header = format!(
+                "{:<0id_w$}{:<18}{:<0name_w$}{:<0type_w$}{:<0desc_w$}{}",
+                "ID",
+                "TABLE",
+                "NAME",
+                "TYPE",
+                "DESCRIPTION",
+                "ICON",
+                id_w = id_w,
+                name_w = name_w,
+                type_w = type_w,
+                desc_w = DESCRIPTION_MAX_LEN + 2,
+            );
+            tell::update!("{}", header);
+            tell::update!("{}", repeat_str("-", header.len() + 2));
+
+            for r in results.iter() {
+                let description =
+                    r.description.as_deref().unwrap_or("").replace('\n', " ");
+
+                tell::update!(
+                    "{:<0id_w$}{:<18}{:<0name_w$}{:<0type_w$}{:<0desc_w$}{}",
+                    r.id,
+                    r.table.to_string(),
+                    r.name,
+                    r.item_type_display_name.as_deref().unwrap_or(""),
+                    truncate_ascii_string(&description, DESCRIPTION_MAX_LEN),
+                    r.icon_path.as_deref().unwrap_or(""),
+                    id_w = id_w,
+                    name_w = name_w,
+                    type_w = type_w,
+                    desc_w = DESCRIPTION_MAX_LEN + 2,
+                );
+            }
+        }
+        SearchOutput::Tsv => {
+            for r in results.iter() {
+                let description =
+                    r.description.as_deref().unwrap_or("").replace('\n', " ");
+
+                tell::update!(
+                    "{}{}{}{}{}{}{}{}{}{}{}{}",
+                    r.id,
+                    TSV_DELIM,
+                    r.table,
+                    TSV_DELIM,
+                    r.name,
+                    TSV_DELIM,
+                    r.item_type_display_name.as_deref().unwrap_or(""),
+                    TSV_DELIM,
+                    description,
+                    TSV_DELIM,
+                    r.icon_path.as_deref().unwrap_or(""),
+                    TSV_EOL
+                );
+            }
+        }
+        SearchOutput::Json => match serde_json::to_string_pretty(results) {
+            Ok(e) => tell::update!("{}", e),
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Could not serialize results.", e.into())
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        },
+    }
+}
+
+fn print_find_results(hash: u32, results: &[FindResult], output: SearchOutput) {
+    match output {
+        SearchOutput::Default => {
+            if results.is_empty() {
+                tell::update!("No definitions found for hash {}.", hash);
+                return;
+            }
+
+            for r in results.iter() {
+                let d = &r.display_properties;
+                tell::update!("Name        : {}", d.name);
+                tell::update!(
+                    "Description : {}",
+                    d.description.as_deref().unwrap_or("")
+                );
+                tell::update!(
+                    "Icon        : {}",
+                    d.icon_path.as_deref().unwrap_or("")
+                );
+                tell::update!();
+            }
+        }
+        SearchOutput::Tsv => {
+            for r in results.iter() {
+                let d = &r.display_properties;
+                tell::update!(
+                    "{}{}{}{}{}{}{}{}",
+                    hash,
+                    TSV_DELIM,
+                    d.name,
+                    TSV_DELIM,
+                    d.description.as_deref().unwrap_or("").replace('\n', " "),
+                    TSV_DELIM,
+                    d.icon_path.as_deref().unwrap_or(""),
+                    TSV_EOL
+                );
+            }
+        }
+        SearchOutput::Json => {
+            //return the original definitions
+            let raw: Vec<&str> =
+                results.iter().map(|e| e.raw_json.as_str()).collect();
+            tell::update!("[{}]", raw.join(","));
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for searching the Destiny 2 manifest database by name or
+/// hash.
+///
+/// Searches the InventoryItem, Activity, ActivityType, Destination, Place and
+/// HistoricalStats definition tables of the local manifest, which can be
+/// downloaded with dclim.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Name, or part of a name, of the definitions to search for
+    ///
+    /// Search is case insensitive.
+    #[structopt(
+        short = "n",
+        long = "name",
+        required_unless = "hash",
+        conflicts_with = "hash"
+    )]
+    name: Option<String>,
+
+    /// Hash of the definition to retrieve
+    ///
+    /// All tables in the manifest will be searched.
+    #[structopt(short = "H", long = "hash")]
+    hash: Option<u32>,
+
+    /// Also match names which are similar to the specified name
+    ///
+    /// Useful when you are not sure of the spelling of a name.
+    #[structopt(short = "z", long = "fuzzy")]
+    fuzzy: bool,
+
+    /// Definition tables to search
+    ///
+    /// Valid values are inventory_item, activity, activity_type, destination,
+    /// place and historical_stats. Can be specified multiple times. Defaults to
+    /// all tables.
+    #[structopt(short = "t", long = "table")]
+    tables: Vec<DefinitionTable>,
+
+    /// Only return items with the specified item type
+    ///
+    /// Only applies to inventory items. Valid values include weapon, armor,
+    /// emblem, mod, ghost, ship, vehicle, emote, subclass, finisher and
+    /// consumable.
+    #[structopt(short = "i", long = "item-type")]
+    item_type: Option<ItemType>,
+
+    /// Only return items with the specified item sub type
+    ///
+    /// Only applies to inventory items. Valid values include auto_rifle,
+    /// shotgun, machine_gun, hand_cannon, rocket_launcher, fusion_rifle,
+    /// sniper_rifle, pulse_rifle, scout_rifle, sidearm, sword,
+    /// linear_fusion_rifle, grenade_launcher, submachine_gun, trace_rifle, bow,
+    /// glaive, helmet_armor, gauntlets_armor, chest_armor, leg_armor and
+    /// class_armor.
+    #[structopt(short = "s", long = "item-sub-type")]
+    item_sub_type: Option<ItemSubType>,
+
+    /// Maximum number of results to display
+    #[structopt(short = "l", long = "limit", default_value = "50")]
+    limit: usize,
+
+    /// Directory where the manifest is stored. (optional)
+    ///
+    /// By default data will be loaded from the appropriate system local
+    /// storage directory.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default), tsv and json.
+    ///
+    /// tsv outputs in a tab (\t) separated format of columns with lines
+    /// ending in a new line character (\n).
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<SearchOutput>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let output: SearchOutput = match opt.output {
+        Some(e) => e,
+        None => match config.output_supported(
+            None,
+            "default",
+            &[Output::Default, Output::Tsv, Output::Json],
+        ) {
+            Ok(e) => e.into(),
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        },
+    };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if let Some(hash) = opt.hash {
+        let results = match manifest.find(hash).await {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!(
+                    "{}",
+                    format_error("Error searching manifest.", e)
+                );
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+        print_find_results(hash, &results, output);
+        return;
+    }
+
+    let mut query = SearchQuery::new(&opt.name.unwrap_or_default());
+    query.fuzzy = opt.fuzzy;
+    query.item_type = opt.item_type;
+    query.item_sub_type = opt.item_sub_type;
+
+    if !opt.tables.is_empty() {
+        query.tables = opt.tables;
+    }
+
+    tell::verbose!("Searching manifest");
+
+    let mut results = match manifest.search(&query).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Error searching manifest.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    tell::verbose!("{} matching definitions found", results.len());
+    results.truncate(opt.limit);
+
+    print_results(&results, output);
+}
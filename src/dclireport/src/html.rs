@@ -0,0 +1,128 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+/// Escapes a string so it can be included in html text or attribute values
+pub use dcli::document::escape_html as escape;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif;
+    background: #15171c; color: #e6e6e6; margin: 0 auto; max-width: 1100px;
+    padding: 16px; }
+a { color: #7fb3ff; }
+h1, h2, h3 { font-weight: 500; }
+.subtitle { color: #9a9a9a; }
+table { border-collapse: collapse; margin-bottom: 24px; width: 100%; }
+th, td { padding: 4px 8px; text-align: right; white-space: nowrap; }
+th { border-bottom: 2px solid #444; color: #9a9a9a; font-weight: 500; }
+td { border-bottom: 1px solid #2a2d35; }
+th:first-child, td:first-child, .left { text-align: left; }
+tr.total td { border-top: 2px solid #444; font-weight: 600; }
+img.icon { height: 24px; width: 24px; vertical-align: middle;
+    margin-right: 6px; }
+img.emblem { height: 48px; width: 48px; vertical-align: middle;
+    margin-right: 12px; }
+.victory { color: #6fcf70; }
+.defeat { color: #e66a6a; }
+.overview td:first-child { color: #9a9a9a; }
+.overview { width: auto; }
+.charts svg { margin: 0 16px 16px 0; }
+.footer { color: #6a6a6a; font-size: 0.8em; margin-top: 32px; }
+"#;
+
+/// Wraps the body in a complete html page, including the page styles
+pub fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n        <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n        <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}\n        <div class=\"footer\">Generated by dclireport         (https://github.com/mikechambers/dcli)</div>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+/// Returns an img tag for the image, or an empty string if there is no
+/// image. base is prepended to the image path, and is used by pages which
+/// are not in the root of the report (for example, "../").
+pub fn image(src: &Option<String>, class: &str, base: &str) -> String {
+    match src {
+        Some(e) => format!(
+            "<img class=\"{}\" src=\"{}{}\" alt=\"\">",
+            class,
+            base,
+            escape(e)
+        ),
+        None => "".to_string(),
+    }
+}
+
+/// Builds an html table. Cell values are included as is, so any text must
+/// be escaped before being added.
+pub struct Table {
+    class: String,
+    headers: Vec<String>,
+    rows: Vec<(String, Vec<String>)>,
+}
+
+impl Table {
+    pub fn new(class: &str, headers: &[&str]) -> Table {
+        Table {
+            class: class.to_string(),
+            headers: headers.iter().map(|e| escape(e)).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn add_row(&mut self, cells: Vec<String>) {
+        self.rows.push(("".to_string(), cells));
+    }
+
+    pub fn add_row_with_class(&mut self, class: &str, cells: Vec<String>) {
+        self.rows.push((class.to_string(), cells));
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = format!("<table class=\"{}\">\n", self.class);
+
+        if !self.headers.is_empty() {
+            out.push_str("<tr>");
+            for h in self.headers.iter() {
+                out.push_str(&format!("<th>{}</th>", h));
+            }
+            out.push_str("</tr>\n");
+        }
+
+        for (class, cells) in self.rows.iter() {
+            if class.is_empty() {
+                out.push_str("<tr>");
+            } else {
+                out.push_str(&format!("<tr class=\"{}\">", class));
+            }
+
+            for c in cells.iter() {
+                out.push_str(&format!("<td>{}</td>", c));
+            }
+            out.push_str("</tr>\n");
+        }
+
+        out.push_str("</table>\n");
+        out
+    }
+}
@@ -0,0 +1,132 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use dcli::apiclient::ApiClient;
+use dcli::apiutils::RESOURCE_BASE_URL;
+use dcli::error::Error;
+
+pub const IMAGE_DIR_NAME: &str = "images";
+
+/// Downloads images from the Bungie servers into the report's image
+/// directory, so the report can be viewed offline. Each image is only
+/// downloaded once, and images which already exist are not downloaded again.
+pub struct ImageCache {
+    dir: PathBuf,
+    client: Option<ApiClient>,
+    cache: HashMap<String, Option<String>>,
+}
+
+impl ImageCache {
+    /// If download is false, only images already in the directory are used
+    pub fn new(output_dir: &Path, download: bool) -> Result<ImageCache, Error> {
+        let dir = output_dir.join(IMAGE_DIR_NAME);
+        std::fs::create_dir_all(&dir)?;
+
+        let client = if download {
+            Some(ApiClient::new()?)
+        } else {
+            None
+        };
+
+        Ok(ImageCache {
+            dir,
+            client,
+            cache: HashMap::new(),
+        })
+    }
+
+    /// Returns the path of the local copy of the image, relative to the
+    /// output directory, or None if the image could not be retrieved.
+    ///
+    /// path can either be a full url, or a path relative to
+    /// RESOURCE_BASE_URL.
+    pub async fn get(&mut self, path: &Option<String>) -> Option<String> {
+        let path = path.as_ref()?;
+
+        if let Some(e) = self.cache.get(path) {
+            return e.clone();
+        }
+
+        let out = match self.download(path).await {
+            Ok(e) => Some(e),
+            Err(e) => {
+                tell::verbose!("Could not retrieve image {} : {}", path, e);
+                None
+            }
+        };
+
+        self.cache.insert(path.to_string(), out.clone());
+        out
+    }
+
+    async fn download(&self, path: &str) -> Result<String, Error> {
+        let url = if path.starts_with("http") {
+            path.to_string()
+        } else {
+            format!("{}{}", RESOURCE_BASE_URL, path)
+        };
+
+        let file_name: String = url
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '_')
+            .collect();
+
+        if file_name.is_empty() {
+            return Err(Error::Unknown {
+                description: format!("Invalid image url : {}", url),
+            });
+        }
+
+        let relative = format!("{}/{}", IMAGE_DIR_NAME, file_name);
+        let file_path = self.dir.join(&file_name);
+        if file_path.exists() {
+            return Ok(relative);
+        }
+
+        let client = match &self.client {
+            Some(e) => e,
+            None => {
+                return Err(Error::Unknown {
+                    description: "Image downloads are disabled".to_string(),
+                })
+            }
+        };
+
+        let response = client.call(&url).await?;
+        if !response.status().is_success() {
+            return Err(Error::Unknown {
+                description: format!("Status {}", response.status()),
+            });
+        }
+
+        let bytes = response.bytes().await?;
+        std::fs::write(&file_path, &bytes)?;
+
+        Ok(relative)
+    }
+}
@@ -0,0 +1,1077 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+mod html;
+mod images;
+mod svg;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::config::Config;
+use dcli::crucible::{
+    AggregateCruciblePerformances, CrucibleActivity,
+    CruciblePlayerActivityPerformance, CruciblePlayerPerformance,
+    ExtendedCruciblePlayerActivityPerformances, Member, Player,
+};
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::completionreason::CompletionReason;
+use dcli::enums::medaltier::MedalTier;
+use dcli::enums::membership::MembershipSelection;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::enums::standing::Standing;
+use dcli::error::Error;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::season::Seasons;
+use dcli::timezone::{set_timezone, to_local};
+use dcli::utils::{
+    calculate_average, calculate_efficiency, calculate_kills_deaths_ratio,
+    calculate_percent, determine_data_dir, format_error, format_f32,
+    human_duration, parse_and_validate_crucible_mode, parse_rfc3339,
+    EXIT_FAILURE,
+};
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+use html::{escape, image, page, Table};
+use images::ImageCache;
+use svg::line_chart;
+
+const MATCH_DIR_NAME: &str = "matches";
+const RATING_SCALE: f32 = 10.0;
+
+//number of matches used to calculate the rolling win rate
+const WIN_RATE_WINDOW: usize = 10;
+
+/// Looks up and caches emblem icons by emblem hash
+struct EmblemCache {
+    icons: HashMap<u32, Option<String>>,
+}
+
+impl EmblemCache {
+    fn new() -> EmblemCache {
+        EmblemCache {
+            icons: HashMap::new(),
+        }
+    }
+
+    async fn get(
+        &mut self,
+        hash: u32,
+        manifest: &mut ManifestInterface,
+        images: &mut ImageCache,
+    ) -> Option<String> {
+        if let Some(e) = self.icons.get(&hash) {
+            return e.clone();
+        }
+
+        let icon_path = match manifest.get_iventory_item_definition(hash).await
+        {
+            Ok(Some(e)) => e.display_properties.icon_path,
+            _ => None,
+        };
+
+        let out = images.get(&icon_path).await;
+        self.icons.insert(hash, out.clone());
+        out
+    }
+}
+
+fn format_date(date: &DateTime<Utc>) -> String {
+    to_local(date).format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn standing_class(standing: &Standing) -> &'static str {
+    match standing {
+        Standing::Victory => "victory",
+        Standing::Defeat => "defeat",
+        _ => "",
+    }
+}
+
+fn generate_score(data: &CrucibleActivity) -> String {
+    let mut teams: Vec<_> = data.teams.values().collect();
+    teams.sort_by_key(|t| t.id);
+
+    teams
+        .iter()
+        .map(|t| t.score.to_string())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+fn match_file_name(activity_id: i64) -> String {
+    format!("{}/{}.html", MATCH_DIR_NAME, activity_id)
+}
+
+//calculates the value for the totals of each match and all of the matches
+//before it
+fn cumulative_values<F>(
+    performances: &[&CruciblePlayerActivityPerformance],
+    f: F,
+) -> Vec<f32>
+where
+    F: Fn(u32, u32, u32) -> f32,
+{
+    let mut kills = 0;
+    let mut deaths = 0;
+    let mut assists = 0;
+
+    performances
+        .iter()
+        .map(|p| {
+            kills += p.performance.stats.kills;
+            deaths += p.performance.stats.deaths;
+            assists += p.performance.stats.assists;
+            f(kills, deaths, assists)
+        })
+        .collect()
+}
+
+fn rolling_win_rate(
+    performances: &[&CruciblePlayerActivityPerformance],
+) -> Vec<f32> {
+    let mut out: Vec<f32> = Vec::with_capacity(performances.len());
+
+    for i in 0..performances.len() {
+        let start = (i + 1).saturating_sub(WIN_RATE_WINDOW);
+        let window = &performances[start..=i];
+        let wins = window
+            .iter()
+            .filter(|p| p.performance.stats.standing == Standing::Victory)
+            .count();
+
+        out.push(calculate_percent(wins as u32, window.len() as u32));
+    }
+
+    out
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn build_index(
+    member: &Member,
+    mode: &Mode,
+    moment: &Moment,
+    time_period: &DateTimePeriod,
+    performances: &[CruciblePlayerActivityPerformance],
+    linked: &[i64],
+    manifest: &mut ManifestInterface,
+    images: &mut ImageCache,
+    emblems: &mut EmblemCache,
+) -> String {
+    let cpp: Vec<&CruciblePlayerPerformance> =
+        performances.iter().map(|x| &x.performance).collect();
+    let aggregate = AggregateCruciblePerformances::with_performances(&cpp);
+
+    let mut body = String::new();
+
+    //performances are sorted newest first
+    let emblem = match performances.first() {
+        Some(e) => {
+            emblems
+                .get(e.performance.stats.emblem_hash, manifest, images)
+                .await
+        }
+        None => None,
+    };
+
+    body.push_str(&format!(
+        "<h1>{}{}</h1>\n<p class=\"subtitle\">{} : {} ({} to {})</p>\n",
+        image(&emblem, "emblem", ""),
+        escape(&member.name.get_bungie_name()),
+        escape(&mode.to_string()),
+        escape(&moment.to_string()),
+        escape(&format_date(&time_period.get_start())),
+        escape(&format_date(&time_period.get_end())),
+    ));
+
+    body.push_str("<h2>Overview</h2>\n");
+    let mut overview = Table::new("overview", &[]);
+    let rows = [
+        ("Matches", aggregate.total_activities.to_string()),
+        (
+            "Record",
+            format!(
+                "{}-{} ({}%)",
+                aggregate.wins,
+                aggregate.losses,
+                format_f32(aggregate.win_rate, 2)
+            ),
+        ),
+        ("Kills", aggregate.kills.to_string()),
+        ("Deaths", aggregate.deaths.to_string()),
+        ("Assists", aggregate.assists.to_string()),
+        ("KD", format_f32(aggregate.kills_deaths_ratio, 2)),
+        ("KDA", format_f32(aggregate.kills_deaths_assists, 2)),
+        ("Efficiency", format_f32(aggregate.efficiency, 2)),
+        (
+            "Kills / Game",
+            format_f32(
+                calculate_average(aggregate.kills, aggregate.total_activities),
+                2,
+            ),
+        ),
+        (
+            "Longest Win Streak",
+            aggregate.longest_win_streak.to_string(),
+        ),
+        (
+            "Longest Loss Streak",
+            aggregate.longest_loss_streak.to_string(),
+        ),
+        ("Mercies", aggregate.total_mercy.to_string()),
+        ("Time Played", human_duration(aggregate.time_played_seconds)),
+    ];
+    for (label, value) in rows.iter() {
+        overview.add_row(vec![escape(label), escape(value)]);
+    }
+    body.push_str(&overview.render());
+
+    //charts are plotted oldest to newest
+    let chronological: Vec<&CruciblePlayerActivityPerformance> =
+        performances.iter().rev().collect();
+
+    body.push_str("<h2>Trends</h2>\n<div class=\"charts\">\n");
+    body.push_str(&line_chart(
+        "KD",
+        &cumulative_values(&chronological, |k, d, _a| {
+            calculate_kills_deaths_ratio(k, d)
+        }),
+        Some(1.0),
+    ));
+    body.push_str(&line_chart(
+        "Efficiency",
+        &cumulative_values(&chronological, calculate_efficiency),
+        Some(1.0),
+    ));
+    body.push_str(&line_chart(
+        &format!("Win % (last {} matches)", WIN_RATE_WINDOW),
+        &rolling_win_rate(&chronological),
+        Some(50.0),
+    ));
+    body.push_str(&line_chart(
+        "Kills per match",
+        &chronological
+            .iter()
+            .map(|p| p.performance.stats.kills as f32)
+            .collect::<Vec<f32>>(),
+        None,
+    ));
+    body.push_str("</div>\n");
+
+    if let Some(extended) = &aggregate.extended {
+        body.push_str("<h2>Weapons</h2>\n");
+        let mut table = Table::new(
+            "weapons",
+            &[
+                "WEAPON", "TYPE", "KILLS", "GAMES", "KILLS/G", "PREC %",
+                "WIN %",
+            ],
+        );
+        for w in extended.weapons.iter() {
+            let icon = images.get(&w.weapon.icon_path).await;
+            table.add_row(vec![
+                format!(
+                    "{}{}",
+                    image(&icon, "icon", ""),
+                    escape(&w.weapon.name)
+                ),
+                escape(&w.weapon.item_sub_type.to_string()),
+                w.kills.to_string(),
+                w.activity_count.to_string(),
+                format_f32(calculate_average(w.kills, w.activity_count), 2),
+                format!("{}%", format_f32(w.precision_kills_percent, 2)),
+                format!(
+                    "{}%",
+                    format_f32(calculate_percent(w.wins, w.activity_count), 2)
+                ),
+            ]);
+        }
+        body.push_str(&table.render());
+
+        body.push_str("<h2>Medals</h2>\n");
+        let mut medals: Vec<_> = extended
+            .medals
+            .iter()
+            .filter(|m| m.medal.tier != MedalTier::Unknown)
+            .collect();
+        medals.sort_by(|a, b| {
+            b.medal
+                .tier
+                .get_order()
+                .cmp(&a.medal.tier.get_order())
+                .then(b.count.cmp(&a.count))
+        });
+
+        let mut table =
+            Table::new("medals", &["MEDAL", "TIER", "COUNT", "PER GAME"]);
+        for m in medals.iter() {
+            let icon = images.get(&m.medal.icon_image_path).await;
+            table.add_row(vec![
+                format!(
+                    "{}{}",
+                    image(&icon, "icon", ""),
+                    escape(&m.medal.name)
+                ),
+                escape(&m.medal.tier.to_string()),
+                m.count.to_string(),
+                format_f32(
+                    calculate_average(m.count, aggregate.total_activities),
+                    2,
+                ),
+            ]);
+        }
+        body.push_str(&table.render());
+    }
+
+    body.push_str("<h2>Matches</h2>\n");
+    let mut table = Table::new(
+        "matches",
+        &[
+            "DATE", "MODE", "MAP", "RESULT", "SCORE", "KILLS", "DEATHS",
+            "ASTS", "K/D", "EFF",
+        ],
+    );
+    for p in performances.iter() {
+        let stats = &p.performance.stats;
+        let date = escape(&format_date(&p.activity_detail.period));
+        let date = if linked.contains(&p.activity_detail.id) {
+            format!(
+                "<a href=\"{}\">{}</a>",
+                match_file_name(p.activity_detail.id),
+                date
+            )
+        } else {
+            date
+        };
+
+        table.add_row(vec![
+            date,
+            escape(&p.activity_detail.mode.to_string()),
+            escape(&p.activity_detail.map_name),
+            format!(
+                "<span class=\"{}\">{}</span>",
+                standing_class(&stats.standing),
+                escape(&stats.standing.to_string())
+            ),
+            stats.team_score.to_string(),
+            stats.kills.to_string(),
+            stats.deaths.to_string(),
+            stats.assists.to_string(),
+            format_f32(stats.kills_deaths_ratio, 2),
+            format_f32(stats.efficiency, 2),
+        ]);
+    }
+    body.push_str(&table.render());
+
+    page(
+        &format!("{} : {}", member.name.get_bungie_name(), mode),
+        &body,
+    )
+}
+
+fn create_player_row(
+    label: String,
+    aggregate: &AggregateCruciblePerformances,
+    rating: String,
+    status: String,
+) -> Vec<String> {
+    let extended = aggregate.extended.as_ref();
+    let value = |f: fn(&ExtendedCruciblePlayerActivityPerformances) -> u32| {
+        extended.map(f).unwrap_or(0).to_string()
+    };
+
+    vec![
+        label,
+        aggregate.kills.to_string(),
+        aggregate.assists.to_string(),
+        aggregate.opponents_defeated.to_string(),
+        aggregate.deaths.to_string(),
+        format_f32(aggregate.kills_deaths_ratio, 2),
+        format_f32(aggregate.kills_deaths_assists, 2),
+        format_f32(aggregate.efficiency, 2),
+        value(|e| e.weapon_kills_super),
+        value(|e| e.weapon_kills_grenade),
+        value(|e| e.weapon_kills_melee),
+        value(|e| e.all_medals_earned),
+        rating,
+        status,
+    ]
+}
+
+fn write_page(output_dir: &Path, name: &str, html: &str) -> Result<(), Error> {
+    std::fs::write(output_dir.join(name), html)?;
+    Ok(())
+}
+
+async fn build_match_page(
+    member: &Member,
+    data: &CrucibleActivity,
+    ratings: &HashMap<u64, f32>,
+    manifest: &mut ManifestInterface,
+    images: &mut ImageCache,
+    emblems: &mut EmblemCache,
+) -> String {
+    let mut body = String::new();
+
+    body.push_str("<p><a href=\"../index.html\">&larr; Back</a></p>\n");
+    body.push_str(&format!(
+        "<h1>{} on {}</h1>\n",
+        escape(&data.details.mode.to_string()),
+        escape(&data.details.map_name)
+    ));
+
+    let member_performance = data.get_member_performance(member);
+    let mut subtitle = vec![format_date(&data.details.period)];
+    if let Some(e) = member_performance {
+        subtitle.push(human_duration(e.stats.activity_duration_seconds));
+        subtitle.push(format!("{}!", e.stats.standing));
+        if e.stats.completion_reason != CompletionReason::Unknown {
+            subtitle.push(e.stats.completion_reason.to_string());
+        }
+    }
+    subtitle.push(generate_score(data));
+
+    body.push_str(&format!(
+        "<p class=\"subtitle\">{}</p>\n",
+        escape(&subtitle.join(" : "))
+    ));
+
+    let headers = [
+        "PLAYER", "KILLS", "ASTS", "K+A", "DEATHS", "K/D", "KD/A", "EFF",
+        "SUP", "GREN", "MEL", "MED", "RATING", "STATUS",
+    ];
+
+    let mut teams: Vec<_> = data.teams.values().collect();
+    teams.sort_by_key(|t| t.id);
+
+    for t in teams.iter() {
+        body.push_str(&format!(
+            "<h3>[{}] {} Team <span class=\"{}\">{}!</span></h3>\n",
+            t.score,
+            escape(&t.display_name),
+            standing_class(&t.standing),
+            escape(&t.standing.to_string())
+        ));
+
+        let mut performances: Vec<&CruciblePlayerPerformance> =
+            t.player_performances.iter().collect();
+        performances.sort_by(|a, b| {
+            b.stats.opponents_defeated.cmp(&a.stats.opponents_defeated)
+        });
+
+        let mut table = Table::new("team", &headers);
+        for p in performances.iter() {
+            let emblem =
+                emblems.get(p.stats.emblem_hash, manifest, images).await;
+
+            let rating = match ratings.get(&p.player.calculate_hash()) {
+                Some(e) if *e > 0.0 => format_f32(e * RATING_SCALE, 0),
+                _ => "".to_string(),
+            };
+
+            let label = format!(
+                "{}{} <span class=\"subtitle\">{} {}</span>",
+                image(&emblem, "icon", "../"),
+                escape(&p.player.name.get_bungie_name()),
+                escape(&p.player.class_type.to_string()),
+                p.player.light_level
+            );
+
+            let aggregate =
+                AggregateCruciblePerformances::with_performances(&[*p]);
+            table.add_row(create_player_row(
+                label,
+                &aggregate,
+                rating,
+                escape(&p.stats.generate_status()),
+            ));
+        }
+
+        let aggregate =
+            AggregateCruciblePerformances::with_performances(&performances);
+        table.add_row_with_class(
+            "total",
+            create_player_row(
+                "TOTAL".to_string(),
+                &aggregate,
+                "".to_string(),
+                "".to_string(),
+            ),
+        );
+        body.push_str(&table.render());
+    }
+
+    if let Some(p) = member_performance {
+        if let Some(extended) = &p.stats.extended {
+            body.push_str("<h2>Weapons</h2>\n");
+            let mut table = Table::new(
+                "weapons",
+                &["WEAPON", "TYPE", "KILLS", "PREC", "PREC %"],
+            );
+
+            let mut weapons = extended.weapons.clone();
+            weapons.sort_by(|a, b| b.kills.cmp(&a.kills));
+            for w in weapons.iter() {
+                let icon = images.get(&w.weapon.icon_path).await;
+                table.add_row(vec![
+                    format!(
+                        "{}{}",
+                        image(&icon, "icon", "../"),
+                        escape(&w.weapon.name)
+                    ),
+                    escape(&w.weapon.item_sub_type.to_string()),
+                    w.kills.to_string(),
+                    w.precision_kills.to_string(),
+                    format!(
+                        "{}%",
+                        format_f32(
+                            calculate_percent(w.precision_kills, w.kills),
+                            2
+                        )
+                    ),
+                ]);
+            }
+            body.push_str(&table.render());
+
+            let medals: Vec<_> = extended
+                .medals
+                .iter()
+                .filter(|m| m.medal.tier != MedalTier::Unknown)
+                .collect();
+
+            if !medals.is_empty() {
+                body.push_str("<h2>Medals</h2>\n");
+                let mut table =
+                    Table::new("medals", &["MEDAL", "TIER", "COUNT"]);
+                for m in medals.iter() {
+                    let icon = images.get(&m.medal.icon_image_path).await;
+                    table.add_row(vec![
+                        format!(
+                            "{}{}",
+                            image(&icon, "icon", "../"),
+                            escape(&m.medal.name)
+                        ),
+                        escape(&m.medal.tier.to_string()),
+                        m.count.to_string(),
+                    ]);
+                }
+                body.push_str(&table.render());
+            }
+        }
+    }
+
+    page(
+        &format!("{} on {}", data.details.mode, data.details.map_name),
+        &body,
+    )
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for generating a static HTML report of Destiny 2
+/// Crucible stats.
+///
+/// The report includes a player overview, trend charts, weapon and medal
+/// tables, a match list and a page for each match with details for all of
+/// the players. Charts are rendered as inline SVG, and weapon, medal and
+/// emblem images are downloaded into the report directory, so the report
+/// can be viewed offline and hosted on any static web server.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie name for player
+    ///
+    /// Name must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
+
+    /// Custom start time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "t", long = "custom-time", parse(try_from_str = parse_rfc3339), required_if("moment", "custom"))]
+    custom_time: Option<DateTime<Utc>>,
+
+    /// Custom end time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --end-moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "e", long = "end-custom-time", parse(try_from_str = parse_rfc3339), required_if("end-moment", "custom"))]
+    end_custom_time: Option<DateTime<Utc>>,
+
+    /// Start moment from which to pull activities
+    ///
+    /// Valid values include weekend (last weekend reset on Friday), weekly
+    /// (last weekly reset on Tuesday), day, week, month, all_time and custom
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season).
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 2w, this-month, last-month, months (2023-05), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When custom is specified, the custom start date in RFC3339 format must
+    /// be specified with the --custom-time argument.
+    ///
+    /// Defaults to season, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// End moment from which to pull activities
+    ///
+    /// Accepts the same values as --moment.
+    ///
+    /// When custom is specified, the custom end date in RFC3339 format must
+    /// be specified with the --end-custom-time argument.
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
+
+    /// Activity mode to return stats for
+    ///
+    /// Supported values are all_pvp (default), control, clash, elimination,
+    /// mayhem, iron_banner, all_private, rumble, pvp_competitive,
+    /// quickplay and trials_of_osiris.
+    ///
+    /// Addition values available are crimsom_doubles, supremacy, survival,
+    /// countdown, all_doubles, doubles, private_clash, private_control,
+    /// private_survival, private_rumble, showdown_competitive, survival_competitive,
+    /// rift_competitive, showdown, lockdown, iron_banner_rift,
+    /// zone_control, iron_banner_zone_control, rift,
+    /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
+    #[structopt(long = "mode", short = "M",
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
+
+    /// Character to retrieve data for
+    ///
+    /// Valid values include hunter, titan, warlock, last_active and all.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// Directory the report will be written to
+    ///
+    /// The directory will be created if it does not exist. Existing report
+    /// files in the directory will be overwritten.
+    #[structopt(short = "o", long = "output-dir", parse(from_os_str))]
+    output_dir: PathBuf,
+
+    /// Maximum number of matches to create match detail pages for
+    ///
+    /// Pages are created for the most recent matches. Use 0 to create pages
+    /// for all matches.
+    #[structopt(long = "limit", short = "L", default_value = "100")]
+    limit: usize,
+
+    /// Don't download weapon, medal and emblem images
+    ///
+    /// Images which have already been downloaded to the report directory
+    /// will still be used.
+    #[structopt(long = "no-images")]
+    no_images: bool,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Sync player activities
+    #[structopt(long = "sync", short = "s")]
+    sync: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let moment = match config.moment(opt.moment, "season") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if opt.sync {
+        match store.sync_member(&member).await {
+            Ok(_e) => (),
+            Err(e) => {
+                tell::error!("Could not sync activity store {}", e);
+                tell::update!("Using existing data");
+            }
+        };
+    }
+
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let performances = match store
+        .retrieve_activities_since(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+            &mut manifest,
+        )
+        .await
+    {
+        Ok(e) => e.unwrap_or_default(),
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if performances.is_empty() {
+        tell::update!("No activities found");
+        return;
+    }
+
+    let output_dir = opt.output_dir;
+    if let Err(e) = std::fs::create_dir_all(output_dir.join(MATCH_DIR_NAME)) {
+        tell::error!(
+            "{}",
+            format_error("Could not create output directory.", e.into())
+        );
+        std::process::exit(EXIT_FAILURE);
+    }
+
+    let mut images = match ImageCache::new(&output_dir, !opt.no_images) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not initialize image directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+    let mut emblems = EmblemCache::new();
+
+    let page_count = if opt.limit == 0 {
+        performances.len()
+    } else {
+        std::cmp::min(opt.limit, performances.len())
+    };
+
+    let mut linked: Vec<i64> = Vec::with_capacity(page_count);
+    for (i, p) in performances.iter().take(page_count).enumerate() {
+        tell::progress!("Creating match page {} of {}", i + 1, page_count);
+
+        let activity_id = p.activity_detail.id;
+        let data =
+            match store.retrieve_activity(activity_id, &mut manifest).await {
+                Ok(e) => e,
+                Err(e) => {
+                    tell::verbose!(
+                        "Could not retrieve activity {} : {}",
+                        activity_id,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+        let players: Vec<&Player> = data
+            .teams
+            .values()
+            .flat_map(|t| t.player_performances.iter().map(|p| &p.player))
+            .collect();
+
+        let ratings = match store
+            .retrieve_combat_ratings_at(
+                &players,
+                &data.details.mode,
+                &data.details.period,
+            )
+            .await
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::verbose!("Could not retrieve combat ratings : {}", e);
+                HashMap::new()
+            }
+        };
+
+        let html = build_match_page(
+            &member,
+            &data,
+            &ratings,
+            &mut manifest,
+            &mut images,
+            &mut emblems,
+        )
+        .await;
+
+        if let Err(e) =
+            write_page(&output_dir, &match_file_name(activity_id), &html)
+        {
+            tell::error!("{}", format_error("Could not write match page.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+
+        linked.push(activity_id);
+    }
+
+    let html = build_index(
+        &member,
+        &mode,
+        &moment,
+        &time_period,
+        &performances,
+        &linked,
+        &mut manifest,
+        &mut images,
+        &mut emblems,
+    )
+    .await;
+
+    if let Err(e) = write_page(&output_dir, "index.html", &html) {
+        tell::error!("{}", format_error("Could not write report.", e));
+        std::process::exit(EXIT_FAILURE);
+    }
+
+    tell::update!(
+        "Report written to {}",
+        output_dir.join("index.html").display()
+    );
+}
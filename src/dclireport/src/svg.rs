@@ -0,0 +1,129 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::html::escape;
+
+const CHART_WIDTH: f32 = 500.0;
+const CHART_HEIGHT: f32 = 180.0;
+const PADDING_LEFT: f32 = 44.0;
+const PADDING_RIGHT: f32 = 12.0;
+const PADDING_TOP: f32 = 28.0;
+const PADDING_BOTTOM: f32 = 20.0;
+
+/// Renders the values as an inline svg line chart, with values plotted from
+/// left to right. If reference is specified, a dashed line is drawn at that
+/// value (for example, at 1.0 for KD).
+pub fn line_chart(
+    title: &str,
+    values: &[f32],
+    reference: Option<f32>,
+) -> String {
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\"         viewBox=\"0 0 {w} {h}\" role=\"img\" aria-label=\"{t}\">\n        <rect width=\"{w}\" height=\"{h}\" fill=\"#1d2027\" rx=\"4\"/>\n        <text x=\"{x}\" y=\"18\" fill=\"#9a9a9a\" font-size=\"13\"         font-family=\"sans-serif\">{t}</text>\n",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT,
+        x = PADDING_LEFT,
+        t = escape(title),
+    );
+
+    if values.is_empty() {
+        out.push_str("</svg>\n");
+        return out;
+    }
+
+    let mut min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+    let mut max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+
+    if let Some(r) = reference {
+        min = min.min(r);
+        max = max.max(r);
+    }
+
+    //give flat lines some space
+    if (max - min).abs() < f32::EPSILON {
+        min -= 1.0;
+        max += 1.0;
+    }
+
+    let plot_w = CHART_WIDTH - PADDING_LEFT - PADDING_RIGHT;
+    let plot_h = CHART_HEIGHT - PADDING_TOP - PADDING_BOTTOM;
+
+    let x_for = |i: usize| -> f32 {
+        if values.len() == 1 {
+            PADDING_LEFT + plot_w / 2.0
+        } else {
+            PADDING_LEFT + plot_w * i as f32 / (values.len() - 1) as f32
+        }
+    };
+    let y_for = |v: f32| -> f32 {
+        PADDING_TOP + plot_h - (v - min) / (max - min) * plot_h
+    };
+
+    //axis labels
+    for (v, y) in [(max, PADDING_TOP), (min, PADDING_TOP + plot_h)].iter() {
+        out.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" fill=\"#6a6a6a\" font-size=\"11\"             font-family=\"sans-serif\" text-anchor=\"end\">{:.2}</text>\n",
+            PADDING_LEFT - 6.0,
+            y + 4.0,
+            v
+        ));
+        out.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\"             stroke=\"#2a2d35\"/>\n",
+            PADDING_LEFT,
+            y,
+            CHART_WIDTH - PADDING_RIGHT,
+            y
+        ));
+    }
+
+    if let Some(r) = reference {
+        let y = y_for(r);
+        out.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\"             stroke=\"#6a6a6a\" stroke-dasharray=\"4 4\"/>\n",
+            PADDING_LEFT,
+            y,
+            CHART_WIDTH - PADDING_RIGHT,
+            y
+        ));
+    }
+
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| format!("{:.1},{:.1}", x_for(i), y_for(*v)))
+        .collect();
+
+    out.push_str(&format!(
+        "<polyline fill=\"none\" stroke=\"#7fb3ff\" stroke-width=\"2\"         points=\"{}\"/>\n",
+        points.join(" ")
+    ));
+
+    out.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" fill=\"#6a6a6a\" font-size=\"11\"         font-family=\"sans-serif\" text-anchor=\"end\">{} matches</text>\n",
+        CHART_WIDTH - PADDING_RIGHT,
+        CHART_HEIGHT - 4.0,
+        values.len()
+    ));
+
+    out.push_str("</svg>\n");
+    out
+}
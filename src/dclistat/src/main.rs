@@ -21,93 +21,126 @@
 */
 
 use chrono::{DateTime, Utc};
-use dcli::crucible::{Member, PlayerName};
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::document::{Alignment, Document, Table};
 use dcli::enums::mode::Mode;
 use dcli::enums::moment::{DateTimePeriod, Moment};
 use dcli::enums::stat::Stat;
+use dcli::error::Error;
+use dcli::output::Output;
 use dcli::playeractivitiessummary::PlayerActivitiesSummary;
+use dcli::season::Seasons;
+use dcli::timezone::set_timezone;
 use dcli::utils::{
     calculate_average, calculate_efficiency, calculate_kills_deaths_assists,
     calculate_kills_deaths_ratio, determine_data_dir, format_error, format_f32,
-    parse_and_validate_crucible_mode, parse_rfc3339,
+    parse_and_validate_crucible_mode, parse_rfc3339, uppercase_first_char,
 };
 use std::path::PathBuf;
 use tell::{Tell, TellLevel};
 
 use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
 
 use dcli::activitystoreinterface::ActivityStoreInterface;
 
 use dcli::utils::EXIT_FAILURE;
 use structopt::StructOpt;
 
-#[allow(clippy::too_many_arguments)]
-fn print_default(data: &PlayerActivitiesSummary, stats: &[Stat]) {
-    let mut out = Vec::<String>::new();
-    for m in stats.iter() {
-        let o: String = match m {
-            Stat::Assists => data.assists.to_string(),
-            Stat::AssistsAvg => format_f32(
-                calculate_average(data.assists, data.total_activities),
-                2,
-            ),
-            Stat::AssistsMax => data.highest_assists.to_string(),
-            Stat::Deaths => data.deaths.to_string(),
-            Stat::DeathsAvg => format_f32(
-                calculate_average(data.deaths, data.total_activities),
-                2,
-            ),
-            Stat::DeathsMax => data.highest_deaths.to_string(),
-            Stat::Kills => data.kills.to_string(),
-            Stat::KillsAvg => format_f32(
-                calculate_average(data.kills, data.total_activities),
-                2,
-            ),
-            Stat::KillsMax => data.highest_kills.to_string(),
-            Stat::OpponentsDefeated => data.opponents_defeated.to_string(),
-            Stat::OpponentsDefeatedAvg => format_f32(
-                calculate_average(
-                    data.opponents_defeated,
-                    data.total_activities,
-                ),
-                2,
-            ),
-            Stat::OpponentsDefeatedMax => {
-                data.highest_opponents_defeated.to_string()
-            }
-            Stat::Efficiency => format_f32(
-                calculate_efficiency(data.kills, data.deaths, data.assists),
-                2,
-            ),
-            Stat::EfficiencyMax => format_f32(data.highest_efficiency, 2),
-            Stat::KD => format_f32(
-                calculate_kills_deaths_ratio(data.kills, data.deaths),
-                2,
-            ),
-            Stat::KDMax => format_f32(data.highest_kills_deaths_ratio, 2),
-            Stat::KDA => format_f32(
-                calculate_kills_deaths_assists(
-                    data.kills,
-                    data.deaths,
-                    data.assists,
-                ),
-                2,
+fn format_stat(data: &PlayerActivitiesSummary, stat: &Stat) -> String {
+    match stat {
+        Stat::Assists => data.assists.to_string(),
+        Stat::AssistsAvg => format_f32(
+            calculate_average(data.assists, data.total_activities),
+            2,
+        ),
+        Stat::AssistsMax => data.highest_assists.to_string(),
+        Stat::Deaths => data.deaths.to_string(),
+        Stat::DeathsAvg => {
+            format_f32(calculate_average(data.deaths, data.total_activities), 2)
+        }
+        Stat::DeathsMax => data.highest_deaths.to_string(),
+        Stat::Kills => data.kills.to_string(),
+        Stat::KillsAvg => {
+            format_f32(calculate_average(data.kills, data.total_activities), 2)
+        }
+        Stat::KillsMax => data.highest_kills.to_string(),
+        Stat::OpponentsDefeated => data.opponents_defeated.to_string(),
+        Stat::OpponentsDefeatedAvg => format_f32(
+            calculate_average(data.opponents_defeated, data.total_activities),
+            2,
+        ),
+        Stat::OpponentsDefeatedMax => {
+            data.highest_opponents_defeated.to_string()
+        }
+        Stat::Efficiency => format_f32(
+            calculate_efficiency(data.kills, data.deaths, data.assists),
+            2,
+        ),
+        Stat::EfficiencyMax => format_f32(data.highest_efficiency, 2),
+        Stat::KD => {
+            format_f32(calculate_kills_deaths_ratio(data.kills, data.deaths), 2)
+        }
+        Stat::KDMax => format_f32(data.highest_kills_deaths_ratio, 2),
+        Stat::KDA => format_f32(
+            calculate_kills_deaths_assists(
+                data.kills,
+                data.deaths,
+                data.assists,
             ),
-            Stat::KDAMax => {
-                format_f32(data.highest_kills_deaths_assists_ratio, 2)
-            }
-            Stat::Games => data.total_activities.to_string(),
-            Stat::Wins => data.wins.to_string(),
-            Stat::Losses => (data.total_activities - data.wins).to_string(),
-            Stat::Mercies => data.completion_reason_mercy.to_string(),
-        };
-
-        out.push(o);
+            2,
+        ),
+        Stat::KDAMax => format_f32(data.highest_kills_deaths_assists_ratio, 2),
+        Stat::Games => data.total_activities.to_string(),
+        Stat::Wins => data.wins.to_string(),
+        Stat::Losses => (data.total_activities - data.wins).to_string(),
+        Stat::Mercies => data.completion_reason_mercy.to_string(),
     }
+}
+
+fn print_default(data: &PlayerActivitiesSummary, stats: &[Stat]) {
+    let out: Vec<String> = stats.iter().map(|m| format_stat(data, m)).collect();
 
     tell::update!("{}", out.join(","));
 }
 
+fn print_document(
+    data: &PlayerActivitiesSummary,
+    stats: &[Stat],
+    member: &Member,
+    mode: &Mode,
+    moment: &Moment,
+    output: Output,
+) {
+    let mut doc = Document::new();
+    doc.add_heading(
+        2,
+        &format!(
+            "{} : {}",
+            uppercase_first_char(&format!("{}", mode)),
+            uppercase_first_char(&format!("{}", moment))
+        ),
+    );
+    doc.add_paragraph(&format!(
+        "Stats for {} across {} activities",
+        member.name.get_bungie_name(),
+        data.total_activities
+    ));
+
+    let mut table =
+        Table::new(&[("Stat", Alignment::Left), ("Value", Alignment::Right)]);
+    for m in stats {
+        table.add_row(vec![
+            uppercase_first_char(&format!("{}", m)),
+            format_stat(data, m),
+        ]);
+    }
+    doc.add_table(table);
+
+    tell::update!("{}", doc.render(output));
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(verbatim_doc_comment)]
 /// Command line tool for retrieving and viewing Destiny 2 Crucible activity history.
@@ -131,8 +164,11 @@ struct Opt {
     /// Name must be in the format of NAME#CODE. Example: foo#3280
     /// You can find your name in game, or on Bungie's site at:
     /// https://www.bungie.net/7/en/User/Account/IdentitySettings
-    #[structopt(long = "name", short = "n", required = true)]
-    name: PlayerName,
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
 
     /// Custom start time in RFC 3339 date / time format
     ///
@@ -164,20 +200,33 @@ struct Opt {
     /// Valid values include daily (last daily reset), weekend
     /// (last weekend reset on Friday), weekly (last weekly reset on Tuesday),
     /// day (last day), week (last week), month (last month), all_time and custom
-    /// as well as the following season moments launch, curse_of_osiris, warmind,
-    /// season_of_the_outlaw, season_of_the_forge, season_of_the_drifter,
-    /// season_of_opulence, season_of_the_undying, season_of_dawn,
-    /// season_of_the_worthy, season_of_arrivals, season_of_the_hunt,
-    /// season_of_the_chosen, season_of_the_splicer, season_of_the_lost, season_of_the_risen,
-    /// witch_queen, season_of_the_haunted, season_of_plunder, season_of_the_seraph, lightfall, season_of_defiance, season_of_the_deep, season_of_the_witch, season_of_the_wish.
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season). Season dates are loaded
+    /// from the manifest, and can be updated via a seasons.json file in the
+    /// data directory.
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 12h, 2w, today, yesterday, this-week, last-week,
+    /// this-month, last-month, this-year, last-year, since-last-reset,
+    /// since-weekly-reset, months (2023-05), ISO weeks (2023-W14), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When a season, expansion or calendar period (such as 2023-05) is
+    /// specified without --end-moment, only activities within that period
+    /// are returned.
     ///
     /// When custom is specified, the custom start date in RFC3339 format must
     /// be specified with the --custom-time argument.
     ///
     /// For example:
     /// --moment custom --custom-time 2020-12-08T17:00:00.774187+00:00
-    #[structopt(long = "moment", short = "T", default_value = "week")]
-    moment: Moment,
+    ///
+    /// Defaults to week, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
 
     /// End moment from which to pull activities from
     ///
@@ -191,20 +240,30 @@ struct Opt {
     /// Valid values include daily (last daily reset), weekend
     /// (last weekend reset on Friday), weekly (last weekly reset on Tuesday),
     /// day (last day), week (last week), month (last month), all_time and custom
-    /// as well as the following season moments launch, curse_of_osiris, warmind,
-    /// season_of_the_outlaw, season_of_the_forge, season_of_the_drifter,
-    /// season_of_opulence, season_of_the_undying, season_of_dawn,
-    /// season_of_the_worthy, season_of_arrivals, season_of_the_hunt,
-    /// season_of_the_chosen, season_of_the_splicer, season_of_the_lost, season_of_the_risen,
-    /// witch_queen, season_of_the_haunted, season_of_plunder, season_of_the_seraph, lightfall, season_of_defiance, season_of_the_deep, season_of_the_witch, season_of_the_wish.
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season). Season dates are loaded
+    /// from the manifest, and can be updated via a seasons.json file in the
+    /// data directory.
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 12h, 2w, today, yesterday, this-week, last-week,
+    /// this-month, last-month, this-year, last-year, since-last-reset,
+    /// since-weekly-reset, months (2023-05), ISO weeks (2023-W14), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
     ///
     /// When custom is specified, the custom start date in RFC3339 format must
     /// be specified with the --end-custom-time argument.
     ///
     /// For example:
     /// --moment custom --end-custom-time 2020-12-08T17:00:00.774187+00:00
-    #[structopt(long = "end-moment", short = "E", default_value = "now")]
-    end_moment: Moment,
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
 
     /// Activity mode to return stats for
     ///
@@ -218,15 +277,30 @@ struct Opt {
     /// rift_competitive, showdown, lockdown, iron_banner_rift,
     /// zone_control, iron_banner_zone_control, rift,
     /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
     #[structopt(long = "mode", short = "M", 
-        parse(try_from_str=parse_and_validate_crucible_mode), default_value = "all_pvp")]
-    mode: Mode,
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
 
     /// Character to retrieve data for
     ///
     /// Valid values include hunter, titan, warlock, last_active and all.
-    #[structopt(short = "C", long = "class", default_value = "all")]
-    character_class_selection: CharacterClassSelection,
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
 
     /// Stat to retrieve data for
     ///
@@ -237,6 +311,29 @@ struct Opt {
     #[structopt(short = "x", long = "stat", required = true)]
     stat: Vec<Stat>,
 
+    /// Format for command output
+    ///
+    /// Valid values are default (Default), markdown and html.
+    ///
+    /// default outputs the stat values separated by commas. markdown renders
+    /// a Markdown table of the stats, which can be pasted into Discord or
+    /// GitHub, and html renders the table as an HTML fragment with inline
+    /// styles, which can be included in an email.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
     ///Print out additional information
     #[structopt(short = "v", long = "verbose")]
     verbose: bool,
@@ -274,55 +371,124 @@ async fn main() {
     tell::verbose!("{:#?}", opt);
     log::info!("{:#?}", opt);
 
-    let data_dir = match determine_data_dir(opt.data_dir) {
+    let config = match Config::load(&opt.data_dir) {
         Ok(e) => e,
         Err(e) => {
-            tell::error!(
-                "{}",
-                format_error("Error initializing data directory.", e)
-            );
+            tell::error!("{}", format_error("Could not load config file.", e));
             std::process::exit(EXIT_FAILURE);
         }
     };
 
-    let start_time = match opt.moment {
-        Moment::Custom => {
-            opt.custom_time.unwrap() //note, this should be ok, because struct opt should ensure valid value
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
         }
-        _ => opt.moment.get_date_time(),
     };
 
-    let end_time = match opt.end_moment {
-        Moment::Custom => {
-            opt.end_custom_time.unwrap() //note, this should be ok, because struct opt should ensure valid value
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
         }
-        _ => opt.end_moment.get_date_time(),
     };
 
-    let time_period =
-        match DateTimePeriod::with_start_end_time(start_time, end_time) {
+    let moment = match config.moment(opt.moment, "week") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
             Ok(e) => e,
-            Err(_e) => {
-                tell::error!("--end-moment must be greater than --moment");
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
                 std::process::exit(EXIT_FAILURE);
             }
         };
 
-    let mut store =
-        match ActivityStoreInterface::init_with_path(&data_dir, opt.api_key)
-            .await
-        {
+    let output =
+        match config.output_supported(opt.output, "default", &Output::REPORT) {
             Ok(e) => e,
             Err(e) => {
-                tell::error!("{}",format_error(
-                "Could not initialize activity store. Have you run dclisync?",
-                e,
-            ));
+                tell::error!("{}", format_error("Invalid output format.", e));
                 std::process::exit(EXIT_FAILURE);
             }
         };
 
-    let member: Member = match store.find_member(&opt.name, true).await {
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    //note, custom times should be ok, because struct opt ensures they are
+    //set when the moments are custom
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}",format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
@@ -343,11 +509,30 @@ async fn main() {
         };
     }
 
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
     let data = match store
         .retrieve_activities_summary(
             &member,
-            &opt.character_class_selection,
-            &opt.mode,
+            &character_class_selection,
+            &mode,
             &time_period,
         )
         .await
@@ -369,5 +554,12 @@ async fn main() {
 
     let data: PlayerActivitiesSummary = data.unwrap();
 
-    print_default(&data, &opt.stat);
+    match output {
+        Output::Markdown | Output::Html => {
+            print_document(&data, &opt.stat, &member, &mode, &moment, output)
+        }
+        Output::Default | Output::Tsv | Output::Jsonl | Output::Json => {
+            print_default(&data, &opt.stat)
+        }
+    }
 }
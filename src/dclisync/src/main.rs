@@ -29,8 +29,8 @@ use std::time::Duration;
 use tell::{Tell, TellLevel};
 
 use dcli::activitystoreinterface::ActivityStoreInterface;
-use dcli::apiinterface::ApiInterface;
-use dcli::crucible::{Member, PlayerName};
+use dcli::config::Config;
+use dcli::crucible::PlayerName;
 use dcli::utils::{determine_data_dir, format_error, EXIT_FAILURE};
 use structopt::StructOpt;
 
@@ -43,10 +43,14 @@ const SHOULD_CONTINUE_CODE: i32 = -1;
 /// history to a sqlite3 database file.
 ///
 /// You may add and remove users via the --add and --remove flags, as well as import
-/// all clan members via the --import flag.
+/// all clan members via the --import-group flag.
+///
+/// Imported groups are tracked. When syncing all players, the rosters for
+/// tracked groups are refreshed (at most once an hour), adding new members and
+/// marking members who have left.
 ///
 /// If multiple flags are specified, they will be run in the following order:
-/// import, add, remove, sync, list
+/// import, remove group, add, remove, sync, list
 ///
 /// Some options require that a Bungie API key is specified via the --api-key KEY flag,
 /// or DESTINY_API_KEY environment variable.
@@ -95,7 +99,7 @@ struct Opt {
         short = "s",
         //conflicts_with_all = &["add", "remove", "list"],
         //required_unless_one=&["list", "add", "remove"],`
-        required_unless_one = &["list", "add", "remove", "import-group"],
+        required_unless_one = &["list", "add", "remove", "import-group", "remove-group"],
         requires="api-key"
     )]
     sync: Option<Vec<PlayerName>>,
@@ -154,12 +158,23 @@ struct Opt {
     /// https://www.bungie.net/en/ClanV2/MyClans
     /// Click your clan, then copy the group id from the URL.
     ///
+    /// The group will be tracked, and its roster refreshed when syncing all
+    /// players. New members will be added to be synced, and members who have
+    /// left the group will be marked as departed.
+    ///
     /// Requires that a Bungie API key is specified via the --api-key KEY flag,
-    /// or DESTINY_API_KEY environment variable.
+    /// the DESTINY_API_KEY environment variable, or the dcli.toml config file.
     ///
     /// You can obtain a key from https://www.bungie.net/en/Application
-    #[structopt(short = "i", long = "import-group", requires = "api-key")]
-    import_group: Option<u32>,
+    #[structopt(short = "i", long = "import-group")]
+    import_group: Option<i64>,
+
+    /// Stop tracking the specified Destiny 2 Group / clan.
+    ///
+    /// Members of the group will still be synced, and can be removed with
+    /// --remove.
+    #[structopt(long = "remove-group")]
+    remove_group: Option<i64>,
 
     /// API key from Bungie required for some actions.
     ///
@@ -187,7 +202,15 @@ async fn main() {
     info!("Arguments : {:#?}", opt);
     tell::verbose!("{:#?}", opt);
 
-    let data_dir = match determine_data_dir(opt.data_dir) {
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
         Ok(e) => e,
         Err(e) => {
             tell::error!(
@@ -200,7 +223,7 @@ async fn main() {
 
     info!("Using data directory : {:#?}", &data_dir);
 
-    let key = match opt.api_key {
+    let key = match config.api_key(opt.api_key) {
         Some(e) => e,
         None => "".to_string(),
     };
@@ -226,53 +249,42 @@ async fn main() {
     if opt.import_group.is_some() {
         let group_id = opt.import_group.unwrap();
 
+        if key.is_empty() {
+            tell::error!(
+                "--import-group requires an API key. Set with --api-key or in the dcli.toml config file."
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+
         tell::update!("Import Group ID : {}", group_id);
 
-        let api = match ApiInterface::new_with_key(&key) {
+        let update = match store.sync_group(group_id).await {
             Ok(e) => e,
             Err(e) => {
-                tell::error!(
-                    "{}",
-                    format_error("Error creating interface.", e)
-                );
+                tell::error!("{}", format_error("Error importing group.", e));
                 std::process::exit(EXIT_FAILURE);
             }
         };
 
-        let members: Vec<Member> =
-            match api.retrieve_group_members(group_id).await {
-                Ok(e) => e,
-                Err(e) => {
-                    tell::error!(
-                        "{}",
-                        format_error("Error creating interface.", e)
-                    );
-                    std::process::exit(EXIT_FAILURE);
-                }
-            };
-
-        for m in members.iter() {
-            //if they dont have a valid Bungie ID / id, then skip
-            if !m.name.is_valid_bungie_name() {
-                tell::update!(
-                    "No valid Bungie ID and code. Skipping. : {}",
-                    m.name.get_short_name()
-                );
+        for m in update.joined.iter() {
+            tell::update!("{}", m.name.get_bungie_name());
+        }
 
-                continue;
-            }
+        for m in update.departed.iter() {
+            tell::update!("Departed : {}", m.name.get_bungie_name());
+        }
+        tell::update!("");
+    }
 
-            match store.add_member_to_sync(m).await {
-                Ok(_) => tell::update!("{}", m.name.get_bungie_name()),
-                Err(e) => {
-                    tell::update!(
-                        "Error adding {}. Skipping. {}",
-                        m.name.get_bungie_name(),
-                        e
-                    );
-                }
+    if let Some(group_id) = opt.remove_group {
+        match store.remove_group(group_id).await {
+            Ok(_) => tell::update!("Removed Group ID : {}", group_id),
+            Err(e) => {
+                tell::error!("{}", format_error("Error removing group.", e));
+                std::process::exit(EXIT_FAILURE);
             }
         }
+        tell::update!("");
     }
 
     if opt.add.is_some() {
@@ -378,7 +390,7 @@ async fn main() {
                 consts::SIGINT, consts::SIGTERM, iterator::Signals,
             };
 
-            let mut signals = match Signals::new(&[SIGINT, SIGTERM]) {
+            let mut signals = match Signals::new([SIGINT, SIGTERM]) {
                 Ok(e) => e,
                 Err(e) => {
                     error!("Could not initialize Signals. Exiting");
@@ -431,6 +443,14 @@ async fn main() {
 
         loop {
             if players.is_empty() {
+                //a roster error shouldnt stop the players from syncing
+                if let Err(e) = store.sync_groups().await {
+                    tell::error!(
+                        "{}",
+                        format_error("Error syncing group rosters.", e)
+                    );
+                }
+
                 match store.sync_all().await {
                     Ok(_) => {}
                     Err(e) => {
@@ -482,5 +502,22 @@ async fn main() {
             tell::update!("{}", member.name.get_bungie_name());
         }
         tell::update!("");
+
+        let clans = match store.retrieve_clans().await {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Error listing groups.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+        if !clans.is_empty() {
+            tell::update!("Tracked Groups");
+            tell::update!("-------------");
+            for clan in clans.iter() {
+                tell::update!("{} ({})", clan.name, clan.group_id);
+            }
+            tell::update!("");
+        }
     }
 }
@@ -22,10 +22,16 @@
 
 mod datetimeformat;
 
+use chrono::Utc;
 use datetimeformat::DateTimeFormat;
+use dcli::config::Config;
 use dcli::enums::moment::Moment;
 use dcli::output::Output;
-use dcli::utils::build_tsv;
+use dcli::season::Seasons;
+use dcli::timezone::{get_timezone, set_timezone, to_local};
+use dcli::utils::{
+    build_tsv, determine_data_dir, format_error, human_duration, EXIT_FAILURE,
+};
 use structopt::StructOpt;
 use tell::{Tell, TellLevel};
 
@@ -51,6 +57,15 @@ struct Opt {
     /// next_weekly (upcoming Tuesday weekly reset), current_daily, next_daily,
     /// current_xur (previous Friday Xur reset), next_xur (upcoming Friday Xur reset),
     /// current_trials (previous Friday Trials reset), next_trials (upcoming Friday Trials reset)
+    ///
+    /// Seasons and expansions can also be specified by name (for example
+    /// season_of_the_deep or lightfall), by season number (season_21) or
+    /// relative to the current season (season, season-1), in which case the
+    /// start of the season is returned.
+    ///
+    /// Time expressions such as 3d, 12h, this-week, last-month, 2023-05,
+    /// 2023-W14 and since-last-reset are also supported, in which case the
+    /// start of the period is returned.
     #[structopt(short = "T", long = "moment", default_value = "now")]
     moment: Moment,
 
@@ -61,6 +76,23 @@ struct Opt {
     #[structopt(short = "f", long = "time-format", default_value = "rfc3339")]
     time_format: DateTimeFormat,
 
+    /// Print the time remaining until the moment, or elapsed since it
+    ///
+    /// Can be combined with reset moments, such as next_weekly or next_daily,
+    /// to display a countdown to the next reset.
+    #[structopt(short = "c", long = "countdown")]
+    countdown: bool,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
     /// Print out additional information
     #[structopt(short = "v", long = "verbose")]
     verbose: bool,
@@ -71,12 +103,10 @@ struct Opt {
     ///
     /// tsv outputs in a tab (\t) separated format of name / value pairs with lines
     /// ending in a new line character (\n).
-    #[structopt(
-        short = "O",
-        long = "output-format",
-        default_value = "default"
-    )]
-    output: Output,
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
 }
 
 #[tokio::main]
@@ -94,24 +124,109 @@ async fn main() {
     log::info!("{:#?}", opt.verbose);
     tell::verbose!("{:#?}", opt.verbose);
 
-    let dt = opt.moment.get_date_time();
+    let config = match Config::load(&None) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::TERMINAL)
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(None)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let dt = match opt.moment.get_date_time(&seasons) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let local = to_local(&dt);
     let date_time_str = match opt.time_format {
-        DateTimeFormat::RFC3339 => dt.to_rfc3339(),
-        DateTimeFormat::RFC2822 => dt.to_rfc2822(),
+        DateTimeFormat::RFC3339 => local.to_rfc3339(),
+        DateTimeFormat::RFC2822 => local.to_rfc2822(),
         DateTimeFormat::Unix => dt.timestamp().to_string(),
     };
 
-    match opt.output {
-        Output::Default => {
-            tell::update!("{}", date_time_str);
+    //positive if the moment is in the future
+    let countdown = (dt - Utc::now()).num_seconds();
+
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => {
+            if opt.countdown {
+                tell::update!(
+                    "{} ({})",
+                    format_countdown(countdown),
+                    date_time_str
+                );
+            } else {
+                tell::update!("{}", date_time_str);
+            }
         }
         Output::Tsv => {
             let mut name_values: Vec<(&str, String)> = Vec::new();
             name_values.push(("date_time", date_time_str));
             name_values.push(("format", format!("{}", opt.time_format)));
             name_values.push(("moment", format!("{}", opt.moment)));
+            name_values.push(("timezone", format!("{}", get_timezone())));
+
+            if opt.countdown {
+                name_values.push(("countdown", countdown.to_string()));
+            }
 
             tell::update!("{}", build_tsv(name_values));
         }
     }
 }
+
+fn format_countdown(seconds: i64) -> String {
+    let duration = human_duration(seconds.unsigned_abs() as u32);
+
+    if duration.is_empty() {
+        "now".to_string()
+    } else if seconds > 0 {
+        format!("in {}", duration)
+    } else {
+        format!("{} ago", duration)
+    }
+}
@@ -0,0 +1,635 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::config::Config;
+use dcli::crucible::{Member, Player};
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::error::Error;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::output::Output;
+use dcli::season::Seasons;
+use dcli::timezone::{set_timezone, to_local};
+use dcli::trials::{TrialsCard, TrialsSummary, TrialsWeekend};
+use dcli::utils::{
+    calculate_percent, determine_data_dir, format_error, format_f32,
+    parse_rfc3339, repeat_str, truncate_ascii_string, EXIT_FAILURE, TSV_DELIM,
+};
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const MAP_COL_W: usize = 20;
+
+fn format_players(players: &[&Player]) -> String {
+    players
+        .iter()
+        .map(|e| e.name.get_bungie_name())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn format_record(wins: u32, losses: u32) -> String {
+    format!("{}-{}", wins, losses)
+}
+
+fn format_count(count: u32) -> String {
+    if count == 0 {
+        "".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+fn print_summary_line(label: &str, summary: &TrialsSummary) {
+    tell::update!(
+        "{} : {} cards, {} ({}% wins), {} flawless, {} mercy, {} forfeits",
+        label,
+        summary.cards,
+        format_record(summary.wins, summary.losses),
+        format_f32(calculate_percent(summary.wins, summary.total_matches()), 2),
+        summary.flawless,
+        summary.mercy,
+        summary.forfeits,
+    );
+}
+
+fn print_card_matches(card: &TrialsCard, col_w: usize) {
+    for m in card.matches.iter() {
+        let stats = &m.performance.performance.stats;
+        let mut notes: Vec<&str> = Vec::new();
+        if m.is_mercy() {
+            notes.push("Mercy");
+        }
+
+        if m.is_forfeit() {
+            notes.push("Forfeit");
+        }
+
+        let opponents: Vec<&Player> = m.opponents.iter().collect();
+
+        tell::update!(
+            "{:>0col_w$}  {:<8}{:<8}{:<0map_w$}{:<16}vs {}",
+            "",
+            format!("{}", stats.standing),
+            format!("{}-{}", stats.team_score, m.opponent_score),
+            truncate_ascii_string(
+                &m.performance.activity_detail.map_name,
+                MAP_COL_W - 2
+            ),
+            notes.join(", "),
+            format_players(&opponents),
+            col_w = col_w,
+            map_w = MAP_COL_W,
+        );
+    }
+}
+
+fn print_default(
+    member: &Member,
+    weekends: &[TrialsWeekend],
+    seasons: &Seasons,
+    details: bool,
+) {
+    let col_w = 8;
+
+    tell::update!(
+        "Trials of Osiris cards for {}",
+        member.name.get_bungie_name()
+    );
+    tell::update!();
+
+    let header = format!(
+        "{:<0col_w$}{:<0col_w$}{:<10}{:>0col_w$}{:>0col_w$}   {}",
+        "CARD",
+        "W-L",
+        "FLAWLESS",
+        "MERCY",
+        "FORFEIT",
+        "TEAMMATES",
+        col_w = col_w,
+    );
+    let header_border = repeat_str("=", header.chars().count() + 30);
+    let footer_border = repeat_str("-", header.chars().count() + 30);
+
+    for w in weekends.iter() {
+        tell::update!(
+            "WEEKEND OF {}",
+            to_local(&w.start)
+                .format("%B %-d, %Y")
+                .to_string()
+                .to_uppercase()
+        );
+        tell::update!("{}", header);
+        tell::update!("{}", header_border);
+
+        for (i, c) in w.cards.iter().enumerate() {
+            tell::update!(
+                "{:<0col_w$}{:<0col_w$}{:<10}{:>0col_w$}{:>0col_w$}   {}",
+                (i + 1).to_string(),
+                format_record(c.wins(), c.losses()),
+                if c.is_flawless() { "Yes" } else { "" },
+                format_count(c.mercy_count()),
+                format_count(c.forfeit_count()),
+                format_players(&c.get_teammates()),
+                col_w = col_w,
+            );
+
+            if details {
+                print_card_matches(c, col_w);
+            }
+        }
+
+        tell::update!("{}", footer_border);
+        print_summary_line("Weekend", &TrialsSummary::with_weekends(&[w]));
+        tell::update!();
+    }
+
+    //group weekends by the season they started in
+    let mut season_weekends: Vec<(String, Vec<&TrialsWeekend>)> = Vec::new();
+    for w in weekends.iter() {
+        let name = match seasons.find_by_date(&w.start) {
+            Some(e) => e.name.to_string(),
+            None => "Unknown".to_string(),
+        };
+
+        match season_weekends.last_mut() {
+            Some(e) if e.0 == name => e.1.push(w),
+            _ => season_weekends.push((name, vec![w])),
+        }
+    }
+
+    let name_col_w = 26;
+    let header = format!(
+        "{:<0name_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>10}{:>0col_w$}{:>0col_w$}",
+        "SEASON",
+        "WKNDS",
+        "CARDS",
+        "WINS",
+        "LOSSES",
+        "WIN %",
+        "FLAWLESS",
+        "MERCY",
+        "FORFEIT",
+        col_w = col_w,
+        name_col_w = name_col_w,
+    );
+
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    let all: Vec<&TrialsWeekend> = weekends.iter().collect();
+    let total = TrialsSummary::with_weekends(&all);
+    for (name, w) in season_weekends
+        .iter()
+        .map(|e| (e.0.as_str(), TrialsSummary::with_weekends(&e.1)))
+        .chain(std::iter::once(("TOTAL", total)))
+    {
+        tell::update!(
+            "{:<0name_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>10}{:>0col_w$}{:>0col_w$}",
+            truncate_ascii_string(name, name_col_w - 2),
+            w.weekends.to_string(),
+            w.cards.to_string(),
+            w.wins.to_string(),
+            w.losses.to_string(),
+            format!(
+                "{}%",
+                format_f32(calculate_percent(w.wins, w.total_matches()), 2)
+            ),
+            w.flawless.to_string(),
+            w.mercy.to_string(),
+            w.forfeits.to_string(),
+            col_w = col_w,
+            name_col_w = name_col_w,
+        );
+    }
+    tell::update!();
+    tell::update!("Cards are reconstructed from consecutive matches played with the same fireteam, and end after 7 wins or 3 losses.");
+}
+
+fn print_tsv(weekends: &[TrialsWeekend]) {
+    for w in weekends.iter() {
+        for (i, c) in w.cards.iter().enumerate() {
+            let values: Vec<String> = vec![
+                w.start.to_rfc3339(),
+                (i + 1).to_string(),
+                c.wins().to_string(),
+                c.losses().to_string(),
+                c.is_flawless().to_string(),
+                c.mercy_count().to_string(),
+                c.forfeit_count().to_string(),
+                format_players(&c.get_teammates()),
+            ];
+
+            tell::update!("{}", values.join(TSV_DELIM));
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for viewing Destiny 2 Trials of Osiris cards, flawless
+/// runs and weekend summaries.
+///
+/// Cards are reconstructed from synced Trials of Osiris activities, by
+/// grouping consecutive matches played with the same fireteam during a
+/// Trials weekend (from the Friday reset to the Tuesday weekly reset). A card
+/// ends after 7 wins or 3 losses.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie name for player
+    ///
+    /// Name must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
+
+    /// Custom start time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "t", long = "custom-time", parse(try_from_str = parse_rfc3339), required_if("moment", "custom"))]
+    custom_time: Option<DateTime<Utc>>,
+
+    /// Custom end time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --end-moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "e", long = "end-custom-time", parse(try_from_str = parse_rfc3339), required_if("end-moment", "custom"))]
+    end_custom_time: Option<DateTime<Utc>>,
+
+    /// Start moment from which to pull Trials activities
+    ///
+    /// Valid values include weekend (last weekend reset on Friday), weekly
+    /// (last weekly reset on Tuesday), day, week, month, all_time and custom
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season).
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 2w, this-month, last-month, months (2023-05), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When custom is specified, the custom start date in RFC3339 format must
+    /// be specified with the --custom-time argument.
+    ///
+    /// Defaults to season, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// End moment from which to pull Trials activities
+    ///
+    /// Accepts the same values as --moment.
+    ///
+    /// When custom is specified, the custom end date in RFC3339 format must
+    /// be specified with the --end-custom-time argument.
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
+
+    /// Character to retrieve data for
+    ///
+    /// Valid values include hunter, titan, warlock, last_active and all.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// Display the result, score, map and opponents for each match
+    #[structopt(short = "d", long = "details")]
+    details: bool,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default) and tsv.
+    ///
+    /// tsv outputs one line per card, in a tab (\t) separated format with
+    /// lines ending in a new line character (\n). Columns are weekend start,
+    /// card number, wins, losses, flawless, mercy, forfeits and teammates.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Sync player activities
+    #[structopt(long = "sync", short = "s")]
+    sync: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let moment = match config.moment(opt.moment, "season") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::TERMINAL)
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if opt.sync {
+        match store.sync_member(&member).await {
+            Ok(_e) => (),
+            Err(e) => {
+                tell::error!("Could not sync activity store {}", e);
+                tell::update!("Using existing data");
+            }
+        };
+    }
+
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = Mode::TrialsOfOsiris;
+    let performances = match store
+        .retrieve_activities_since(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+            &mut manifest,
+        )
+        .await
+    {
+        Ok(e) => e.unwrap_or_default(),
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if performances.is_empty() {
+        tell::update!("No Trials of Osiris activities found");
+        return;
+    }
+
+    let rosters = match store
+        .retrieve_activity_rosters(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+        )
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let weekends = TrialsWeekend::with_activities(&performances, &rosters);
+
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => {
+            print_default(&member, &weekends, &seasons, opt.details)
+        }
+        Output::Tsv => print_tsv(&weekends),
+    }
+}
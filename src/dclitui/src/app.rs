@@ -0,0 +1,325 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::crucible::{
+    AggregateCruciblePerformances, CrucibleActivity,
+    CruciblePlayerActivityPerformance, CruciblePlayerPerformance, Member,
+    Player,
+};
+use dcli::currentactivity::ActivityStatus;
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::error::Error;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::season::Seasons;
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Focus {
+    Matches,
+    Details,
+}
+
+/// What the event loop needs to do after a key press
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Action {
+    None,
+    Quit,
+    Reload,
+    LoadDetails,
+    Sync,
+}
+
+pub struct MatchDetails {
+    pub activity: CrucibleActivity,
+    pub ratings: HashMap<u64, f32>,
+}
+
+pub struct App {
+    pub member: Member,
+    pub character_class_selection: CharacterClassSelection,
+    pub modes: Vec<Mode>,
+    pub mode_index: usize,
+    pub moments: Vec<Moment>,
+    pub moment_index: usize,
+    pub session_start: DateTime<Utc>,
+
+    pub activities: Vec<CruciblePlayerActivityPerformance>,
+    pub selected: usize,
+    pub details: Option<MatchDetails>,
+    pub details_scroll: usize,
+    pub session: Option<AggregateCruciblePerformances>,
+    pub status: Option<Result<ActivityStatus, String>>,
+
+    pub focus: Focus,
+    pub syncing: bool,
+    pub last_sync: Option<DateTime<Utc>>,
+    pub message: Option<String>,
+
+    store: ActivityStoreInterface,
+    manifest: ManifestInterface,
+    seasons: Seasons,
+}
+
+impl App {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        member: Member,
+        character_class_selection: CharacterClassSelection,
+        modes: Vec<Mode>,
+        moments: Vec<Moment>,
+        session_start: DateTime<Utc>,
+        store: ActivityStoreInterface,
+        manifest: ManifestInterface,
+        seasons: Seasons,
+    ) -> App {
+        App {
+            member,
+            character_class_selection,
+            modes,
+            mode_index: 0,
+            moments,
+            moment_index: 0,
+            session_start,
+            activities: Vec::new(),
+            selected: 0,
+            details: None,
+            details_scroll: 0,
+            session: None,
+            status: None,
+            focus: Focus::Matches,
+            syncing: false,
+            last_sync: None,
+            message: None,
+            store,
+            manifest,
+            seasons,
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.modes[self.mode_index]
+    }
+
+    pub fn moment(&self) -> &Moment {
+        &self.moments[self.moment_index]
+    }
+
+    pub fn selected_activity(
+        &self,
+    ) -> Option<&CruciblePlayerActivityPerformance> {
+        self.activities.get(self.selected)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Char('c')
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                Action::Quit
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Matches => Focus::Details,
+                    Focus::Details => Focus::Matches,
+                };
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::Home | KeyCode::Char('g') => self.move_selection(i64::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_selection(i64::MAX),
+            KeyCode::Char('m') => {
+                self.mode_index =
+                    next_index(self.mode_index, self.modes.len(), 1);
+                Action::Reload
+            }
+            KeyCode::Char('M') => {
+                self.mode_index =
+                    next_index(self.mode_index, self.modes.len(), -1);
+                Action::Reload
+            }
+            KeyCode::Char('t') => {
+                self.moment_index =
+                    next_index(self.moment_index, self.moments.len(), 1);
+                Action::Reload
+            }
+            KeyCode::Char('T') => {
+                self.moment_index =
+                    next_index(self.moment_index, self.moments.len(), -1);
+                Action::Reload
+            }
+            KeyCode::Char('r') => Action::Sync,
+            KeyCode::Enter => {
+                self.focus = Focus::Details;
+                Action::LoadDetails
+            }
+            _ => Action::None,
+        }
+    }
+
+    //moves the selection in the focused pane. Selecting a different match
+    //loads its details
+    fn move_selection(&mut self, delta: i64) -> Action {
+        match self.focus {
+            Focus::Matches => {
+                if self.activities.is_empty() {
+                    return Action::None;
+                }
+
+                let max = self.activities.len() as i64 - 1;
+                let selected =
+                    (self.selected as i64).saturating_add(delta).clamp(0, max);
+
+                if selected as usize == self.selected {
+                    return Action::None;
+                }
+
+                self.selected = selected as usize;
+                Action::LoadDetails
+            }
+            Focus::Details => {
+                //the scroll is clamped to the content when drawn
+                self.details_scroll = (self.details_scroll as i64)
+                    .saturating_add(delta)
+                    .max(0) as usize;
+                Action::None
+            }
+        }
+    }
+
+    /// Reloads the matches and session stats for the current mode and
+    /// moment, keeping the selected match if it is still in the list.
+    pub async fn reload(&mut self) -> Result<(), Error> {
+        let mode = self.mode();
+        let time_period = DateTimePeriod::with_moments(
+            self.moment(),
+            None,
+            None,
+            None,
+            &self.seasons,
+        )?;
+
+        let selected_id =
+            self.selected_activity().map(|e| e.activity_detail.id);
+
+        self.activities = self
+            .store
+            .retrieve_activities_since(
+                &self.member,
+                &self.character_class_selection,
+                &mode,
+                &time_period,
+                &mut self.manifest,
+            )
+            .await?
+            .unwrap_or_default();
+
+        self.selected = selected_id
+            .and_then(|id| {
+                self.activities
+                    .iter()
+                    .position(|e| e.activity_detail.id == id)
+            })
+            .unwrap_or(0);
+
+        //session stats are always for the session, regardless of moment
+        let session_period =
+            DateTimePeriod::with_start_time(self.session_start)?;
+        let session = self
+            .store
+            .retrieve_activities_since(
+                &self.member,
+                &self.character_class_selection,
+                &mode,
+                &session_period,
+                &mut self.manifest,
+            )
+            .await?
+            .unwrap_or_default();
+
+        self.session = if session.is_empty() {
+            None
+        } else {
+            let cpp: Vec<&CruciblePlayerPerformance> =
+                session.iter().map(|x| &x.performance).collect();
+            Some(AggregateCruciblePerformances::with_performances(&cpp))
+        };
+
+        Ok(())
+    }
+
+    /// Loads the teams and stored combat ratings for the selected match
+    pub async fn load_details(&mut self) -> Result<(), Error> {
+        let id = match self.selected_activity() {
+            Some(e) => e.activity_detail.id,
+            None => {
+                self.details = None;
+                return Ok(());
+            }
+        };
+
+        if let Some(e) = &self.details {
+            if e.activity.details.id == id {
+                return Ok(());
+            }
+        }
+
+        let activity =
+            self.store.retrieve_activity(id, &mut self.manifest).await?;
+
+        let players: Vec<&Player> = activity
+            .teams
+            .values()
+            .flat_map(|t| t.player_performances.iter().map(|p| &p.player))
+            .collect();
+
+        //ratings are optional, so we dont fail if they cant be retrieved
+        let ratings = self
+            .store
+            .retrieve_combat_ratings_at(
+                &players,
+                &activity.details.mode,
+                &activity.details.period,
+            )
+            .await
+            .unwrap_or_default();
+
+        self.details = Some(MatchDetails { activity, ratings });
+        self.details_scroll = 0;
+
+        Ok(())
+    }
+}
+
+fn next_index(index: usize, len: usize, delta: i64) -> usize {
+    (index as i64 + delta).rem_euclid(len as i64) as usize
+}
@@ -0,0 +1,278 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::io::Write;
+
+use crossterm::{
+    cursor, queue,
+    style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
+};
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the area inside of the rect's border
+    pub fn inner(&self) -> Rect {
+        Rect {
+            x: self.x + 1,
+            y: self.y + 1,
+            width: self.width.saturating_sub(2),
+            height: self.height.saturating_sub(2),
+        }
+    }
+
+    /// Splits the rect horizontally, with the left rect being width wide
+    pub fn split_horizontal(&self, width: u16) -> (Rect, Rect) {
+        let width = std::cmp::min(width, self.width);
+        (
+            Rect::new(self.x, self.y, width, self.height),
+            Rect::new(self.x + width, self.y, self.width - width, self.height),
+        )
+    }
+
+    /// Splits the rect vertically, with the top rect being height tall
+    pub fn split_vertical(&self, height: u16) -> (Rect, Rect) {
+        let height = std::cmp::min(height, self.height);
+        (
+            Rect::new(self.x, self.y, self.width, height),
+            Rect::new(
+                self.x,
+                self.y + height,
+                self.width,
+                self.height - height,
+            ),
+        )
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bold: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    pub fn fg(color: Color) -> Style {
+        Style {
+            fg: Some(color),
+            ..Default::default()
+        }
+    }
+
+    pub fn bold() -> Style {
+        Style {
+            bold: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn reverse() -> Style {
+        Style {
+            reverse: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+struct Cell {
+    ch: char,
+    style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            ch: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+/// Screen sized buffer which panes are drawn into, and then written to the
+/// terminal in a single pass, to avoid flickering.
+pub struct Buffer {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    pub fn new(width: u16, height: u16) -> Buffer {
+        Buffer {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    fn set(&mut self, x: u16, y: u16, ch: char, style: Style) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let index = y as usize * self.width as usize + x as usize;
+        self.cells[index] = Cell { ch, style };
+    }
+
+    /// Writes the string at the specified position, truncating it at
+    /// max_width characters.
+    pub fn set_string(
+        &mut self,
+        x: u16,
+        y: u16,
+        s: &str,
+        max_width: u16,
+        style: Style,
+    ) {
+        for (i, ch) in s.chars().take(max_width as usize).enumerate() {
+            self.set(x + i as u16, y, ch, style);
+        }
+    }
+
+    /// Fills a row of the rect with the style, used to highlight rows
+    pub fn fill_row(&mut self, rect: &Rect, y: u16, style: Style) {
+        if y >= self.height {
+            return;
+        }
+
+        let end = std::cmp::min(rect.x + rect.width, self.width);
+        for x in rect.x..end {
+            let index = y as usize * self.width as usize + x as usize;
+            self.cells[index].style = style;
+        }
+    }
+
+    /// Draws a border around the rect with the title in the top border.
+    /// The border is highlighted when the pane has focus.
+    pub fn draw_box(&mut self, rect: &Rect, title: &str, focused: bool) {
+        if rect.width < 2 || rect.height < 2 {
+            return;
+        }
+
+        let style = if focused {
+            Style {
+                fg: Some(Color::Yellow),
+                bold: true,
+                reverse: false,
+            }
+        } else {
+            Style::default()
+        };
+
+        let right = rect.x + rect.width - 1;
+        let bottom = rect.y + rect.height - 1;
+
+        for x in rect.x..=right {
+            self.set(x, rect.y, '─', style);
+            self.set(x, bottom, '─', style);
+        }
+
+        for y in rect.y..=bottom {
+            self.set(rect.x, y, '│', style);
+            self.set(right, y, '│', style);
+        }
+
+        self.set(rect.x, rect.y, '┌', style);
+        self.set(right, rect.y, '┐', style);
+        self.set(rect.x, bottom, '└', style);
+        self.set(right, bottom, '┘', style);
+
+        if !title.is_empty() {
+            self.set_string(
+                rect.x + 2,
+                rect.y,
+                &format!(" {} ", title),
+                rect.width.saturating_sub(4),
+                style,
+            );
+        }
+    }
+
+    /// Writes the buffer to the terminal
+    pub fn flush<W: Write>(&self, out: &mut W) -> crossterm::Result<()> {
+        for y in 0..self.height {
+            queue!(out, cursor::MoveTo(0, y))?;
+
+            let mut current = Style::default();
+            queue!(out, SetAttribute(Attribute::Reset))?;
+
+            let start = y as usize * self.width as usize;
+            let row = &self.cells[start..start + self.width as usize];
+
+            let mut run = String::new();
+            for cell in row {
+                if cell.style != current {
+                    queue!(out, Print(&run))?;
+                    run.clear();
+                    apply_style(out, &cell.style)?;
+                    current = cell.style;
+                }
+                run.push(cell.ch);
+            }
+            queue!(out, Print(&run))?;
+        }
+
+        queue!(out, SetAttribute(Attribute::Reset))?;
+        out.flush()?;
+
+        Ok(())
+    }
+}
+
+fn apply_style<W: Write>(out: &mut W, style: &Style) -> crossterm::Result<()> {
+    queue!(out, SetAttribute(Attribute::Reset))?;
+
+    if let Some(e) = style.fg {
+        queue!(out, SetForegroundColor(e))?;
+    }
+
+    if style.bold {
+        queue!(out, SetAttribute(Attribute::Bold))?;
+    }
+
+    if style.reverse {
+        queue!(out, SetAttribute(Attribute::Reverse))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,616 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+mod app;
+mod buffer;
+mod ui;
+
+use std::io::{stdout, Stdout, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use crossterm::event::{self, Event, KeyEvent};
+use crossterm::{cursor, execute, terminal};
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::apiinterface::ApiInterface;
+use dcli::config::Config;
+use dcli::crucible::Member;
+use dcli::currentactivity::{retrieve_activity_status, ActivityStatus};
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::Moment;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::season::Seasons;
+use dcli::timezone::set_timezone;
+use dcli::utils::{
+    determine_data_dir, format_error, parse_and_validate_crucible_mode,
+    parse_rfc3339, EXIT_FAILURE,
+};
+
+use app::{Action, App};
+use buffer::Buffer;
+
+//modes which can be switched between. The mode specified when launching is
+//added to the front of the list if it is not included.
+const MODES: [Mode; 8] = [
+    Mode::AllPvP,
+    Mode::Control,
+    Mode::Clash,
+    Mode::Rumble,
+    Mode::IronBanner,
+    Mode::TrialsOfOsiris,
+    Mode::PvPCompetitive,
+    Mode::PrivateMatchesAll,
+];
+
+const MOMENTS: [&str; 7] = [
+    "daily", "weekend", "weekly", "week", "month", "season", "all_time",
+];
+
+//how often the screen is redrawn when there are no other events, so times
+//stay current
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+enum AppEvent {
+    Input(KeyEvent),
+    Resize,
+    Tick,
+    SyncStarted,
+    SyncComplete(Option<String>),
+    Status(Result<ActivityStatus, String>),
+}
+
+/// Switches the terminal into full screen raw mode, and restores it when
+/// dropped, including when panicking.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter(out: &mut Stdout) -> crossterm::Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+        //library output would draw over the dashboard
+        Tell::set_level(TellLevel::Silent);
+
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let mut out = stdout();
+        let _ = execute!(out, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        let _ = out.flush();
+        Tell::set_level(TellLevel::Progress);
+    }
+}
+
+fn render(app: &App, out: &mut Stdout) -> crossterm::Result<()> {
+    let (width, height) = terminal::size()?;
+    let mut buffer = Buffer::new(width, height);
+    ui::draw(app, &mut buffer);
+    buffer.flush(out)
+}
+
+//reads terminal input on its own thread, since crossterm reads block
+fn spawn_input_reader(tx: UnboundedSender<AppEvent>) {
+    std::thread::spawn(move || loop {
+        let event = match event::read() {
+            Ok(Event::Key(e)) => AppEvent::Input(e),
+            Ok(Event::Resize(_, _)) => AppEvent::Resize,
+            Ok(_) => continue,
+            Err(_) => return,
+        };
+
+        if tx.send(event).is_err() {
+            return;
+        }
+    });
+}
+
+fn spawn_ticker(tx: UnboundedSender<AppEvent>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if tx.send(AppEvent::Tick).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Syncs the member's activities, and retrieves their current activity, on
+/// an interval, or when triggered. Uses its own connections to the stores so
+/// the dashboard stays responsive while syncing.
+async fn run_sync(
+    data_dir: PathBuf,
+    api_key: Option<String>,
+    member: Member,
+    interval: Duration,
+    tx: UnboundedSender<AppEvent>,
+    mut trigger: UnboundedReceiver<()>,
+) {
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir, api_key,
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            let _ = tx.send(AppEvent::SyncComplete(Some(e.to_string())));
+            return;
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            let _ = tx.send(AppEvent::SyncComplete(Some(e.to_string())));
+            return;
+        }
+    };
+
+    let client = match ApiInterface::new() {
+        Ok(e) => e,
+        Err(e) => {
+            let _ = tx.send(AppEvent::SyncComplete(Some(e.to_string())));
+            return;
+        }
+    };
+
+    loop {
+        if tx.send(AppEvent::SyncStarted).is_err() {
+            return;
+        }
+
+        let result = store.sync_member(&member).await.err();
+        if tx
+            .send(AppEvent::SyncComplete(result.map(|e| e.to_string())))
+            .is_err()
+        {
+            return;
+        }
+
+        let status = retrieve_activity_status(&client, &mut manifest, &member)
+            .await
+            .map_err(|e| e.to_string());
+        if tx.send(AppEvent::Status(status)).is_err() {
+            return;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {},
+            e = trigger.recv() => {
+                if e.is_none() {
+                    return;
+                }
+            },
+        }
+    }
+}
+
+async fn reload(app: &mut App) {
+    if let Err(e) = app.reload().await {
+        app.message = Some(format!("Could not load activities : {}", e));
+        return;
+    }
+
+    if let Err(e) = app.load_details().await {
+        app.message = Some(format!("Could not load match : {}", e));
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Full screen terminal dashboard for Destiny 2 Crucible activity.
+///
+/// Displays panes for the player's current activity (as in dclia), the
+/// latest matches (as in dcliah), details for the selected match (as in
+/// dcliad) and stats for the current session. Activities are synced in the
+/// background, and the dashboard updates when new matches are found.
+///
+/// Keyboard controls:
+/// up / down (or j / k) : select match, or scroll match details
+/// page up / page down, home / end : move through the match list
+/// tab : switch between the matches and match details panes
+/// enter : view details for the selected match
+/// m / M : switch to the next / previous mode
+/// t / T : switch to the next / previous moment
+/// r : sync now
+/// q / esc : quit
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie name for player
+    ///
+    /// Name must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
+
+    /// Moment from which to display matches when launched
+    ///
+    /// Can be switched while running with t / T, which cycle through
+    /// daily, weekend, weekly, week, month, season and all_time.
+    ///
+    /// Accepts the same values as dcliah, such as weekly, season_21,
+    /// 3d or last-week.
+    ///
+    /// Defaults to week, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// Activity mode to display matches for when launched
+    ///
+    /// Can be switched while running with m / M, which cycle through
+    /// all_pvp, control, clash, rumble, iron_banner, trials_of_osiris,
+    /// pvp_competitive and all_private.
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
+    #[structopt(long = "mode", short = "M",
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
+
+    /// Character to retrieve data for
+    ///
+    /// Valid values include hunter, titan, warlock, last_active and all.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// Start of the session in RFC 3339 date / time format
+    ///
+    /// Session stats include all matches since the session start. Can be
+    /// used with dclitime to resume a session.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Defaults to when dclitui is launched.
+    #[structopt(short = "t", long = "session-start", parse(try_from_str = parse_rfc3339))]
+    session_start: Option<DateTime<Utc>>,
+
+    /// Number of seconds between background syncs
+    ///
+    /// Each sync retrieves new matches and the player's current activity.
+    #[structopt(short = "i", long = "sync-interval", default_value = "30")]
+    sync_interval: u64,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let moment = match config.moment(opt.moment, "week") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if moment == Moment::Custom {
+        tell::error!("Custom moments are not supported. Use a time expression such as 2023-05-01 instead.");
+        std::process::exit(EXIT_FAILURE);
+    }
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let api_key = config.api_key(opt.api_key);
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        api_key.clone(),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut modes = MODES.to_vec();
+    if !modes.contains(&mode) {
+        modes.insert(0, mode);
+    }
+    let mode_index = modes.iter().position(|e| e == &mode).unwrap_or(0);
+
+    let mut moments: Vec<Moment> = MOMENTS
+        .iter()
+        .filter_map(|e| Moment::from_str(e).ok())
+        .collect();
+    if !moments.contains(&moment) {
+        moments.insert(0, moment.clone());
+    }
+    let moment_index = moments.iter().position(|e| e == &moment).unwrap_or(0);
+
+    let mut app = App::new(
+        member.clone(),
+        character_class_selection,
+        modes,
+        moments,
+        opt.session_start.unwrap_or_else(Utc::now),
+        store,
+        manifest,
+        seasons,
+    );
+    app.mode_index = mode_index;
+    app.moment_index = moment_index;
+
+    if let Err(e) = app.reload().await {
+        tell::error!(
+            "{}",
+            format_error("Could not retrieve data from activity store.", e)
+        );
+        std::process::exit(EXIT_FAILURE);
+    }
+
+    if let Err(e) = app.load_details().await {
+        app.message = Some(format!("Could not load match : {}", e));
+    }
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<AppEvent>();
+    let (trigger_tx, trigger_rx) = mpsc::unbounded_channel::<()>();
+
+    let mut out = stdout();
+    let _guard = match TerminalGuard::enter(&mut out) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("Could not initialize terminal : {}", e);
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    spawn_input_reader(tx.clone());
+    spawn_ticker(tx.clone());
+    tokio::spawn(run_sync(
+        data_dir,
+        api_key,
+        member,
+        Duration::from_secs(std::cmp::max(opt.sync_interval, 1)),
+        tx,
+        trigger_rx,
+    ));
+
+    let _ = render(&app, &mut out);
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            AppEvent::Input(key) => {
+                app.message = None;
+                match app.handle_key(key) {
+                    Action::Quit => break,
+                    Action::Reload => reload(&mut app).await,
+                    Action::LoadDetails => {
+                        if let Err(e) = app.load_details().await {
+                            app.message =
+                                Some(format!("Could not load match : {}", e));
+                        }
+                    }
+                    Action::Sync => {
+                        let _ = trigger_tx.send(());
+                    }
+                    Action::None => (),
+                }
+            }
+            AppEvent::Resize | AppEvent::Tick => (),
+            AppEvent::SyncStarted => app.syncing = true,
+            AppEvent::SyncComplete(e) => {
+                app.syncing = false;
+                match e {
+                    Some(e) => {
+                        app.message = Some(format!("Could not sync : {}", e))
+                    }
+                    None => {
+                        app.last_sync = Some(Utc::now());
+                        reload(&mut app).await;
+                    }
+                }
+            }
+            AppEvent::Status(e) => app.status = Some(e),
+        }
+
+        let _ = render(&app, &mut out);
+    }
+}
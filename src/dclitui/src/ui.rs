@@ -0,0 +1,419 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use chrono::Utc;
+use crossterm::style::Color;
+
+use dcli::crucible::CruciblePlayerActivityPerformance;
+use dcli::currentactivity::ActivityStatus;
+use dcli::enums::standing::Standing;
+use dcli::timezone::to_local;
+use dcli::utils::{
+    f32_are_equal, format_f32, human_date_format, human_duration,
+    truncate_ascii_string,
+};
+
+use crate::app::{App, Focus};
+use crate::buffer::{Buffer, Rect, Style};
+
+//scale for stored combat ratings, matching dcliad
+const RATING_SCALE: f32 = 10.0;
+
+const TOP_PANE_HEIGHT: u16 = 6;
+const MATCHES_PANE_WIDTH: u16 = 58;
+
+const HELP: &str =
+    "q quit | tab switch pane | up/down select | m/M mode | t/T moment | r sync";
+
+/// A line of text within a pane, with an optional style
+struct Line {
+    text: String,
+    style: Style,
+}
+
+impl Line {
+    fn new(text: String) -> Line {
+        Line {
+            text,
+            style: Style::default(),
+        }
+    }
+
+    fn styled(text: String, style: Style) -> Line {
+        Line { text, style }
+    }
+}
+
+pub fn draw(app: &App, buffer: &mut Buffer) {
+    let area = buffer.area();
+
+    if area.width < 40 || area.height < TOP_PANE_HEIGHT + 6 {
+        buffer.set_string(
+            0,
+            0,
+            "Terminal too small",
+            area.width,
+            Style::bold(),
+        );
+        return;
+    }
+
+    let (header, rest) = area.split_vertical(1);
+    let (body, footer) = rest.split_vertical(rest.height - 1);
+    let (top, bottom) = body.split_vertical(TOP_PANE_HEIGHT);
+
+    draw_header(app, buffer, &header);
+
+    let (activity_rect, session_rect) = top.split_horizontal(top.width / 2);
+    draw_lines(
+        buffer,
+        &activity_rect,
+        "Current Activity",
+        false,
+        &activity_lines(app),
+        0,
+    );
+    draw_lines(
+        buffer,
+        &session_rect,
+        "Session",
+        false,
+        &session_lines(app),
+        0,
+    );
+
+    let matches_width = std::cmp::min(MATCHES_PANE_WIDTH, bottom.width / 2);
+    let (matches_rect, details_rect) = bottom.split_horizontal(matches_width);
+    draw_matches(app, buffer, &matches_rect);
+
+    let details = details_lines(app);
+    let max_scroll = details
+        .len()
+        .saturating_sub(details_rect.inner().height as usize);
+    draw_lines(
+        buffer,
+        &details_rect,
+        "Match Details",
+        app.focus == Focus::Details,
+        &details,
+        std::cmp::min(app.details_scroll, max_scroll),
+    );
+
+    match &app.message {
+        Some(e) => buffer.set_string(
+            0,
+            footer.y,
+            e,
+            footer.width,
+            Style::fg(Color::Red),
+        ),
+        None => {
+            buffer.set_string(0, footer.y, HELP, footer.width, Style::default())
+        }
+    }
+}
+
+fn draw_header(app: &App, buffer: &mut Buffer, rect: &Rect) {
+    let sync = if app.syncing {
+        "Syncing...".to_string()
+    } else {
+        match app.last_sync {
+            Some(e) => format!("Synced {}", human_date_format(&e)),
+            None => "Not synced".to_string(),
+        }
+    };
+
+    let title = format!(
+        " {} : {} : {} ",
+        app.member.name.get_bungie_name(),
+        app.mode(),
+        app.moment()
+    );
+
+    buffer.fill_row(rect, rect.y, Style::reverse());
+    buffer.set_string(rect.x, rect.y, &title, rect.width, Style::reverse());
+
+    let sync = format!("{} ", sync);
+    let sync_len = sync.chars().count() as u16;
+    if rect.width > title.chars().count() as u16 + sync_len {
+        buffer.set_string(
+            rect.x + rect.width - sync_len,
+            rect.y,
+            &sync,
+            sync_len,
+            Style::reverse(),
+        );
+    }
+}
+
+//draws a box with the lines inside of it, starting at the scroll line
+fn draw_lines(
+    buffer: &mut Buffer,
+    rect: &Rect,
+    title: &str,
+    focused: bool,
+    lines: &[Line],
+    scroll: usize,
+) {
+    buffer.draw_box(rect, title, focused);
+    let inner = rect.inner();
+
+    for (i, line) in lines
+        .iter()
+        .skip(scroll)
+        .take(inner.height as usize)
+        .enumerate()
+    {
+        buffer.set_string(
+            inner.x,
+            inner.y + i as u16,
+            &line.text,
+            inner.width,
+            line.style,
+        );
+    }
+}
+
+fn activity_lines(app: &App) -> Vec<Line> {
+    let status = match &app.status {
+        Some(Ok(e)) => e,
+        Some(Err(e)) => {
+            return vec![Line::styled(
+                format!("Could not retrieve current activity : {}", e),
+                Style::fg(Color::Red),
+            )]
+        }
+        None => {
+            return vec![Line::new("Checking current activity...".to_string())]
+        }
+    };
+
+    let mut out = vec![Line::styled(status.human_status(), Style::bold())];
+
+    if let ActivityStatus::InActivity(e) = status {
+        let seconds = (Utc::now() - e.started).num_seconds().max(0) as u32;
+        out.push(Line::new(format!(
+            "Started {} ({})",
+            human_date_format(&e.started),
+            human_duration(seconds)
+        )));
+
+        if !e.destination_name.is_empty() {
+            out.push(Line::new(e.destination_name.to_string()));
+        }
+    }
+
+    out
+}
+
+fn session_lines(app: &App) -> Vec<Line> {
+    let mut out = vec![Line::new(format!(
+        "Since {}",
+        human_date_format(&app.session_start)
+    ))];
+
+    match &app.session {
+        Some(e) => {
+            out.push(Line::styled(
+                format!(
+                    "{} matches : {}-{} : {}% wins",
+                    e.total_activities,
+                    e.wins,
+                    e.losses,
+                    format_f32(e.win_rate, 2)
+                ),
+                Style::bold(),
+            ));
+            out.push(Line::new(format!(
+                "KD {} : Eff {} : {} kills : {} deaths",
+                format_f32(e.kills_deaths_ratio, 2),
+                format_f32(e.efficiency, 2),
+                e.kills,
+                e.deaths
+            )));
+            out.push(Line::new(format!(
+                "Longest streaks : {}W {}L",
+                e.longest_win_streak, e.longest_loss_streak
+            )));
+        }
+        None => out.push(Line::new("No matches this session".to_string())),
+    }
+
+    out
+}
+
+fn standing_style(standing: Standing) -> Style {
+    match standing {
+        Standing::Victory => Style::fg(Color::Green),
+        Standing::Defeat => Style::fg(Color::Red),
+        Standing::Unknown => Style::default(),
+    }
+}
+
+fn match_row(activity: &CruciblePlayerActivityPerformance) -> String {
+    let stats = &activity.performance.stats;
+    format!(
+        "{:<12}{:<16}{:<8}{:>4}{:>4}{:>6}",
+        to_local(&activity.activity_detail.period).format("%m/%d %H:%M"),
+        truncate_ascii_string(&activity.activity_detail.map_name, 15),
+        stats.standing.to_string(),
+        stats.kills,
+        stats.deaths,
+        format_f32(stats.kills_deaths_ratio, 2),
+    )
+}
+
+fn draw_matches(app: &App, buffer: &mut Buffer, rect: &Rect) {
+    let title = format!("Matches ({})", app.activities.len());
+    buffer.draw_box(rect, &title, app.focus == Focus::Matches);
+
+    let inner = rect.inner();
+    if inner.height < 2 {
+        return;
+    }
+
+    let header = format!(
+        "{:<12}{:<16}{:<8}{:>4}{:>4}{:>6}",
+        "DATE", "MAP", "W/L", "K", "D", "K/D"
+    );
+    buffer.set_string(inner.x, inner.y, &header, inner.width, Style::bold());
+
+    if app.activities.is_empty() {
+        buffer.set_string(
+            inner.x,
+            inner.y + 1,
+            "No activities found",
+            inner.width,
+            Style::default(),
+        );
+        return;
+    }
+
+    //keep the selected match visible
+    let rows = inner.height as usize - 1;
+    let offset = app.selected.saturating_sub(rows.saturating_sub(1));
+
+    for (i, activity) in
+        app.activities.iter().enumerate().skip(offset).take(rows)
+    {
+        let y = inner.y + 1 + (i - offset) as u16;
+        let mut style = standing_style(activity.performance.stats.standing);
+        if i == app.selected {
+            style.reverse = app.focus == Focus::Matches;
+            style.bold = true;
+        }
+
+        buffer.set_string(inner.x, y, &match_row(activity), inner.width, style);
+        if i == app.selected {
+            buffer.fill_row(&inner, y, style);
+        }
+    }
+}
+
+fn details_lines(app: &App) -> Vec<Line> {
+    let details = match &app.details {
+        Some(e) => e,
+        None => return vec![Line::new("No match selected".to_string())],
+    };
+
+    let activity = &details.activity;
+    let mut out = vec![
+        Line::styled(
+            format!(
+                "{} on {}",
+                activity.details.mode, activity.details.map_name
+            ),
+            Style::bold(),
+        ),
+        Line::new(human_date_format(&activity.details.period)),
+    ];
+
+    if let Some(e) = activity.get_member_performance(&app.member) {
+        out.push(Line::styled(
+            format!(
+                "{}! ({})",
+                e.stats.standing,
+                human_duration(e.stats.activity_duration_seconds)
+            ),
+            standing_style(e.stats.standing),
+        ));
+    }
+
+    for team in activity.teams.values() {
+        out.push(Line::new(String::new()));
+        out.push(Line::styled(
+            format!(
+                "[{}] {} Team {}!",
+                team.score, team.display_name, team.standing
+            ),
+            standing_style(team.standing),
+        ));
+        out.push(Line::styled(
+            format!(
+                "{:<24}{:>7}{:>7}{:>7}{:>7}{:>7}{:>8}{:>4}",
+                "PLAYER", "KILLS", "ASTS", "DEATHS", "K/D", "EFF", "RATING", ""
+            ),
+            Style::bold(),
+        ));
+
+        let mut performances = team.player_performances.clone();
+        performances.sort_by(|a, b| {
+            b.stats.opponents_defeated.cmp(&a.stats.opponents_defeated)
+        });
+
+        for p in &performances {
+            let rating = *details
+                .ratings
+                .get(&p.player.calculate_hash())
+                .unwrap_or(&0.0)
+                * RATING_SCALE;
+            let rating = if f32_are_equal(rating, 0.0) {
+                "".to_string()
+            } else {
+                format_f32(rating, 0)
+            };
+
+            let style = if app.member.has_membership_id(p.player.member_id) {
+                Style::bold()
+            } else {
+                Style::default()
+            };
+
+            out.push(Line::styled(
+                format!(
+                    "{:<24}{:>7}{:>7}{:>7}{:>7}{:>7}{:>8}{:>4}",
+                    truncate_ascii_string(&p.player.name.get_bungie_name(), 23),
+                    p.stats.kills,
+                    p.stats.assists,
+                    p.stats.deaths,
+                    format_f32(p.stats.kills_deaths_ratio, 2),
+                    format_f32(p.stats.efficiency, 2),
+                    rating,
+                    p.stats.generate_status(),
+                ),
+                style,
+            ));
+        }
+    }
+
+    out
+}
@@ -0,0 +1,671 @@
+/*
+* Copyright 2023 Mike Chambers
+* https://github.com/mikechambers/dcli
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy of
+* this software and associated documentation files (the "Software"), to deal in
+* the Software without restriction, including without limitation the rights to
+* use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+* of the Software, and to permit persons to whom the Software is furnished to do
+* so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+* FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+* COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+* IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+* CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use dcli::activitystoreinterface::ActivityStoreInterface;
+use dcli::config::Config;
+use dcli::crucible::{CruciblePlayerActivityPerformance, Member};
+use dcli::enums::character::CharacterClassSelection;
+use dcli::enums::membership::MembershipSelection;
+use dcli::enums::mode::Mode;
+use dcli::enums::moment::{DateTimePeriod, Moment};
+use dcli::enums::weapongrouping::WeaponGrouping;
+use dcli::enums::weapongroupstat::WeaponGroupStatType;
+use dcli::error::Error;
+use dcli::manifestinterface::ManifestInterface;
+use dcli::output::Output;
+use dcli::season::{Season, Seasons};
+use dcli::timezone::set_timezone;
+use dcli::utils::{
+    determine_data_dir, format_error, format_f32,
+    parse_and_validate_crucible_mode, parse_rfc3339, repeat_str,
+    truncate_ascii_string, EXIT_FAILURE, TSV_DELIM,
+};
+use dcli::weapongroups::{
+    summarize_by_season, WeaponGroupStat, WeaponGroupSummary,
+};
+use structopt::StructOpt;
+use tell::{Tell, TellLevel};
+
+const GROUP_COL_W: usize = 24;
+
+/// Weapon group stats for the entire period, and for each season within it.
+struct GroupingSummaries {
+    all: WeaponGroupSummary,
+    by_season: Vec<(Season, WeaponGroupSummary)>,
+}
+
+fn format_percent(value: f32) -> String {
+    format!("{}%", format_f32(value, 2))
+}
+
+fn format_stat(value: f32, stat: WeaponGroupStatType) -> String {
+    match stat {
+        WeaponGroupStatType::KillsPerGame => format_f32(value, 2),
+        _ => format_percent(value),
+    }
+}
+
+fn format_season_name(season: &Season) -> String {
+    match season.number {
+        Some(e) => format!("S{}", e),
+        None => season.name.to_string(),
+    }
+}
+
+fn print_default(
+    member: &Member,
+    mode: &Mode,
+    summaries: &[GroupingSummaries],
+    season_stat: WeaponGroupStatType,
+) {
+    let col_w = 10;
+
+    tell::update!(
+        "{} weapon stats for {} ({} matches)",
+        mode,
+        member.name.get_bungie_name(),
+        summaries.first().map(|e| e.all.activity_count).unwrap_or(0)
+    );
+    tell::update!();
+
+    for s in summaries.iter() {
+        let summary = &s.all;
+        let header = format!(
+            "{:<0group_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+            summary.grouping.to_string().to_uppercase(),
+            "KILLS",
+            "SHARE",
+            "PREC %",
+            "GAMES",
+            "KILLS/G",
+            "WIN %",
+            col_w = col_w,
+            group_col_w = GROUP_COL_W,
+        );
+
+        tell::update!("{}", header);
+        tell::update!("{}", repeat_str("=", header.chars().count()));
+
+        for g in summary.groups.iter() {
+            tell::update!(
+                "{:<0group_col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}{:>0col_w$}",
+                truncate_ascii_string(&g.group.to_string(), GROUP_COL_W - 2),
+                g.kills.to_string(),
+                format_percent(summary.kills_share(g)),
+                format_percent(g.precision_kills_percent()),
+                g.activity_count.to_string(),
+                format_f32(g.kills_per_game(), 2),
+                format_percent(g.win_rate()),
+                col_w = col_w,
+                group_col_w = GROUP_COL_W,
+            );
+        }
+        tell::update!();
+
+        if s.by_season.len() > 1 {
+            print_seasons(summary, &s.by_season, season_stat, col_w);
+            tell::update!();
+        }
+    }
+}
+
+fn print_seasons(
+    summary: &WeaponGroupSummary,
+    by_season: &[(Season, WeaponGroupSummary)],
+    season_stat: WeaponGroupStatType,
+    col_w: usize,
+) {
+    let mut header = format!(
+        "{:<0group_col_w$}",
+        format!("{} BY SEASON", season_stat.to_string().to_uppercase()),
+        group_col_w = GROUP_COL_W,
+    );
+    for (season, _s) in by_season.iter() {
+        header.push_str(&format!(
+            "{:>0col_w$}",
+            format_season_name(season),
+            col_w = col_w
+        ));
+    }
+    header.push_str(&format!("{:>0col_w$}", "+/-", col_w = col_w));
+
+    tell::update!("{}", header);
+    tell::update!("{}", repeat_str("=", header.chars().count()));
+
+    for g in summary.groups.iter() {
+        let mut line = format!(
+            "{:<0group_col_w$}",
+            truncate_ascii_string(&g.group.to_string(), GROUP_COL_W - 2),
+            group_col_w = GROUP_COL_W,
+        );
+
+        let values: Vec<Option<f32>> = by_season
+            .iter()
+            .map(|(_season, s)| {
+                s.get_group(&g.group).map(|e| s.get_stat(e, season_stat))
+            })
+            .collect();
+
+        for v in values.iter() {
+            let value = match v {
+                Some(e) => format_stat(*e, season_stat),
+                None => "-".to_string(),
+            };
+            line.push_str(&format!("{:>0col_w$}", value, col_w = col_w));
+        }
+
+        //change between the last two seasons in the period
+        let delta = match (values[values.len() - 2], values[values.len() - 1]) {
+            (Some(a), Some(b)) => {
+                let d = b - a;
+                let sign = if d > 0.0 { "+" } else { "" };
+                format!("{}{}", sign, format_f32(d, 2))
+            }
+            _ => "".to_string(),
+        };
+        line.push_str(&format!("{:>0col_w$}", delta, col_w = col_w));
+
+        tell::update!("{}", line);
+    }
+}
+
+fn create_tsv_row(
+    summary: &WeaponGroupSummary,
+    g: &WeaponGroupStat,
+    season: &str,
+) -> String {
+    let values: Vec<String> = vec![
+        summary.grouping.to_string(),
+        g.group.to_string(),
+        season.to_string(),
+        g.kills.to_string(),
+        summary.kills_share(g).to_string(),
+        g.precision_kills.to_string(),
+        g.precision_kills_percent().to_string(),
+        g.activity_count.to_string(),
+        g.kills_per_game().to_string(),
+        g.win_rate().to_string(),
+    ];
+
+    values.join(TSV_DELIM)
+}
+
+fn print_tsv(summaries: &[GroupingSummaries]) {
+    for s in summaries.iter() {
+        for g in s.all.groups.iter() {
+            tell::update!("{}", create_tsv_row(&s.all, g, "all"));
+        }
+
+        for (season, summary) in s.by_season.iter() {
+            for g in summary.groups.iter() {
+                tell::update!("{}", create_tsv_row(summary, g, &season.id));
+            }
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(verbatim_doc_comment)]
+/// Command line tool for viewing Destiny 2 Crucible weapon stats grouped by
+/// weapon archetype, ammo type and element.
+///
+/// Displays the share of weapon kills, precision kill percentage, kills per
+/// game and win rate for each group, as well as how the selected stat for
+/// each group has changed across seasons.
+///
+/// Games and wins for a group only include matches where a weapon from the
+/// group got at least one kill.
+///
+/// Created by Mike Chambers.
+/// https://www.mikechambers.com
+///
+/// Get support, request features or just chat on the dcli Discord server:
+/// https://discord.gg/2Y8bV2Mq3p
+///
+/// Get the latest version, download the source and log issues at:
+/// https://github.com/mikechambers/dcli
+///
+/// Released under an MIT License.
+struct Opt {
+    /// Bungie name for player
+    ///
+    /// Name must be in the format of NAME#CODE. Example: foo#3280
+    /// You can find your name in game, or on Bungie's site at:
+    /// https://www.bungie.net/7/en/User/Account/IdentitySettings
+    ///
+    /// Can also be an alias from the dcli.toml config file. If not specified,
+    /// the player set in the config file will be used.
+    #[structopt(long = "name", short = "n")]
+    name: Option<String>,
+
+    /// Custom start time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "t", long = "custom-time", parse(try_from_str = parse_rfc3339), required_if("moment", "custom"))]
+    custom_time: Option<DateTime<Utc>>,
+
+    /// Custom end time in RFC 3339 date / time format
+    ///
+    /// Must be a valid date in the past.
+    ///
+    /// Example RFC 3339 format: 2020-12-08T17:00:00.774187+00:00
+    ///
+    /// Required when --end-moment is set to custom, but otherwise not applicable.
+    #[structopt(short = "e", long = "end-custom-time", parse(try_from_str = parse_rfc3339), required_if("end-moment", "custom"))]
+    end_custom_time: Option<DateTime<Utc>>,
+
+    /// Start moment from which to pull activities
+    ///
+    /// Valid values include weekend (last weekend reset on Friday), weekly
+    /// (last weekly reset on Tuesday), day, week, month, all_time and custom
+    /// as well as seasons and expansions, specified by name (for example
+    /// season_of_the_deep, lightfall or the_final_shape), by season number
+    /// (season_21) or relative to the current season (season for the current
+    /// season, season-1 for the previous season).
+    ///
+    /// Relative and calendar based time expressions are also supported,
+    /// such as 3d, 2w, this-month, last-month, months (2023-05), dates
+    /// (2023-05-01) and ranges (2023-01-01..2023-02-01). Calendar based
+    /// expressions use the timezone set with --timezone.
+    ///
+    /// When custom is specified, the custom start date in RFC3339 format must
+    /// be specified with the --custom-time argument.
+    ///
+    /// Defaults to all_time, unless moment is set in the dcli.toml config file.
+    #[structopt(long = "moment", short = "T")]
+    moment: Option<Moment>,
+
+    /// End moment from which to pull activities
+    ///
+    /// Accepts the same values as --moment.
+    ///
+    /// When custom is specified, the custom end date in RFC3339 format must
+    /// be specified with the --end-custom-time argument.
+    ///
+    /// Defaults to now, or the end of the period if --moment is a season,
+    /// expansion or calendar period.
+    #[structopt(long = "end-moment", short = "E")]
+    end_moment: Option<Moment>,
+
+    /// Activity mode to return stats for
+    ///
+    /// Supported values are all_pvp (default), control, clash, elimination,
+    /// mayhem, iron_banner, all_private, rumble, pvp_competitive,
+    /// quickplay and trials_of_osiris.
+    ///
+    /// Addition values available are crimsom_doubles, supremacy, survival,
+    /// countdown, all_doubles, doubles, private_clash, private_control,
+    /// private_survival, private_rumble, showdown_competitive, survival_competitive,
+    /// rift_competitive, showdown, lockdown, iron_banner_rift,
+    /// zone_control, iron_banner_zone_control, rift,
+    /// scorched, scorched_team, breakthrough, clash_quickplay, trials_of_the_nine, relic, countdown_competitive, checkmate_all, checkmate_control, checkmate_rumble, checkmate_survival, checkmate_rumble, checkmate_clash, checkmate_countdown, collision_competitive, iron_banner_tribute, iron_banner_fortress
+    ///
+    /// Defaults to all_pvp, unless mode is set in the dcli.toml config file.
+    #[structopt(long = "mode", short = "M",
+        parse(try_from_str=parse_and_validate_crucible_mode))]
+    mode: Option<Mode>,
+
+    /// Character to retrieve data for
+    ///
+    /// Valid values include hunter, titan, warlock, last_active and all.
+    ///
+    /// Defaults to all, unless class is set in the dcli.toml config file.
+    #[structopt(short = "C", long = "class")]
+    character_class_selection: Option<CharacterClassSelection>,
+
+    /// Destiny memberships to include data for
+    ///
+    /// Valid values are all, primary, or a platform (xbox, playstation,
+    /// steam, stadia, blizzard and epic). all includes data for every
+    /// membership linked to the player's Bungie.net account, such as
+    /// memberships played before cross save was enabled.
+    ///
+    /// Defaults to all, unless membership is set in the dcli.toml config file.
+    #[structopt(long = "membership")]
+    membership: Option<MembershipSelection>,
+
+    /// How to group weapons
+    ///
+    /// Valid values are archetype (hand cannon, shotgun, sniper rifle...),
+    /// ammo (primary, special and heavy) and element.
+    ///
+    /// If not specified, all groupings will be displayed.
+    #[structopt(long = "group-by", short = "g")]
+    grouping: Option<WeaponGrouping>,
+
+    /// Stat to compare across seasons
+    ///
+    /// Valid values are kills_share, precision_percent, kills_per_game and
+    /// wins_percent.
+    #[structopt(
+        long = "season-stat",
+        short = "S",
+        default_value = "kills_share"
+    )]
+    season_stat: WeaponGroupStatType,
+
+    /// Maximum number of seasons to compare, counting back from the most
+    /// recent season in the period
+    ///
+    /// Use 0 to compare all seasons within the period.
+    #[structopt(long = "seasons", default_value = "4")]
+    seasons: usize,
+
+    /// Format for command output
+    ///
+    /// Valid values are default (Default) and tsv.
+    ///
+    /// tsv outputs one line per group, per season, in a tab (\t) separated
+    /// format with lines ending in a new line character (\n). Columns are
+    /// grouping, group, season (or all for the entire period), kills, kills
+    /// share, precision kills, precision %, games, kills per game and win %.
+    ///
+    /// Defaults to default, unless output_format is set in the dcli.toml config file.
+    #[structopt(short = "O", long = "output-format")]
+    output: Option<Output>,
+
+    /// Timezone used to display dates / times, and for calendar based moments
+    ///
+    /// Must be an IANA timezone name, such as America/Los_Angeles or
+    /// Europe/Berlin.
+    ///
+    /// Defaults to the TZ environment variable or system timezone, unless
+    /// timezone is set in the dcli.toml config file.
+    #[structopt(long = "timezone")]
+    timezone: Option<String>,
+
+    ///Print out additional information
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Sync player activities
+    #[structopt(long = "sync", short = "s")]
+    sync: bool,
+
+    /// Directory where Destiny 2 manifest and activity database files are stored. (optional)
+    ///
+    /// This will normally be downloaded using the dclim tool, and uses
+    /// a system appropriate directory by default.
+    #[structopt(short = "D", long = "data-dir", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+
+    /// API key from Bungie required for some actions.
+    ///
+    /// If specified the key will be passed to all Destiny API calls.
+    ///
+    /// You can obtain a key from https://www.bungie.net/en/Application
+    #[structopt(short = "k", long = "api-key", env = "DESTINY_API_KEY")]
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let level = if opt.verbose {
+        TellLevel::Verbose
+    } else {
+        TellLevel::Progress
+    };
+    Tell::init(level);
+
+    tell::verbose!("{:#?}", opt);
+    log::info!("{:#?}", opt);
+
+    let config = match Config::load(&opt.data_dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load config file.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    match config.timezone(opt.timezone.clone()) {
+        Ok(e) => set_timezone(e),
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid timezone.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let name = match config.player_name(&opt.name) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid player name.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let moment = match config.moment(opt.moment, "all_time") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mode = match config.mode(
+        opt.mode,
+        "all_pvp",
+        parse_and_validate_crucible_mode,
+    ) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid mode.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let character_class_selection =
+        match config.class(opt.character_class_selection, "all") {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid character class.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let output =
+        match config.output_supported(opt.output, "default", &Output::TERMINAL)
+        {
+            Ok(e) => e,
+            Err(e) => {
+                tell::error!("{}", format_error("Invalid output format.", e));
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+
+    let data_dir = match determine_data_dir(config.data_dir(opt.data_dir)) {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Error initializing data directory.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let seasons = match Seasons::load(&data_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Could not load season data.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let time_period = match DateTimePeriod::with_moments(
+        &moment,
+        opt.custom_time,
+        opt.end_moment.as_ref(),
+        opt.end_custom_time,
+        &seasons,
+    ) {
+        Ok(e) => e,
+        Err(Error::DateTimePeriodOrder) => {
+            tell::error!("--end-moment must be greater than --moment");
+            std::process::exit(EXIT_FAILURE);
+        }
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid moment.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut store = match ActivityStoreInterface::init_with_path(
+        &data_dir,
+        config.api_key(opt.api_key),
+    )
+    .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error(
+                "Could not initialize activity store. Have you run dclisync?",
+                e,
+            ));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member: Member = match store.find_member(&name, true).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "Could not find Bungie ID. Please check name and try again. {}",
+                e
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if opt.sync {
+        match store.sync_member(&member).await {
+            Ok(_e) => (),
+            Err(e) => {
+                tell::error!("Could not sync activity store {}", e);
+                tell::update!("Using existing data");
+            }
+        };
+    }
+
+    let membership = match config.membership(opt.membership, "all") {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!("{}", format_error("Invalid membership.", e));
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let member = match store.select_membership(&member, &membership).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not find selected membership.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let mut manifest = match ManifestInterface::new(&data_dir, false).await {
+        Ok(e) => e,
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error(
+                    "Could not initialize manifest. Have you run dclim?",
+                    e,
+                )
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    let performances = match store
+        .retrieve_activities_since(
+            &member,
+            &character_class_selection,
+            &mode,
+            &time_period,
+            &mut manifest,
+        )
+        .await
+    {
+        Ok(e) => e.unwrap_or_default(),
+        Err(e) => {
+            tell::error!(
+                "{}",
+                format_error("Could not retrieve data from activity store.", e)
+            );
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if performances.is_empty() {
+        tell::update!("No activities found");
+        return;
+    }
+
+    let groupings = match opt.grouping {
+        Some(e) => vec![e],
+        None => WeaponGrouping::all().to_vec(),
+    };
+
+    let all: Vec<&CruciblePlayerActivityPerformance> =
+        performances.iter().collect();
+
+    let max_seasons = opt.seasons;
+    let summaries: Vec<GroupingSummaries> = groupings
+        .iter()
+        .map(|g| {
+            let mut by_season =
+                summarize_by_season(&performances, *g, &seasons);
+            if max_seasons > 0 && by_season.len() > max_seasons {
+                by_season.drain(..by_season.len() - max_seasons);
+            }
+
+            GroupingSummaries {
+                all: WeaponGroupSummary::with_activities(&all, *g),
+                by_season,
+            }
+        })
+        .collect();
+
+    match output {
+        Output::Default
+        | Output::Markdown
+        | Output::Html
+        | Output::Jsonl
+        | Output::Json => {
+            print_default(&member, &mode, &summaries, opt.season_stat)
+        }
+        Output::Tsv => print_tsv(&summaries),
+    }
+}
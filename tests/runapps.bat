@@ -43,4 +43,43 @@ echo ------------- RUNNING dclitime.exe ---------------
 
 call dclitime.exe
 
+echo ------------- RUNNING dclich.exe --------------
 
+dclich.exe --name mesh#3230
+
+echo ------------- RUNNING dclitrials.exe --------------
+
+dclitrials.exe --name mesh#3230
+
+echo ------------- RUNNING dclileavers.exe --------------
+
+dclileavers.exe --name mesh#3230
+
+echo ------------- RUNNING dclibalance.exe --------------
+
+dclibalance.exe --name mesh#3230
+
+echo ------------- RUNNING dclimedals.exe --------------
+
+dclimedals.exe --name mesh#3230
+
+echo ------------- RUNNING dcliweapons.exe --------------
+
+dcliweapons.exe --name mesh#3230
+
+echo ------------- RUNNING dclif.exe --------------
+
+dclif.exe --name mesh#3230
+
+echo ------------- RUNNING dclireport.exe --------------
+
+dclireport.exe --name mesh#3230 --output-dir dclireport_output
+
+echo ------------- RUNNING dcliclan.exe --------------
+
+dcliclan.exe
+
+
+echo ------------- RUNNING dclicompare.exe --------------
+
+dclicompare.exe --name mesh#3230 --name mesh#3230
\ No newline at end of file